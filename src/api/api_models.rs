@@ -1,6 +1,7 @@
 use form_urlencoded::Serializer;
 use regex::Regex;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::convert::Into;
 
 use crate::app::models::*;
@@ -8,13 +9,40 @@ use crate::app::models::*;
 pub enum SearchType {
     Artist,
     Album,
+    Track,
+    Playlist,
+    Show,
+    Episode,
 }
 
 impl SearchType {
+    pub fn for_filter(filter: SearchFilter) -> Vec<SearchType> {
+        match filter {
+            SearchFilter::All => vec![
+                Self::Track,
+                Self::Album,
+                Self::Artist,
+                Self::Playlist,
+                Self::Show,
+                Self::Episode,
+            ],
+            SearchFilter::Songs => vec![Self::Track],
+            SearchFilter::Albums => vec![Self::Album],
+            SearchFilter::Artists => vec![Self::Artist],
+            SearchFilter::Playlists => vec![Self::Playlist],
+            SearchFilter::Podcasts => vec![Self::Show],
+            SearchFilter::Episodes => vec![Self::Episode],
+        }
+    }
+
     fn into_string(self) -> &'static str {
         match self {
             Self::Artist => "artist",
             Self::Album => "album",
+            Self::Track => "track",
+            Self::Playlist => "playlist",
+            Self::Show => "show",
+            Self::Episode => "episode",
         }
     }
 }
@@ -59,6 +87,24 @@ impl<T> Page<T> {
     }
 }
 
+// Followed artists are paged with a cursor rather than an offset
+#[derive(Deserialize, Debug, Clone)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+    pub cursors: Cursors,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Cursors {
+    pub after: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct FollowedArtists {
+    pub artists: CursorPage<Artist>,
+}
+
 trait WithImages {
     fn images(&self) -> &[Image];
 
@@ -82,6 +128,9 @@ trait WithImages {
 pub struct Playlist {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub collaborative: bool,
     pub images: Vec<Image>,
     pub owner: PlaylistOwner,
 }
@@ -104,6 +153,8 @@ pub struct Podcast {
     pub name: String,
     pub images: Vec<Image>,
     pub publisher: String,
+    #[serde(default)]
+    pub description: String,
 }
 
 impl WithImages for Podcast {
@@ -112,10 +163,95 @@ impl WithImages for Podcast {
     }
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct Episode {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub html_description: Option<String>,
+    #[serde(default)]
+    pub release_date: String,
+    pub duration_ms: u32,
+    // Only given when the user's progress is known
+    #[serde(default)]
+    pub resume_point: Option<ResumePoint>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ResumePoint {
+    pub fully_played: bool,
+    #[serde(default)]
+    pub resume_position_ms: u32,
+}
+
+impl Into<EpisodeDescription> for Episode {
+    fn into(self) -> EpisodeDescription {
+        EpisodeDescription {
+            id: self.id,
+            title: self.name,
+            description: self.description,
+            html_description: self.html_description,
+            release_date: self.release_date,
+            duration: self.duration_ms,
+            fully_played: self
+                .resume_point
+                .map(|point| point.fully_played)
+                .unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Category {
+    pub id: String,
+    pub name: String,
+    pub icons: Vec<Image>,
+}
+
+impl WithImages for Category {
+    fn images(&self) -> &[Image] {
+        &self.icons[..]
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NewReleases {
+    pub albums: Page<Album>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Categories {
+    pub categories: Page<Category>,
+}
+
+// Playlists that aren't available are null
+#[derive(Deserialize, Debug, Clone)]
+pub struct CategoryPlaylists {
+    pub playlists: Page<Option<Playlist>>,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct PlaylistTrack {
     pub is_local: bool,
     pub track: Option<TrackItem>,
+    pub added_by: Option<User>,
+}
+
+impl Page<PlaylistTrack> {
+    // Ids of the songs along with the id of whoever added them
+    pub fn added_by(&self) -> Vec<(String, String)> {
+        self.items
+            .iter()
+            .filter(|item| !item.is_local)
+            .filter_map(|item| {
+                let track = item.track.as_ref()?;
+                let user = item.added_by.as_ref()?;
+                Some((track.id.clone(), user.id.clone()))
+            })
+            .collect()
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -123,6 +259,12 @@ pub struct SavedAlbum {
     pub album: Album,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct SavedTrack {
+    pub track: TrackItem,
+    pub added_at: String,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct Album {
     pub id: String,
@@ -130,6 +272,16 @@ pub struct Album {
     pub artists: Vec<Artist>,
     pub name: String,
     pub images: Vec<Image>,
+    // Only in full album objects
+    pub release_date: Option<String>,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub copyrights: Vec<Copyright>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Copyright {
+    pub text: String,
 }
 
 impl WithImages for Album {
@@ -172,6 +324,43 @@ pub struct TopTracks {
     pub tracks: Vec<TrackItem>,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelatedArtists {
+    pub artists: Vec<Artist>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TrackList {
+    // In the same order as the requested ids, null for tracks that can't be found
+    pub tracks: Vec<Option<TrackItem>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayHistory {
+    pub track: TrackItem,
+    pub played_at: String,
+    // Missing for songs played outside of an album, playlist, artist or show
+    pub context: Option<PlayContext>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayContext {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub uri: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    pub id: String,
+    pub display_name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Recommendations {
+    pub tracks: Vec<TrackItem>,
+}
+
 impl<Item> Default for Tracks<Item> {
     fn default() -> Self {
         Self { items: vec![] }
@@ -185,12 +374,54 @@ pub struct TrackItem {
     pub duration_ms: i64,
     pub artists: Vec<Artist>,
     pub album: Option<Album>,
+    #[serde(default)]
+    pub explicit: bool,
+    // Missing from the tracks of an album
+    pub popularity: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AudioFeatures {
+    pub energy: f32,
+    pub valence: f32,
+    pub danceability: f32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AudioFeaturesList {
+    // In the same order as the requested ids, null for tracks without features
+    pub audio_features: Vec<Option<AudioFeatures>>,
+}
+
+// Credits aren't part of the Web API, they come from what the Spotify clients use
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackCredits {
+    #[serde(default)]
+    pub role_credits: Vec<RoleCredits>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleCredits {
+    pub role_title: String,
+    pub artists: Vec<CreditedArtist>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreditedArtist {
+    pub name: String,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct RawSearchResults {
     pub albums: Option<Page<Album>>,
     pub artists: Option<Page<Artist>>,
+    pub tracks: Option<Page<TrackItem>>,
+    // Results that are no longer available are sometimes listed as null
+    pub playlists: Option<Page<Option<Playlist>>>,
+    pub shows: Option<Page<Option<Podcast>>>,
+    pub episodes: Option<Page<Option<Episode>>>,
 }
 
 impl Into<ArtistSummary> for Artist {
@@ -206,18 +437,167 @@ impl Into<Vec<SongDescription>> for Page<PlaylistTrack> {
         let items = self
             .items
             .into_iter()
-            .filter_map(|PlaylistTrack { is_local, track }| track.filter(|_| !is_local))
+            .filter(|item| !item.is_local)
+            .filter_map(|item| item.track)
             .collect::<Vec<TrackItem>>();
         Tracks { items }.into()
     }
 }
 
+impl Into<Vec<SongDescription>> for Page<SavedTrack> {
+    fn into(self) -> Vec<SongDescription> {
+        let added_at = self
+            .items
+            .iter()
+            .map(|saved| parse_utc_timestamp(&saved.added_at))
+            .collect::<Vec<Option<i64>>>();
+        let items = self
+            .items
+            .into_iter()
+            .map(|saved| saved.track)
+            .collect::<Vec<TrackItem>>();
+        let songs: Vec<SongDescription> = Tracks { items }.into();
+        songs
+            .into_iter()
+            .zip(added_at)
+            .map(|(song, added_at)| SongDescription { added_at, ..song })
+            .collect()
+    }
+}
+
+// Seconds since the epoch for a UTC timestamp such as 2021-03-14T15:09:26.535Z
+pub fn parse_utc_timestamp(timestamp: &str) -> Option<i64> {
+    let mut parts = timestamp.trim_end_matches('Z').splitn(2, 'T');
+    let date = parts
+        .next()?
+        .split('-')
+        .map(|p| p.parse::<i64>().ok())
+        .collect::<Option<Vec<i64>>>()?;
+    let time = parts
+        .next()?
+        .split(':')
+        .map(|p| p.parse::<f64>().ok())
+        .collect::<Option<Vec<f64>>>()?;
+    match (&date[..], &time[..]) {
+        (&[year, month, day], &[hours, minutes, seconds]) => {
+            // Days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+            let year = if month <= 2 { year - 1 } else { year };
+            let era = year.div_euclid(400);
+            let year_of_era = year - era * 400;
+            let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+            let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+            let days = era * 146_097 + day_of_era - 719_468;
+            Some(days * 86400 + hours as i64 * 3600 + minutes as i64 * 60 + seconds as i64)
+        }
+        _ => None,
+    }
+}
+
+impl Into<Vec<PlayedSong>> for Page<PlayHistory> {
+    fn into(self) -> Vec<PlayedSong> {
+        let played_at = self
+            .items
+            .iter()
+            .map(|item| parse_utc_timestamp(&item.played_at))
+            .collect::<Vec<Option<i64>>>();
+        let items = self
+            .items
+            .into_iter()
+            .map(|item| item.track)
+            .collect::<Vec<TrackItem>>();
+        let songs: Vec<SongDescription> = Tracks { items }.into();
+        songs
+            .into_iter()
+            .zip(played_at)
+            .filter_map(|(song, played_at)| {
+                Some(PlayedSong {
+                    song,
+                    played_at: played_at?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Page<PlayHistory> {
+    // Most recent first, songs played outside of a playlist or show count as played from their album.
+    // Only albums are described here, the names of playlists and shows have to be fetched
+    pub fn recent_contexts(&self) -> Vec<RecentContext> {
+        let mut contexts: Vec<RecentContext> = vec![];
+        for item in self.items.iter() {
+            let kind = item.context.as_ref().map(|c| &c.kind[..]);
+            let id = item
+                .context
+                .as_ref()
+                .and_then(|c| c.uri.rsplit(':').next())
+                .map(|id| id.to_string());
+            let context = match (kind, id) {
+                (Some("playlist"), Some(id)) => Some(RecentContext {
+                    id,
+                    kind: RecentKind::Playlist,
+                    title: String::new(),
+                    subtitle: String::new(),
+                    art: None,
+                }),
+                (Some("show"), Some(id)) => Some(RecentContext {
+                    id,
+                    kind: RecentKind::Show,
+                    title: String::new(),
+                    subtitle: String::new(),
+                    art: None,
+                }),
+                _ => item.track.album.as_ref().map(|album| RecentContext {
+                    id: album.id.clone(),
+                    kind: RecentKind::Album,
+                    title: album.name.clone(),
+                    subtitle: album
+                        .artists
+                        .iter()
+                        .map(|a| a.name.clone())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    art: album.best_image_for_width(200).map(|i| i.url.clone()),
+                }),
+            };
+            if let Some(context) = context {
+                if !contexts.iter().any(|c| c.id == context.id) {
+                    contexts.push(context);
+                }
+            }
+        }
+        contexts
+    }
+}
+
 impl Into<Vec<SongDescription>> for TopTracks {
     fn into(self) -> Vec<SongDescription> {
         Tracks { items: self.tracks }.into()
     }
 }
 
+impl Into<Vec<ArtistSummary>> for RelatedArtists {
+    fn into(self) -> Vec<ArtistSummary> {
+        self.artists.into_iter().map(|a| a.into()).collect()
+    }
+}
+
+impl Into<Vec<SongDescription>> for TrackList {
+    fn into(self) -> Vec<SongDescription> {
+        let items = self
+            .tracks
+            .into_iter()
+            .flatten()
+            .collect::<Vec<TrackItem>>();
+        Tracks { items }.into()
+    }
+}
+
+impl Into<Vec<SongDescription>> for Recommendations {
+    fn into(self) -> Vec<SongDescription> {
+        Tracks { items: self.tracks }.into()
+    }
+}
+
 impl Into<Vec<SongDescription>> for Tracks<TrackItem> {
     fn into(self) -> Vec<SongDescription> {
         self.items
@@ -229,6 +609,8 @@ impl Into<Vec<SongDescription>> for Tracks<TrackItem> {
                      id,
                      name,
                      duration_ms,
+                     explicit,
+                     popularity,
                  }| {
                     let artists = artists
                         .into_iter()
@@ -257,6 +639,9 @@ impl Into<Vec<SongDescription>> for Tracks<TrackItem> {
                         album: album_ref,
                         duration: duration_ms as u32,
                         art,
+                        explicit,
+                        popularity,
+                        added_at: None,
                     }
                 },
             )
@@ -291,6 +676,9 @@ impl Into<Vec<SongDescription>> for Album {
                     album: album_ref.clone(),
                     duration: item.duration_ms as u32,
                     art: art.clone(),
+                    explicit: item.explicit,
+                    popularity: item.popularity,
+                    added_at: None,
                 }
             })
             .collect()
@@ -309,6 +697,12 @@ impl Into<AlbumDescription> for Album {
             .collect::<Vec<ArtistRef>>();
         let songs: Vec<SongDescription> = self.clone().into();
         let art = self.best_image_for_width(200).map(|i| i.url.clone());
+        let copyright = self
+            .copyrights
+            .iter()
+            .map(|c| c.text.clone())
+            .collect::<Vec<String>>()
+            .join("\n");
 
         AlbumDescription {
             id: self.id,
@@ -317,6 +711,9 @@ impl Into<AlbumDescription> for Album {
             art,
             songs,
             is_liked: false,
+            release_date: self.release_date.filter(|date| !date.is_empty()),
+            label: self.label.filter(|label| !label.is_empty()),
+            copyright: Some(copyright).filter(|copyright| !copyright.is_empty()),
         }
     }
 }
@@ -328,6 +725,9 @@ impl Into<PlaylistDescription> for Playlist {
         PlaylistDescription {
             id: self.id,
             title: self.name,
+            description: self.description.unwrap_or_default(),
+            collaborative: self.collaborative,
+            added_by: HashMap::new(),
             art,
             songs: vec![],
             owner: UserRef { id, display_name },
@@ -335,6 +735,48 @@ impl Into<PlaylistDescription> for Playlist {
     }
 }
 
+impl Into<Vec<Option<SongFeatures>>> for AudioFeaturesList {
+    fn into(self) -> Vec<Option<SongFeatures>> {
+        self.audio_features
+            .into_iter()
+            .map(|f| {
+                f.map(|f| SongFeatures {
+                    energy: f.energy,
+                    valence: f.valence,
+                    danceability: f.danceability,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Into<SongCredits> for TrackCredits {
+    fn into(self) -> SongCredits {
+        let mut credits = SongCredits::default();
+        for role in self.role_credits {
+            let names = role.artists.into_iter().map(|a| a.name);
+            match &role.role_title.to_lowercase()[..] {
+                "performers" => credits.performers.extend(names),
+                "writers" => credits.writers.extend(names),
+                "producers" => credits.producers.extend(names),
+                _ => {}
+            }
+        }
+        credits
+    }
+}
+
+impl Into<CategoryDescription> for Category {
+    fn into(self) -> CategoryDescription {
+        let icon = self.best_image_for_width(200).map(|i| i.url.clone());
+        CategoryDescription {
+            id: self.id,
+            name: self.name,
+            icon,
+        }
+    }
+}
+
 impl Into<PodcastDescription> for Podcast {
     fn into(self) -> PodcastDescription {
         let art = self.best_image_for_width(200).map(|i| i.url.clone());
@@ -342,7 +784,22 @@ impl Into<PodcastDescription> for Podcast {
             id: self.id,
             name: self.name,
             art,
-            publisher: self.publisher
+            publisher: self.publisher,
+        }
+    }
+}
+
+impl Into<ShowDescription> for Podcast {
+    fn into(self) -> ShowDescription {
+        let art = self.best_image_for_width(300).map(|i| i.url.clone());
+        ShowDescription {
+            id: self.id,
+            name: self.name,
+            publisher: self.publisher,
+            description: self.description,
+            art,
+            episodes: vec![],
+            is_followed: false,
         }
     }
 }
@@ -90,7 +90,7 @@ impl CacheManager {
         Some(Self { root })
     }
 
-    fn cache_path(&self, resource: &str) -> PathBuf {
+    pub fn cache_path(&self, resource: &str) -> PathBuf {
         self.root.join(resource)
     }
 
@@ -3,6 +3,7 @@ use futures::{join, FutureExt};
 use regex::Regex;
 use serde::de::DeserializeOwned;
 use serde_json::from_slice;
+use std::collections::HashMap;
 use std::convert::Into;
 use std::future::Future;
 
@@ -13,13 +14,36 @@ use crate::app::models::*;
 
 lazy_static! {
     static ref ME_ALBUMS_CACHE: Regex = Regex::new(r"^me_albums_\w+_\w+\.json\.expiry$").unwrap();
-    pub static ref ALL_CACHE: Regex =
-        Regex::new(r"^(me_albums_|me_playlists_|album_|playlist_|artist_)\w+\.json(\.expiry)?$")
-            .unwrap();
+    static ref ME_TRACKS_CACHE: Regex = Regex::new(r"^me_tracks_\w+_\w+\.json\.expiry$").unwrap();
+    static ref ME_PLAYLISTS_CACHE: Regex =
+        Regex::new(r"^me_playlists_\w+_\w+\.json\.expiry$").unwrap();
+    static ref ME_FOLLOWING_CACHE: Regex =
+        Regex::new(r"^me_following_\w+\.json\.expiry$").unwrap();
+    static ref ME_PODCASTS_CACHE: Regex =
+        Regex::new(r"^me_podcasts_\w+_\w+\.json\.expiry$").unwrap();
+    pub static ref ALL_CACHE: Regex = Regex::new(
+        r"^(me_albums_|me_tracks_|me_playlists_|me_following_|album_|playlist_|artist_|show_|user_)\w+\.json(\.expiry)?$"
+    )
+    .unwrap();
 }
 
 pub type SpotifyResult<T> = Result<T, SpotifyApiError>;
 
+// The browse category holding the playlists personalized for the user
+const MADE_FOR_YOU_CATEGORY: &str = "made-for-x";
+
+// Personalized playlists Spotify creates for every user
+const MADE_FOR_YOU_MIXES: [&str; 4] =
+    ["Daily Mix", "Discover Weekly", "Release Radar", "On Repeat"];
+
+fn playlist_cache(id: &str) -> Regex {
+    Regex::new(&format!(
+        r"^playlist_(item_)?{}(_\w+_\w+)?\.json\.expiry$",
+        regex::escape(id)
+    ))
+    .unwrap()
+}
+
 pub trait SpotifyApiClient {
     fn get_artist(&self, id: &str) -> BoxFuture<SpotifyResult<ArtistDescription>>;
 
@@ -27,6 +51,9 @@ pub trait SpotifyApiClient {
 
     fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
 
+    // Skips the cache, to pick up changes made elsewhere
+    fn refresh_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
+
     fn get_saved_albums(
         &self,
         offset: u32,
@@ -37,6 +64,20 @@ pub trait SpotifyApiClient {
 
     fn remove_saved_album(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
 
+    fn get_followed_artists(&self) -> BoxFuture<SpotifyResult<Vec<ArtistSummary>>>;
+
+    fn follow_artist(&self, id: &str) -> BoxFuture<SpotifyResult<ArtistSummary>>;
+
+    fn unfollow_artist(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
+
+    fn get_saved_tracks(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
+    fn remove_saved_track(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
+
     fn get_saved_playlists(
         &self,
         offset: u32,
@@ -49,57 +90,202 @@ pub trait SpotifyApiClient {
         limit: u32,
     ) -> BoxFuture<SpotifyResult<Vec<PodcastDescription>>>;
 
+    fn get_show(&self, id: &str) -> BoxFuture<SpotifyResult<ShowDescription>>;
+
+    fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<EpisodeDescription>>>;
+
+    fn save_show(&self, id: &str) -> BoxFuture<SpotifyResult<PodcastDescription>>;
+
+    fn remove_saved_show(&self, id: &str) -> BoxFuture<SpotifyResult<()>>;
+
+    fn get_new_releases(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>>;
+
+    fn get_categories(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<CategoryDescription>>>;
+
+    fn get_category_playlists(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>>;
+
+    fn get_made_for_you(&self) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>>;
+
+    fn get_charts(
+        &self,
+        country: Option<&str>,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>>;
+
+    fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+        track_ids: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<PlaylistDescription>>;
+
+    fn add_to_playlist(&self, id: &str, track_ids: Vec<String>) -> BoxFuture<SpotifyResult<()>>;
+
+    // Tracks are given with their position in the playlist
+    fn remove_from_playlist(
+        &self,
+        id: &str,
+        tracks: Vec<(String, usize)>,
+    ) -> BoxFuture<SpotifyResult<()>>;
+
+    fn move_playlist_track(&self, id: &str, from: usize, to: usize)
+        -> BoxFuture<SpotifyResult<()>>;
+
+    fn update_playlist_details(
+        &self,
+        id: &str,
+        name: &str,
+        description: &str,
+    ) -> BoxFuture<SpotifyResult<()>>;
+
+    // At most 256 KB of base64 encoded JPEG data
+    fn upload_playlist_image(&self, id: &str, image: String) -> BoxFuture<SpotifyResult<()>>;
+
+    // Only the results of the filtered type are there, unless the filter is All
     fn search(
         &self,
         query: &str,
+        filter: SearchFilter,
         offset: u32,
         limit: u32,
     ) -> BoxFuture<SpotifyResult<SearchResults>>;
 
+    fn search_tracks(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
     fn get_artist_albums(
         &self,
         id: &str,
+        group: ReleaseGroup,
         offset: u32,
         limit: u32,
     ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>>;
 
+    fn get_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
+    // Up to 5 seed tracks
+    fn get_recommendations(
+        &self,
+        seed_ids: Vec<String>,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
+    fn get_artist_recommendations(
+        &self,
+        artist_id: &str,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>>;
+
+    fn get_audio_features(
+        &self,
+        ids: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<Vec<(String, Option<SongFeatures>)>>>;
+
+    fn get_track_credits(&self, id: &str) -> BoxFuture<SpotifyResult<SongCredits>>;
+
+    fn get_recently_played(&self, limit: u32) -> BoxFuture<SpotifyResult<Vec<PlayedSong>>>;
+
+    fn get_recently_played_contexts(
+        &self,
+        count: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<RecentContext>>>;
+
     fn update_token(&self, token: String);
 }
 
 enum SpotCacheKey<'a> {
     SavedAlbums(u32, u32),
+    SavedTracks(u32, u32),
     SavedPlaylists(u32, u32),
     SavedPodcasts(u32, u32),
+    NewReleases(u32, u32),
+    Categories(u32, u32),
+    CategoryPlaylists(&'a str, u32, u32),
+    // The country code, or "global"
+    Charts(&'a str),
     Album(&'a str),
     AlbumLiked(&'a str),
     Playlist(&'a str),
+    Show(&'a str),
+    ShowEpisodes(&'a str, u32, u32),
+    ShowSaved(&'a str),
     PlaylistTracks(&'a str, u32, u32),
-    ArtistAlbums(&'a str, u32, u32),
+    ArtistAlbums(&'a str, ReleaseGroup, u32, u32),
     Artist(&'a str),
     ArtistTopTracks(&'a str),
+    RelatedArtists(&'a str),
+    ArtistFollowed(&'a str),
+    TrackCredits(&'a str),
+    // The cursor of the page, the id of the last artist of the page before
+    FollowedArtists(&'a str),
+    User(&'a str),
 }
 
 impl<'a> SpotCacheKey<'a> {
     fn into_raw(self) -> String {
         match self {
             Self::SavedAlbums(offset, limit) => format!("me_albums_{}_{}.json", offset, limit),
+            Self::SavedTracks(offset, limit) => format!("me_tracks_{}_{}.json", offset, limit),
             Self::SavedPlaylists(offset, limit) => {
                 format!("me_playlists_{}_{}.json", offset, limit)
             }
             Self::SavedPodcasts(offset, limit) => {
                 format!("me_podcasts_{}_{}.json", offset, limit)
             }
+            Self::NewReleases(offset, limit) => {
+                format!("new_releases_{}_{}.json", offset, limit)
+            }
+            Self::Categories(offset, limit) => format!("categories_{}_{}.json", offset, limit),
+            Self::CategoryPlaylists(id, offset, limit) => {
+                format!("category_playlists_{}_{}_{}.json", id, offset, limit)
+            }
+            Self::Charts(country) => format!("charts_{}.json", country),
             Self::Album(id) => format!("album_{}.json", id),
             Self::AlbumLiked(id) => format!("album_liked_{}.json", id),
             Self::Playlist(id) => format!("playlist_{}.json", id),
+            Self::Show(id) => format!("show_{}.json", id),
+            Self::ShowEpisodes(id, offset, limit) => {
+                format!("show_episodes_{}_{}_{}.json", id, offset, limit)
+            }
+            Self::ShowSaved(id) => format!("show_saved_{}.json", id),
             Self::PlaylistTracks(id, offset, limit) => {
                 format!("playlist_item_{}_{}_{}.json", id, offset, limit)
             }
-            Self::ArtistAlbums(id, offset, limit) => {
-                format!("artist_albums_{}_{}_{}.json", id, offset, limit)
-            }
+            Self::ArtistAlbums(id, group, offset, limit) => format!(
+                "artist_albums_{}_{}_{}_{}.json",
+                id,
+                group.api_name(),
+                offset,
+                limit
+            ),
             Self::Artist(id) => format!("artist_{}.json", id),
             Self::ArtistTopTracks(id) => format!("artist_top_tracks_{}.json", id),
+            Self::RelatedArtists(id) => format!("artist_related_{}.json", id),
+            Self::ArtistFollowed(id) => format!("artist_followed_{}.json", id),
+            Self::TrackCredits(id) => format!("track_credits_{}.json", id),
+            Self::FollowedArtists(after) => format!("me_following_{}.json", after),
+            Self::User(id) => format!("user_{}.json", id),
         }
     }
 }
@@ -163,6 +349,36 @@ impl CachedSpotifyClient {
         let result = from_slice::<T>(&raw);
         Ok(result?)
     }
+
+    // Maps song ids to the name of whoever added them, falling back to their user id
+    async fn name_users(
+        &self,
+        owner: &UserRef,
+        added_by: Vec<(String, String)>,
+    ) -> HashMap<String, String> {
+        let mut names = HashMap::new();
+        names.insert(owner.id.clone(), owner.display_name.clone());
+
+        let mut result = HashMap::new();
+        for (song_id, user_id) in added_by {
+            if !names.contains_key(&user_id) {
+                let user: SpotifyResult<User> = self
+                    .cache_get_or_write(SpotCacheKey::User(&user_id), None, |etag| {
+                        self.client.get_user(&user_id).etag(etag).send()
+                    })
+                    .await;
+                let name = user
+                    .ok()
+                    .and_then(|user| user.display_name)
+                    .unwrap_or_else(|| user_id.clone());
+                names.insert(user_id.clone(), name);
+            }
+            result
+                .entry(song_id)
+                .or_insert_with(|| names[&user_id].clone());
+        }
+        result
+    }
 }
 
 impl SpotifyApiClient for CachedSpotifyClient {
@@ -195,6 +411,92 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_followed_artists(&self) -> BoxFuture<SpotifyResult<Vec<ArtistSummary>>> {
+        Box::pin(async move {
+            let mut artists: Vec<ArtistSummary> = vec![];
+            let mut after: Option<String> = None;
+            loop {
+                let key = SpotCacheKey::FollowedArtists(after.as_deref().unwrap_or("start"));
+                let page = self
+                    .cache_get_or_write(key, None, |etag| {
+                        self.client
+                            .get_followed_artists(after.as_deref(), 50)
+                            .etag(etag)
+                            .send()
+                    })
+                    .await?
+                    .artists;
+                artists.extend(page.items.into_iter().map(|artist| artist.into()));
+                match (page.next, page.cursors.after) {
+                    (Some(_), Some(cursor)) => after = Some(cursor),
+                    _ => break,
+                }
+            }
+            Ok(artists)
+        })
+    }
+
+    fn follow_artist(&self, id: &str) -> BoxFuture<SpotifyResult<ArtistSummary>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_FOLLOWING_CACHE)
+                .await
+                .unwrap_or(());
+            self.client.follow_artist(&id).send_no_response().await?;
+            let artist = self
+                .cache_get_or_write(SpotCacheKey::Artist(&id), None, |etag| {
+                    self.client.get_artist(&id).etag(etag).send()
+                })
+                .await?;
+            Ok(artist.into())
+        })
+    }
+
+    fn unfollow_artist(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_FOLLOWING_CACHE)
+                .await
+                .unwrap_or(());
+            self.client.unfollow_artist(&id).send_no_response().await
+        })
+    }
+
+    fn get_saved_tracks(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(SpotCacheKey::SavedTracks(offset, limit), None, |etag| {
+                    self.client
+                        .get_saved_tracks(offset, limit)
+                        .etag(etag)
+                        .send()
+                })
+                .await?;
+
+            Ok(page.into())
+        })
+    }
+
+    fn remove_saved_track(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_TRACKS_CACHE)
+                .await
+                .unwrap_or(());
+            self.client.remove_saved_track(&id).send_no_response().await
+        })
+    }
+
     fn get_saved_playlists(
         &self,
         offset: u32,
@@ -245,6 +547,244 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn get_show(&self, id: &str) -> BoxFuture<SpotifyResult<ShowDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let show = self.cache_get_or_write(SpotCacheKey::Show(&id), None, |etag| {
+                self.client.get_show(&id).etag(etag).send()
+            });
+
+            let episodes = self.get_show_episodes(&id, 0, 20);
+
+            let saved = self.cache_get_or_write(
+                SpotCacheKey::ShowSaved(&id),
+                Some(CachePolicy::AlwaysRevalidate),
+                |etag| self.client.is_show_saved(&id).etag(etag).send(),
+            );
+
+            let (show, episodes, saved) = join!(show, episodes, saved);
+
+            let mut show: ShowDescription = show?.into();
+            show.episodes = episodes?;
+            // Not knowing shouldn't keep the show from showing
+            show.is_followed = saved
+                .ok()
+                .and_then(|saved| saved.first().copied())
+                .unwrap_or(false);
+
+            Ok(show)
+        })
+    }
+
+    fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<EpisodeDescription>>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(
+                    SpotCacheKey::ShowEpisodes(&id, offset, limit),
+                    None,
+                    |etag| {
+                        self.client
+                            .get_show_episodes(&id, offset, limit)
+                            .etag(etag)
+                            .send()
+                    },
+                )
+                .await?;
+
+            let episodes = page
+                .items
+                .into_iter()
+                .map(|episode| episode.into())
+                .collect::<Vec<EpisodeDescription>>();
+
+            Ok(episodes)
+        })
+    }
+
+    fn save_show(&self, id: &str) -> BoxFuture<SpotifyResult<PodcastDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_PODCASTS_CACHE)
+                .await
+                .unwrap_or(());
+            self.client.save_show(&id).send_no_response().await?;
+            let show = self
+                .cache_get_or_write(SpotCacheKey::Show(&id), None, |etag| {
+                    self.client.get_show(&id).etag(etag).send()
+                })
+                .await?;
+            Ok(show.into())
+        })
+    }
+
+    fn remove_saved_show(&self, id: &str) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_PODCASTS_CACHE)
+                .await
+                .unwrap_or(());
+            self.client.remove_saved_show(&id).send_no_response().await
+        })
+    }
+
+    fn get_new_releases(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>> {
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(SpotCacheKey::NewReleases(offset, limit), None, |etag| {
+                    self.client
+                        .get_new_releases(offset, limit)
+                        .etag(etag)
+                        .send()
+                })
+                .await?;
+
+            let albums = page
+                .albums
+                .items
+                .into_iter()
+                .map(|album| album.into())
+                .collect::<Vec<AlbumDescription>>();
+
+            Ok(albums)
+        })
+    }
+
+    fn get_categories(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<CategoryDescription>>> {
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(SpotCacheKey::Categories(offset, limit), None, |etag| {
+                    self.client.get_categories(offset, limit).etag(etag).send()
+                })
+                .await?;
+
+            let categories = page
+                .categories
+                .items
+                .into_iter()
+                .map(|category| category.into())
+                .collect::<Vec<CategoryDescription>>();
+
+            Ok(categories)
+        })
+    }
+
+    fn get_category_playlists(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let page = self
+                .cache_get_or_write(
+                    SpotCacheKey::CategoryPlaylists(&id, offset, limit),
+                    None,
+                    |etag| {
+                        self.client
+                            .get_category_playlists(&id, offset, limit)
+                            .etag(etag)
+                            .send()
+                    },
+                )
+                .await?;
+
+            let playlists = page
+                .playlists
+                .items
+                .into_iter()
+                .flatten()
+                .map(|playlist| playlist.into())
+                .collect::<Vec<PlaylistDescription>>();
+
+            Ok(playlists)
+        })
+    }
+
+    // Daily mixes are listed in their own category, while the weekly ones
+    // are only found among the playlists the user follows
+    fn get_made_for_you(&self) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>> {
+        Box::pin(async move {
+            let mut playlists = self
+                .get_category_playlists(MADE_FOR_YOU_CATEGORY, 0, 50)
+                .await?;
+
+            // Mixes can be anywhere among the saved playlists, so all of them are looked at
+            let mut offset = 0u32;
+            let limit = 50u32;
+            loop {
+                let followed = self.get_saved_playlists(offset, limit).await?;
+                let loaded = followed.len() as u32;
+                for playlist in followed {
+                    let is_mix = playlist.owner.id == "spotify"
+                        && MADE_FOR_YOU_MIXES
+                            .iter()
+                            .any(|mix| playlist.title.starts_with(mix));
+                    if is_mix && !playlists.iter().any(|p| p.id == playlist.id) {
+                        playlists.push(playlist);
+                    }
+                }
+
+                if loaded < limit {
+                    break;
+                }
+
+                offset += limit;
+            }
+            Ok(playlists)
+        })
+    }
+
+    fn get_charts(
+        &self,
+        country: Option<&str>,
+    ) -> BoxFuture<SpotifyResult<Vec<PlaylistDescription>>> {
+        let country = country.map(|c| c.to_owned());
+
+        Box::pin(async move {
+            let key = country.as_deref().unwrap_or("global");
+            let page = self
+                .cache_get_or_write(SpotCacheKey::Charts(key), None, |etag| {
+                    self.client
+                        .get_chart_playlists(country.as_deref(), 0, 50)
+                        .etag(etag)
+                        .send()
+                })
+                .await?;
+
+            let playlists = page
+                .playlists
+                .items
+                .into_iter()
+                .flatten()
+                .map(|playlist| playlist.into())
+                .collect::<Vec<PlaylistDescription>>();
+
+            Ok(playlists)
+        })
+    }
+
     fn get_album(&self, id: &str) -> BoxFuture<SpotifyResult<AlbumDescription>> {
         let id = id.to_owned();
 
@@ -293,6 +833,175 @@ impl SpotifyApiClient for CachedSpotifyClient {
         })
     }
 
+    fn create_playlist(
+        &self,
+        name: &str,
+        description: &str,
+        public: bool,
+        track_ids: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        let name = name.to_owned();
+        let description = description.to_owned();
+
+        Box::pin(async move {
+            let user = self
+                .client
+                .get_current_user()
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+
+            let playlist: PlaylistDescription = self
+                .client
+                .create_playlist(&user.id, &name, &description, public)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?
+                .into();
+
+            // The endpoint accepts at most 100 tracks at once
+            let uris = track_ids
+                .iter()
+                .map(|id| format!("spotify:track:{}", id))
+                .collect::<Vec<String>>();
+            for chunk in uris.chunks(100) {
+                self.client
+                    .add_to_playlist(&playlist.id, chunk)
+                    .send_no_response()
+                    .await?;
+            }
+
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_PLAYLISTS_CACHE)
+                .await
+                .unwrap_or(());
+            Ok(playlist)
+        })
+    }
+
+    fn add_to_playlist(&self, id: &str, track_ids: Vec<String>) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+
+            // The endpoint accepts at most 100 tracks at once
+            let uris = track_ids
+                .iter()
+                .map(|id| format!("spotify:track:{}", id))
+                .collect::<Vec<String>>();
+            for chunk in uris.chunks(100) {
+                self.client
+                    .add_to_playlist(&id, chunk)
+                    .send_no_response()
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove_from_playlist(
+        &self,
+        id: &str,
+        mut tracks: Vec<(String, usize)>,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+
+            // The endpoint accepts at most 100 tracks at once, removing the last ones first
+            // keeps the positions of the following chunks valid
+            tracks.sort_by(|a, b| b.1.cmp(&a.1));
+            let uris = tracks
+                .iter()
+                .map(|(id, position)| (format!("spotify:track:{}", id), *position))
+                .collect::<Vec<(String, usize)>>();
+            for chunk in uris.chunks(100) {
+                self.client
+                    .remove_from_playlist(&id, chunk)
+                    .send_no_response()
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn move_playlist_track(
+        &self,
+        id: &str,
+        from: usize,
+        to: usize,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+        // Spotify inserts the track before the given position, counted before the move
+        let insert_before = if to > from { to + 1 } else { to };
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+            self.client
+                .reorder_playlist_tracks(&id, from, insert_before)
+                .send_no_response()
+                .await
+        })
+    }
+
+    fn update_playlist_details(
+        &self,
+        id: &str,
+        name: &str,
+        description: &str,
+    ) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+        let name = name.to_owned();
+        let description = description.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_PLAYLISTS_CACHE)
+                .await
+                .unwrap_or(());
+            self.client
+                .update_playlist_details(&id, &name, &description)
+                .send_no_response()
+                .await
+        })
+    }
+
+    fn upload_playlist_image(&self, id: &str, image: String) -> BoxFuture<SpotifyResult<()>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+            self.cache
+                .set_expired_pattern("spot/net", &*ME_PLAYLISTS_CACHE)
+                .await
+                .unwrap_or(());
+            self.client
+                .upload_playlist_image(&id, image)
+                .send_no_response()
+                .await
+        })
+    }
+
     fn get_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
         let id = id.to_owned();
 
@@ -305,6 +1014,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
 
             let mut playlist: PlaylistDescription = playlist.into();
             let mut tracks: Vec<SongDescription> = vec![];
+            let mut added_by: Vec<(String, String)> = vec![];
 
             let mut offset = 0u32;
             let limit = 100u32;
@@ -322,6 +1032,9 @@ impl SpotifyApiClient for CachedSpotifyClient {
                     )
                     .await?;
 
+                if playlist.collaborative {
+                    added_by.append(&mut songs.added_by());
+                }
                 let mut songs: Vec<SongDescription> = songs.into();
 
                 let songs_loaded = songs.len() as u32;
@@ -335,13 +1048,27 @@ impl SpotifyApiClient for CachedSpotifyClient {
             }
 
             playlist.songs = tracks;
+            playlist.added_by = self.name_users(&playlist.owner, added_by).await;
             Ok(playlist)
         })
     }
 
+    fn refresh_playlist(&self, id: &str) -> BoxFuture<SpotifyResult<PlaylistDescription>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            self.cache
+                .set_expired_pattern("spot/net", &playlist_cache(&id))
+                .await
+                .unwrap_or(());
+            self.get_playlist(&id).await
+        })
+    }
+
     fn get_artist_albums(
         &self,
         id: &str,
+        group: ReleaseGroup,
         offset: u32,
         limit: u32,
     ) -> BoxFuture<SpotifyResult<Vec<AlbumDescription>>> {
@@ -350,11 +1077,11 @@ impl SpotifyApiClient for CachedSpotifyClient {
         Box::pin(async move {
             let albums = self
                 .cache_get_or_write(
-                    SpotCacheKey::ArtistAlbums(&id, offset, limit),
+                    SpotCacheKey::ArtistAlbums(&id, group, offset, limit),
                     None,
                     |etag| {
                         self.client
-                            .get_artist_albums(&id, offset, limit)
+                            .get_artist_albums(&id, group.api_name(), offset, limit)
                             .etag(etag)
                             .send()
                     },
@@ -379,21 +1106,65 @@ impl SpotifyApiClient for CachedSpotifyClient {
                 self.client.get_artist(&id).etag(etag).send()
             });
 
-            let albums = self.get_artist_albums(&id, 0, 20);
+            let albums = self.get_artist_albums(&id, ReleaseGroup::Albums, 0, 20);
+            let singles = self.get_artist_albums(&id, ReleaseGroup::Singles, 0, 20);
+            let compilations = self.get_artist_albums(&id, ReleaseGroup::Compilations, 0, 20);
+            let appears_on = self.get_artist_albums(&id, ReleaseGroup::AppearsOn, 0, 20);
 
             let top_tracks =
                 self.cache_get_or_write(SpotCacheKey::ArtistTopTracks(&id), None, |etag| {
                     self.client.get_artist_top_tracks(&id).etag(etag).send()
                 });
 
-            let (artist, albums, top_tracks) = join!(artist, albums, top_tracks);
+            let related_artists =
+                self.cache_get_or_write(SpotCacheKey::RelatedArtists(&id), None, |etag| {
+                    self.client.get_related_artists(&id).etag(etag).send()
+                });
+
+            let followed = self.cache_get_or_write(
+                SpotCacheKey::ArtistFollowed(&id),
+                Some(CachePolicy::AlwaysRevalidate),
+                |etag| self.client.is_artist_followed(&id).etag(etag).send(),
+            );
+
+            let (
+                artist,
+                albums,
+                singles,
+                compilations,
+                appears_on,
+                top_tracks,
+                related_artists,
+                followed,
+            ) = join!(
+                artist,
+                albums,
+                singles,
+                compilations,
+                appears_on,
+                top_tracks,
+                related_artists,
+                followed
+            );
 
             let artist = artist?;
+            // The other shelves are left empty rather than failing the whole page,
+            // related artists in particular come from a deprecated endpoint
             let result = ArtistDescription {
                 id: artist.id,
                 name: artist.name,
                 albums: albums?,
+                singles: singles.unwrap_or_default(),
+                compilations: compilations.unwrap_or_default(),
+                appears_on: appears_on.unwrap_or_default(),
                 top_tracks: top_tracks?.into(),
+                related_artists: related_artists
+                    .map(|artists| artists.into())
+                    .unwrap_or_default(),
+                is_followed: followed
+                    .ok()
+                    .and_then(|followed| followed.first().copied())
+                    .unwrap_or(false),
             };
             Ok(result)
         })
@@ -402,6 +1173,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
     fn search(
         &self,
         query: &str,
+        filter: SearchFilter,
         offset: u32,
         limit: u32,
     ) -> BoxFuture<SpotifyResult<SearchResults>> {
@@ -410,7 +1182,7 @@ impl SpotifyApiClient for CachedSpotifyClient {
         Box::pin(async move {
             let results = self
                 .client
-                .search(query, offset, limit)
+                .search(query, SearchType::for_filter(filter), offset, limit)
                 .send()
                 .await?
                 .deserialize()
@@ -432,7 +1204,226 @@ impl SpotifyApiClient for CachedSpotifyClient {
                 .map(|saved| saved.into())
                 .collect::<Vec<ArtistSummary>>();
 
-            Ok(SearchResults { albums, artists })
+            let songs: Vec<SongDescription> = Tracks {
+                items: results.tracks.unwrap_or_else(Page::empty).items,
+            }
+            .into();
+
+            let playlists = results
+                .playlists
+                .unwrap_or_else(Page::empty)
+                .items
+                .into_iter()
+                .flatten()
+                .map(|playlist| playlist.into())
+                .collect::<Vec<PlaylistDescription>>();
+
+            let podcasts = results
+                .shows
+                .unwrap_or_else(Page::empty)
+                .items
+                .into_iter()
+                .flatten()
+                .map(|show| show.into())
+                .collect::<Vec<PodcastDescription>>();
+
+            let episodes = results
+                .episodes
+                .unwrap_or_else(Page::empty)
+                .items
+                .into_iter()
+                .flatten()
+                .map(|episode| episode.into())
+                .collect::<Vec<EpisodeDescription>>();
+
+            Ok(SearchResults {
+                albums,
+                artists,
+                songs,
+                playlists,
+                podcasts,
+                episodes,
+            })
+        })
+    }
+
+    fn search_tracks(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        let query = query.to_owned();
+
+        Box::pin(async move {
+            let results = self
+                .client
+                .search_tracks(query, limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?;
+
+            let items = results.tracks.unwrap_or_else(Page::empty).items;
+            Ok(Tracks { items }.into())
+        })
+    }
+
+    fn get_tracks(&self, ids: Vec<String>) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        Box::pin(async move {
+            let mut songs = vec![];
+            // The endpoint accepts at most 50 ids at once
+            for chunk in ids.chunks(50) {
+                let chunk_songs: Vec<SongDescription> = self
+                    .client
+                    .get_tracks(chunk)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?
+                    .into();
+                songs.extend(chunk_songs);
+            }
+            Ok(songs)
+        })
+    }
+
+    fn get_recommendations(
+        &self,
+        seed_ids: Vec<String>,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        Box::pin(async move {
+            let songs = self
+                .client
+                .get_recommendations(&seed_ids, &[], limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?
+                .into();
+            Ok(songs)
+        })
+    }
+
+    fn get_artist_recommendations(
+        &self,
+        artist_id: &str,
+        limit: u32,
+    ) -> BoxFuture<SpotifyResult<Vec<SongDescription>>> {
+        let seed_ids = vec![artist_id.to_owned()];
+
+        Box::pin(async move {
+            let songs = self
+                .client
+                .get_recommendations(&[], &seed_ids, limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?
+                .into();
+            Ok(songs)
+        })
+    }
+
+    // Not cached, as it changes with every song played
+    fn get_recently_played(&self, limit: u32) -> BoxFuture<SpotifyResult<Vec<PlayedSong>>> {
+        Box::pin(async move {
+            let songs = self
+                .client
+                .get_recently_played(limit)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?
+                .into();
+            Ok(songs)
+        })
+    }
+
+    // The plays aren't cached, the playlists and shows they were played from are
+    fn get_recently_played_contexts(
+        &self,
+        count: usize,
+    ) -> BoxFuture<SpotifyResult<Vec<RecentContext>>> {
+        Box::pin(async move {
+            // The most plays the endpoint returns, songs are often played from the same context
+            let mut contexts = self
+                .client
+                .get_recently_played(50)
+                .send()
+                .await?
+                .deserialize()
+                .ok_or(SpotifyApiError::NoContent)?
+                .recent_contexts();
+            contexts.truncate(count);
+
+            let mut described = vec![];
+            for mut context in contexts {
+                let id = context.id.clone();
+                match context.kind {
+                    RecentKind::Album => {}
+                    RecentKind::Playlist => {
+                        let playlist: PlaylistDescription = self
+                            .cache_get_or_write(SpotCacheKey::Playlist(&id), None, |etag| {
+                                self.client.get_playlist(&id).etag(etag).send()
+                            })
+                            .await?
+                            .into();
+                        context.title = playlist.title;
+                        context.subtitle = playlist.owner.display_name;
+                        context.art = playlist.art;
+                    }
+                    RecentKind::Show => {
+                        let show: PodcastDescription = self
+                            .cache_get_or_write(SpotCacheKey::Show(&id), None, |etag| {
+                                self.client.get_show(&id).etag(etag).send()
+                            })
+                            .await?
+                            .into();
+                        context.title = show.name;
+                        context.subtitle = show.publisher;
+                        context.art = show.art;
+                    }
+                }
+                described.push(context);
+            }
+            Ok(described)
+        })
+    }
+
+    fn get_audio_features(
+        &self,
+        ids: Vec<String>,
+    ) -> BoxFuture<SpotifyResult<Vec<(String, Option<SongFeatures>)>>> {
+        Box::pin(async move {
+            let mut features = vec![];
+            // The endpoint accepts at most 100 ids at once
+            for chunk in ids.chunks(100) {
+                let chunk_features: Vec<Option<SongFeatures>> = self
+                    .client
+                    .get_audio_features(chunk)
+                    .send()
+                    .await?
+                    .deserialize()
+                    .ok_or(SpotifyApiError::NoContent)?
+                    .into();
+                features.extend(chunk.iter().cloned().zip(chunk_features));
+            }
+            Ok(features)
+        })
+    }
+
+    fn get_track_credits(&self, id: &str) -> BoxFuture<SpotifyResult<SongCredits>> {
+        let id = id.to_owned();
+
+        Box::pin(async move {
+            let credits = self
+                .cache_get_or_write(SpotCacheKey::TrackCredits(&id), None, |etag| {
+                    self.client.get_track_credits(&id).etag(etag).send()
+                })
+                .await?;
+
+            Ok(credits.into())
         })
     }
 }
@@ -3,7 +3,8 @@ use isahc::config::Configurable;
 use isahc::http::{method::Method, request::Builder, StatusCode, Uri};
 use isahc::{AsyncReadResponseExt, HttpClient, Request};
 use serde::de::Deserialize;
-use serde_json::from_str;
+use serde::Serialize;
+use serde_json::{from_str, json};
 use std::convert::Into;
 use std::marker::PhantomData;
 use std::str::FromStr;
@@ -14,6 +15,9 @@ pub use super::api_models::*;
 use super::cache::CacheError;
 
 const SPOTIFY_HOST: &str = "api.spotify.com";
+// Serves what the Web API doesn't have, undocumented and subject to change:
+// whatever relies on it must cope with failures, see SongCreditsLoader
+const SPCLIENT_HOST: &str = "spclient.wg.spotify.com";
 
 fn make_query_params<'a>() -> Serializer<'a, String> {
     Serializer::new(String::new())
@@ -35,14 +39,18 @@ where
         self
     }
 
-    fn uri(mut self, path: String, query: Option<&str>) -> Self {
+    fn uri(self, path: String, query: Option<&str>) -> Self {
+        self.uri_on(SPOTIFY_HOST, path, query)
+    }
+
+    fn uri_on(mut self, host: &str, path: String, query: Option<&str>) -> Self {
         let path_and_query = match query {
             None => path,
             Some(query) => format!("{}?{}", path, query),
         };
         let uri = Uri::builder()
             .scheme("https")
-            .authority(SPOTIFY_HOST)
+            .authority(host)
             .path_and_query(&path_and_query[..])
             .build()
             .unwrap();
@@ -50,6 +58,34 @@ where
         self
     }
 
+    fn json_body<B2>(self, body: B2) -> SpotifyRequest<'a, Vec<u8>, R>
+    where
+        B2: Serialize,
+    {
+        let Self {
+            client, request, ..
+        } = self;
+        SpotifyRequest {
+            client,
+            request: request.header("Content-Type", "application/json"),
+            body: serde_json::to_vec(&body).unwrap(),
+            _type: PhantomData,
+        }
+    }
+
+    // The image is sent as base64 encoded JPEG data
+    fn jpeg_body(self, image: String) -> SpotifyRequest<'a, Vec<u8>, R> {
+        let Self {
+            client, request, ..
+        } = self;
+        SpotifyRequest {
+            client,
+            request: request.header("Content-Type", "image/jpeg"),
+            body: image.into_bytes(),
+            _type: PhantomData,
+        }
+    }
+
     fn authenticated(mut self) -> Result<Self, SpotifyApiError> {
         let token = self.client.token.lock().unwrap();
         let token = token.as_ref().ok_or(SpotifyApiError::NoToken)?;
@@ -241,11 +277,12 @@ impl SpotifyClient {
     pub(crate) fn get_artist_albums(
         &self,
         id: &str,
+        include_groups: &str,
         offset: u32,
         limit: u32,
     ) -> SpotifyRequest<'_, (), Page<Album>> {
         let query = make_query_params()
-            .append_pair("include_groups", "album,single")
+            .append_pair("include_groups", include_groups)
             .append_pair("country", "from_token")
             .append_pair("offset", &offset.to_string()[..])
             .append_pair("limit", &limit.to_string()[..])
@@ -256,6 +293,78 @@ impl SpotifyClient {
             .uri(format!("/v1/artists/{}/albums", id), Some(&query))
     }
 
+    pub(crate) fn get_new_releases(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), NewReleases> {
+        let query = make_query_params()
+            .append_pair("country", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/browse/new-releases".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_categories(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), Categories> {
+        let query = make_query_params()
+            .append_pair("country", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/browse/categories".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_category_playlists(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), CategoryPlaylists> {
+        let query = make_query_params()
+            .append_pair("country", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request().method(Method::GET).uri(
+            format!("/v1/browse/categories/{}/playlists", id),
+            Some(&query),
+        )
+    }
+
+    // The charts are the playlists of the toplists category, the worldwide ones without a country
+    pub(crate) fn get_chart_playlists(
+        &self,
+        country: Option<&str>,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), CategoryPlaylists> {
+        let mut query = make_query_params();
+        query
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..]);
+        if let Some(country) = country {
+            query.append_pair("country", country);
+        }
+        let query = query.finish();
+
+        self.request().method(Method::GET).uri(
+            "/v1/browse/categories/toplists/playlists".to_string(),
+            Some(&query),
+        )
+    }
+
     pub(crate) fn get_artist_top_tracks(&self, id: &str) -> SpotifyRequest<'_, (), TopTracks> {
         let query = make_query_params()
             .append_pair("market", "from_token")
@@ -266,6 +375,12 @@ impl SpotifyClient {
             .uri(format!("/v1/artists/{}/top-tracks", id), Some(&query))
     }
 
+    pub(crate) fn get_related_artists(&self, id: &str) -> SpotifyRequest<'_, (), RelatedArtists> {
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/artists/{}/related-artists", id), None)
+    }
+
     pub(crate) fn is_album_saved(&self, id: &str) -> SpotifyRequest<'_, (), Vec<bool>> {
         let query = make_query_params().append_pair("ids", id).finish();
         self.request()
@@ -287,6 +402,153 @@ impl SpotifyClient {
             .uri("/v1/me/albums".to_string(), Some(&query))
     }
 
+    pub(crate) fn is_artist_followed(&self, id: &str) -> SpotifyRequest<'_, (), Vec<bool>> {
+        let query = make_query_params()
+            .append_pair("type", "artist")
+            .append_pair("ids", id)
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/following/contains".to_string(), Some(&query))
+    }
+
+    pub(crate) fn follow_artist(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        let query = make_query_params()
+            .append_pair("type", "artist")
+            .append_pair("ids", id)
+            .finish();
+        self.request()
+            .method(Method::PUT)
+            .uri("/v1/me/following".to_string(), Some(&query))
+    }
+
+    pub(crate) fn unfollow_artist(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        let query = make_query_params()
+            .append_pair("type", "artist")
+            .append_pair("ids", id)
+            .finish();
+        self.request()
+            .method(Method::DELETE)
+            .uri("/v1/me/following".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_followed_artists(
+        &self,
+        after: Option<&str>,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), FollowedArtists> {
+        let mut query = make_query_params();
+        query
+            .append_pair("type", "artist")
+            .append_pair("limit", &limit.to_string()[..]);
+        if let Some(after) = after {
+            query.append_pair("after", after);
+        }
+        let query = query.finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/following".to_string(), Some(&query))
+    }
+
+    pub(crate) fn remove_saved_track(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        let query = make_query_params().append_pair("ids", id).finish();
+        self.request()
+            .method(Method::DELETE)
+            .uri("/v1/me/tracks".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_current_user(&self) -> SpotifyRequest<'_, (), User> {
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me".to_string(), None)
+    }
+
+    pub(crate) fn get_user(&self, id: &str) -> SpotifyRequest<'_, (), User> {
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/users/{}", id), None)
+    }
+
+    pub(crate) fn create_playlist(
+        &self,
+        user_id: &str,
+        name: &str,
+        description: &str,
+        public: bool,
+    ) -> SpotifyRequest<'_, Vec<u8>, Playlist> {
+        let mut body = json!({ "name": name, "public": public });
+        if !description.is_empty() {
+            body["description"] = description.into();
+        }
+        self.request()
+            .method(Method::POST)
+            .uri(format!("/v1/users/{}/playlists", user_id), None)
+            .json_body(body)
+    }
+
+    pub(crate) fn add_to_playlist(
+        &self,
+        id: &str,
+        uris: &[String],
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::POST)
+            .uri(format!("/v1/playlists/{}/tracks", id), None)
+            .json_body(json!({ "uris": uris }))
+    }
+
+    // Each track is removed at its position only, other copies of it are kept
+    pub(crate) fn remove_from_playlist(
+        &self,
+        id: &str,
+        tracks: &[(String, usize)],
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        let tracks = tracks
+            .iter()
+            .map(|(uri, position)| json!({ "uri": uri, "positions": [position] }))
+            .collect::<Vec<_>>();
+        self.request()
+            .method(Method::DELETE)
+            .uri(format!("/v1/playlists/{}/tracks", id), None)
+            .json_body(json!({ "tracks": tracks }))
+    }
+
+    pub(crate) fn reorder_playlist_tracks(
+        &self,
+        id: &str,
+        range_start: usize,
+        insert_before: usize,
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::PUT)
+            .uri(format!("/v1/playlists/{}/tracks", id), None)
+            .json_body(json!({ "range_start": range_start, "insert_before": insert_before }))
+    }
+
+    pub(crate) fn update_playlist_details(
+        &self,
+        id: &str,
+        name: &str,
+        description: &str,
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::PUT)
+            .uri(format!("/v1/playlists/{}", id), None)
+            .json_body(json!({ "name": name, "description": description }))
+    }
+
+    pub(crate) fn upload_playlist_image(
+        &self,
+        id: &str,
+        image: String,
+    ) -> SpotifyRequest<'_, Vec<u8>, ()> {
+        self.request()
+            .method(Method::PUT)
+            .uri(format!("/v1/playlists/{}/images", id), None)
+            .jpeg_body(image)
+    }
+
     pub(crate) fn get_album(&self, id: &str) -> SpotifyRequest<'_, (), Album> {
         self.request()
             .method(Method::GET)
@@ -295,7 +557,7 @@ impl SpotifyClient {
 
     pub(crate) fn get_playlist(&self, id: &str) -> SpotifyRequest<'_, (), Playlist> {
         let query = make_query_params()
-            .append_pair("fields", "id,name,images,owner")
+            .append_pair("fields", "id,name,description,collaborative,images,owner")
             .finish();
         self.request()
             .method(Method::GET)
@@ -318,6 +580,75 @@ impl SpotifyClient {
             .uri(format!("/v1/playlists/{}/tracks", id), Some(&query))
     }
 
+    pub(crate) fn get_tracks(&self, ids: &[String]) -> SpotifyRequest<'_, (), TrackList> {
+        let query = make_query_params()
+            .append_pair("ids", &ids.join(","))
+            .append_pair("market", "from_token")
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/tracks".to_string(), Some(&query))
+    }
+
+    // At most 5 seeds in total, tracks and artists together
+    pub(crate) fn get_recommendations(
+        &self,
+        seed_tracks: &[String],
+        seed_artists: &[String],
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), Recommendations> {
+        let mut query = make_query_params();
+        query
+            .append_pair("limit", &limit.to_string()[..])
+            .append_pair("market", "from_token");
+        if !seed_tracks.is_empty() {
+            query.append_pair("seed_tracks", &seed_tracks.join(","));
+        }
+        if !seed_artists.is_empty() {
+            query.append_pair("seed_artists", &seed_artists.join(","));
+        }
+        let query = query.finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/recommendations".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_audio_features(
+        &self,
+        ids: &[String],
+    ) -> SpotifyRequest<'_, (), AudioFeaturesList> {
+        let query = make_query_params()
+            .append_pair("ids", &ids.join(","))
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/audio-features".to_string(), Some(&query))
+    }
+
+    pub(crate) fn get_track_credits(&self, id: &str) -> SpotifyRequest<'_, (), TrackCredits> {
+        self.request().method(Method::GET).uri_on(
+            SPCLIENT_HOST,
+            format!("/track-credits-view/v0/experimental/{}/credits", id),
+            None,
+        )
+    }
+
+    pub(crate) fn get_recently_played(
+        &self,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), Page<PlayHistory>> {
+        let query = make_query_params()
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/player/recently-played".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_saved_albums(
         &self,
         offset: u32,
@@ -333,6 +664,22 @@ impl SpotifyClient {
             .uri("/v1/me/albums".to_string(), Some(&query))
     }
 
+    pub(crate) fn get_saved_tracks(
+        &self,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), Page<SavedTrack>> {
+        let query = make_query_params()
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .append_pair("market", "from_token")
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/tracks".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_saved_playlists(
         &self,
         offset: u32,
@@ -348,6 +695,53 @@ impl SpotifyClient {
             .uri("/v1/me/playlists".to_string(), Some(&query))
     }
 
+    pub(crate) fn get_show(&self, id: &str) -> SpotifyRequest<'_, (), Podcast> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .finish();
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/shows/{}", id), Some(&query))
+    }
+
+    pub(crate) fn get_show_episodes(
+        &self,
+        id: &str,
+        offset: u32,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), Page<Episode>> {
+        let query = make_query_params()
+            .append_pair("market", "from_token")
+            .append_pair("offset", &offset.to_string()[..])
+            .append_pair("limit", &limit.to_string()[..])
+            .finish();
+
+        self.request()
+            .method(Method::GET)
+            .uri(format!("/v1/shows/{}/episodes", id), Some(&query))
+    }
+
+    pub(crate) fn is_show_saved(&self, id: &str) -> SpotifyRequest<'_, (), Vec<bool>> {
+        let query = make_query_params().append_pair("ids", id).finish();
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/me/shows/contains".to_string(), Some(&query))
+    }
+
+    pub(crate) fn save_show(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        let query = make_query_params().append_pair("ids", id).finish();
+        self.request()
+            .method(Method::PUT)
+            .uri("/v1/me/shows".to_string(), Some(&query))
+    }
+
+    pub(crate) fn remove_saved_show(&self, id: &str) -> SpotifyRequest<'_, (), ()> {
+        let query = make_query_params().append_pair("ids", id).finish();
+        self.request()
+            .method(Method::DELETE)
+            .uri("/v1/me/shows".to_string(), Some(&query))
+    }
+
     pub(crate) fn get_saved_podcasts(
         &self,
         offset: u32,
@@ -366,12 +760,13 @@ impl SpotifyClient {
     pub(crate) fn search(
         &self,
         query: String,
+        types: Vec<SearchType>,
         offset: u32,
         limit: u32,
     ) -> SpotifyRequest<'_, (), RawSearchResults> {
         let query = SearchQuery {
             query,
-            types: vec![SearchType::Album, SearchType::Artist],
+            types,
             limit,
             offset,
         };
@@ -380,6 +775,23 @@ impl SpotifyClient {
             .method(Method::GET)
             .uri("/v1/search".to_string(), Some(&query.into_query_string()))
     }
+
+    pub(crate) fn search_tracks(
+        &self,
+        query: String,
+        limit: u32,
+    ) -> SpotifyRequest<'_, (), RawSearchResults> {
+        let query = SearchQuery {
+            query,
+            types: vec![SearchType::Track],
+            limit,
+            offset: 0,
+        };
+
+        self.request()
+            .method(Method::GET)
+            .uri("/v1/search".to_string(), Some(&query.into_query_string()))
+    }
 }
 
 #[cfg(test)]
@@ -428,4 +840,14 @@ pub mod tests {
 
         assert_eq!(query.into_query_string(), "type=album&q=%D0%BA%D0%B8%D1%80%D0%B8%D0%BB%D0%BB%D0%B8%D1%86%D0%B0&offset=0&limit=5&market=from_token");
     }
+
+    #[test]
+    fn test_parse_utc_timestamp() {
+        assert_eq!(parse_utc_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_utc_timestamp("2021-03-14T15:09:26.535Z"),
+            Some(1_615_734_566)
+        );
+        assert_eq!(parse_utc_timestamp("2021-03-14"), None);
+    }
 }
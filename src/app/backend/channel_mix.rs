@@ -0,0 +1,104 @@
+use librespot::playback::audio_backend::Sink;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Arc;
+
+// Shared between the player and its sink, so that changes apply to what is currently playing
+#[derive(Debug, Default)]
+pub struct ChannelMix {
+    mono: AtomicBool,
+    // In thousandths, from -1000 (left only) to 1000 (right only)
+    balance: AtomicI32,
+}
+
+impl ChannelMix {
+    pub fn set(&self, mono: bool, balance: f64) {
+        let balance = (balance.max(-1.0).min(1.0) * 1000.0).round() as i32;
+        self.mono.store(mono, Ordering::Relaxed);
+        self.balance.store(balance, Ordering::Relaxed);
+    }
+
+    fn is_neutral(&self) -> bool {
+        !self.mono.load(Ordering::Relaxed) && self.balance.load(Ordering::Relaxed) == 0
+    }
+
+    // Samples are interleaved, left then right.
+    // Balance pans the summed signal too when mono is on.
+    fn apply(&self, samples: &mut [i16]) {
+        let mono = self.mono.load(Ordering::Relaxed);
+        let balance = self.balance.load(Ordering::Relaxed) as f32 / 1000.0;
+        let left_gain = 1.0 - balance.max(0.0);
+        let right_gain = 1.0 + balance.min(0.0);
+
+        for frame in samples.chunks_exact_mut(2) {
+            let (left, right) = if mono {
+                let sum = (frame[0] as i32 + frame[1] as i32) / 2;
+                (sum as f32, sum as f32)
+            } else {
+                (frame[0] as f32, frame[1] as f32)
+            };
+            frame[0] = (left * left_gain) as i16;
+            frame[1] = (right * right_gain) as i16;
+        }
+    }
+}
+
+pub struct ChannelMixSink {
+    sink: Box<dyn Sink>,
+    mix: Arc<ChannelMix>,
+    buffer: Vec<i16>,
+}
+
+impl ChannelMixSink {
+    pub fn new(sink: Box<dyn Sink>, mix: Arc<ChannelMix>) -> Self {
+        Self {
+            sink,
+            mix,
+            buffer: vec![],
+        }
+    }
+}
+
+impl Sink for ChannelMixSink {
+    fn start(&mut self) -> io::Result<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, data: &[i16]) -> io::Result<()> {
+        if self.mix.is_neutral() {
+            return self.sink.write(data);
+        }
+        self.buffer.clear();
+        self.buffer.extend_from_slice(data);
+        self.mix.apply(&mut self.buffer);
+        self.sink.write(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mono_and_balance() {
+        let mix = ChannelMix::default();
+        let mut samples = [1000, 0, -400, 400];
+
+        mix.set(true, 0.0);
+        mix.apply(&mut samples);
+        assert_eq!(samples, [500, 500, 0, 0]);
+
+        mix.set(true, 0.5);
+        mix.apply(&mut samples);
+        assert_eq!(samples, [250, 500, 0, 0]);
+
+        mix.set(false, -2.0);
+        mix.apply(&mut samples);
+        assert_eq!(samples, [250, 0, 0, 0]);
+    }
+}
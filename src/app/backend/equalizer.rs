@@ -0,0 +1,195 @@
+use librespot::playback::audio_backend::Sink;
+use std::f32::consts::PI;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+const SAMPLE_RATE: f32 = 44100.0;
+const BANDS: usize = 10;
+// Centre frequency of each band, in Hz
+const FREQUENCIES: [f32; BANDS] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+// Bands are about an octave wide
+const Q: f32 = 1.41;
+const MAX_GAIN_DB: f32 = 12.0;
+
+// Shared between the player and its sink, so that changes apply to what is currently playing
+#[derive(Debug, Default)]
+pub struct Equalizer {
+    // In dB, for each band from the lowest to the highest frequency
+    gains: Mutex<[f32; BANDS]>,
+    changed: AtomicBool,
+}
+
+impl Equalizer {
+    // Missing bands are left flat
+    pub fn set(&self, gains: &[f64]) {
+        if let Ok(mut current) = self.gains.lock() {
+            for (i, gain) in current.iter_mut().enumerate() {
+                let new_gain = gains.get(i).copied().unwrap_or(0.0) as f32;
+                *gain = new_gain.max(-MAX_GAIN_DB).min(MAX_GAIN_DB);
+            }
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn take_gains(&self) -> Option<[f32; BANDS]> {
+        if self.changed.swap(false, Ordering::Relaxed) {
+            self.gains.lock().ok().map(|gains| *gains)
+        } else {
+            None
+        }
+    }
+}
+
+// A peaking filter, see https://www.w3.org/TR/audio-eq-cookbook/
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    fn peaking(frequency: f32, gain_db: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * PI * frequency / SAMPLE_RATE;
+        let alpha = w0.sin() / (2.0 * Q);
+        let cos_w0 = w0.cos();
+        let a0 = 1.0 + alpha / a;
+        Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: -2.0 * cos_w0 / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: -2.0 * cos_w0 / a0,
+            a2: (1.0 - alpha / a) / a0,
+        }
+    }
+}
+
+// The last two inputs and outputs of a filter, for one channel
+#[derive(Clone, Copy, Debug, Default)]
+struct BiquadState {
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl BiquadState {
+    fn process(&mut self, filter: &Biquad, x: f32) -> f32 {
+        let y = filter.b0 * x + filter.b1 * self.x1 + filter.b2 * self.x2
+            - filter.a1 * self.y1
+            - filter.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+pub struct EqualizerSink {
+    sink: Box<dyn Sink>,
+    equalizer: Arc<Equalizer>,
+    // Only bands with a gain, by band index
+    filters: Vec<(usize, Biquad)>,
+    // For each band, left then right
+    states: [[BiquadState; 2]; BANDS],
+    buffer: Vec<i16>,
+}
+
+impl EqualizerSink {
+    pub fn new(sink: Box<dyn Sink>, equalizer: Arc<Equalizer>) -> Self {
+        Self {
+            sink,
+            equalizer,
+            filters: vec![],
+            states: Default::default(),
+            buffer: vec![],
+        }
+    }
+
+    // Filter states are kept, so that changing a band doesn't interrupt playback
+    fn update_filters(&mut self) {
+        if let Some(gains) = self.equalizer.take_gains() {
+            self.filters = gains
+                .iter()
+                .enumerate()
+                .filter(|(_, &gain)| gain != 0.0)
+                .map(|(i, &gain)| (i, Biquad::peaking(FREQUENCIES[i], gain)))
+                .collect();
+            for (i, &gain) in gains.iter().enumerate() {
+                if gain == 0.0 {
+                    self.states[i] = Default::default();
+                }
+            }
+        }
+    }
+
+    // Samples are interleaved, left then right
+    fn apply(&mut self, samples: &mut [i16]) {
+        for frame in samples.chunks_exact_mut(2) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let mut value = *sample as f32;
+                for (band, filter) in self.filters.iter() {
+                    value = self.states[*band][channel].process(filter, value);
+                }
+                *sample = value.max(i16::MIN as f32).min(i16::MAX as f32) as i16;
+            }
+        }
+    }
+}
+
+impl Sink for EqualizerSink {
+    fn start(&mut self) -> io::Result<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, data: &[i16]) -> io::Result<()> {
+        self.update_filters();
+        if self.filters.is_empty() {
+            return self.sink.write(data);
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer.extend_from_slice(data);
+        self.apply(&mut buffer);
+        let result = self.sink.write(&buffer);
+        self.buffer = buffer;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // Peak amplitude of a sine at the given frequency, once the filter has settled
+    fn filtered_peak(filter: &Biquad, frequency: f32) -> f32 {
+        let mut state = BiquadState::default();
+        (0..SAMPLE_RATE as usize)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin())
+            .map(|x| state.process(filter, x))
+            .skip(SAMPLE_RATE as usize / 2)
+            .fold(0.0, |peak, y| y.abs().max(peak))
+    }
+
+    #[test]
+    fn test_peaking_filter_gain() {
+        let filter = Biquad::peaking(1000.0, 6.0);
+        assert!((filtered_peak(&filter, 1000.0) - 1.995).abs() < 0.05);
+        assert!((filtered_peak(&filter, 31.0) - 1.0).abs() < 0.05);
+
+        let flat = Biquad::peaking(1000.0, 0.0);
+        assert!((filtered_peak(&flat, 1000.0) - 1.0).abs() < 0.01);
+    }
+}
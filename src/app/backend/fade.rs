@@ -0,0 +1,177 @@
+use librespot::playback::audio_backend::Sink;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const SAMPLE_RATE: f32 = 44100.0;
+
+// Shared between the player and its sink, the sink ramps its gain towards silence or full volume
+#[derive(Debug, Default)]
+pub struct Fade {
+    // In milliseconds, 0 to cut right away
+    duration: AtomicU32,
+    silent: AtomicBool,
+    // Set when the gain should jump to full volume, rather than ramp up
+    reset: AtomicBool,
+    // Whether anything was written since the last reset
+    started: AtomicBool,
+}
+
+impl Fade {
+    pub fn set_duration(&self, duration: u32) {
+        self.duration.store(duration, Ordering::Relaxed);
+    }
+
+    // How long to wait for a fade out, nothing is heard until a new track starts writing
+    pub fn fade_out(&self) -> Option<Duration> {
+        self.silent.store(true, Ordering::Relaxed);
+        let duration = self.duration.load(Ordering::Relaxed);
+        if duration > 0 && self.started.load(Ordering::Relaxed) {
+            Some(Duration::from_millis(duration as u64))
+        } else {
+            None
+        }
+    }
+
+    pub fn fade_in(&self) {
+        self.silent.store(false, Ordering::Relaxed);
+    }
+
+    // For a new track, which starts at full volume
+    pub fn reset(&self) {
+        self.silent.store(false, Ordering::Relaxed);
+        self.started.store(false, Ordering::Relaxed);
+        self.reset.store(true, Ordering::Relaxed);
+    }
+
+    // Gain change per frame
+    fn step(&self) -> f32 {
+        let frames = self.duration.load(Ordering::Relaxed) as f32 * SAMPLE_RATE / 1000.0;
+        if frames < 1.0 {
+            1.0
+        } else {
+            1.0 / frames
+        }
+    }
+}
+
+pub struct FadeSink {
+    sink: Box<dyn Sink>,
+    fade: Arc<Fade>,
+    gain: f32,
+    buffer: Vec<i16>,
+}
+
+impl FadeSink {
+    pub fn new(sink: Box<dyn Sink>, fade: Arc<Fade>) -> Self {
+        Self {
+            sink,
+            fade,
+            gain: 1.0,
+            buffer: vec![],
+        }
+    }
+
+    // Samples are interleaved, left then right
+    fn apply(&mut self, samples: &mut [i16]) {
+        let target = if self.fade.silent.load(Ordering::Relaxed) {
+            0.0
+        } else {
+            1.0
+        };
+        let step = self.fade.step();
+        for frame in samples.chunks_exact_mut(2) {
+            self.gain = if self.gain < target {
+                (self.gain + step).min(target)
+            } else {
+                (self.gain - step).max(target)
+            };
+            for sample in frame.iter_mut() {
+                *sample = (*sample as f32 * self.gain) as i16;
+            }
+        }
+    }
+}
+
+impl Sink for FadeSink {
+    fn start(&mut self) -> io::Result<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, data: &[i16]) -> io::Result<()> {
+        if self.fade.reset.swap(false, Ordering::Relaxed) {
+            self.gain = 1.0;
+        }
+        self.fade.started.store(true, Ordering::Relaxed);
+        if self.gain == 1.0 && !self.fade.silent.load(Ordering::Relaxed) {
+            return self.sink.write(data);
+        }
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer.extend_from_slice(data);
+        self.apply(&mut buffer);
+        let result = self.sink.write(&buffer);
+        self.buffer = buffer;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    struct NullSink;
+
+    impl Sink for NullSink {
+        fn start(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn stop(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn write(&mut self, _data: &[i16]) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn faded(sink: &mut FadeSink, frames: usize) -> Vec<i16> {
+        let mut samples = vec![1000; frames * 2];
+        sink.apply(&mut samples);
+        samples
+    }
+
+    #[test]
+    fn test_fade_out_and_in() {
+        let fade = Arc::new(Fade::default());
+        fade.set_duration(10);
+        let mut sink = FadeSink::new(Box::new(NullSink), fade.clone());
+        // 10ms
+        let frames = 441;
+
+        assert_eq!(fade.fade_out(), None);
+        sink.write(&[0, 0]).unwrap();
+        assert_eq!(fade.fade_out(), Some(Duration::from_millis(10)));
+        let samples = faded(&mut sink, frames);
+        assert!(samples[0] > 990);
+        assert!(samples[frames] < 600 && samples[frames] > 400);
+        assert_eq!(samples[2 * frames - 1], 0);
+
+        fade.fade_in();
+        let samples = faded(&mut sink, frames);
+        assert!(samples[0] < 10);
+        assert!(samples[2 * frames - 1] >= 999);
+
+        fade.fade_out();
+        faded(&mut sink, frames);
+        fade.reset();
+        assert_eq!(fade.fade_out(), None);
+    }
+}
@@ -0,0 +1,102 @@
+use futures::compat::Future01CompatExt;
+use librespot::core::session::Session;
+use librespot::protocol::playlist4changes::SelectedListContent;
+
+use super::SpotifyError;
+use crate::app::models::PlaylistFolder;
+
+// The Web API only knows of a flat list of playlists, folders are only found
+// in the list of playlists as kept by the Spotify clients, which looks like:
+//   spotify:start-group:<id>:<url-encoded name>
+//   spotify:user:<user>:playlist:<id>
+//   spotify:end-group:<id>
+pub async fn get_playlist_folders(session: &Session) -> Result<Vec<PlaylistFolder>, SpotifyError> {
+    let uri = format!("hm://playlist/user/{}/rootlist", session.username());
+    let response = session
+        .mercury()
+        .get(uri)
+        .compat()
+        .await
+        .map_err(|_| SpotifyError::FoldersFailed)?;
+    let payload = response
+        .payload
+        .first()
+        .ok_or(SpotifyError::FoldersFailed)?;
+    let rootlist = protobuf::parse_from_bytes::<SelectedListContent>(payload)
+        .map_err(|_| SpotifyError::FoldersFailed)?;
+    let uris = rootlist
+        .get_contents()
+        .get_items()
+        .iter()
+        .map(|item| item.get_uri());
+    Ok(parse_rootlist(uris))
+}
+
+fn decode_name(name: &str) -> String {
+    form_urlencoded::parse(name.as_bytes())
+        .next()
+        .map(|(name, _)| name.into_owned())
+        .unwrap_or_default()
+}
+
+// Folders are listed in the order they appear, parents before the folders they contain
+fn parse_rootlist<'a>(uris: impl Iterator<Item = &'a str>) -> Vec<PlaylistFolder> {
+    let mut folders: Vec<PlaylistFolder> = vec![];
+    let mut open: Vec<usize> = vec![];
+    for uri in uris {
+        let parts = uri.splitn(4, ':').collect::<Vec<&str>>();
+        match parts[..] {
+            ["spotify", "start-group", id, name] => {
+                folders.push(PlaylistFolder {
+                    id: id.to_string(),
+                    name: decode_name(name),
+                    depth: open.len(),
+                    playlist_ids: vec![],
+                });
+                open.push(folders.len() - 1);
+            }
+            ["spotify", "end-group", ..] => {
+                open.pop();
+            }
+            _ => {
+                if let Some(index) = uri.rfind(":playlist:") {
+                    let id = &uri[index + ":playlist:".len()..];
+                    for &i in &open {
+                        folders[i].playlist_ids.push(id.to_string());
+                    }
+                }
+            }
+        }
+    }
+    folders
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_rootlist() {
+        let uris = vec![
+            "spotify:user:me:playlist:a",
+            "spotify:start-group:1:Road+trips",
+            "spotify:playlist:b",
+            "spotify:start-group:2:Summer%3A+2020",
+            "spotify:user:me:playlist:c",
+            "spotify:end-group:2",
+            "spotify:end-group:1",
+            "spotify:playlist:d",
+        ];
+
+        let folders = parse_rootlist(uris.into_iter());
+
+        assert_eq!(folders.len(), 2);
+        assert_eq!(folders[0].name, "Road trips");
+        assert_eq!(folders[0].depth, 0);
+        assert_eq!(folders[0].playlist_ids, vec!["b", "c"]);
+        assert_eq!(folders[1].name, "Summer: 2020");
+        assert_eq!(folders[1].depth, 1);
+        assert_eq!(folders[1].playlist_ids, vec!["c"]);
+    }
+}
@@ -6,21 +6,42 @@ use std::rc::Rc;
 use std::thread;
 use tokio_core::reactor::Core;
 
-use crate::app::state::PlaybackAction;
+use crate::app::models::PlaylistFolder;
+use crate::app::state::{BrowserAction, PlaybackAction};
 use crate::app::{credentials, AppAction};
 
+mod channel_mix;
+mod equalizer;
+mod fade;
+mod folders;
 mod player;
+mod visualizer;
+mod volume;
 pub use player::{SpotifyError, SpotifyPlayer, SpotifyPlayerDelegate};
 
 #[derive(Debug, Clone)]
 pub enum Command {
     Login(String, String),
-    PlayerLoad(SpotifyId),
+    PlayerLoad(SpotifyId, u32),
+    PlayerPreload(SpotifyId),
     PlayerResume,
     PlayerPause,
     PlayerStop,
     PlayerSeek(u32),
+    // Whether to force mono, and the balance from -1 (left) to 1 (right)
+    PlayerSetChannelMix(bool, f64),
+    // The pregain in dB to normalize volume with, None to play tracks as they are
+    PlayerSetNormalization(Option<f32>),
+    // Gains in dB from the lowest to the highest band, empty to turn the equalizer off
+    PlayerSetEqualizer(Vec<f64>),
+    // Whether to analyze what is played for the visualizer
+    PlayerSetVisualizer(bool),
+    // How long pausing, resuming and stopping fade for, in milliseconds
+    PlayerSetFade(u32),
+    // From 0 to 1, on top of the system volume
+    PlayerSetVolume(f64),
     RefreshToken,
+    FetchPlaylistFolders,
 }
 
 struct AppPlayerDelegate {
@@ -38,7 +59,14 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
     fn end_of_track_reached(&self) {
         self.sender
             .borrow_mut()
-            .unbounded_send(PlaybackAction::Next.into())
+            .unbounded_send(PlaybackAction::TrackEnded.into())
+            .unwrap();
+    }
+
+    fn preload_next_track(&self) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(PlaybackAction::PreloadNext.into())
             .unwrap();
     }
 
@@ -69,6 +97,20 @@ impl SpotifyPlayerDelegate for AppPlayerDelegate {
             .unbounded_send(PlaybackAction::SyncSeek(position).into())
             .unwrap();
     }
+
+    fn notify_spectrum(&self, levels: Vec<f32>) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(PlaybackAction::SyncSpectrum(levels).into())
+            .unwrap();
+    }
+
+    fn playlist_folders_fetched(&self, folders: Vec<PlaylistFolder>) {
+        self.sender
+            .borrow_mut()
+            .unbounded_send(BrowserAction::SetPlaylistFolders(folders).into())
+            .unwrap();
+    }
 }
 
 pub fn start_player_service(
@@ -1,10 +1,11 @@
 use futures::channel::mpsc::UnboundedReceiver;
 use futures::compat::Future01CompatExt;
-use futures::stream::StreamExt;
+use futures::future::{self, FutureExt};
+use futures::stream::{self, StreamExt};
 use futures01::future::Future as OldFuture;
 use futures01::stream::Stream as OldStream;
 
-use tokio_core::reactor::Handle;
+use tokio_core::reactor::{Handle, Timeout};
 
 use librespot::core::authentication::Credentials;
 use librespot::core::config::SessionConfig;
@@ -15,19 +16,28 @@ use librespot::playback::audio_backend;
 use librespot::playback::config::PlayerConfig;
 use librespot::playback::player::{Player, PlayerEvent};
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::error::Error;
 use std::fmt;
 use std::rc::{Rc, Weak};
+use std::sync::Arc;
 
+use super::channel_mix::{ChannelMix, ChannelMixSink};
+use super::equalizer::{Equalizer, EqualizerSink};
+use super::fade::{Fade, FadeSink};
+use super::folders::get_playlist_folders;
+use super::visualizer::{Visualizer, VisualizerSink};
+use super::volume::{Volume, VolumeSink};
 use super::Command;
 use crate::app::credentials;
+use crate::app::models::PlaylistFolder;
 
 #[derive(Debug)]
 pub enum SpotifyError {
     LoginFailed,
     TokenFailed,
     PlayerNotReady,
+    FoldersFailed,
 }
 
 impl Error for SpotifyError {}
@@ -38,33 +48,91 @@ impl fmt::Display for SpotifyError {
             Self::LoginFailed => write!(f, "Login failed!"),
             Self::TokenFailed => write!(f, "Token retrieval failed!"),
             Self::PlayerNotReady => write!(f, "Player is not responding."),
+            Self::FoldersFailed => write!(f, "Could not load playlist folders."),
         }
     }
 }
 
 pub trait SpotifyPlayerDelegate {
     fn end_of_track_reached(&self);
+    fn preload_next_track(&self);
     fn login_successful(&self, credentials: credentials::Credentials);
     fn refresh_successful(&self, token: String);
     fn report_error(&self, error: SpotifyError);
     fn notify_playback_state(&self, position: u32);
+    fn notify_spectrum(&self, levels: Vec<f32>);
+    fn playlist_folders_fetched(&self, folders: Vec<PlaylistFolder>);
 }
 
 pub struct SpotifyPlayer {
     player: RefCell<Option<Player>>,
     session: RefCell<Option<Session>>,
+    channel_mix: Arc<ChannelMix>,
+    equalizer: Arc<Equalizer>,
+    visualizer: Arc<Visualizer>,
+    fade: Arc<Fade>,
+    volume: Arc<Volume>,
+    // Taken once the player starts
+    spectrum: Option<UnboundedReceiver<Vec<f32>>>,
+    // Pregain in dB when normalizing volume
+    normalization: Cell<Option<f32>>,
+    player_outdated: Cell<bool>,
     delegate: Rc<dyn SpotifyPlayerDelegate>,
 }
 
 impl SpotifyPlayer {
     pub fn new(delegate: Rc<dyn SpotifyPlayerDelegate>) -> Self {
+        let (visualizer, spectrum) = Visualizer::new();
         Self {
             player: RefCell::new(None),
             session: RefCell::new(None),
+            channel_mix: Arc::new(ChannelMix::default()),
+            equalizer: Arc::new(Equalizer::default()),
+            visualizer: Arc::new(visualizer),
+            fade: Arc::new(Fade::default()),
+            volume: Arc::new(Volume::default()),
+            spectrum: Some(spectrum),
+            normalization: Cell::new(None),
+            player_outdated: Cell::new(false),
             delegate,
         }
     }
 
+    fn create_player(&self, session: Session, handle: &Handle) -> Player {
+        let new_player = create_player(
+            session,
+            self.normalization.get(),
+            self.channel_mix.clone(),
+            self.equalizer.clone(),
+            self.visualizer.clone(),
+            self.fade.clone(),
+            self.volume.clone(),
+        );
+        handle.spawn(player_subscribe_to_playing_event(
+            &new_player,
+            Rc::downgrade(&self.delegate),
+        ));
+        handle.spawn(player_end_of_track_event(
+            &new_player,
+            Rc::downgrade(&self.delegate),
+        ));
+        handle.spawn(player_preload_event(
+            &new_player,
+            Rc::downgrade(&self.delegate),
+        ));
+        self.player_outdated.set(false);
+        new_player
+    }
+
+    // Commands are handled in order, so whatever comes next waits for the fade to end
+    async fn fade_out(&self, handle: &Handle) {
+        if let Some(duration) = self.fade.fade_out() {
+            if let Ok(timeout) = Timeout::new(duration, handle) {
+                let _ = timeout.compat().await;
+            }
+        }
+    }
+
     async fn handle(&self, action: Command, handle: &Handle) -> Result<(), SpotifyError> {
         let mut player = self.player.borrow_mut();
         let mut session = self.session.borrow_mut();
@@ -72,15 +140,18 @@ impl SpotifyPlayer {
             Command::PlayerResume => {
                 let player = player.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
                 player.play();
+                self.fade.fade_in();
                 Ok(())
             }
             Command::PlayerPause => {
                 let player = player.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
+                self.fade_out(handle).await;
                 player.pause();
                 Ok(())
             }
             Command::PlayerStop => {
                 let player = player.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
+                self.fade_out(handle).await;
                 player.stop();
                 Ok(())
             }
@@ -89,9 +160,50 @@ impl SpotifyPlayer {
                 player.seek(position);
                 Ok(())
             }
-            Command::PlayerLoad(track) => {
+            Command::PlayerLoad(track, position) => {
+                if self.player_outdated.get() {
+                    if let Some(session) = session.as_ref() {
+                        player.replace(self.create_player(session.clone(), handle));
+                    }
+                }
                 let player = player.as_mut().ok_or(SpotifyError::PlayerNotReady)?;
-                player.load(track, true, 0);
+                self.fade.reset();
+                player.load(track, true, position);
+                Ok(())
+            }
+            // Loading a preloaded track right as the previous one ends keeps the sink
+            // running, so that there is no gap between the two
+            Command::PlayerPreload(track) => {
+                let player = player.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
+                player.preload(track);
+                Ok(())
+            }
+            Command::PlayerSetChannelMix(mono, balance) => {
+                self.channel_mix.set(mono, balance);
+                Ok(())
+            }
+            // The player only reads this when created, so a new one is created
+            // for the next track rather than interrupting the current one
+            Command::PlayerSetNormalization(pregain) => {
+                if self.normalization.replace(pregain) != pregain {
+                    self.player_outdated.set(true);
+                }
+                Ok(())
+            }
+            Command::PlayerSetEqualizer(gains) => {
+                self.equalizer.set(&gains);
+                Ok(())
+            }
+            Command::PlayerSetVisualizer(enabled) => {
+                self.visualizer.set_enabled(enabled);
+                Ok(())
+            }
+            Command::PlayerSetFade(duration) => {
+                self.fade.set_duration(duration);
+                Ok(())
+            }
+            Command::PlayerSetVolume(volume) => {
+                self.volume.set(volume);
                 Ok(())
             }
             Command::RefreshToken => {
@@ -100,6 +212,12 @@ impl SpotifyPlayer {
                 self.delegate.refresh_successful(token);
                 Ok(())
             }
+            Command::FetchPlaylistFolders => {
+                let session = session.as_ref().ok_or(SpotifyError::PlayerNotReady)?;
+                let folders = get_playlist_folders(session).await?;
+                self.delegate.playlist_folders_fetched(folders);
+                Ok(())
+            }
             Command::Login(username, password) => {
                 let new_session =
                     create_session(username.clone(), password.clone(), handle.clone()).await?;
@@ -112,16 +230,7 @@ impl SpotifyPlayer {
                 };
                 self.delegate.login_successful(credentials);
 
-                let new_player = create_player(new_session.clone());
-                handle.spawn(player_subscribe_to_playing_event(
-                    &new_player,
-                    Rc::downgrade(&self.delegate),
-                ));
-                handle.spawn(player_end_of_track_event(
-                    &new_player,
-                    Rc::downgrade(&self.delegate),
-                ));
-                player.replace(new_player);
+                player.replace(self.create_player(new_session.clone(), handle));
                 session.replace(new_session);
 
                 Ok(())
@@ -130,20 +239,26 @@ impl SpotifyPlayer {
     }
 
     pub async fn start(
-        self,
+        mut self,
         handle: Handle,
         receiver: UnboundedReceiver<Command>,
     ) -> Result<(), ()> {
+        let spectrum = self.spectrum.take();
         let _self = &self;
         let handle = &handle;
-        receiver
-            .for_each(|action| async move {
-                match _self.handle(action, handle).await {
-                    Ok(_) => {}
-                    Err(err) => _self.delegate.report_error(err),
-                }
-            })
-            .await;
+        let commands = receiver.for_each(|action| async move {
+            match _self.handle(action, handle).await {
+                Ok(_) => {}
+                Err(err) => _self.delegate.report_error(err),
+            }
+        });
+        // Stops along with the commands, as the sink never closes this stream
+        let spectrum = stream::iter(spectrum)
+            .flatten()
+            .for_each(|levels| async move {
+                _self.delegate.notify_spectrum(levels);
+            });
+        future::select(commands.boxed_local(), spectrum.boxed_local()).await;
         Ok(())
     }
 }
@@ -153,10 +268,16 @@ const CLIENT_ID: &str = "782ae96ea60f4cdf986a766049607005";
 const SCOPES: &str = "user-read-private,\
 playlist-read-private,\
 playlist-read-collaborative,\
+playlist-modify-public,\
+playlist-modify-private,\
+ugc-image-upload,\
 user-library-read,\
 user-library-modify,\
+user-follow-read,\
+user-follow-modify,\
 user-top-read,\
-user-read-recently-played";
+user-read-recently-played,\
+user-read-playback-position";
 
 async fn get_access_token(session: &Session) -> Result<String, SpotifyError> {
     let token = keymaster::get_token(session, CLIENT_ID, SCOPES)
@@ -180,12 +301,35 @@ async fn create_session(
     result.map_err(|_| SpotifyError::LoginFailed)
 }
 
-fn create_player(session: Session) -> Player {
+fn create_player(
+    session: Session,
+    normalization: Option<f32>,
+    channel_mix: Arc<ChannelMix>,
+    equalizer: Arc<Equalizer>,
+    visualizer: Arc<Visualizer>,
+    fade: Arc<Fade>,
+    volume: Arc<Volume>,
+) -> Player {
     let preferred = std::env::var("AUDIO_BACKEND").unwrap_or_else(|_| "pulseaudio".to_string());
     let alsa_device = std::env::var("ALSA_DEVICE").ok();
     let backend = audio_backend::find(Some(preferred)).unwrap();
-    let player_config = PlayerConfig::default();
-    let (new_player, _) = Player::new(player_config, session, None, move || backend(alsa_device));
+    let player_config = match normalization {
+        Some(pregain) => PlayerConfig {
+            normalisation: true,
+            normalisation_pregain: pregain,
+            ..PlayerConfig::default()
+        },
+        None => PlayerConfig::default(),
+    };
+    let (new_player, _) = Player::new(player_config, session, None, move || {
+        // Applied last, so that the visualizer isn't affected by the volume
+        let sink = VolumeSink::new(backend(alsa_device), volume);
+        let sink = VisualizerSink::new(Box::new(sink), visualizer);
+        let sink = ChannelMixSink::new(Box::new(sink), channel_mix);
+        let sink = EqualizerSink::new(Box::new(sink), equalizer);
+        let sink: Box<dyn audio_backend::Sink> = Box::new(FadeSink::new(Box::new(sink), fade));
+        sink
+    });
     new_player
 }
 
@@ -207,6 +351,19 @@ fn player_end_of_track_event(
         })
 }
 
+fn player_preload_event(
+    player: &Player,
+    delegate: Weak<dyn SpotifyPlayerDelegate>,
+) -> impl OldFuture<Item = (), Error = ()> {
+    player
+        .get_player_event_channel()
+        .filter(|event| matches!(event, PlayerEvent::TimeToPreloadNextTrack { .. }))
+        .for_each(move |_| {
+            delegate.upgrade().ok_or(())?.preload_next_track();
+            Ok(())
+        })
+}
+
 fn player_subscribe_to_playing_event(
     player: &Player,
     delegate: Weak<dyn SpotifyPlayerDelegate>,
@@ -0,0 +1,186 @@
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use librespot::playback::audio_backend::Sink;
+use std::f32::consts::PI;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+const SAMPLE_RATE: f32 = 44100.0;
+// About 20 frames per second
+const FRAME_SIZE: usize = 2048;
+const BANDS: usize = 24;
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 16000.0;
+// Levels below this many dB under full scale are shown as silence
+const RANGE_DB: f32 = 60.0;
+
+// Shared between the player and its sink, analysis frames are sent from the sink
+pub struct Visualizer {
+    enabled: AtomicBool,
+    sender: UnboundedSender<Vec<f32>>,
+}
+
+impl Visualizer {
+    pub fn new() -> (Self, UnboundedReceiver<Vec<f32>>) {
+        let (sender, receiver) = unbounded();
+        let visualizer = Self {
+            enabled: AtomicBool::new(false),
+            sender,
+        };
+        (visualizer, receiver)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+// In-place radix-2 FFT, the length must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+// Bands are evenly spread on a logarithmic scale
+fn band_edge(band: usize) -> f32 {
+    MIN_FREQUENCY * (MAX_FREQUENCY / MIN_FREQUENCY).powf(band as f32 / BANDS as f32)
+}
+
+// Levels between 0 and 1 for each band, from the lowest frequency
+fn analyze(samples: &[f32]) -> Vec<f32> {
+    let mut re = samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let hann = 0.5 - 0.5 * (2.0 * PI * i as f32 / FRAME_SIZE as f32).cos();
+            x * hann
+        })
+        .collect::<Vec<f32>>();
+    let mut im = vec![0.0; FRAME_SIZE];
+    fft(&mut re, &mut im);
+
+    let bin_width = SAMPLE_RATE / FRAME_SIZE as f32;
+    // What a full scale sine peaks at, once windowed
+    let full_scale = FRAME_SIZE as f32 / 4.0;
+    (0..BANDS)
+        .map(|band| {
+            let first = ((band_edge(band) / bin_width) as usize).max(1);
+            let last = ((band_edge(band + 1) / bin_width) as usize)
+                .max(first + 1)
+                .min(FRAME_SIZE / 2);
+            let peak = (first..last)
+                .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt())
+                .fold(0.0, f32::max);
+            let db = 20.0 * (peak / full_scale).max(1e-6).log10();
+            ((db + RANGE_DB) / RANGE_DB).max(0.0).min(1.0)
+        })
+        .collect()
+}
+
+pub struct VisualizerSink {
+    sink: Box<dyn Sink>,
+    visualizer: Arc<Visualizer>,
+    // Left and right channels mixed together
+    samples: Vec<f32>,
+}
+
+impl VisualizerSink {
+    pub fn new(sink: Box<dyn Sink>, visualizer: Arc<Visualizer>) -> Self {
+        Self {
+            sink,
+            visualizer,
+            samples: Vec::with_capacity(FRAME_SIZE),
+        }
+    }
+}
+
+impl Sink for VisualizerSink {
+    fn start(&mut self) -> io::Result<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.samples.clear();
+        self.sink.stop()
+    }
+
+    fn write(&mut self, data: &[i16]) -> io::Result<()> {
+        if self.visualizer.is_enabled() {
+            for frame in data.chunks_exact(2) {
+                let sample = (frame[0] as f32 + frame[1] as f32) / 2.0 / i16::MAX as f32;
+                self.samples.push(sample);
+                if self.samples.len() == FRAME_SIZE {
+                    let _ = self
+                        .visualizer
+                        .sender
+                        .unbounded_send(analyze(&self.samples));
+                    self.samples.clear();
+                }
+            }
+        } else {
+            self.samples.clear();
+        }
+        self.sink.write(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn sine(frequency: f32) -> Vec<f32> {
+        (0..FRAME_SIZE)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze_sine() {
+        let levels = analyze(&sine(1000.0));
+        let loudest = (0..BANDS)
+            .max_by(|&a, &b| levels[a].partial_cmp(&levels[b]).unwrap())
+            .unwrap();
+        assert!(band_edge(loudest) <= 1000.0 && 1000.0 < band_edge(loudest + 1));
+        assert!(levels[loudest] > 0.95);
+        assert!(levels[0] < 0.5);
+
+        let silence = analyze(&[0.0; FRAME_SIZE]);
+        assert!(silence.iter().all(|&level| level == 0.0));
+    }
+}
@@ -0,0 +1,96 @@
+use librespot::playback::audio_backend::Sink;
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+const FULL: u32 = 1000;
+
+// Shared between the player and its sink, so that changes apply to what is currently playing
+#[derive(Debug)]
+pub struct Volume {
+    // In thousandths, from 0 (muted) to 1000 (as is)
+    level: AtomicU32,
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            level: AtomicU32::new(FULL),
+        }
+    }
+}
+
+impl Volume {
+    pub fn set(&self, volume: f64) {
+        let level = (volume.max(0.0).min(1.0) * FULL as f64).round() as u32;
+        self.level.store(level, Ordering::Relaxed);
+    }
+
+    fn is_full(&self) -> bool {
+        self.level.load(Ordering::Relaxed) == FULL
+    }
+
+    fn apply(&self, samples: &mut [i16]) {
+        let gain = self.level.load(Ordering::Relaxed) as f32 / FULL as f32;
+        for sample in samples.iter_mut() {
+            *sample = (*sample as f32 * gain) as i16;
+        }
+    }
+}
+
+pub struct VolumeSink {
+    sink: Box<dyn Sink>,
+    volume: Arc<Volume>,
+    buffer: Vec<i16>,
+}
+
+impl VolumeSink {
+    pub fn new(sink: Box<dyn Sink>, volume: Arc<Volume>) -> Self {
+        Self {
+            sink,
+            volume,
+            buffer: vec![],
+        }
+    }
+}
+
+impl Sink for VolumeSink {
+    fn start(&mut self) -> io::Result<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, data: &[i16]) -> io::Result<()> {
+        if self.volume.is_full() {
+            return self.sink.write(data);
+        }
+        self.buffer.clear();
+        self.buffer.extend_from_slice(data);
+        self.volume.apply(&mut self.buffer);
+        self.sink.write(&self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_volume() {
+        let volume = Volume::default();
+        assert!(volume.is_full());
+
+        let mut samples = [1000, -1000];
+        volume.set(0.25);
+        volume.apply(&mut samples);
+        assert_eq!(samples, [250, -250]);
+
+        volume.set(-1.0);
+        volume.apply(&mut samples);
+        assert_eq!(samples, [0, 0]);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::app::components::utils::set_art_placeholder;
 use crate::app::components::{screen_add_css_provider, Component};
 use crate::app::dispatch::Worker;
 use crate::app::loader::ImageLoader;
@@ -5,6 +6,7 @@ use crate::app::models::AlbumModel;
 use gladis::Gladis;
 use gtk::prelude::*;
 use gtk::RevealerExt;
+use std::rc::Rc;
 
 #[derive(Gladis, Clone)]
 struct AlbumWidget {
@@ -39,11 +41,16 @@ impl Album {
                 if let (Some(image), Some(revealer)) = (image.upgrade(), revealer.upgrade()) {
                     let loader = ImageLoader::new();
                     let result = loader.load_remote(&url, "jpg", 200, 200).await;
-                    image.set_from_pixbuf(result.as_ref());
+                    if let Some(pixbuf) = result.as_ref() {
+                        image.set_from_pixbuf(Some(pixbuf));
+                    } else {
+                        set_art_placeholder(&image);
+                    }
                     revealer.set_reveal_child(true);
                 }
             });
         } else {
+            set_art_placeholder(&widget.cover_image);
             widget.revealer.set_reveal_child(true);
         }
 
@@ -63,6 +70,28 @@ impl Album {
         }
     }
 
+    // Right clicking the cover or pressing the Menu key opens the menu
+    pub fn connect_menu_requested<F: Fn(&AlbumModel, &gtk::Widget) + 'static>(&self, f: F) {
+        let f = Rc::new(f);
+        let f_clone = Rc::clone(&f);
+        self.widget.cover_btn.connect_button_press_event(
+            clone!(@weak self.model as model => @default-return Inhibit(false), move |button, event| {
+                if event.get_button() == 3 {
+                    f_clone(&model, button.upcast_ref());
+                    Inhibit(true)
+                } else {
+                    Inhibit(false)
+                }
+            }),
+        );
+        self.widget.cover_btn.connect_popup_menu(
+            clone!(@weak self.model as model => @default-return false, move |button| {
+                f(&model, button.upcast_ref());
+                true
+            }),
+        );
+    }
+
     pub fn connect_album_pressed<F: Fn(&AlbumModel) + 'static>(&self, f: F) {
         self.widget
             .cover_btn
@@ -0,0 +1,111 @@
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::handle_error;
+use crate::app::models::*;
+use crate::app::state::BrowserAction;
+use crate::app::{ActionDispatcher, AppModel};
+
+// The menu of album cards, to add an album to the library or remove it
+pub struct AlbumMenu {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl AlbumMenu {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    // Only the part of the library loaded so far is known, saving an album twice is harmless
+    fn is_saved(&self, id: &str) -> bool {
+        self.app_model
+            .map_state_opt(|s| s.browser.home_state())
+            .map(|home| home.albums.iter().any(|a| a.uri().as_deref() == Some(id)))
+            .unwrap_or(false)
+    }
+
+    // Enough to show the album in the library until the API call completes
+    fn describe(album: &AlbumModel, id: String) -> AlbumDescription {
+        AlbumDescription {
+            id,
+            title: album.title().unwrap_or_default(),
+            artists: vec![ArtistRef {
+                id: String::new(),
+                name: album.artist().unwrap_or_default(),
+            }],
+            art: album.cover_url(),
+            songs: vec![],
+            is_liked: true,
+            release_date: None,
+            label: None,
+            copyright: None,
+        }
+    }
+
+    fn save_action(&self, album: &AlbumModel, id: String) -> SimpleAction {
+        let action = SimpleAction::new("save", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        let album = Self::describe(album, id);
+        action.connect_activate(move |_, _| {
+            // Applied right away, before the API call completes
+            dispatcher.dispatch(BrowserAction::SaveAlbum(album.clone()).into());
+            let api = app_model.get_spotify();
+            let id = album.id.clone();
+            dispatcher.dispatch_async(Box::pin(async move {
+                match api.save_album(&id).await {
+                    Ok(album) => Some(BrowserAction::SaveAlbum(album).into()),
+                    Err(err) => handle_error(err),
+                }
+            }));
+        });
+        action
+    }
+
+    fn unsave_action(&self, id: String) -> SimpleAction {
+        let action = SimpleAction::new("unsave", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        action.connect_activate(move |_, _| {
+            // Applied right away, before the API call completes
+            dispatcher.dispatch(BrowserAction::UnsaveAlbum(id.clone()).into());
+            let api = app_model.get_spotify();
+            let id = id.clone();
+            dispatcher.dispatch_async(Box::pin(async move {
+                match api.remove_saved_album(&id).await {
+                    Ok(_) => None,
+                    Err(err) => handle_error(err),
+                }
+            }));
+        });
+        action
+    }
+
+    // Built when shown, as the album may have been saved or removed in the meantime
+    pub fn popup(&self, album: &AlbumModel, relative_to: &gtk::Widget) {
+        let id = match album.uri() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let group = SimpleActionGroup::new();
+        let menu = gio::Menu::new();
+        if self.is_saved(&id) {
+            group.add_action(&self.unsave_action(id));
+            menu.append(Some("Remove from your library"), Some("album.unsave"));
+        } else {
+            group.add_action(&self.save_action(album, id));
+            menu.append(Some("Add to your library"), Some("album.save"));
+        }
+        relative_to.insert_action_group("album", Some(&group));
+
+        let popover = gtk::Popover::from_model(Some(relative_to), &menu);
+        popover.popup();
+    }
+}
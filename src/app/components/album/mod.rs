@@ -1,2 +1,5 @@
 mod album;
 pub use album::Album;
+
+mod album_menu;
+pub use album_menu::AlbumMenu;
@@ -1,11 +1,15 @@
+use gio::prelude::*;
 use gladis::Gladis;
 use gtk::prelude::*;
 use gtk::ScrolledWindowExt;
 use std::rc::Rc;
 
-use crate::app::components::{screen_add_css_provider, Album, Component, EventListener, Playlist};
+use crate::app::components::utils::wrap_flowbox_item;
+use crate::app::components::{
+    screen_add_css_provider, Album, AlbumMenu, Artist, Component, EventListener, Playlist,
+};
 use crate::app::models::*;
-use crate::app::{AppEvent, BrowserEvent, Worker};
+use crate::app::{AppEvent, BrowserEvent, ListStore, Worker};
 
 use super::ArtistDetailsModel;
 
@@ -13,8 +17,18 @@ use super::ArtistDetailsModel;
 struct ArtistDetailsWidget {
     pub root: gtk::ScrolledWindow,
     pub artist_name: gtk::Label,
+    pub follow_button: gtk::Button,
+    pub radio_button: gtk::Button,
     pub top_tracks: gtk::ListBox,
+    pub releases_albums: gtk::RadioButton,
+    pub releases_singles: gtk::RadioButton,
+    pub releases_compilations: gtk::RadioButton,
     pub artist_releases: gtk::FlowBox,
+    pub appears_on_section: gtk::Expander,
+    pub appears_on: gtk::FlowBox,
+    pub appears_on_more: gtk::Button,
+    pub related_artists_section: gtk::Expander,
+    pub related_artists: gtk::FlowBox,
 }
 
 impl ArtistDetailsWidget {
@@ -22,16 +36,27 @@ impl ArtistDetailsWidget {
         screen_add_css_provider(resource!("/components/artist_details.css"));
         Self::from_resource(resource!("/components/artist_details.ui")).unwrap()
     }
+
+    fn release_groups(&self) -> [(&gtk::RadioButton, ReleaseGroup); 3] {
+        [
+            (&self.releases_albums, ReleaseGroup::Albums),
+            (&self.releases_singles, ReleaseGroup::Singles),
+            (&self.releases_compilations, ReleaseGroup::Compilations),
+        ]
+    }
 }
 
 pub struct ArtistDetails {
     model: Rc<ArtistDetailsModel>,
     widget: ArtistDetailsWidget,
+    worker: Worker,
+    album_menu: Rc<AlbumMenu>,
+    related_artists_model: gio::ListStore,
     children: Vec<Box<dyn EventListener>>,
 }
 
 impl ArtistDetails {
-    pub fn new(model: ArtistDetailsModel, worker: Worker) -> Self {
+    pub fn new(model: ArtistDetailsModel, worker: Worker, album_menu: Rc<AlbumMenu>) -> Self {
         model.load_artist_details(model.id.clone());
 
         let widget = ArtistDetailsWidget::new();
@@ -44,34 +69,133 @@ impl ArtistDetails {
             }
         });
 
-        if let Some(store) = model.get_list_store() {
-            let model_clone = Rc::clone(&model);
-
-            widget
-                .artist_releases
-                .bind_model(Some(store.unsafe_store()), move |item| {
-                    let item = item.downcast_ref::<AlbumModel>().unwrap();
-                    let child = gtk::FlowBoxChild::new();
-                    let album = Album::new(item, worker.clone());
-                    let weak = Rc::downgrade(&model_clone);
-                    album.connect_album_pressed(move |a| {
-                        if let (Some(id), Some(m)) = (a.uri().as_ref(), weak.upgrade()) {
-                            m.open_album(id);
+        for (button, group) in widget.release_groups().iter() {
+            let group = *group;
+            let weak_model = Rc::downgrade(&model);
+            button.connect_toggled(move |button| {
+                if let (true, Some(model)) = (button.get_active(), weak_model.upgrade()) {
+                    model.set_release_group(group);
+                }
+            });
+        }
+
+        widget
+            .follow_button
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.toggle_follow();
+            }));
+
+        widget
+            .radio_button
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.start_radio();
+            }));
+
+        widget
+            .appears_on_more
+            .connect_clicked(clone!(@weak model => move |_| {
+                let _ = model.load_more_appears_on();
+            }));
+
+        let related_artists_model = gio::ListStore::new(ArtistModel::static_type());
+        let weak_model = Rc::downgrade(&model);
+        let worker_clone = worker.clone();
+        widget
+            .related_artists
+            .bind_model(Some(&related_artists_model), move |item| {
+                wrap_flowbox_item(item, |item: &ArtistModel| {
+                    let artist = Artist::new(item, worker_clone.clone());
+                    let weak = weak_model.clone();
+                    artist.connect_artist_pressed(move |a| {
+                        if let (Some(id), Some(m)) = (a.id().as_ref(), weak.upgrade()) {
+                            m.open_artist(id);
                         }
                     });
-                    child.add(album.get_root_widget());
-                    child.show_all();
-                    child.upcast::<gtk::Widget>()
-                });
-        }
+                    artist.get_root_widget().clone()
+                })
+            });
 
         let playlist = Box::new(Playlist::new(widget.top_tracks.clone(), Rc::clone(&model)));
 
-        Self {
+        let artist_details = Self {
             widget,
             model,
+            worker,
+            album_menu,
+            related_artists_model,
             children: vec![playlist],
+        };
+        artist_details.bind_releases();
+        if let Some(store) = artist_details.model.get_appears_on_store() {
+            artist_details.bind_albums(&artist_details.widget.appears_on, &store);
         }
+        artist_details
+    }
+
+    fn bind_albums(&self, flowbox: &gtk::FlowBox, store: &ListStore<AlbumModel>) {
+        let weak_model = Rc::downgrade(&self.model);
+        let worker = self.worker.clone();
+        let album_menu = Rc::clone(&self.album_menu);
+
+        flowbox.bind_model(Some(store.unsafe_store()), move |item| {
+            let item = item.downcast_ref::<AlbumModel>().unwrap();
+            let child = gtk::FlowBoxChild::new();
+            let album = Album::new(item, worker.clone());
+            let weak = weak_model.clone();
+            album.connect_album_pressed(move |a| {
+                if let (Some(id), Some(m)) = (a.uri().as_ref(), weak.upgrade()) {
+                    m.open_album(id);
+                }
+            });
+            let album_menu = Rc::clone(&album_menu);
+            album.connect_menu_requested(move |a, widget| album_menu.popup(a, widget));
+            child.add(album.get_root_widget());
+            child.show_all();
+            child.upcast::<gtk::Widget>()
+        });
+    }
+
+    // Each group of releases has its own store, the one shown is bound to the flowbox
+    fn bind_releases(&self) {
+        if let Some(store) = self.model.get_list_store() {
+            self.bind_albums(&self.widget.artist_releases, &store);
+        }
+    }
+
+    // Paged with its button rather than by scrolling, which loads the discography
+    fn update_appears_on(&self) {
+        let has_releases = self.model.has_releases(ReleaseGroup::AppearsOn);
+        self.widget.appears_on_section.set_visible(has_releases);
+        self.widget
+            .appears_on_more
+            .set_visible(self.model.has_more_appears_on());
+    }
+
+    // Groups the artist has no releases in are left out, except the one shown
+    fn update_release_groups(&self) {
+        let current = self.model.release_group();
+        for (button, group) in self.widget.release_groups().iter() {
+            button.set_visible(*group == current || self.model.has_releases(*group));
+        }
+    }
+
+    // Only set along with the artist, unlike releases that come with every page
+    fn update_related_artists(&self) {
+        if self.related_artists_model.get_n_items() > 0 {
+            return;
+        }
+        let mut has_artists = false;
+        if let Some(artists) = self.model.get_related_artists() {
+            for artist in artists.iter() {
+                self.related_artists_model.append(&ArtistModel::new(
+                    &artist.name,
+                    &artist.photo,
+                    &artist.id,
+                ));
+            }
+            has_artists = !artists.is_empty();
+        }
+        self.widget.related_artists_section.set_visible(has_artists);
     }
 
     fn update_details(&self) {
@@ -80,6 +204,22 @@ impl ArtistDetails {
             context.add_class("artist__loaded");
             self.widget.artist_name.set_text(&name);
         }
+        self.update_release_groups();
+        self.update_appears_on();
+        self.update_related_artists();
+        self.update_followed();
+    }
+
+    fn update_followed(&self) {
+        let is_followed = self.model.is_followed();
+        let button = &self.widget.follow_button;
+        button.set_label(if is_followed { "Following" } else { "Follow" });
+        let context = button.get_style_context();
+        if is_followed {
+            context.remove_class("suggested-action");
+        } else {
+            context.add_class("suggested-action");
+        }
     }
 }
 
@@ -101,6 +241,18 @@ impl EventListener for ArtistDetails {
             {
                 self.update_details();
             }
+            AppEvent::BrowserEvent(BrowserEvent::ArtistReleaseGroupChanged(id))
+                if id == &self.model.id =>
+            {
+                self.bind_releases();
+                self.update_release_groups();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::ArtistFollowed(id))
+            | AppEvent::BrowserEvent(BrowserEvent::ArtistUnfollowed(id))
+                if id == &self.model.id =>
+            {
+                self.update_followed();
+            }
             _ => {}
         }
         self.broadcast_event(event);
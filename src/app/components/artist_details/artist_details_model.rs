@@ -1,14 +1,15 @@
 use gdk::SELECTION_CLIPBOARD;
 use gio::prelude::*;
-use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gio::{ActionMapExt, SimpleAction};
 use gtk::Clipboard;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::{handle_error, PlaylistModel};
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
 use crate::app::models::*;
 use crate::app::state::{
-    BrowserAction, BrowserEvent, PlaybackAction, PlaylistSource, SelectionAction, SelectionState,
+    BrowserAction, BrowserEvent, PlaybackAction, PlaylistSource, RadioSeed, SelectionAction,
+    SelectionState,
 };
 use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, ListStore};
 
@@ -37,9 +38,96 @@ impl ArtistDetailsModel {
             .map_state_opt(|s| s.browser.artist_state(&self.id)?.artist.as_ref())
     }
 
+    pub fn get_related_artists(&self) -> Option<impl Deref<Target = Vec<ArtistSummary>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.artist_state(&self.id)?.related_artists))
+    }
+
+    pub fn is_followed(&self) -> bool {
+        self.app_model
+            .get_state()
+            .browser
+            .artist_state(&self.id)
+            .map(|s| s.is_followed)
+            .unwrap_or(false)
+    }
+
+    pub fn toggle_follow(&self) {
+        let id = self.id.clone();
+        let is_followed = self.is_followed();
+        let api = self.app_model.get_spotify();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            if !is_followed {
+                match api.follow_artist(&id).await {
+                    Ok(artist) => Some(BrowserAction::FollowArtist(artist).into()),
+                    Err(err) => handle_error(err),
+                }
+            } else {
+                match api.unfollow_artist(&id).await {
+                    Ok(_) => Some(BrowserAction::UnfollowArtist(id).into()),
+                    Err(err) => handle_error(err),
+                }
+            }
+        }));
+    }
+
+    // Only once the artist's name is known, which the radio is named after
+    pub fn start_radio(&self) {
+        let name = self.get_artist_name().map(|name| name.clone());
+        if let Some(name) = name {
+            let seed = RadioSeed::Artist(self.id.clone(), name);
+            self.dispatcher
+                .dispatch(PlaybackAction::StartRadio(seed).into());
+        }
+    }
+
+    pub fn release_group(&self) -> ReleaseGroup {
+        self.app_model
+            .get_state()
+            .browser
+            .artist_state(&self.id)
+            .map(|s| s.release_group)
+            .unwrap_or(ReleaseGroup::Albums)
+    }
+
+    pub fn set_release_group(&self, group: ReleaseGroup) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetArtistReleaseGroup(self.id.clone(), group).into());
+    }
+
+    pub fn has_releases(&self, group: ReleaseGroup) -> bool {
+        self.app_model
+            .get_state()
+            .browser
+            .artist_state(&self.id)
+            .map(|s| s.releases(group).len() > 0)
+            .unwrap_or(false)
+    }
+
+    // The releases of the group picked
     pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
+        self.app_model.map_state_opt(|s| {
+            let state = s.browser.artist_state(&self.id)?;
+            Some(state.releases(state.release_group))
+        })
+    }
+
+    // Releases of other artists, shown in their own section
+    pub fn get_appears_on_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
+        self.app_model.map_state_opt(|s| {
+            let state = s.browser.artist_state(&self.id)?;
+            Some(state.releases(ReleaseGroup::AppearsOn))
+        })
+    }
+
+    pub fn has_more_appears_on(&self) -> bool {
         self.app_model
-            .map_state_opt(|s| Some(&s.browser.artist_state(&self.id)?.albums))
+            .get_state()
+            .browser
+            .artist_state(&self.id)
+            .and_then(|s| s.next_page(ReleaseGroup::AppearsOn)?.next_offset)
+            .is_some()
     }
 
     pub fn load_artist_details(&self, id: String) {
@@ -57,18 +145,32 @@ impl ArtistDetailsModel {
             .dispatch(AppAction::ViewAlbum(id.to_string()));
     }
 
+    pub fn open_artist(&self, id: &str) {
+        self.dispatcher
+            .dispatch(AppAction::ViewArtist(id.to_string()));
+    }
+
+    // Only the group of releases shown is paged
     pub fn load_more(&self) -> Option<()> {
+        self.load_more_releases(self.release_group())
+    }
+
+    pub fn load_more_appears_on(&self) -> Option<()> {
+        self.load_more_releases(ReleaseGroup::AppearsOn)
+    }
+
+    fn load_more_releases(&self, group: ReleaseGroup) -> Option<()> {
         let api = self.app_model.get_spotify();
         let state = self.app_model.get_state();
-        let next_page = &state.browser.artist_state(&self.id)?.next_page;
+        let next_page = state.browser.artist_state(&self.id)?.next_page(group)?;
 
-        let id = next_page.data.clone();
+        let id = self.id.clone();
         let batch_size = next_page.batch_size;
         let offset = next_page.next_offset?;
 
         self.dispatcher.dispatch_async(Box::pin(async move {
-            match api.get_artist_albums(&id, offset, batch_size).await {
-                Ok(albums) => Some(BrowserAction::AppendArtistReleases(albums).into()),
+            match api.get_artist_albums(&id, group, offset, batch_size).await {
+                Ok(albums) => Some(BrowserAction::AppendArtistReleases(id, group, albums).into()),
                 Err(err) => handle_error(err),
             }
         }));
@@ -116,7 +218,7 @@ impl PlaylistModel for ArtistDetailsModel {
         let songs = self.tracks_ref()?;
         let song = songs.iter().find(|&song| song.id == id)?;
 
-        let group = SimpleActionGroup::new();
+        let group = song_actions(self.dispatcher.as_ref(), song);
 
         let album_id = song.album.id.clone();
         let view_album = SimpleAction::new("view_album", None);
@@ -161,6 +263,7 @@ impl PlaylistModel for ArtistDetailsModel {
             );
         }
         menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
         Some(menu.upcast())
     }
 
@@ -0,0 +1,65 @@
+use std::rc::Rc;
+
+use crate::app::components::{handle_error, EventListener};
+use crate::app::state::{PlaybackAction, PlaybackEvent};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+// Spotify accepts at most 5 seeds
+const SEEDS: usize = 5;
+const RECOMMENDATIONS: u32 = 20;
+
+// Queues recommendations based on the last songs, when there's nothing left to play
+pub struct Autoplay {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    // The last song recommendations were requested after, so they're only requested once
+    requested_after: Option<String>,
+}
+
+impl Autoplay {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            requested_after: None,
+        }
+    }
+
+    fn seed_ids(&self) -> Vec<String> {
+        let state = self.app_model.get_state();
+        let ids: Vec<String> = state.playback.songs().map(|song| song.id.clone()).collect();
+        ids[ids.len().saturating_sub(SEEDS)..].to_vec()
+    }
+
+    // A radio always carries on, whatever the setting
+    fn queue_recommendations(&mut self) {
+        let enabled = {
+            let state = self.app_model.get_state();
+            state.settings.autoplay || state.playback.radio.is_some()
+        };
+        if !enabled {
+            return;
+        }
+        let seed_ids = self.seed_ids();
+        if seed_ids.is_empty() || self.requested_after.as_ref() == seed_ids.last() {
+            return;
+        }
+        self.requested_after = seed_ids.last().cloned();
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_recommendations(seed_ids, RECOMMENDATIONS).await {
+                Ok(tracks) => Some(PlaybackAction::Autoplay(tracks).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+}
+
+impl EventListener for Autoplay {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::QueueEnding) = event {
+            self.queue_recommendations();
+        }
+    }
+}
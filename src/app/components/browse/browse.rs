@@ -0,0 +1,107 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use gtk::ScrolledWindowExt;
+
+use std::rc::{Rc, Weak};
+
+use super::BrowseModel;
+use crate::app::components::{Album, Component, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::models::AlbumModel;
+use crate::app::AppEvent;
+
+#[derive(Clone, Gladis)]
+struct BrowseWidget {
+    pub scrolled_window: gtk::ScrolledWindow,
+    pub flowbox: gtk::FlowBox,
+}
+
+impl BrowseWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/browse.ui")).unwrap()
+    }
+
+    fn root(&self) -> &gtk::Widget {
+        self.scrolled_window.upcast_ref()
+    }
+}
+
+pub struct Browse {
+    widget: BrowseWidget,
+    worker: Worker,
+    model: Rc<BrowseModel>,
+}
+
+impl Browse {
+    pub fn new(worker: Worker, model: BrowseModel) -> Self {
+        let model = Rc::new(model);
+
+        let widget = BrowseWidget::new();
+
+        let weak_model = Rc::downgrade(&model);
+        widget.scrolled_window.connect_edge_reached(move |_, pos| {
+            if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
+                let _ = model.load_more_categories();
+            }
+        });
+
+        Self {
+            widget,
+            worker,
+            model,
+        }
+    }
+
+    fn bind_flowbox(&self, store: &gio::ListStore) {
+        let weak_model = Rc::downgrade(&self.model);
+        let worker_clone = self.worker.clone();
+
+        self.widget.flowbox.bind_model(Some(store), move |item| {
+            let item = item.downcast_ref::<AlbumModel>().unwrap();
+            let child = create_category_for(item, worker_clone.clone(), weak_model.clone());
+            child.show_all();
+            child.upcast::<gtk::Widget>()
+        });
+    }
+}
+
+impl EventListener for Browse {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => {
+                let _ = self.model.refresh_categories();
+                self.bind_flowbox(self.model.get_list_store().unwrap().unsafe_store())
+            }
+            AppEvent::LoginCompleted(_) => {
+                let _ = self.model.refresh_categories();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for Browse {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.root()
+    }
+}
+
+// Categories are shown as tiles, like albums
+fn create_category_for(
+    category_model: &AlbumModel,
+    worker: Worker,
+    model: Weak<BrowseModel>,
+) -> gtk::FlowBoxChild {
+    let child = gtk::FlowBoxChild::new();
+
+    let album = Album::new(category_model, worker);
+    child.add(album.get_root_widget());
+
+    album.connect_album_pressed(move |a| {
+        if let (Some(model), Some(id)) = (model.upgrade(), a.uri()) {
+            model.open_category(id);
+        }
+    });
+
+    child
+}
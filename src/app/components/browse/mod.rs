@@ -0,0 +1,5 @@
+mod browse;
+mod browse_model;
+
+pub use browse::*;
+pub use browse_model::*;
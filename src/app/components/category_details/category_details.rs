@@ -0,0 +1,77 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use gtk::ScrolledWindowExt;
+use std::rc::Rc;
+
+use super::CategoryDetailsModel;
+use crate::app::components::{Album, Component, EventListener};
+use crate::app::models::AlbumModel;
+use crate::app::Worker;
+
+#[derive(Clone, Gladis)]
+struct CategoryDetailsWidget {
+    pub root: gtk::ScrolledWindow,
+    pub category_name: gtk::Label,
+    pub playlists: gtk::FlowBox,
+}
+
+impl CategoryDetailsWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/category_details.ui")).unwrap()
+    }
+}
+
+pub struct CategoryDetails {
+    widget: CategoryDetailsWidget,
+}
+
+impl CategoryDetails {
+    pub fn new(model: CategoryDetailsModel, worker: Worker) -> Self {
+        let _ = model.load_playlists();
+
+        let widget = CategoryDetailsWidget::new();
+        if let Some(title) = model.get_title() {
+            widget.category_name.set_label(&title);
+        }
+
+        let model = Rc::new(model);
+
+        let weak_model = Rc::downgrade(&model);
+        widget.root.connect_edge_reached(move |_, pos| {
+            if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
+                let _ = model.load_more();
+            }
+        });
+
+        if let Some(store) = model.get_list_store() {
+            let model_clone = Rc::clone(&model);
+
+            widget
+                .playlists
+                .bind_model(Some(store.unsafe_store()), move |item| {
+                    let item = item.downcast_ref::<AlbumModel>().unwrap();
+                    let child = gtk::FlowBoxChild::new();
+                    let album = Album::new(item, worker.clone());
+                    let weak = Rc::downgrade(&model_clone);
+                    album.connect_album_pressed(move |a| {
+                        if let (Some(id), Some(m)) = (a.uri(), weak.upgrade()) {
+                            m.open_playlist(id);
+                        }
+                    });
+                    child.add(album.get_root_widget());
+                    child.show_all();
+                    child.upcast::<gtk::Widget>()
+                });
+        }
+
+        Self { widget }
+    }
+}
+
+impl Component for CategoryDetails {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.root.upcast_ref()
+    }
+}
+
+impl EventListener for CategoryDetails {}
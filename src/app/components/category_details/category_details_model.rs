@@ -0,0 +1,76 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::components::handle_error;
+use crate::app::models::*;
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
+
+pub struct CategoryDetailsModel {
+    pub id: String,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl CategoryDetailsModel {
+    pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            id,
+            app_model,
+            dispatcher,
+        }
+    }
+
+    // As listed on the browse page, which the category was opened from
+    pub fn get_title(&self) -> Option<String> {
+        let state = self.app_model.get_state();
+        let categories = &state.browser.home_state()?.categories;
+        let category = categories
+            .iter()
+            .find(|c| c.uri().as_ref() == Some(&self.id))?;
+        category.title()
+    }
+
+    pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.category_state(&self.id)?.playlists))
+    }
+
+    pub fn load_playlists(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let state = self.app_model.get_state();
+        let batch_size = state.browser.category_state(&self.id)?.next_page.batch_size;
+        let id = self.id.clone();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_category_playlists(&id, 0, batch_size).await {
+                Ok(playlists) => Some(BrowserAction::SetCategoryPlaylists(id, playlists).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    pub fn load_more(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.category_state(&self.id)?.next_page;
+
+        let id = next_page.data.clone();
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_category_playlists(&id, offset, batch_size).await {
+                Ok(playlists) => Some(BrowserAction::AppendCategoryPlaylists(id, playlists).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    pub fn open_playlist(&self, id: String) {
+        self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
+    }
+}
@@ -0,0 +1,5 @@
+mod category_details;
+mod category_details_model;
+
+pub use category_details::*;
+pub use category_details_model::*;
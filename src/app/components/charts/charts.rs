@@ -0,0 +1,111 @@
+use gio::SettingsExt;
+use gladis::Gladis;
+use gtk::prelude::*;
+
+use std::rc::{Rc, Weak};
+
+use super::ChartsModel;
+use crate::app::components::{Album, Component, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::models::AlbumModel;
+use crate::app::settings::SpotSettings;
+use crate::app::AppEvent;
+
+#[derive(Clone, Gladis)]
+struct ChartsWidget {
+    pub root: gtk::Widget,
+    pub country: gtk::ComboBoxText,
+    pub flowbox: gtk::FlowBox,
+}
+
+impl ChartsWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/charts.ui")).unwrap()
+    }
+}
+
+// Top playlists worldwide or in one country, which is remembered in the settings
+pub struct Charts {
+    widget: ChartsWidget,
+    worker: Worker,
+    model: Rc<ChartsModel>,
+    _settings: gio::Settings,
+}
+
+impl Charts {
+    pub fn new(worker: Worker, model: ChartsModel) -> Self {
+        let model = Rc::new(model);
+
+        let widget = ChartsWidget::new();
+
+        let settings = SpotSettings::gsettings();
+        settings.bind(
+            "charts-country",
+            &widget.country,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+
+        Self {
+            widget,
+            worker,
+            model,
+            _settings: settings,
+        }
+    }
+
+    fn bind_flowbox(&self, store: &gio::ListStore) {
+        let weak_model = Rc::downgrade(&self.model);
+        let worker_clone = self.worker.clone();
+
+        self.widget.flowbox.bind_model(Some(store), move |item| {
+            let item = item.downcast_ref::<AlbumModel>().unwrap();
+            let child = create_chart_for(item, worker_clone.clone(), weak_model.clone());
+            child.show_all();
+            child.upcast::<gtk::Widget>()
+        });
+    }
+}
+
+impl EventListener for Charts {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => {
+                self.model.refresh_charts();
+                self.bind_flowbox(self.model.get_list_store().unwrap().unsafe_store())
+            }
+            AppEvent::LoginCompleted(_) => {
+                self.model.refresh_charts();
+            }
+            AppEvent::SettingsChanged => {
+                let _ = self.model.update_country();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for Charts {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+}
+
+fn create_chart_for(
+    playlist_model: &AlbumModel,
+    worker: Worker,
+    model: Weak<ChartsModel>,
+) -> gtk::FlowBoxChild {
+    let child = gtk::FlowBoxChild::new();
+
+    let album = Album::new(playlist_model, worker);
+    child.add(album.get_root_widget());
+
+    album.connect_album_pressed(move |a| {
+        if let (Some(model), Some(id)) = (model.upgrade(), a.uri()) {
+            model.open_playlist(id);
+        }
+    });
+
+    child
+}
@@ -0,0 +1,55 @@
+use std::cell::Ref;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::components::handle_error;
+use crate::app::models::*;
+use crate::app::state::HomeState;
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
+
+pub struct ChartsModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl ChartsModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, HomeState>> {
+        self.app_model.map_state_opt(|s| s.browser.home_state())
+    }
+
+    pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
+        Some(Ref::map(self.state()?, |s| &s.charts))
+    }
+
+    // The charts of the country picked in the settings
+    pub fn refresh_charts(&self) {
+        let api = self.app_model.get_spotify();
+        let country = self.app_model.get_state().settings.charts_country.clone();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_charts(country.as_deref()).await {
+                Ok(playlists) => Some(BrowserAction::SetCharts(country, playlists).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn update_country(&self) -> Option<()> {
+        let country = self.app_model.get_state().settings.charts_country.clone();
+        if country != self.state()?.charts_country {
+            self.refresh_charts();
+        }
+        Some(())
+    }
+
+    pub fn open_playlist(&self, id: String) {
+        self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
+    }
+}
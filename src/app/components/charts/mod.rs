@@ -0,0 +1,5 @@
+mod charts;
+mod charts_model;
+
+pub use charts::*;
+pub use charts_model::*;
@@ -1,10 +1,14 @@
 use gladis::Gladis;
+use glib::ToVariant;
 use gtk::prelude::*;
 use gtk::LinkButtonExt;
 use std::rc::Rc;
 
 use super::DetailsModel;
 
+use crate::app::components::utils::{
+    explicit_filter_button, format_total_duration, set_art_placeholder,
+};
 use crate::app::components::{screen_add_css_provider, Component, EventListener, Playlist};
 use crate::app::dispatch::Worker;
 use crate::app::loader::ImageLoader;
@@ -14,11 +18,16 @@ use crate::app::{AppEvent, BrowserEvent};
 struct DetailsWidget {
     pub root: gtk::Widget,
     pub album_label: gtk::Label,
+    pub filter_entry: gtk::SearchEntry,
     pub album_tracks: gtk::ListBox,
     pub album_art: gtk::Image,
     pub like_button: gtk::Button,
     pub artist_button: gtk::LinkButton,
     pub artist_button_label: gtk::Label,
+    pub album_info: gtk::Label,
+    pub play_button: gtk::Button,
+    pub explicit_button: gtk::MenuButton,
+    pub copyright: gtk::Label,
 }
 
 impl DetailsWidget {
@@ -27,8 +36,8 @@ impl DetailsWidget {
         Self::from_resource(resource!("/components/details.ui")).unwrap()
     }
 
-    fn set_loaded(&self) {
-        let context = self.root.get_style_context();
+    fn set_loaded(root: &gtk::Widget) {
+        let context = root.get_style_context();
         context.add_class("details--loaded");
     }
 }
@@ -37,6 +46,7 @@ pub struct Details {
     model: Rc<DetailsModel>,
     worker: Worker,
     widget: DetailsWidget,
+    explicit_filter: gio::SimpleAction,
     children: Vec<Box<dyn EventListener>>,
 }
 
@@ -48,7 +58,14 @@ impl Details {
 
         let model = Rc::new(model);
         let widget = DetailsWidget::new();
-        let playlist = Box::new(Playlist::new(widget.album_tracks.clone(), model.clone()));
+        let playlist = Playlist::new(widget.album_tracks.clone(), model.clone());
+        playlist.connect_filter_entry(&widget.filter_entry);
+
+        widget
+            .play_button
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.play_all();
+            }));
 
         widget
             .like_button
@@ -56,11 +73,20 @@ impl Details {
                 model.toggle_save_album();
             }));
 
+        let explicit_filter = explicit_filter_button(
+            &widget.explicit_button,
+            model.explicit.get(),
+            clone!(@weak model => move |filter| {
+                model.explicit.set(filter);
+            }),
+        );
+
         Self {
             model,
             worker,
             widget,
-            children: vec![playlist],
+            explicit_filter,
+            children: vec![Box::new(playlist)],
         }
     }
 
@@ -70,6 +96,11 @@ impl Details {
             self.widget
                 .like_button
                 .set_label(if is_liked { "♥" } else { "♡" });
+            self.widget.like_button.set_tooltip_text(Some(if is_liked {
+                "Remove from your library"
+            } else {
+                "Add to your library"
+            }));
         }
     }
 
@@ -80,6 +111,28 @@ impl Details {
 
             self.widget.album_label.set_label(album);
             self.widget.artist_button_label.set_label(artist);
+            self.widget
+                .play_button
+                .set_sensitive(!info.songs.is_empty());
+
+            let mut album_info: Vec<String> = vec![];
+            album_info.extend(info.release_date.clone());
+            album_info.extend(info.label.clone());
+            let count = info.songs.len();
+            album_info.push(format!(
+                "{} {}, {}",
+                count,
+                if count == 1 { "song" } else { "songs" },
+                format_total_duration(info.duration())
+            ));
+            let album_info = album_info.join(" · ");
+            self.widget.album_info.set_label(&album_info);
+            self.widget.album_info.show();
+
+            if let Some(copyright) = info.copyright.as_ref() {
+                self.widget.copyright.set_label(copyright);
+                self.widget.copyright.show();
+            }
 
             let weak_model = Rc::downgrade(&self.model);
             self.widget.artist_button.connect_activate_link(move |_| {
@@ -89,17 +142,34 @@ impl Details {
                 glib::signal::Inhibit(true)
             });
 
-            let widget = self.widget.clone();
             if let Some(art) = info.art.clone() {
+                let model = Rc::downgrade(&self.model);
+                let image = self.widget.album_art.downgrade();
+                let root = self.widget.root.downgrade();
                 self.worker.send_local_task(async move {
                     let pixbuf = ImageLoader::new()
                         .load_remote(&art[..], "jpg", 100, 100)
                         .await;
-                    widget.album_art.set_from_pixbuf(pixbuf.as_ref());
-                    widget.set_loaded();
+                    // Drop covers that arrive after the page was left or its cover changed
+                    let (model, image, root) =
+                        match (model.upgrade(), image.upgrade(), root.upgrade()) {
+                            (Some(model), Some(image), Some(root)) => (model, image, root),
+                            _ => return,
+                        };
+                    let current_art = model.get_album_info().and_then(|info| info.art.clone());
+                    if current_art.as_ref() != Some(&art) {
+                        return;
+                    }
+                    if let Some(pixbuf) = pixbuf.as_ref() {
+                        image.set_from_pixbuf(Some(pixbuf));
+                    } else {
+                        set_art_placeholder(&image);
+                    }
+                    DetailsWidget::set_loaded(&root);
                 });
             } else {
-                widget.set_loaded();
+                set_art_placeholder(&self.widget.album_art);
+                DetailsWidget::set_loaded(&self.widget.root);
             }
         }
     }
@@ -123,6 +193,7 @@ impl EventListener for Details {
             {
                 self.update_details();
                 self.update_liked();
+                self.model.load_audio_features();
             }
             AppEvent::BrowserEvent(BrowserEvent::AlbumSaved(id))
             | AppEvent::BrowserEvent(BrowserEvent::AlbumUnsaved(id))
@@ -130,6 +201,12 @@ impl EventListener for Details {
             {
                 self.update_liked();
             }
+            AppEvent::SettingsChanged => {
+                let filter = self.model.explicit.get();
+                self.explicit_filter
+                    .set_state(&filter.as_str().to_variant());
+                self.model.load_audio_features();
+            }
             _ => {}
         }
         self.broadcast_event(event);
@@ -1,12 +1,14 @@
 use gdk::SELECTION_CLIPBOARD;
 use gio::prelude::*;
-use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gio::{ActionMapExt, SimpleAction};
 use gtk::Clipboard;
 use std::cell::Ref;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::{handle_error, PlaylistModel};
+use crate::app::components::{
+    append_song_menu, handle_error, song_actions, ExplicitFilterModel, PlaylistModel,
+};
 use crate::app::dispatch::ActionDispatcher;
 use crate::app::models::*;
 use crate::app::state::{
@@ -16,6 +18,7 @@ use crate::app::{AppAction, AppEvent, AppModel, AppState};
 
 pub struct DetailsModel {
     pub id: String,
+    pub explicit: ExplicitFilterModel,
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
 }
@@ -23,6 +26,7 @@ pub struct DetailsModel {
 impl DetailsModel {
     pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
         Self {
+            explicit: ExplicitFilterModel::new(id.clone(), Rc::clone(&app_model)),
             id,
             app_model,
             dispatcher,
@@ -50,6 +54,14 @@ impl DetailsModel {
         }));
     }
 
+    // From the first song shown
+    pub fn play_all(&self) {
+        let first = self.songs().first().map(|song| song.get_id());
+        if let Some(id) = first {
+            self.play_song(&id);
+        }
+    }
+
     pub fn view_artist(&self) {
         if let Some(album) = self.get_album_info() {
             let artist = &album.artists.first().unwrap().id;
@@ -59,10 +71,19 @@ impl DetailsModel {
     }
 
     pub fn toggle_save_album(&self) {
-        if let Some(album) = self.get_album_info() {
+        let album = self.get_album_info().map(|album| album.clone());
+        if let Some(album) = album {
             let id = album.id.clone();
             let is_liked = album.is_liked;
 
+            // Applied right away, before the API call completes
+            let action = if !is_liked {
+                BrowserAction::SaveAlbum(album)
+            } else {
+                BrowserAction::UnsaveAlbum(id.clone())
+            };
+            self.dispatcher.dispatch(action.into());
+
             let api = self.app_model.get_spotify();
 
             self.dispatcher.dispatch_async(Box::pin(async move {
@@ -73,7 +94,7 @@ impl DetailsModel {
                     }
                 } else {
                     match api.remove_saved_album(&id).await {
-                        Ok(_) => Some(BrowserAction::UnsaveAlbum(id).into()),
+                        Ok(_) => None,
                         Err(err) => handle_error(err),
                     }
                 }
@@ -86,6 +107,38 @@ impl DetailsModel {
     fn state(&self) -> Ref<'_, AppState> {
         self.app_model.get_state()
     }
+
+    fn wants_audio_features(&self) -> bool {
+        self.state().settings.mood_tags
+    }
+
+    pub fn load_audio_features(&self) {
+        if !self.wants_audio_features() {
+            return;
+        }
+        let ids: Vec<String> = {
+            let state = self.state();
+            self.songs_ref()
+                .map(|songs| {
+                    songs
+                        .iter()
+                        .filter(|s| !state.browser.has_fetched_audio_features(&s.id))
+                        .map(|s| s.id.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        if ids.is_empty() {
+            return;
+        }
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_audio_features(ids).await {
+                Ok(features) => Some(BrowserAction::SetAudioFeatures(features).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
 }
 
 impl PlaylistModel for DetailsModel {
@@ -115,8 +168,9 @@ impl PlaylistModel for DetailsModel {
     fn songs(&self) -> Vec<SongModel> {
         let songs = self.songs_ref();
         match songs {
-            Some(songs) => songs
-                .iter()
+            Some(songs) => self
+                .explicit
+                .filter(songs.iter())
                 .enumerate()
                 .map(|(i, s)| s.to_song_model(i))
                 .collect(),
@@ -126,29 +180,48 @@ impl PlaylistModel for DetailsModel {
 
     fn play_song(&self, id: &str) {
         let source = PlaylistSource::Album(self.id.clone());
-        if self.app_model.get_state().playback.source != source {
-            let songs = self.songs_ref();
+        if !self.app_model.get_state().playback.plays_as_loaded(&source) {
+            let songs = self.songs_ref().map(|songs| {
+                self.explicit
+                    .filter(songs.iter())
+                    .cloned()
+                    .collect::<Vec<SongDescription>>()
+            });
             if let Some(songs) = songs {
                 self.dispatcher
-                    .dispatch(PlaybackAction::LoadPlaylist(source, songs.clone()).into());
+                    .dispatch(PlaybackAction::LoadPlaylist(source, songs).into());
             }
         }
         self.dispatcher
             .dispatch(PlaybackAction::Load(id.to_string()).into());
     }
 
+    fn has_columns(&self) -> bool {
+        true
+    }
+
     fn should_refresh_songs(&self, event: &AppEvent) -> bool {
-        matches!(
-            event,
-            AppEvent::BrowserEvent(BrowserEvent::AlbumDetailsLoaded(id)) if id == &self.id
-        )
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::AlbumDetailsLoaded(id)) => id == &self.id,
+            AppEvent::SettingsChanged => true,
+            AppEvent::BrowserEvent(BrowserEvent::AudioFeaturesLoaded) => true,
+            _ => false,
+        }
+    }
+
+    fn song_tags(&self, id: &str) -> Option<String> {
+        let state = self.state();
+        if !state.settings.mood_tags {
+            return None;
+        }
+        state.browser.audio_features(id)?.describe()
     }
 
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let songs = self.songs_ref()?;
         let song = songs.iter().find(|&song| song.id == id)?;
 
-        let group = SimpleActionGroup::new();
+        let group = song_actions(self.dispatcher.as_ref(), song);
 
         for (i, artist) in song.artists.iter().enumerate() {
             let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
@@ -168,14 +241,6 @@ impl PlaylistModel for DetailsModel {
         });
         group.add_action(&copy_link);
 
-        let queue = SimpleAction::new("queue", None);
-        let dispatcher = self.dispatcher.box_clone();
-        let song = song.clone();
-        queue.connect_activate(move |_, _| {
-            dispatcher.dispatch(PlaybackAction::Queue(song.clone()).into());
-        });
-        group.add_action(&queue);
-
         Some(group.upcast())
     }
 
@@ -192,7 +257,7 @@ impl PlaylistModel for DetailsModel {
         }
 
         menu.append(Some("Copy link"), Some("song.copy_link"));
-        menu.append(Some("Queue"), Some("song.queue"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
 
         Some(menu.upcast())
     }
@@ -0,0 +1,130 @@
+use gio::prelude::*;
+use gladis::Gladis;
+use glib::ToVariant;
+use gtk::prelude::*;
+
+use crate::app::components::{screen_add_css_provider, Component};
+use crate::app::models::EpisodeModel;
+
+#[derive(Gladis, Clone)]
+struct EpisodeWidget {
+    root: gtk::Widget,
+    played_icon: gtk::Image,
+    title: gtk::Label,
+    description: gtk::Label,
+    show_notes_expander: gtk::Expander,
+    show_notes: gtk::Label,
+    release_date: gtk::Label,
+    duration: gtk::Label,
+    menu_btn: gtk::MenuButton,
+}
+
+impl EpisodeWidget {
+    pub fn new() -> Self {
+        screen_add_css_provider(resource!("/components/episode.css"));
+        Self::from_resource(resource!("/components/episode.ui")).unwrap()
+    }
+
+    fn set_played(root: &gtk::Widget, played_icon: &gtk::Image, played: bool) {
+        let context = root.get_style_context();
+        if played {
+            context.add_class("episode--played");
+        } else {
+            context.remove_class("episode--played");
+        }
+        played_icon.set_visible(played);
+    }
+}
+
+// Episodes can't be played yet, they are only listed
+pub struct Episode {
+    widget: EpisodeWidget,
+}
+
+impl Episode {
+    // on_played is called with the episode id when it's marked as played or unplayed
+    pub fn new<F>(episode_model: &EpisodeModel, on_played: F) -> Self
+    where
+        F: Fn(&str, bool) + 'static,
+    {
+        let widget = EpisodeWidget::new();
+
+        let bindings = [
+            ("title", &widget.title),
+            ("description", &widget.description),
+            ("release-date", &widget.release_date),
+            ("duration", &widget.duration),
+        ];
+        for (property, label) in bindings.iter() {
+            episode_model
+                .bind_property(property, *label, "label")
+                .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
+                .build();
+        }
+        episode_model
+            .bind_property("description", &widget.root, "tooltip-text")
+            .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
+            .build();
+
+        // Links in the notes open in the browser, which labels do by default
+        episode_model
+            .bind_property("show-notes", &widget.show_notes, "label")
+            .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
+            .build();
+        let has_notes = !widget.show_notes.get_label().is_empty();
+        widget.show_notes_expander.set_visible(has_notes);
+
+        // The full notes replace the truncated description once expanded
+        widget
+            .show_notes_expander
+            .bind_property("expanded", &widget.description, "visible")
+            .flags(glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::INVERT_BOOLEAN)
+            .build();
+
+        EpisodeWidget::set_played(
+            &widget.root,
+            &widget.played_icon,
+            episode_model.get_played(),
+        );
+
+        let played = gio::SimpleAction::new_stateful(
+            "played",
+            None,
+            &episode_model.get_played().to_variant(),
+        );
+        // The row may hold the only reference to its episode, as in search results
+        played.connect_activate(clone!(@strong episode_model => move |_, _| {
+            let played = !episode_model.get_played();
+            episode_model.set_played(played);
+            if let Some(id) = episode_model.id() {
+                on_played(&id, played);
+            }
+        }));
+        episode_model.connect_played_local(clone!(
+            @weak played as action,
+            @weak widget.root as root,
+            @weak widget.played_icon as played_icon => move |episode| {
+                let played = episode.get_played();
+                EpisodeWidget::set_played(&root, &played_icon, played);
+                action.set_state(&played.to_variant());
+            }
+        ));
+
+        let group = gio::SimpleActionGroup::new();
+        group.add_action(&played);
+        widget.root.insert_action_group("episode", Some(&group));
+
+        // Shown with a check mark, as the action is stateful
+        let menu = gio::Menu::new();
+        menu.append(Some("Played"), Some("episode.played"));
+        widget.menu_btn.set_menu_model(Some(&menu));
+
+        Self { widget }
+    }
+}
+
+impl Component for Episode {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+}
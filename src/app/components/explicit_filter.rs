@@ -0,0 +1,48 @@
+use std::rc::Rc;
+
+use crate::app::models::SongDescription;
+use crate::app::settings::{ExplicitFilter, SpotSettings};
+use crate::app::AppModel;
+
+// The explicit content filter of an album or a playlist, shared by their pages.
+// Once explicit songs are hidden, they are dropped from the queue too, see AppState.
+pub struct ExplicitFilterModel {
+    context_id: String,
+    app_model: Rc<AppModel>,
+}
+
+impl ExplicitFilterModel {
+    pub fn new(context_id: String, app_model: Rc<AppModel>) -> Self {
+        Self {
+            context_id,
+            app_model,
+        }
+    }
+
+    pub fn get(&self) -> ExplicitFilter {
+        self.app_model
+            .get_state()
+            .settings
+            .explicit_filter(&self.context_id)
+    }
+
+    pub fn set(&self, filter: ExplicitFilter) {
+        SpotSettings::save_explicit_filter(&self.context_id, filter);
+    }
+
+    pub fn hides_explicit(&self) -> bool {
+        self.app_model
+            .get_state()
+            .settings
+            .hides_explicit(&self.context_id)
+    }
+
+    // The songs that are listed and played, in the same order
+    pub fn filter<'a, I>(&self, songs: I) -> impl Iterator<Item = &'a SongDescription>
+    where
+        I: Iterator<Item = &'a SongDescription>,
+    {
+        let hides_explicit = self.hides_explicit();
+        songs.filter(move |song| !(hides_explicit && song.explicit))
+    }
+}
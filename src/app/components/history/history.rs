@@ -0,0 +1,62 @@
+use gladis::Gladis;
+use std::rc::Rc;
+
+use super::HistoryModel;
+use crate::app::components::{Component, EventListener, Playlist};
+use crate::app::state::PlaybackEvent;
+use crate::app::AppEvent;
+
+#[derive(Clone, Gladis)]
+struct HistoryWidget {
+    root: gtk::Widget,
+    listbox: gtk::ListBox,
+}
+
+impl HistoryWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/history.ui")).unwrap()
+    }
+}
+
+pub struct History {
+    widget: HistoryWidget,
+    model: Rc<HistoryModel>,
+    children: Vec<Box<dyn EventListener>>,
+}
+
+impl History {
+    pub fn new(model: HistoryModel) -> Self {
+        let model = Rc::new(model);
+        let widget = HistoryWidget::new();
+        let playlist = Playlist::new(widget.listbox.clone(), model.clone());
+
+        Self {
+            widget,
+            model,
+            children: vec![Box::new(playlist)],
+        }
+    }
+}
+
+impl Component for History {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
+    }
+}
+
+impl EventListener for History {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started | AppEvent::LoginCompleted(_) => self.model.refresh_history(),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(id)) => {
+                self.model.record_played(id)
+            }
+            _ => {}
+        }
+        self.broadcast_event(event);
+    }
+}
@@ -0,0 +1,199 @@
+use gdk::SELECTION_CLIPBOARD;
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction};
+use gtk::Clipboard;
+use std::cell::Ref;
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
+use crate::app::models::*;
+use crate::app::state::{
+    BrowserAction, BrowserEvent, HomeState, PlaybackAction, PlaylistSource, SelectionAction,
+    SelectionState,
+};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// The most the recently played endpoint returns
+const REPORTED_PLAYS: u32 = 50;
+
+fn is_same_day(a: &glib::DateTime, b: &glib::DateTime) -> bool {
+    a.get_year() == b.get_year() && a.get_day_of_year() == b.get_day_of_year()
+}
+
+fn day_title(played_at: i64) -> Option<String> {
+    let day = glib::DateTime::new_from_unix_local(played_at);
+    let today = glib::DateTime::new_now_local();
+    let yesterday = today.add_days(-1);
+    if is_same_day(&day, &today) {
+        Some("Today".to_string())
+    } else if yesterday.map(|y| is_same_day(&day, &y)).unwrap_or(false) {
+        Some("Yesterday".to_string())
+    } else {
+        day.format("%A %-e %B").map(|s| s.to_string())
+    }
+}
+
+pub struct HistoryModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl HistoryModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, HomeState>> {
+        self.app_model.map_state_opt(|s| s.browser.home_state())
+    }
+
+    // The same song may have been played several times, the most recent play comes first
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        self.state()?
+            .history
+            .iter()
+            .find(|played| played.song.id == id)
+            .map(|played| played.song.clone())
+    }
+
+    pub fn refresh_history(&self) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_recently_played(REPORTED_PLAYS).await {
+                Ok(played) => Some(BrowserAction::SetHistory(played).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn record_played(&self, id: &str) {
+        let song = self.app_model.get_state().playback.song(id).cloned();
+        let played_at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        if let Some(song) = song {
+            self.dispatcher
+                .dispatch(BrowserAction::AddToHistory(PlayedSong { song, played_at }).into());
+        }
+    }
+}
+
+impl PlaylistModel for HistoryModel {
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id.clone()
+    }
+
+    fn songs(&self) -> Vec<SongModel> {
+        match self.state() {
+            Some(state) => state
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, played)| played.song.to_song_model(i))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    // Songs played several times are only queued once
+    fn play_song(&self, id: &str) {
+        let songs = self.state().map(|state| {
+            let mut seen = HashSet::new();
+            state
+                .history
+                .iter()
+                .filter(|played| seen.insert(played.song.id.clone()))
+                .map(|played| played.song.clone())
+                .collect::<Vec<SongDescription>>()
+        });
+        if let Some(songs) = songs {
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadPlaylist(PlaylistSource::None, songs).into());
+            self.dispatcher
+                .dispatch(PlaybackAction::Load(id.to_string()).into());
+        }
+    }
+
+    fn should_refresh_songs(&self, event: &AppEvent) -> bool {
+        matches!(event, AppEvent::BrowserEvent(BrowserEvent::HistoryUpdated))
+    }
+
+    fn section_title(&self, index: usize) -> Option<String> {
+        let played_at = self.state()?.history.get(index)?.played_at;
+        day_title(played_at)
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song(id)?;
+        let group = song_actions(self.dispatcher.as_ref(), song);
+
+        let album_id = song.album.id.clone();
+        let view_album = SimpleAction::new("view_album", None);
+        let dispatcher = self.dispatcher.box_clone();
+        view_album.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+        });
+        group.add_action(&view_album);
+
+        for (i, artist) in song.artists.iter().enumerate() {
+            let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
+            let dispatcher = self.dispatcher.box_clone();
+            let id = artist.id.clone();
+            view_artist.connect_activate(move |_, _| {
+                dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+            });
+            group.add_action(&view_artist);
+        }
+
+        let track_id = song.id.clone();
+        let copy_link = SimpleAction::new("copy_link", None);
+        copy_link.connect_activate(move |_, _| {
+            let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+            clipboard.set_text(&format!("https://open.spotify.com/track/{}", &track_id));
+        });
+        group.add_action(&copy_link);
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song(id)?;
+
+        let menu = gio::Menu::new();
+        menu.append(Some("View album"), Some("song.view_album"));
+        for (i, artist) in song.artists.iter().enumerate() {
+            menu.append(
+                Some(&format!("More from {}", artist.name)),
+                Some(&format!("song.view_artist_{}", i)),
+            );
+        }
+
+        menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+
+        Some(menu.upcast())
+    }
+
+    fn select_song(&self, id: &str) {
+        if let Some(song) = self.song(id) {
+            self.dispatcher
+                .dispatch(SelectionAction::Select(song).into());
+        }
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(SelectionAction::Deselect(id.to_string()).into());
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+    }
+}
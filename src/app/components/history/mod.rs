@@ -0,0 +1,5 @@
+mod history;
+pub use history::*;
+
+mod history_model;
+pub use history_model::*;
@@ -1,3 +1,4 @@
+use gio::prelude::*;
 use gladis::Gladis;
 use gtk::prelude::*;
 use gtk::ScrolledWindowExt;
@@ -5,14 +6,25 @@ use gtk::ScrolledWindowExt;
 use std::rc::{Rc, Weak};
 
 use super::LibraryModel;
-use crate::app::components::{Album, Component, EventListener};
+use crate::app::components::utils::wrap_flowbox_item;
+use crate::app::components::{Album, AlbumMenu, Artist, Component, EventListener};
 use crate::app::dispatch::Worker;
-use crate::app::models::AlbumModel;
-use crate::app::AppEvent;
+use crate::app::models::{AlbumModel, ArtistModel};
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, BrowserEvent};
 
 #[derive(Clone, Gladis)]
 struct LibraryWidget {
     pub scrolled_window: gtk::ScrolledWindow,
+    pub recent_label: gtk::Label,
+    pub recent_window: gtk::ScrolledWindow,
+    pub recent: gtk::FlowBox,
+    pub made_for_you_label: gtk::Label,
+    pub made_for_you_window: gtk::ScrolledWindow,
+    pub made_for_you: gtk::FlowBox,
+    pub artists_label: gtk::Label,
+    pub artists: gtk::FlowBox,
+    pub albums_label: gtk::Label,
     pub flowbox: gtk::FlowBox,
 }
 
@@ -30,10 +42,14 @@ pub struct Library {
     widget: LibraryWidget,
     worker: Worker,
     model: Rc<LibraryModel>,
+    artists_model: gio::ListStore,
+    recent_model: gio::ListStore,
+    made_for_you_model: gio::ListStore,
+    album_menu: Rc<AlbumMenu>,
 }
 
 impl Library {
-    pub fn new(worker: Worker, model: LibraryModel) -> Self {
+    pub fn new(worker: Worker, model: LibraryModel, album_menu: Rc<AlbumMenu>) -> Self {
         let model = Rc::new(model);
 
         let widget = LibraryWidget::new();
@@ -45,20 +61,142 @@ impl Library {
             }
         });
 
+        let artists_model = gio::ListStore::new(ArtistModel::static_type());
+        let weak_model = Rc::downgrade(&model);
+        let worker_clone = worker.clone();
+        widget
+            .artists
+            .bind_model(Some(&artists_model), move |item| {
+                wrap_flowbox_item(item, |item: &ArtistModel| {
+                    let artist = Artist::new(item, worker_clone.clone());
+                    let weak = weak_model.clone();
+                    artist.connect_artist_pressed(move |a| {
+                        if let (Some(id), Some(m)) = (a.id(), weak.upgrade()) {
+                            m.open_artist(id);
+                        }
+                    });
+                    artist.get_root_widget().clone()
+                })
+            });
+
+        let recent_model = gio::ListStore::new(AlbumModel::static_type());
+        let weak_model = Rc::downgrade(&model);
+        let worker_clone = worker.clone();
+        widget.recent.bind_model(Some(&recent_model), move |item| {
+            wrap_flowbox_item(item, |item: &AlbumModel| {
+                let album = Album::new(item, worker_clone.clone());
+                let weak = weak_model.clone();
+                album.connect_album_pressed(move |a| {
+                    if let (Some(id), Some(m)) = (a.uri(), weak.upgrade()) {
+                        m.open_recent(id);
+                    }
+                });
+                album.get_root_widget().clone()
+            })
+        });
+
+        let made_for_you_model = gio::ListStore::new(AlbumModel::static_type());
+        let weak_model = Rc::downgrade(&model);
+        let worker_clone = worker.clone();
+        widget
+            .made_for_you
+            .bind_model(Some(&made_for_you_model), move |item| {
+                wrap_flowbox_item(item, |item: &AlbumModel| {
+                    let album = Album::new(item, worker_clone.clone());
+                    let weak = weak_model.clone();
+                    album.connect_album_pressed(move |a| {
+                        if let (Some(id), Some(m)) = (a.uri(), weak.upgrade()) {
+                            m.open_playlist(id);
+                        }
+                    });
+                    album.get_root_widget().clone()
+                })
+            });
+
         Self {
             widget,
             worker,
             model,
+            artists_model,
+            recent_model,
+            made_for_you_model,
+            album_menu,
+        }
+    }
+
+    // Albums are only titled when there's another section above them
+    fn update_albums_label(&self) {
+        let has_sections = self.widget.artists.get_visible()
+            || self.widget.recent_window.get_visible()
+            || self.widget.made_for_you_window.get_visible();
+        self.widget.albums_label.set_visible(has_sections);
+    }
+
+    fn update_recently_played(&self) {
+        self.recent_model.remove_all();
+        let mut has_recent = false;
+        if let Some(recent) = self.model.get_recently_played() {
+            for context in recent.iter() {
+                self.recent_model.append(&AlbumModel::new(
+                    &context.subtitle,
+                    &context.title,
+                    &context.art,
+                    &context.id,
+                ));
+            }
+            has_recent = !recent.is_empty();
         }
+        self.widget.recent_label.set_visible(has_recent);
+        self.widget.recent_window.set_visible(has_recent);
+        self.update_albums_label();
+    }
+
+    fn update_made_for_you(&self) {
+        self.made_for_you_model.remove_all();
+        let mut has_mixes = false;
+        if let Some(playlists) = self.model.get_made_for_you() {
+            for playlist in playlists.iter() {
+                self.made_for_you_model.append(&AlbumModel::from(playlist));
+            }
+            has_mixes = !playlists.is_empty();
+        }
+        self.widget.made_for_you_label.set_visible(has_mixes);
+        self.widget.made_for_you_window.set_visible(has_mixes);
+        self.update_albums_label();
+    }
+
+    // The section is only shown to those following artists
+    fn update_followed_artists(&self) {
+        self.artists_model.remove_all();
+        let mut has_artists = false;
+        if let Some(artists) = self.model.get_followed_artists() {
+            for artist in artists.iter() {
+                self.artists_model.append(&ArtistModel::new(
+                    &artist.name,
+                    &artist.photo,
+                    &artist.id,
+                ));
+            }
+            has_artists = !artists.is_empty();
+        }
+        self.widget.artists_label.set_visible(has_artists);
+        self.widget.artists.set_visible(has_artists);
+        self.update_albums_label();
     }
 
     fn bind_flowbox(&self, store: &gio::ListStore) {
         let weak_model = Rc::downgrade(&self.model);
         let worker_clone = self.worker.clone();
+        let album_menu = Rc::clone(&self.album_menu);
 
         self.widget.flowbox.bind_model(Some(store), move |item| {
             let item = item.downcast_ref::<AlbumModel>().unwrap();
-            let child = create_album_for(item, worker_clone.clone(), weak_model.clone());
+            let child = create_album_for(
+                item,
+                worker_clone.clone(),
+                weak_model.clone(),
+                Rc::clone(&album_menu),
+            );
             child.show_all();
             child.upcast::<gtk::Widget>()
         });
@@ -70,10 +208,28 @@ impl EventListener for Library {
         match event {
             AppEvent::Started => {
                 let _ = self.model.refresh_saved_albums();
+                self.model.refresh_followed_artists();
+                self.model.refresh_recently_played();
+                self.model.refresh_made_for_you();
                 self.bind_flowbox(self.model.get_list_store().unwrap().unsafe_store())
             }
             AppEvent::LoginCompleted(_) => {
                 let _ = self.model.refresh_saved_albums();
+                self.model.refresh_followed_artists();
+                self.model.refresh_recently_played();
+                self.model.refresh_made_for_you();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.model.refresh_recently_played();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::RecentlyPlayedUpdated) => {
+                self.update_recently_played();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::MadeForYouUpdated) => {
+                self.update_made_for_you();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::FollowedArtistsUpdated) => {
+                self.update_followed_artists();
             }
             _ => {}
         }
@@ -90,6 +246,7 @@ fn create_album_for(
     album_model: &AlbumModel,
     worker: Worker,
     model: Weak<LibraryModel>,
+    album_menu: Rc<AlbumMenu>,
 ) -> gtk::FlowBoxChild {
     let child = gtk::FlowBoxChild::new();
 
@@ -101,6 +258,7 @@ fn create_album_for(
             model.open_album(id);
         }
     });
+    album.connect_menu_requested(move |a, widget| album_menu.popup(a, widget));
 
     child
 }
@@ -7,6 +7,9 @@ use crate::app::models::*;
 use crate::app::state::HomeState;
 use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
 
+// Number of albums, playlists and shows to jump back in to
+const RECENTLY_PLAYED_COUNT: usize = 12;
+
 pub struct LibraryModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
@@ -28,6 +31,54 @@ impl LibraryModel {
         Some(Ref::map(self.state()?, |s| &s.albums))
     }
 
+    pub fn get_followed_artists(&self) -> Option<impl Deref<Target = Vec<ArtistSummary>> + '_> {
+        Some(Ref::map(self.state()?, |s| &s.followed_artists))
+    }
+
+    pub fn get_recently_played(&self) -> Option<impl Deref<Target = Vec<RecentContext>> + '_> {
+        Some(Ref::map(self.state()?, |s| &s.recently_played))
+    }
+
+    pub fn refresh_recently_played(&self) {
+        let api = self.app_model.get_spotify();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api
+                .get_recently_played_contexts(RECENTLY_PLAYED_COUNT)
+                .await
+            {
+                Ok(recent) => Some(BrowserAction::SetRecentlyPlayed(recent).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn get_made_for_you(&self) -> Option<impl Deref<Target = Vec<PlaylistDescription>> + '_> {
+        Some(Ref::map(self.state()?, |s| &s.made_for_you))
+    }
+
+    pub fn refresh_made_for_you(&self) {
+        let api = self.app_model.get_spotify();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_made_for_you().await {
+                Ok(playlists) => Some(BrowserAction::SetMadeForYou(playlists).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn refresh_followed_artists(&self) {
+        let api = self.app_model.get_spotify();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_followed_artists().await {
+                Ok(artists) => Some(BrowserAction::SetFollowedArtists(artists).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
     pub fn refresh_saved_albums(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
         let batch_size = self.state()?.next_albums_page.batch_size;
@@ -62,4 +113,24 @@ impl LibraryModel {
     pub fn open_album(&self, album_id: String) {
         self.dispatcher.dispatch(AppAction::ViewAlbum(album_id));
     }
+
+    pub fn open_recent(&self, id: String) {
+        let kind = self
+            .get_recently_played()
+            .and_then(|recent| recent.iter().find(|c| c.id == id).map(|c| c.kind));
+        match kind {
+            Some(RecentKind::Album) => self.dispatcher.dispatch(AppAction::ViewAlbum(id)),
+            Some(RecentKind::Playlist) => self.dispatcher.dispatch(AppAction::ViewPlaylist(id)),
+            Some(RecentKind::Show) => self.dispatcher.dispatch(AppAction::ViewShow(id)),
+            None => {}
+        }
+    }
+
+    pub fn open_playlist(&self, id: String) {
+        self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
+    }
+
+    pub fn open_artist(&self, artist_id: String) {
+        self.dispatcher.dispatch(AppAction::ViewArtist(artist_id));
+    }
 }
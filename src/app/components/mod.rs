@@ -27,12 +27,33 @@ pub use login::*;
 mod player_notifier;
 pub use player_notifier::PlayerNotifier;
 
+mod autoplay;
+pub use autoplay::Autoplay;
+
+mod radio;
+pub use radio::Radio;
+
+mod song_credits;
+pub use song_credits::SongCreditsLoader;
+
+mod session_keeper;
+pub use session_keeper::SessionKeeper;
+
+mod shuffle_keeper;
+pub use shuffle_keeper::ShuffleKeeper;
+
+mod up_next_notifier;
+pub use up_next_notifier::UpNextNotifier;
+
 mod library;
 pub use library::*;
 
 mod details;
 pub use details::*;
 
+mod explicit_filter;
+pub use explicit_filter::ExplicitFilterModel;
+
 mod search;
 pub use search::*;
 
@@ -42,6 +63,9 @@ use album::*;
 mod artist;
 use artist::*;
 
+mod episode;
+use episode::*;
+
 mod artist_details;
 pub use artist_details::*;
 
@@ -57,9 +81,36 @@ pub use notification::*;
 mod saved_playlists;
 pub use saved_playlists::*;
 
+mod saved_tracks;
+pub use saved_tracks::*;
+
+mod smart_playlists;
+pub use smart_playlists::*;
+
+mod history;
+pub use history::*;
+
 mod podcasts;
 pub use podcasts::*;
 
+mod new_releases;
+pub use new_releases::*;
+
+mod charts;
+pub use charts::*;
+
+mod browse;
+pub use browse::*;
+
+mod category_details;
+pub use category_details::*;
+
+mod recommendations;
+pub use recommendations::*;
+
+mod show_details;
+pub use show_details::*;
+
 mod playlist_details;
 pub use playlist_details::*;
 
@@ -69,6 +120,15 @@ pub use window::*;
 mod selection_editor;
 pub use selection_editor::*;
 
+mod settings;
+pub use settings::*;
+
+mod resume_card;
+pub use resume_card::*;
+
+mod visualizer;
+pub use visualizer::*;
+
 pub mod utils;
 
 pub fn handle_error(err: SpotifyApiError) -> Option<AppAction> {
@@ -22,26 +22,77 @@ impl ScreenFactory {
         }
     }
 
+    fn make_album_menu(&self) -> Rc<AlbumMenu> {
+        Rc::new(AlbumMenu::new(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        ))
+    }
+
+    fn make_show_menu(&self) -> Rc<ShowMenu> {
+        Rc::new(ShowMenu::new(
+            Rc::clone(&self.app_model),
+            self.dispatcher.box_clone(),
+        ))
+    }
+
     pub fn make_library(&self) -> Library {
         let model = LibraryModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        Library::new(self.worker.clone(), model)
+        Library::new(self.worker.clone(), model, self.make_album_menu())
     }
 
     pub fn make_saved_playlists(&self) -> SavedPlaylists {
         let model =
             SavedPlaylistsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        SavedPlaylists::new(self.worker.clone(), model)
+        let import = PlaylistImport::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        SavedPlaylists::new(self.worker.clone(), model, import)
     }
 
-    pub fn make_podcasts(&self) -> Podcasts {
+    pub fn make_saved_tracks(&self) -> SavedTracks {
+        let model = SavedTracksModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        SavedTracks::new(model)
+    }
+
+    pub fn make_smart_playlists(&self) -> SmartPlaylists {
         let model =
-            PodcastsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        Podcasts::new(self.worker.clone(), model)
+            SmartPlaylistsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        SmartPlaylists::new(model)
+    }
+
+    pub fn make_history(&self) -> History {
+        let model = HistoryModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        History::new(model)
+    }
+
+    pub fn make_podcasts(&self) -> Podcasts {
+        let model = PodcastsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        Podcasts::new(self.worker.clone(), model, self.make_show_menu())
+    }
+
+    pub fn make_new_releases(&self) -> NewReleases {
+        let model = NewReleasesModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        NewReleases::new(self.worker.clone(), model, self.make_album_menu())
+    }
+
+    pub fn make_charts(&self) -> Charts {
+        let model = ChartsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        Charts::new(self.worker.clone(), model)
+    }
+
+    pub fn make_browse(&self) -> Browse {
+        let model = BrowseModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        Browse::new(self.worker.clone(), model)
     }
 
     pub fn make_now_playing(&self) -> NowPlaying {
         let model = NowPlayingModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        NowPlaying::new(model)
+        let visualizer_model = VisualizerModel::new(Rc::clone(&self.app_model));
+        NowPlaying::new(model, visualizer_model)
+    }
+
+    pub fn make_resume_card(&self) -> ResumeCard {
+        let model = ResumeCardModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        ResumeCard::new(model, self.worker.clone())
     }
 
     pub fn make_album_details(&self, id: String) -> Details {
@@ -52,13 +103,18 @@ impl ScreenFactory {
     pub fn make_search_results(&self) -> SearchResults {
         let model =
             SearchResultsModel::new(Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        SearchResults::new(model, self.worker.clone())
+        SearchResults::new(
+            model,
+            self.worker.clone(),
+            self.make_album_menu(),
+            self.make_show_menu(),
+        )
     }
 
     pub fn make_artist_details(&self, id: String) -> ArtistDetails {
         let model =
             ArtistDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
-        ArtistDetails::new(model, self.worker.clone())
+        ArtistDetails::new(model, self.worker.clone(), self.make_album_menu())
     }
 
     pub fn make_playlist_details(&self, id: String) -> PlaylistDetails {
@@ -66,4 +122,22 @@ impl ScreenFactory {
             PlaylistDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
         PlaylistDetails::new(model, self.worker.clone())
     }
+
+    pub fn make_category_details(&self, id: String) -> CategoryDetails {
+        let model =
+            CategoryDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        CategoryDetails::new(model, self.worker.clone())
+    }
+
+    pub fn make_recommendations(&self, id: String) -> Recommendations {
+        let model =
+            RecommendationsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        Recommendations::new(model)
+    }
+
+    pub fn make_show_details(&self, id: String) -> ShowDetails {
+        let model =
+            ShowDetailsModel::new(id, Rc::clone(&self.app_model), self.dispatcher.box_clone());
+        ShowDetails::new(model, self.worker.clone())
+    }
 }
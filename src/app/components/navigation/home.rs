@@ -1,9 +1,20 @@
 use gtk::prelude::*;
 use gtk::{ListBoxExt, StackExt, StackSidebarExt};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use super::NavigationModel;
+use crate::app::components::utils::Debouncer;
 use crate::app::components::{Component, EventListener, ScreenFactory};
+use crate::app::state::PlaybackEvent;
 use crate::app::AppEvent;
 
+// How long a track must play before now playing is shown, so that skipping doesn't trigger it
+const AUTO_SHOW_SETTLE_MS: u32 = 1500;
+// Now playing isn't shown automatically for a while after the user left it
+const AUTO_SHOW_SUPPRESSED_FOR: Duration = Duration::from_secs(30);
+
 fn find_listbox_descendant(w: &gtk::Widget) -> Option<gtk::ListBox> {
     match w.clone().downcast::<gtk::ListBox>() {
         Ok(listbox) => Some(listbox),
@@ -15,42 +26,149 @@ fn find_listbox_descendant(w: &gtk::Widget) -> Option<gtk::ListBox> {
 }
 
 pub struct HomePane {
+    root: gtk::Box,
     stack: gtk::Stack,
     stack_sidebar: gtk::StackSidebar,
+    model: Rc<NavigationModel>,
+    auto_show: Debouncer,
+    auto_hide: Debouncer,
+    // The page to go back to, while now playing is shown automatically
+    auto_shown_from: Rc<RefCell<Option<String>>>,
+    dismissed_at: Rc<Cell<Option<Instant>>>,
     components: Vec<Box<dyn EventListener>>,
 }
 
 impl HomePane {
-    pub fn new(stack_sidebar: gtk::StackSidebar, screen_factory: &ScreenFactory) -> Self {
+    pub fn new(
+        stack_sidebar: gtk::StackSidebar,
+        screen_factory: &ScreenFactory,
+        model: Rc<NavigationModel>,
+    ) -> Self {
         let library = screen_factory.make_library();
         let podcasts = screen_factory.make_podcasts();
+        let new_releases = screen_factory.make_new_releases();
+        let charts = screen_factory.make_charts();
+        let browse = screen_factory.make_browse();
         let saved_playlists = screen_factory.make_saved_playlists();
+        let saved_tracks = screen_factory.make_saved_tracks();
+        let smart_playlists = screen_factory.make_smart_playlists();
+        let history = screen_factory.make_history();
         let now_playing = screen_factory.make_now_playing();
+        let resume_card = screen_factory.make_resume_card();
 
         let stack = gtk::Stack::new();
         stack.set_transition_type(gtk::StackTransitionType::Crossfade);
         stack.add_titled(library.get_root_widget(), "library", "Library");
         stack.add_titled(podcasts.get_root_widget(), "podcasts", "Podcasts");
+        stack.add_titled(
+            new_releases.get_root_widget(),
+            "new_releases",
+            "New releases",
+        );
+        stack.add_titled(charts.get_root_widget(), "charts", "Charts");
+        stack.add_titled(browse.get_root_widget(), "browse", "Browse");
         stack.add_titled(
             saved_playlists.get_root_widget(),
             "saved_playlists",
             "Playlists",
         );
+        stack.add_titled(
+            saved_tracks.get_root_widget(),
+            "saved_tracks",
+            "Liked songs",
+        );
+        stack.add_titled(
+            smart_playlists.get_root_widget(),
+            "smart_playlists",
+            "Smart playlists",
+        );
+        stack.add_titled(history.get_root_widget(), "history", "Recently played");
         stack.add_titled(now_playing.get_root_widget(), "now_playing", "Now playing");
 
         stack_sidebar.set_stack(&stack);
 
+        let auto_shown_from: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+        let dismissed_at = Rc::new(Cell::new(None));
+        stack.connect_property_visible_child_name_notify(
+            clone!(@weak auto_shown_from, @weak dismissed_at => move |stack| {
+                let is_now_playing = stack
+                    .get_visible_child_name()
+                    .map(|name| name.as_str() == "now_playing")
+                    .unwrap_or(false);
+                if !is_now_playing && auto_shown_from.replace(None).is_some() {
+                    dismissed_at.set(Some(Instant::now()));
+                }
+            }),
+        );
+
+        let root = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        root.pack_start(resume_card.get_root_widget(), false, false, 0);
+        root.pack_start(&stack, true, true, 0);
+
         Self {
+            root,
             stack,
             stack_sidebar,
+            model,
+            auto_show: Debouncer::new(),
+            auto_hide: Debouncer::new(),
+            auto_shown_from,
+            dismissed_at,
             components: vec![
                 Box::new(library),
+                Box::new(new_releases),
+                Box::new(charts),
+                Box::new(browse),
                 Box::new(saved_playlists),
+                Box::new(saved_tracks),
+                Box::new(smart_playlists),
+                Box::new(history),
                 Box::new(now_playing),
+                Box::new(resume_card),
             ],
         }
     }
 
+    fn schedule_auto_show(&self) {
+        if !self.model.auto_shows_now_playing() {
+            return;
+        }
+        let hide_delay = self.model.auto_hide_now_playing_delay();
+        let auto_hide = self.auto_hide.clone();
+        let stack = &self.stack;
+        let auto_shown_from = &self.auto_shown_from;
+        let dismissed_at = &self.dismissed_at;
+        self.auto_show.debounce(
+            AUTO_SHOW_SETTLE_MS,
+            clone!(@weak stack, @weak auto_shown_from, @weak dismissed_at => move || {
+                let recently_dismissed = dismissed_at
+                    .get()
+                    .map(|at| at.elapsed() < AUTO_SHOW_SUPPRESSED_FOR)
+                    .unwrap_or(false);
+                let current = stack.get_visible_child_name().map(|name| name.to_string());
+                if recently_dismissed || current.is_none() {
+                    return;
+                }
+                if current.as_deref() != Some("now_playing") {
+                    stack.set_visible_child_name("now_playing");
+                    auto_shown_from.replace(current);
+                }
+                if let Some(delay) = hide_delay {
+                    auto_hide.debounce(
+                        delay * 1000,
+                        clone!(@weak stack, @weak auto_shown_from => move || {
+                            // Taken first, so that going back isn't mistaken for the user leaving
+                            let previous = auto_shown_from.replace(None);
+                            if let Some(previous) = previous {
+                                stack.set_visible_child_name(&previous);
+                            }
+                        }),
+                    );
+                }
+            }),
+        );
+    }
+
     pub fn connect_navigated<F: Fn() + 'static>(&self, f: F) {
         // stack sidebar wraps a listbox with a scroll window, so i'm cheating a bit there to get the listbox ;)
         if let Some(listbox) = find_listbox_descendant(self.stack_sidebar.upcast_ref()) {
@@ -63,7 +181,7 @@ impl HomePane {
 
 impl Component for HomePane {
     fn get_root_widget(&self) -> &gtk::Widget {
-        self.stack.upcast_ref()
+        self.root.upcast_ref()
     }
 
     fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
@@ -73,8 +191,11 @@ impl Component for HomePane {
 
 impl EventListener for HomePane {
     fn on_event(&mut self, event: &AppEvent) {
-        if let AppEvent::NowPlayingShown = event {
-            self.stack.set_visible_child_name("now_playing");
+        match event {
+            AppEvent::NowPlayingShown => self.stack.set_visible_child_name("now_playing"),
+            AppEvent::HomePageShown(page) => self.stack.set_visible_child_name(page),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => self.schedule_auto_show(),
+            _ => {}
         }
         self.broadcast_event(event);
     }
@@ -18,6 +18,7 @@ pub struct Navigation {
     back_button: gtk::Button,
     screen_factory: ScreenFactory,
     children: Vec<Box<dyn ListenerComponent>>,
+    startup_view_opened: bool,
 }
 
 impl Navigation {
@@ -52,6 +53,7 @@ impl Navigation {
             back_button,
             screen_factory,
             children: vec![],
+            startup_view_opened: false,
         }
     }
 
@@ -89,7 +91,11 @@ impl Navigation {
     }
 
     fn make_home(&self) -> Box<dyn ListenerComponent> {
-        let home = HomePane::new(self.home_stack_sidebar.clone(), &self.screen_factory);
+        let home = HomePane::new(
+            self.home_stack_sidebar.clone(),
+            &self.screen_factory,
+            Rc::clone(&self.model),
+        );
 
         home.connect_navigated(
             clone!(@weak self.model as model, @weak self.leaflet as leaflet => move || {
@@ -116,6 +122,13 @@ impl Navigation {
             ScreenName::PlaylistDetails(id) => {
                 Box::new(self.screen_factory.make_playlist_details(id.to_owned()))
             }
+            ScreenName::Category(id) => {
+                Box::new(self.screen_factory.make_category_details(id.to_owned()))
+            }
+            ScreenName::Recommendations(id) => {
+                Box::new(self.screen_factory.make_recommendations(id.to_owned()))
+            }
+            ScreenName::Show(id) => Box::new(self.screen_factory.make_show_details(id.to_owned())),
         };
 
         let widget = component.get_root_widget();
@@ -162,6 +175,10 @@ impl EventListener for Navigation {
                 self.push_screen(&ScreenName::Home);
                 self.do_update_back_button();
             }
+            AppEvent::LoginCompleted(_) if !self.startup_view_opened => {
+                self.startup_view_opened = true;
+                self.model.open_startup_view();
+            }
             AppEvent::BrowserEvent(BrowserEvent::NavigationPushed(name)) => {
                 self.push_screen(name);
                 self.do_update_back_button();
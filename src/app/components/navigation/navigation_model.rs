@@ -1,5 +1,8 @@
+use crate::api::SpotifyApiError;
+use crate::app::components::handle_error;
+use crate::app::settings::StartupView;
 use crate::app::state::ScreenName;
-use crate::app::{ActionDispatcher, AppModel, BrowserAction};
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction};
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -26,6 +29,66 @@ impl NavigationModel {
             .dispatch(BrowserAction::NavigationPopTo(ScreenName::Home).into())
     }
 
+    pub fn open_startup_view(&self) {
+        let startup_view = self.app_model.get_state().settings.startup_view.clone();
+        match startup_view {
+            StartupView::Library => self.view_home_page("library"),
+            StartupView::SavedPlaylists => self.view_home_page("saved_playlists"),
+            StartupView::Podcasts => self.view_home_page("podcasts"),
+            StartupView::NowPlaying => self.view_home_page("now_playing"),
+            StartupView::Search => self
+                .dispatcher
+                .dispatch(BrowserAction::NavigationPush(ScreenName::Search).into()),
+            StartupView::Context(uri) => self.open_startup_context(&uri),
+        }
+    }
+
+    fn view_home_page(&self, page: &str) {
+        self.dispatcher
+            .dispatch(AppAction::ViewHomePage(page.to_string()));
+    }
+
+    // The context might not exist anymore, so we check before navigating to it
+    fn open_startup_context(&self, uri: &str) {
+        let unavailable = AppAction::ShowNotification(
+            "The startup view is unavailable, showing home instead".to_string(),
+        );
+        let screen = match ScreenName::from_uri(uri) {
+            Some(screen) => screen,
+            None => {
+                self.dispatcher.dispatch(unavailable);
+                return;
+            }
+        };
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            let result = match &screen {
+                ScreenName::AlbumDetails(id) => api.get_album(id).await.map(|_| ()),
+                ScreenName::PlaylistDetails(id) => api.get_playlist(id).await.map(|_| ()),
+                ScreenName::Artist(id) => api.get_artist(id).await.map(|_| ()),
+                _ => Ok(()),
+            };
+            match result {
+                Ok(_) => Some(BrowserAction::NavigationPush(screen).into()),
+                Err(SpotifyApiError::BadStatus(400)) | Err(SpotifyApiError::BadStatus(404)) => {
+                    Some(unavailable)
+                }
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn auto_shows_now_playing(&self) -> bool {
+        self.app_model.get_state().settings.auto_show_now_playing
+    }
+
+    // Seconds before going back to the page shown before now playing, if at all
+    pub fn auto_hide_now_playing_delay(&self) -> Option<u32> {
+        let settings = &self.app_model.get_state().settings;
+        Some(settings.auto_hide_now_playing_delay).filter(|_| settings.auto_hide_now_playing)
+    }
+
     pub fn visible_child_name(&self) -> impl Deref<Target = ScreenName> + '_ {
         self.app_model.map_state(|s| s.browser.current_screen())
     }
@@ -0,0 +1,5 @@
+mod new_releases;
+mod new_releases_model;
+
+pub use new_releases::*;
+pub use new_releases_model::*;
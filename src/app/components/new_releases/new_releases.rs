@@ -0,0 +1,116 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use gtk::ScrolledWindowExt;
+
+use std::rc::{Rc, Weak};
+
+use super::NewReleasesModel;
+use crate::app::components::{Album, AlbumMenu, Component, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::models::AlbumModel;
+use crate::app::AppEvent;
+
+#[derive(Clone, Gladis)]
+struct NewReleasesWidget {
+    pub scrolled_window: gtk::ScrolledWindow,
+    pub flowbox: gtk::FlowBox,
+}
+
+impl NewReleasesWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/new_releases.ui")).unwrap()
+    }
+
+    fn root(&self) -> &gtk::Widget {
+        self.scrolled_window.upcast_ref()
+    }
+}
+
+pub struct NewReleases {
+    widget: NewReleasesWidget,
+    worker: Worker,
+    model: Rc<NewReleasesModel>,
+    album_menu: Rc<AlbumMenu>,
+}
+
+impl NewReleases {
+    pub fn new(worker: Worker, model: NewReleasesModel, album_menu: Rc<AlbumMenu>) -> Self {
+        let model = Rc::new(model);
+
+        let widget = NewReleasesWidget::new();
+
+        let weak_model = Rc::downgrade(&model);
+        widget.scrolled_window.connect_edge_reached(move |_, pos| {
+            if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
+                let _ = model.load_more_new_releases();
+            }
+        });
+
+        Self {
+            widget,
+            worker,
+            model,
+            album_menu,
+        }
+    }
+
+    fn bind_flowbox(&self, store: &gio::ListStore) {
+        let weak_model = Rc::downgrade(&self.model);
+        let worker_clone = self.worker.clone();
+        let album_menu = Rc::clone(&self.album_menu);
+
+        self.widget.flowbox.bind_model(Some(store), move |item| {
+            let item = item.downcast_ref::<AlbumModel>().unwrap();
+            let child = create_album_for(
+                item,
+                worker_clone.clone(),
+                weak_model.clone(),
+                Rc::clone(&album_menu),
+            );
+            child.show_all();
+            child.upcast::<gtk::Widget>()
+        });
+    }
+}
+
+impl EventListener for NewReleases {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => {
+                let _ = self.model.refresh_new_releases();
+                self.bind_flowbox(self.model.get_list_store().unwrap().unsafe_store())
+            }
+            AppEvent::LoginCompleted(_) => {
+                let _ = self.model.refresh_new_releases();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for NewReleases {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.root()
+    }
+}
+
+fn create_album_for(
+    album_model: &AlbumModel,
+    worker: Worker,
+    model: Weak<NewReleasesModel>,
+    album_menu: Rc<AlbumMenu>,
+) -> gtk::FlowBoxChild {
+    let child = gtk::FlowBoxChild::new();
+
+    let album = Album::new(album_model, worker);
+    child.add(album.get_root_widget());
+
+    album.connect_album_pressed(move |a| {
+        if let (Some(model), Some(id)) = (model.upgrade(), a.uri()) {
+            model.open_album(id);
+        }
+    });
+    album.connect_menu_requested(move |a, widget| album_menu.popup(a, widget));
+
+    child
+}
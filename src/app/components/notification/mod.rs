@@ -1,4 +1,5 @@
 use gtk::prelude::*;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::app::components::EventListener;
@@ -6,22 +7,38 @@ use crate::app::{ActionDispatcher, AppAction, AppEvent};
 
 pub struct NotificationModel {
     dispatcher: Box<dyn ActionDispatcher>,
+    undo_action: RefCell<Option<AppAction>>,
 }
 
 impl NotificationModel {
     pub fn new(dispatcher: Box<dyn ActionDispatcher>) -> Self {
-        Self { dispatcher }
+        Self {
+            dispatcher,
+            undo_action: RefCell::new(None),
+        }
     }
 
     fn close(&self) {
         self.dispatcher.dispatch(AppAction::HideNotification);
     }
+
+    fn set_undo_action(&self, action: Option<AppAction>) {
+        self.undo_action.replace(action);
+    }
+
+    fn undo(&self) {
+        if let Some(action) = self.undo_action.borrow_mut().take() {
+            self.dispatcher.dispatch(action);
+        }
+        self.close();
+    }
 }
 
 pub struct Notification {
     model: Rc<NotificationModel>,
     root: gtk::Box,
     content: gtk::Label,
+    undo_btn: gtk::Button,
 }
 
 impl Notification {
@@ -30,17 +47,22 @@ impl Notification {
         root: gtk::Box,
         content: gtk::Label,
         close_btn: gtk::Button,
+        undo_btn: gtk::Button,
     ) -> Self {
         let model = Rc::new(model);
         close_btn.connect_clicked(clone!(@weak model => move |_| model.close()));
+        undo_btn.connect_clicked(clone!(@weak model => move |_| model.undo()));
 
         Self {
             model,
             root,
             content,
+            undo_btn,
         }
     }
-    fn show(&self, content: &str) {
+    fn show(&self, content: &str, undo: Option<&AppAction>) {
+        self.model.set_undo_action(undo.cloned());
+        self.undo_btn.set_visible(undo.is_some());
         glib::timeout_add_local(
             4000,
             clone!(@weak self.model as model => @default-return glib::Continue(false), move || {
@@ -55,6 +77,7 @@ impl Notification {
     }
 
     fn hide(&self) {
+        self.model.set_undo_action(None);
         self.root
             .get_style_context()
             .remove_class("notification--shown")
@@ -64,7 +87,10 @@ impl Notification {
 impl EventListener for Notification {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
-            AppEvent::NotificationShown(content) => self.show(&content),
+            AppEvent::NotificationShown(content) => self.show(&content, None),
+            AppEvent::UndoableNotificationShown(content, undo) => {
+                self.show(&content, Some(undo.as_ref()))
+            }
             AppEvent::NotificationHidden => self.hide(),
             // AppEvent::Started => self.show("Welcome to Spot!"),
             _ => {}
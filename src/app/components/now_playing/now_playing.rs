@@ -2,7 +2,11 @@ use gladis::Gladis;
 use gtk::prelude::*;
 use std::rc::Rc;
 
-use crate::app::components::{screen_add_css_provider, Component, EventListener, Playlist};
+use crate::app::components::utils::scroll_to_row;
+use crate::app::components::{
+    screen_add_css_provider, Component, EventListener, Playlist, Visualizer, VisualizerModel,
+};
+use crate::app::state::PlaybackEvent;
 use crate::app::AppEvent;
 
 use super::NowPlayingModel;
@@ -11,8 +15,17 @@ use super::NowPlayingModel;
 struct NowPlayingWidget {
     root: gtk::Widget,
     listbox: gtk::ListBox,
+    scrolled: gtk::ScrolledWindow,
+    go_to: gtk::Entry,
     shuffle: gtk::Button,
     clear: gtk::Button,
+    clear_upcoming: gtk::Button,
+    save: gtk::Button,
+    radio: gtk::Label,
+    pinned: gtk::Box,
+    pinned_label: gtk::Label,
+    unpin: gtk::Button,
+    visualizer: gtk::DrawingArea,
 }
 
 impl NowPlayingWidget {
@@ -24,11 +37,12 @@ impl NowPlayingWidget {
 
 pub struct NowPlaying {
     widget: NowPlayingWidget,
+    model: Rc<NowPlayingModel>,
     children: Vec<Box<dyn EventListener>>,
 }
 
 impl NowPlaying {
-    pub fn new(model: NowPlayingModel) -> Self {
+    pub fn new(model: NowPlayingModel, visualizer_model: VisualizerModel) -> Self {
         let widget = NowPlayingWidget::new();
         let model = Rc::new(model);
 
@@ -44,11 +58,115 @@ impl NowPlaying {
                 model.clear_queue();
             }));
 
-        let playlist = Playlist::new(widget.listbox.clone(), model);
+        widget
+            .clear_upcoming
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.clear_upcoming();
+            }));
+
+        widget
+            .save
+            .connect_clicked(clone!(@weak model => move |button| {
+                Self::prompt_playlist_name(button, model);
+            }));
+
+        widget.go_to.connect_activate(
+            clone!(@weak model, @weak widget.listbox as listbox, @weak widget.scrolled as scrolled => move |entry| {
+                let position = entry
+                    .get_text()
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&p| p >= 1 && p <= model.queue_len());
+                let context = entry.get_style_context();
+                if let Some(position) = position {
+                    context.remove_class("error");
+                    let adjustment = scrolled.get_vadjustment().unwrap();
+                    scroll_to_row(&listbox, &adjustment, position as i32 - 1);
+                    let ctrl = gtk::get_current_event_state()
+                        .map(|state| state.contains(gdk::ModifierType::CONTROL_MASK))
+                        .unwrap_or(false);
+                    if ctrl {
+                        model.play_song_at(position - 1);
+                    }
+                } else {
+                    context.add_class("error");
+                }
+            }),
+        );
+
+        widget.go_to.connect_changed(|entry| {
+            entry.get_style_context().remove_class("error");
+        });
+
+        widget
+            .unpin
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.unpin();
+            }));
+
+        let playlist = Playlist::new(widget.listbox.clone(), model.clone());
+        let visualizer = Visualizer::new(visualizer_model, widget.visualizer.clone());
 
         Self {
             widget,
-            children: vec![Box::new(playlist)],
+            model,
+            children: vec![Box::new(playlist), Box::new(visualizer)],
+        }
+    }
+
+    fn prompt_playlist_name(button: &gtk::Button, model: Rc<NowPlayingModel>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::Dialog::with_buttons(
+            Some("Save queue as playlist"),
+            window.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Save", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+
+        let entry = gtk::Entry::new();
+        entry.set_placeholder_text(Some("Playlist name"));
+        entry.set_activates_default(true);
+        entry.set_margin_start(12);
+        entry.set_margin_end(12);
+        entry.set_margin_top(12);
+        entry.set_margin_bottom(12);
+        dialog.get_content_area().add(&entry);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                model.save_as_playlist(entry.get_text().as_str());
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn update_radio(&self) {
+        if let Some(name) = self.model.radio_name() {
+            let radio = &self.widget.radio;
+            radio.set_text(&format!("Radio: {}", name));
+            radio.set_tooltip_text(Some(&format!("Songs recommended after {}", name)));
+            radio.show();
+        } else {
+            self.widget.radio.hide();
+        }
+    }
+
+    fn update_pinned(&self) {
+        if let Some(title) = self.model.pinned_song_title() {
+            self.widget
+                .pinned_label
+                .set_text(&format!("Playing next: {}", title));
+            self.widget.pinned.show();
+        } else {
+            self.widget.pinned.hide();
         }
     }
 }
@@ -65,6 +183,12 @@ impl Component for NowPlaying {
 
 impl EventListener for NowPlaying {
     fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged)
+        | AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event
+        {
+            self.update_pinned();
+            self.update_radio();
+        }
         self.broadcast_event(event);
     }
 }
@@ -1,12 +1,12 @@
 use gdk::SELECTION_CLIPBOARD;
 use gio::prelude::*;
-use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gio::{ActionMapExt, SimpleAction};
 use gtk::Clipboard;
 use std::cell::Ref;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::PlaylistModel;
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
 use crate::app::models::SongModel;
 use crate::app::state::{
     PlaybackAction, PlaybackEvent, PlaybackState, SelectionAction, SelectionState,
@@ -42,6 +42,63 @@ impl NowPlayingModel {
     pub fn clear_queue(&self) {
         self.dispatcher.dispatch(PlaybackAction::ClearQueue.into());
     }
+
+    pub fn clear_upcoming(&self) {
+        self.dispatcher
+            .dispatch(PlaybackAction::ClearUpcoming.into());
+    }
+
+    pub fn save_as_playlist(&self, name: &str) {
+        let name = name.trim().to_string();
+        let ids = self
+            .queue()
+            .songs()
+            .map(|s| s.id.clone())
+            .collect::<Vec<String>>();
+        if name.is_empty() || ids.is_empty() {
+            return;
+        }
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.create_playlist(&name, "", false, ids).await {
+                Ok(playlist) => Some(AppAction::ShowNotification(format!(
+                    "Saved the queue as “{}”",
+                    playlist.title
+                ))),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn queue_len(&self) -> usize {
+        self.queue().songs().count()
+    }
+
+    pub fn play_song_at(&self, index: usize) {
+        let id = self.queue().songs().nth(index).map(|s| s.id.clone());
+        if let Some(id) = id {
+            self.play_song(&id);
+        }
+    }
+
+    pub fn pinned_song_title(&self) -> Option<String> {
+        self.queue()
+            .pinned_song()
+            .map(|song| format!("{} — {}", song.title, song.artists_name()))
+    }
+
+    // What the radio playing, if any, is based on
+    pub fn radio_name(&self) -> Option<String> {
+        self.queue()
+            .radio
+            .as_ref()
+            .map(|seed| seed.name().to_string())
+    }
+
+    pub fn unpin(&self) {
+        self.dispatcher.dispatch(PlaybackAction::Unpin.into());
+    }
 }
 
 impl PlaylistModel for NowPlayingModel {
@@ -69,10 +126,47 @@ impl PlaylistModel for NowPlayingModel {
         )
     }
 
+    fn can_remove_song(&self, id: &str) -> bool {
+        self.queue().current_song_id.as_deref() != Some(id)
+    }
+
+    fn remove_song(&self, id: &str) {
+        let removed = self
+            .queue()
+            .songs()
+            .enumerate()
+            .find(|(_, song)| song.id == id)
+            .map(|(position, song)| (position, song.clone()));
+        if let Some((position, song)) = removed {
+            let message = format!("Removed {} from the queue", song.title);
+            let undo: AppAction = PlaybackAction::QueueAt(song, position).into();
+            self.dispatcher
+                .dispatch(PlaybackAction::Dequeue(id.to_string()).into());
+            self.dispatcher
+                .dispatch(AppAction::ShowUndoableNotification(message, Box::new(undo)));
+        }
+    }
+
+    fn can_move_songs(&self) -> bool {
+        true
+    }
+
+    fn move_song(&self, id: &str, position: usize) {
+        self.dispatcher
+            .dispatch(PlaybackAction::Move(id.to_string(), position).into());
+    }
+
+    fn moved_song(&self, event: &AppEvent) -> Option<(usize, usize)> {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::SongMoved(from, to)) => Some((*from, *to)),
+            _ => None,
+        }
+    }
+
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let queue = self.queue();
         let song = queue.song(id)?;
-        let group = SimpleActionGroup::new();
+        let group = song_actions(self.dispatcher.as_ref(), song);
 
         let album_id = song.album.id.clone();
         let view_album = SimpleAction::new("view_album", None);
@@ -126,7 +220,7 @@ impl PlaylistModel for NowPlayingModel {
         }
 
         menu.append(Some("Copy link"), Some("song.copy_link"));
-        menu.append(Some("Dequeue"), Some("song.dequeue"));
+        append_song_menu(&menu, &self.app_model.get_state(), true);
 
         Some(menu.upcast())
     }
@@ -3,3 +3,9 @@ pub use playback_control::*;
 
 mod playback_info;
 pub use playback_info::*;
+
+mod sleep_timer;
+pub use sleep_timer::*;
+
+mod repeat;
+pub use repeat::*;
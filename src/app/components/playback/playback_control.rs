@@ -1,3 +1,5 @@
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
 use glib::signal;
 use gtk::prelude::*;
 use gtk::{BinExt, ImageExt, LabelExt, RangeExt};
@@ -52,6 +54,21 @@ impl PlaybackControlModel {
         self.dispatcher.dispatch(PlaybackAction::TogglePlay.into());
     }
 
+    pub fn swap_with_next(&self, position: u32) {
+        let position = if self.state().settings.swap_resumes_position {
+            Some(position)
+        } else {
+            None
+        };
+        self.dispatcher
+            .dispatch(PlaybackAction::SwapWithNext(position).into());
+    }
+
+    pub fn clear_upcoming(&self) {
+        self.dispatcher
+            .dispatch(PlaybackAction::ClearUpcoming.into());
+    }
+
     pub fn seek_to(&self, position: u32) {
         self.dispatcher
             .dispatch(PlaybackAction::Seek(position).into());
@@ -125,6 +142,8 @@ impl PlaybackControl {
             model.play_prev_song()
         }));
 
+        Self::add_next_menu(&widget, &model);
+
         Self {
             model,
             widget,
@@ -133,6 +152,40 @@ impl PlaybackControl {
         }
     }
 
+    // Secondary click on the next button offers to swap the current and next tracks,
+    // or to drop everything after the current one
+    fn add_next_menu(widget: &PlaybackControlWidget, model: &Rc<PlaybackControlModel>) {
+        let swap = SimpleAction::new("swap_next", None);
+        let seek_bar = &widget.seek_bar;
+        swap.connect_activate(clone!(@weak model, @weak seek_bar => move |_, _| {
+            model.swap_with_next(seek_bar.get_value() as u32);
+        }));
+        let clear_upcoming = SimpleAction::new("clear_upcoming", None);
+        clear_upcoming.connect_activate(clone!(@weak model => move |_, _| {
+            model.clear_upcoming();
+        }));
+        let group = SimpleActionGroup::new();
+        group.add_action(&swap);
+        group.add_action(&clear_upcoming);
+        widget.next.insert_action_group("playback", Some(&group));
+
+        let menu = gio::Menu::new();
+        menu.append(Some("Swap with next"), Some("playback.swap_next"));
+        menu.append(
+            Some("Clear upcoming songs"),
+            Some("playback.clear_upcoming"),
+        );
+        let popover = gtk::Popover::from_model(Some(&widget.next), &menu);
+        widget.next.connect_button_press_event(move |_, event| {
+            if event.get_button() == 3 {
+                popover.popup();
+                signal::Inhibit(true)
+            } else {
+                signal::Inhibit(false)
+            }
+        });
+    }
+
     fn set_playing(&self, is_playing: bool) {
         let playback_image = if is_playing {
             "media-playback-pause-symbolic"
@@ -210,7 +263,8 @@ impl EventListener for PlaybackControl {
                 self.update_playing();
                 self.update_current_info();
             }
-            AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(pos)) => {
+            AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(pos))
+            | AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(pos)) => {
                 self.sync_seek(*pos);
             }
             _ => {}
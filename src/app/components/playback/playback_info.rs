@@ -79,8 +79,19 @@ impl PlaybackInfo {
                     let loader = ImageLoader::new();
                     let result = loader.load_remote(&url, "jpg", 48, 48).await;
                     if let (Some(image1), Some(image2)) = (image1.upgrade(), image2.upgrade()) {
-                        image1.set_from_pixbuf(result.as_ref());
-                        image2.set_from_pixbuf(result.as_ref());
+                        if let Some(pixbuf) = result.as_ref() {
+                            image1.set_from_pixbuf(Some(pixbuf));
+                            image2.set_from_pixbuf(Some(pixbuf));
+                        } else {
+                            image1.set_from_icon_name(
+                                Some("emblem-music-symbolic"),
+                                gtk::IconSize::Button,
+                            );
+                            image2.set_from_icon_name(
+                                Some("emblem-music-symbolic"),
+                                gtk::IconSize::Button,
+                            );
+                        }
                     }
                 });
             }
@@ -0,0 +1,100 @@
+use gtk::prelude::*;
+use gtk::{BinExt, ImageExt};
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::settings::SpotSettings;
+use crate::app::state::{PlaybackAction, PlaybackEvent, RepeatMode};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+pub struct RepeatControlModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl RepeatControlModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn repeat_mode(&self) -> RepeatMode {
+        self.app_model.get_state().playback.repeat_mode
+    }
+
+    fn set_repeat_mode(&self, mode: RepeatMode) {
+        self.dispatcher
+            .dispatch(PlaybackAction::SetRepeatMode(mode).into());
+    }
+
+    fn cycle(&self) {
+        self.set_repeat_mode(self.repeat_mode().next());
+    }
+
+    fn restore(&self) {
+        self.set_repeat_mode(RepeatMode::from_id(&SpotSettings::repeat_mode()));
+    }
+
+    // Whether it came from here or MPRIS, the mode is remembered for the next startup
+    fn save(&self) {
+        let id = self.repeat_mode().id();
+        if SpotSettings::repeat_mode() != id {
+            SpotSettings::save_repeat_mode(id);
+        }
+    }
+}
+
+pub struct RepeatControl {
+    model: Rc<RepeatControlModel>,
+    button: gtk::Button,
+}
+
+impl RepeatControl {
+    pub fn new(model: RepeatControlModel, button: gtk::Button) -> Self {
+        let model = Rc::new(model);
+
+        button.connect_clicked(clone!(@weak model => move |_| {
+            model.cycle();
+        }));
+
+        Self { model, button }
+    }
+
+    fn update_repeat_mode(&self) {
+        let (icon, tooltip) = match self.model.repeat_mode() {
+            RepeatMode::Off => ("media-playlist-repeat-symbolic", "Repeat off"),
+            RepeatMode::All => ("media-playlist-repeat-symbolic", "Repeat all"),
+            RepeatMode::One => ("media-playlist-repeat-song-symbolic", "Repeat current song"),
+        };
+        if let Some(image) = self
+            .button
+            .get_child()
+            .and_then(|child| child.downcast::<gtk::Image>().ok())
+        {
+            image.set_from_icon_name(Some(icon), gtk::IconSize::Button);
+        }
+        self.button.set_tooltip_text(Some(tooltip));
+
+        let style_context = self.button.get_style_context();
+        if self.model.repeat_mode() == RepeatMode::Off {
+            style_context.add_class("dim-label");
+        } else {
+            style_context.remove_class("dim-label");
+        }
+    }
+}
+
+impl EventListener for RepeatControl {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.model.restore(),
+            AppEvent::PlaybackEvent(PlaybackEvent::RepeatModeChanged) => {
+                self.update_repeat_mode();
+                self.model.save();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use glib::{StaticVariantType, ToVariant};
+use gtk::prelude::*;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::app::components::utils::{format_duration, Clock};
+use crate::app::components::EventListener;
+use crate::app::state::{PlaybackAction, PlaybackEvent, SleepTimer};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+pub struct SleepTimerControlModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl SleepTimerControlModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn sleep_timer(&self) -> Option<SleepTimer> {
+        self.app_model.get_state().playback.sleep_timer
+    }
+
+    fn quits(&self) -> bool {
+        self.app_model.get_state().settings.sleep_timer_quits
+    }
+
+    // Either "off", "end-of-track" or a number of minutes
+    fn set_sleep_timer(&self, id: &str) {
+        let timer = match id {
+            "end-of-track" => Some(SleepTimer::EndOfTrack),
+            minutes => minutes
+                .parse::<u64>()
+                .ok()
+                .map(|minutes| SleepTimer::At(Instant::now() + Duration::from_secs(minutes * 60))),
+        };
+        self.dispatcher
+            .dispatch(PlaybackAction::SetSleepTimer(timer).into());
+    }
+
+    fn expire(&self) {
+        self.dispatcher
+            .dispatch(PlaybackAction::SleepTimerExpired.into());
+    }
+}
+
+pub struct SleepTimerControl {
+    model: Rc<SleepTimerControlModel>,
+    remaining: gtk::Label,
+    action: SimpleAction,
+    clock: Clock,
+}
+
+impl SleepTimerControl {
+    pub fn new(
+        model: SleepTimerControlModel,
+        button: gtk::MenuButton,
+        remaining: gtk::Label,
+    ) -> Self {
+        let model = Rc::new(model);
+
+        let action = SimpleAction::new_stateful(
+            "timer",
+            Some(String::static_variant_type().as_ref()),
+            &"off".to_variant(),
+        );
+        action.connect_activate(clone!(@weak model => move |action, param| {
+            if let Some(param) = param {
+                action.set_state(param);
+                model.set_sleep_timer(param.get_str().unwrap_or("off"));
+            }
+        }));
+
+        let group = SimpleActionGroup::new();
+        group.add_action(&action);
+        button.insert_action_group("sleep", Some(&group));
+
+        let menu = gio::Menu::new();
+        menu.append(Some("Off"), Some("sleep.timer::off"));
+        menu.append(Some("15 minutes"), Some("sleep.timer::15"));
+        menu.append(Some("30 minutes"), Some("sleep.timer::30"));
+        menu.append(Some("1 hour"), Some("sleep.timer::60"));
+        menu.append(
+            Some("End of current track"),
+            Some("sleep.timer::end-of-track"),
+        );
+        button.set_menu_model(Some(&menu));
+
+        Self {
+            model,
+            remaining,
+            action,
+            clock: Clock::new(),
+        }
+    }
+
+    fn update_sleep_timer(&self) {
+        match self.model.sleep_timer() {
+            Some(SleepTimer::At(at)) => {
+                let model = Rc::clone(&self.model);
+                let remaining = &self.remaining;
+                let tick = clone!(@weak model, @weak remaining => move || {
+                    let left = at.saturating_duration_since(Instant::now());
+                    remaining.set_text(&format_duration(left.as_millis() as f64));
+                    if left == Duration::from_secs(0) {
+                        model.expire();
+                    }
+                });
+                tick();
+                self.clock.start(tick);
+                self.remaining.show();
+            }
+            Some(SleepTimer::EndOfTrack) => {
+                self.clock.stop();
+                self.remaining.set_text("End of track");
+                self.remaining.show();
+            }
+            None => {
+                self.clock.stop();
+                self.action.set_state(&"off".to_variant());
+                self.remaining.hide();
+            }
+        }
+    }
+
+    fn quit_if_needed(&self) {
+        if self.model.quits() {
+            if let Some(app) = gio::Application::get_default() {
+                app.quit();
+            }
+        }
+    }
+}
+
+impl EventListener for SleepTimerControl {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::PlaybackEvent(PlaybackEvent::SleepTimerChanged) => {
+                self.update_sleep_timer();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::SleepTimerEnded) => {
+                self.quit_if_needed();
+            }
+            _ => {}
+        }
+    }
+}
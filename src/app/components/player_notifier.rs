@@ -1,17 +1,61 @@
 use futures::channel::mpsc::UnboundedSender;
 use librespot::core::spotify_id::SpotifyId;
+use std::rc::Rc;
 
 use crate::app::backend::Command;
 use crate::app::components::EventListener;
-use crate::app::{state::PlaybackEvent, AppEvent};
+use crate::app::{state::PlaybackEvent, AppEvent, AppModel};
 
 pub struct PlayerNotifier {
+    app_model: Rc<AppModel>,
     sender: UnboundedSender<Command>,
+    start_position: u32,
 }
 
 impl PlayerNotifier {
-    pub fn new(sender: UnboundedSender<Command>) -> Self {
-        Self { sender }
+    pub fn new(app_model: Rc<AppModel>, sender: UnboundedSender<Command>) -> Self {
+        Self {
+            app_model,
+            sender,
+            start_position: 0,
+        }
+    }
+
+    fn channel_mix(&self) -> Command {
+        let settings = &self.app_model.get_state().settings;
+        Command::PlayerSetChannelMix(settings.force_mono, settings.audio_balance)
+    }
+
+    fn normalization(&self) -> Command {
+        let settings = &self.app_model.get_state().settings;
+        if settings.volume_normalization {
+            Command::PlayerSetNormalization(Some(settings.normalization_level.pregain()))
+        } else {
+            Command::PlayerSetNormalization(None)
+        }
+    }
+
+    fn equalizer(&self) -> Command {
+        let settings = &self.app_model.get_state().settings;
+        if settings.equalizer_enabled {
+            Command::PlayerSetEqualizer(settings.equalizer_bands.clone())
+        } else {
+            Command::PlayerSetEqualizer(vec![])
+        }
+    }
+
+    fn visualizer(&self) -> Command {
+        Command::PlayerSetVisualizer(self.app_model.get_state().settings.visualizer)
+    }
+
+    fn fade(&self) -> Command {
+        Command::PlayerSetFade(self.app_model.get_state().settings.fade_duration)
+    }
+
+    fn send(&self, command: Command) {
+        self.sender.unbounded_send(command).unwrap_or_else(|_| {
+            println!("Could not send message to player");
+        });
     }
 }
 
@@ -21,23 +65,42 @@ impl EventListener for PlayerNotifier {
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused) => Some(Command::PlayerPause),
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => Some(Command::PlayerResume),
             AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => Some(Command::PlayerStop),
+            AppEvent::PlaybackEvent(PlaybackEvent::StartPositionSet(position)) => {
+                self.start_position = *position;
+                None
+            }
             AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(id)) => {
-                SpotifyId::from_base62(&id).ok().map(Command::PlayerLoad)
+                let position = std::mem::take(&mut self.start_position);
+                SpotifyId::from_base62(&id)
+                    .ok()
+                    .map(|id| Command::PlayerLoad(id, position))
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PreloadRequested(id)) => {
+                SpotifyId::from_base62(&id).ok().map(Command::PlayerPreload)
             }
             AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(position)) => {
                 Some(Command::PlayerSeek(*position))
             }
+            AppEvent::PlaybackEvent(PlaybackEvent::VolumeChanged) => Some(
+                Command::PlayerSetVolume(self.app_model.get_state().playback.volume),
+            ),
             AppEvent::LoginStarted(username, password) => {
                 Some(Command::Login(username.to_owned(), password.to_owned()))
             }
             AppEvent::FreshTokenRequested => Some(Command::RefreshToken),
+            AppEvent::LoginCompleted(_) => Some(Command::FetchPlaylistFolders),
+            AppEvent::Started | AppEvent::SettingsChanged => Some(self.channel_mix()),
             _ => None,
         };
 
         if let Some(command) = command {
-            self.sender.unbounded_send(command).unwrap_or_else(|_| {
-                println!("Could not send message to player");
-            });
+            self.send(command);
+        }
+        if let AppEvent::Started | AppEvent::SettingsChanged = event {
+            self.send(self.normalization());
+            self.send(self.equalizer());
+            self.send(self.visualizer());
+            self.send(self.fade());
         }
     }
 }
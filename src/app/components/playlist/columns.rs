@@ -0,0 +1,116 @@
+use gtk::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::app::models::SongSort;
+
+// Headers above the songs of a list, clicking one sorts the songs by that column
+// and clicking it again brings back the original order
+pub struct PlaylistColumns {
+    sort: Rc<Cell<SongSort>>,
+    on_sort: Rc<dyn Fn(SongSort)>,
+    index: gtk::SizeGroup,
+    labels: gtk::SizeGroup,
+    album: gtk::SizeGroup,
+    popularity: gtk::SizeGroup,
+    duration: gtk::SizeGroup,
+    menu: gtk::SizeGroup,
+}
+
+impl PlaylistColumns {
+    pub fn new<F: Fn(SongSort) + 'static>(on_sort: F) -> Self {
+        let group = || gtk::SizeGroup::new(gtk::SizeGroupMode::Horizontal);
+        Self {
+            sort: Rc::new(Cell::new(SongSort::Default)),
+            on_sort: Rc::new(on_sort),
+            index: group(),
+            labels: group(),
+            album: group(),
+            popularity: group(),
+            duration: group(),
+            menu: group(),
+        }
+    }
+
+    pub fn sort(&self) -> SongSort {
+        self.sort.get()
+    }
+
+    pub fn align<W1, W2, W3, W4, W5, W6>(
+        &self,
+        index: &W1,
+        labels: &W2,
+        album: &W3,
+        popularity: &W4,
+        duration: &W5,
+        menu: &W6,
+    ) where
+        W1: IsA<gtk::Widget>,
+        W2: IsA<gtk::Widget>,
+        W3: IsA<gtk::Widget>,
+        W4: IsA<gtk::Widget>,
+        W5: IsA<gtk::Widget>,
+        W6: IsA<gtk::Widget>,
+    {
+        self.index.add_widget(index);
+        self.labels.add_widget(labels);
+        self.album.add_widget(album);
+        self.popularity.add_widget(popularity);
+        self.duration.add_widget(duration);
+        self.menu.add_widget(menu);
+    }
+
+    // A new header is built every time, as the first row of the list may change
+    pub fn header(&self) -> gtk::Widget {
+        let header = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        header.set_margin_start(2);
+        header.set_margin_end(2);
+        header.get_style_context().add_class("playlist__columns");
+
+        let index = self.sort_button("#", SongSort::Default);
+        let labels = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        for &(label, sort) in &[("Title", SongSort::Title), ("Artist", SongSort::Artist)] {
+            labels.pack_start(&self.sort_button(label, sort), false, true, 0);
+        }
+        let album = self.sort_button("Album", SongSort::Album);
+        let popularity = self.sort_button("Popularity", SongSort::Popularity);
+        let duration = self.sort_button("Duration", SongSort::Duration);
+        let menu = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+
+        header.pack_start(&index, false, true, 0);
+        header.pack_start(&labels, false, true, 0);
+        header.pack_start(&album, false, true, 0);
+        header.pack_end(&menu, false, true, 0);
+        header.pack_end(&duration, false, true, 0);
+        header.pack_end(&popularity, false, true, 0);
+        self.align(&index, &labels, &album, &popularity, &duration, &menu);
+
+        header.show_all();
+        header.upcast()
+    }
+
+    fn sort_button(&self, label: &str, sort: SongSort) -> gtk::Button {
+        let is_sorted = sort != SongSort::Default && self.sort.get() == sort;
+        let label = if is_sorted {
+            format!("{} \u{25be}", label)
+        } else {
+            label.to_string()
+        };
+        let button = gtk::Button::with_label(&label);
+        button.set_relief(gtk::ReliefStyle::None);
+        button.set_can_focus(false);
+
+        let current = self.sort.clone();
+        let on_sort = self.on_sort.clone();
+        button.connect_clicked(move |_| {
+            let next = if current.get() == sort {
+                SongSort::Default
+            } else {
+                sort
+            };
+            current.set(next);
+            on_sort(next);
+        });
+        button
+    }
+}
@@ -1,3 +1,6 @@
+mod columns;
+pub use columns::*;
+
 mod playlist;
 pub use playlist::*;
 
@@ -1,14 +1,21 @@
 use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use glib::signal;
 use gtk::prelude::*;
 use gtk::ListBoxExt;
+use std::cell::RefCell;
 use std::ops::Deref;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
+use super::PlaylistColumns;
 use crate::app::components::{Component, EventListener, Song};
-use crate::app::models::SongModel;
+use crate::app::models::{SongCredits, SongDescription, SongModel, SongSort};
 use crate::app::{
-    state::{PlaybackEvent, SelectionEvent, SelectionState},
-    AppEvent, ListStore,
+    state::{
+        BrowserAction, BrowserEvent, PlaybackAction, PlaybackEvent, RadioSeed, SelectionEvent,
+        SelectionState,
+    },
+    ActionDispatcher, AppAction, AppEvent, AppState, ListStore,
 };
 
 pub trait PlaylistModel {
@@ -24,6 +31,46 @@ pub trait PlaylistModel {
         None
     }
 
+    // Songs for which this returns true can be swiped away
+    fn can_remove_song(&self, _id: &str) -> bool {
+        false
+    }
+    fn remove_song(&self, _id: &str) {}
+
+    // Songs can be dragged to another position when this returns true,
+    // the list then moves a single row when moved_song returns its old and new positions
+    fn can_move_songs(&self) -> bool {
+        false
+    }
+    fn move_song(&self, _id: &str, _position: usize) {}
+    fn moved_song(&self, _event: &AppEvent) -> Option<(usize, usize)> {
+        None
+    }
+
+    // Songs for which this returns some are added at the end of the list,
+    // rather than rebuilding the whole list
+    fn appended_songs(&self, _event: &AppEvent) -> Option<Vec<SongModel>> {
+        None
+    }
+
+    // Short descriptive tags (e.g. moods) shown on the row
+    fn song_tags(&self, _id: &str) -> Option<String> {
+        None
+    }
+    fn is_song_visible(&self, _id: &str) -> bool {
+        true
+    }
+
+    // A header is shown above a song whenever this differs from the song before it
+    fn section_title(&self, _index: usize) -> Option<String> {
+        None
+    }
+
+    // Shows the album and popularity of songs, under headers that sort the list
+    fn has_columns(&self) -> bool {
+        false
+    }
+
     fn select_song(&self, _id: &str) {}
     fn deselect_song(&self, _id: &str) {}
 
@@ -32,10 +79,82 @@ pub trait PlaylistModel {
     }
 }
 
+// The actions of a song that don't depend on where it's listed, PlaylistModel::actions_for
+// adds its own to the group, see append_song_menu for the matching menu items
+pub fn song_actions(
+    dispatcher: &dyn ActionDispatcher,
+    song: &SongDescription,
+) -> SimpleActionGroup {
+    let group = SimpleActionGroup::new();
+
+    let queue = SimpleAction::new("queue", None);
+    let queue_dispatcher = dispatcher.box_clone();
+    let queued_song = song.clone();
+    queue.connect_activate(move |_, _| {
+        queue_dispatcher.dispatch(PlaybackAction::Queue(queued_song.clone()).into());
+    });
+    group.add_action(&queue);
+
+    let pin = SimpleAction::new("pin", None);
+    let pin_dispatcher = dispatcher.box_clone();
+    let pinned_song = song.clone();
+    pin.connect_activate(move |_, _| {
+        pin_dispatcher.dispatch(PlaybackAction::Pin(pinned_song.clone()).into());
+    });
+    group.add_action(&pin);
+
+    let radio = SimpleAction::new("radio", None);
+    let radio_dispatcher = dispatcher.box_clone();
+    let seed = RadioSeed::Song(song.clone());
+    radio.connect_activate(move |_, _| {
+        radio_dispatcher.dispatch(PlaybackAction::StartRadio(seed.clone()).into());
+    });
+    group.add_action(&radio);
+
+    let more_like_this = SimpleAction::new("more_like_this", None);
+    let more_like_this_dispatcher = dispatcher.box_clone();
+    let track_id = song.id.clone();
+    more_like_this.connect_activate(move |_, _| {
+        more_like_this_dispatcher.dispatch(AppAction::ViewRecommendations(track_id.clone()));
+    });
+    group.add_action(&more_like_this);
+
+    let credits = SimpleAction::new("credits", None);
+    let credits_dispatcher = dispatcher.box_clone();
+    let track_id = song.id.clone();
+    credits.connect_activate(move |_, _| {
+        credits_dispatcher.dispatch(BrowserAction::ShowSongCredits(track_id.clone()).into());
+    });
+    group.add_action(&credits);
+
+    group
+}
+
+// Songs already in the queue can be removed from it instead of queued,
+// the model then adds a "dequeue" action
+pub fn append_song_menu(menu: &gio::Menu, state: &AppState, in_queue: bool) {
+    menu.append(Some("Play next"), Some("song.pin"));
+    if in_queue {
+        menu.append(Some("Dequeue"), Some("song.dequeue"));
+    } else {
+        menu.append(Some("Add to queue"), Some("song.queue"));
+    }
+    menu.append(Some("Start radio"), Some("song.radio"));
+    menu.append(Some("More like this"), Some("song.more_like_this"));
+    if state.browser.has_song_credits() {
+        menu.append(Some("Show credits"), Some("song.credits"));
+    }
+}
+
+// Dragged rows carry the id of their song
+const ROW_TARGET: &str = "SPOT_PLAYLIST_ROW";
+
 pub struct Playlist<Model> {
     listbox: gtk::ListBox,
     list_model: ListStore<SongModel>,
     model: Rc<Model>,
+    columns: Option<Rc<PlaylistColumns>>,
+    filter: Rc<RefCell<String>>,
 }
 
 impl<Model> Playlist<Model>
@@ -45,6 +164,19 @@ where
     pub fn new(listbox: gtk::ListBox, model: Rc<Model>) -> Self {
         let list_model = ListStore::new();
 
+        let columns = if model.has_columns() {
+            let mut list_model = list_model.clone();
+            let listbox = listbox.downgrade();
+            Some(Rc::new(PlaylistColumns::new(move |sort| {
+                list_model.sort_by(|a, b| sort.compare(a, b));
+                if let Some(listbox) = listbox.upgrade() {
+                    listbox.invalidate_headers();
+                }
+            })))
+        } else {
+            None
+        };
+
         listbox.set_selection_mode(gtk::SelectionMode::Multiple);
         listbox.get_style_context().add_class("playlist");
         listbox.set_activate_on_single_click(true);
@@ -69,19 +201,94 @@ where
             }
         }));
 
+        let list_model_clone = list_model.clone();
+        let weak_model = Rc::downgrade(&model);
+        let filter = Rc::new(RefCell::new(String::new()));
+        let filter_clone = filter.clone();
+        listbox.set_filter_func(Some(Box::new(move |row| {
+            let song: SongModel = list_model_clone.get(row.get_index() as u32);
+            let is_visible = weak_model
+                .upgrade()
+                .map(|model| model.is_song_visible(&song.get_id()))
+                .unwrap_or(true);
+            let query = filter_clone.borrow();
+            is_visible && (query.is_empty() || song.matches(&query))
+        })));
+
+        let weak_model = Rc::downgrade(&model);
+        let columns_clone = columns.clone();
+        listbox.set_header_func(Some(Box::new(move |row, before| {
+            let model = match weak_model.upgrade() {
+                Some(model) => model,
+                None => return,
+            };
+            let title = model.section_title(row.get_index() as usize);
+            let previous = before.and_then(|b| model.section_title(b.get_index() as usize));
+            let section = match title {
+                Some(title) if Some(&title) != previous.as_ref() => {
+                    let label = gtk::Label::new(Some(&title));
+                    label.set_halign(gtk::Align::Start);
+                    label.get_style_context().add_class("playlist__section");
+                    label.show();
+                    Some(label.upcast::<gtk::Widget>())
+                }
+                _ => None,
+            };
+            let column_headers = columns_clone
+                .as_ref()
+                .filter(|_| row.get_index() == 0)
+                .map(|columns| columns.header());
+            match (section, column_headers) {
+                (Some(section), Some(column_headers)) => {
+                    let header = gtk::Box::new(gtk::Orientation::Vertical, 0);
+                    header.add(&section);
+                    header.add(&column_headers);
+                    header.show();
+                    row.set_header(Some(&header));
+                }
+                (section, column_headers) => {
+                    row.set_header(section.or(column_headers).as_ref());
+                }
+            }
+        })));
+
+        if model.can_move_songs() {
+            Self::add_drop_target(&listbox, Rc::downgrade(&model));
+        }
+
         let weak_model = Rc::downgrade(&model);
         let weak_listbox = listbox.downgrade();
+        let columns_clone = columns.clone();
         listbox.bind_model(Some(list_model.unsafe_store()), move |item| {
             let item = item.downcast_ref::<SongModel>().unwrap();
             let id = &item.get_id();
 
             let row = gtk::ListBoxRow::new();
             let song = Song::new(item.clone());
-            row.add(song.get_root_widget());
+
+            // Wrapped in an event box, so that the row can receive the swipe gesture
+            let event_box = gtk::EventBox::new();
+            event_box.add(song.get_root_widget());
+            Self::add_swipe_gesture(&event_box, song.get_root_widget(), weak_model.clone(), id);
+            row.add(&event_box);
+            song.bind_popup_menu(&row);
+
+            let is_sorted = match columns_clone.as_ref() {
+                Some(columns) => {
+                    song.show_columns(columns);
+                    columns.sort() != SongSort::Default
+                }
+                None => false,
+            };
 
             if let Some(model) = weak_model.upgrade() {
+                song.set_tags(model.song_tags(id).as_deref());
                 song.set_menu(model.menu_for(id).as_ref());
                 song.set_actions(model.actions_for(id).as_ref());
+                // Positions in a sorted list don't match those in the playlist
+                if model.can_move_songs() && !is_sorted {
+                    Self::add_drag_source(&row, id);
+                }
 
                 if let Some(listbox) = weak_listbox.upgrade() {
                     Self::set_row_state(&listbox, item, &row, &*model);
@@ -96,9 +303,121 @@ where
             listbox,
             list_model,
             model,
+            columns,
+            filter,
         }
     }
 
+    // Only narrows down the rows already in the list, songs aren't fetched again
+    pub fn connect_filter_entry(&self, entry: &gtk::SearchEntry) {
+        let filter = self.filter.clone();
+        entry.connect_search_changed(clone!(@weak self.listbox as listbox => move |entry| {
+            filter.replace(entry.get_text().trim().to_string());
+            listbox.invalidate_filter();
+        }));
+    }
+
+    fn sort(&self) -> SongSort {
+        self.columns
+            .as_ref()
+            .map(|columns| columns.sort())
+            .unwrap_or(SongSort::Default)
+    }
+
+    fn add_swipe_gesture(
+        event_box: &gtk::EventBox,
+        child: &gtk::Widget,
+        model: Weak<Model>,
+        id: &str,
+    ) {
+        // Portion of the row width past which a swiped song is removed
+        const THRESHOLD: f64 = 0.4;
+
+        let gesture = gtk::GestureDrag::new(event_box);
+
+        let id_clone = id.to_string();
+        let weak_model = model.clone();
+        gesture.connect_drag_update(
+            clone!(@weak event_box, @weak child => move |gesture, x, y| {
+                let can_remove = weak_model
+                    .upgrade()
+                    .map(|model| model.can_remove_song(&id_clone))
+                    .unwrap_or(false);
+                if !can_remove || x.abs() < y.abs() {
+                    return;
+                }
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                let width = event_box.get_allocated_width().max(1) as f64;
+                let offset = x as i32;
+                child.set_margin_start(offset.max(0));
+                child.set_margin_end((-offset).max(0));
+                child.set_opacity(1.0 - 0.8 * (x.abs() / width).min(1.0));
+            }),
+        );
+
+        let id = id.to_string();
+        gesture.connect_drag_end(clone!(@weak event_box, @weak child => move |_, x, _| {
+            child.set_margin_start(0);
+            child.set_margin_end(0);
+            child.set_opacity(1.0);
+            let width = event_box.get_allocated_width() as f64;
+            if let Some(model) = model.upgrade() {
+                if x.abs() > width * THRESHOLD && model.can_remove_song(&id) {
+                    model.remove_song(&id);
+                }
+            }
+        }));
+
+        // The gesture must live as long as the widget it's attached to
+        event_box.connect_destroy(move |_| {
+            let _ = &gesture;
+        });
+    }
+
+    fn add_drag_source(row: &gtk::ListBoxRow, id: &str) {
+        let targets = [gtk::TargetEntry::new(
+            ROW_TARGET,
+            gtk::TargetFlags::SAME_APP,
+            0,
+        )];
+        row.drag_source_set(
+            gdk::ModifierType::BUTTON1_MASK,
+            &targets,
+            gdk::DragAction::MOVE,
+        );
+        let id = id.to_string();
+        row.connect_drag_data_get(move |_, _, data, _, _| {
+            data.set(&data.get_target(), 8, id.as_bytes());
+        });
+    }
+
+    // Songs are dropped at the position of the row under the pointer
+    fn add_drop_target(listbox: &gtk::ListBox, model: Weak<Model>) {
+        let targets = [gtk::TargetEntry::new(
+            ROW_TARGET,
+            gtk::TargetFlags::SAME_APP,
+            0,
+        )];
+        listbox.drag_dest_set(gtk::DestDefaults::ALL, &targets, gdk::DragAction::MOVE);
+
+        listbox.connect_drag_motion(|listbox, _, _, y, _| {
+            if let Some(row) = listbox.get_row_at_y(y) {
+                listbox.drag_highlight_row(&row);
+            }
+            signal::Inhibit(false)
+        });
+        listbox.connect_drag_leave(|listbox, _, _| {
+            listbox.drag_unhighlight_row();
+        });
+        listbox.connect_drag_data_received(move |listbox, _, _, y, data, _, _| {
+            let id = String::from_utf8(data.get_data()).ok();
+            let position = listbox.get_row_at_y(y).map(|row| row.get_index() as usize);
+            if let (Some(model), Some(id), Some(position)) = (model.upgrade(), id, position) {
+                model.move_song(&id, position);
+            }
+        });
+    }
+
     fn set_row_state<M: PlaylistModel>(
         listbox: &gtk::ListBox,
         item: &SongModel,
@@ -123,20 +442,89 @@ where
     }
 
     fn update_list(&self) {
-        for (i, song) in self.model.songs().iter().enumerate() {
-            let is_current = self
-                .model
-                .current_song_id()
-                .map(|s| s == song.get_id())
+        let current_song_id = self.model.current_song_id();
+        for song in self.list_model.iter() {
+            let is_current = current_song_id
+                .as_ref()
+                .map(|s| s == &song.get_id())
                 .unwrap_or(false);
-            let model_song = self.list_model.get(i as u32);
-            model_song.set_playing(is_current);
+            song.set_playing(is_current);
+        }
+    }
+
+    // Only the rows in between are renumbered, rather than rebuilding the whole list
+    fn move_row(&mut self, from: usize, to: usize) {
+        let len = self.list_model.len();
+        if from >= len || to >= len || self.sort() != SongSort::Default {
+            self.reset_list();
+            return;
+        }
+        let song = self.list_model.get(from as u32);
+        self.list_model.remove(from as u32);
+        self.list_model.insert(to as u32, song);
+        for i in from.min(to)..=from.max(to) {
+            self.list_model.get(i as u32).set_index(i as u32 + 1);
+        }
+    }
+
+    fn append_rows(&mut self, songs: Vec<SongModel>) {
+        if self.sort() != SongSort::Default {
+            self.reset_list();
+            return;
+        }
+        for song in songs {
+            self.list_model.append(song);
         }
     }
 
     fn reset_list(&mut self) {
+        let sort = self.sort();
         let list_model = &mut self.list_model;
         list_model.replace_all(self.model.songs());
+        if sort != SongSort::Default {
+            list_model.sort_by(|a, b| sort.compare(a, b));
+        }
+    }
+
+    // Next to the row of the song, only where the list is shown
+    fn show_credits(&self, id: &str, credits: &SongCredits) {
+        let row = self
+            .list_model
+            .iter()
+            .position(|song| song.get_id() == id)
+            .and_then(|index| self.listbox.get_row_at_index(index as i32))
+            .filter(|row| row.is_mapped());
+        let row = match row {
+            Some(row) => row,
+            None => return,
+        };
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+        let roles = [
+            ("Performed by", &credits.performers),
+            ("Written by", &credits.writers),
+            ("Produced by", &credits.producers),
+        ];
+        for (role, names) in roles.iter().filter(|(_, names)| !names.is_empty()) {
+            let title = gtk::Label::new(Some(*role));
+            title.set_halign(gtk::Align::Start);
+            title.get_style_context().add_class("heading");
+            content.add(&title);
+
+            let people = gtk::Label::new(Some(&names.join("\n")));
+            people.set_halign(gtk::Align::Start);
+            people.set_margin_bottom(8);
+            content.add(&people);
+        }
+
+        let popover = gtk::Popover::new(Some(&row));
+        popover.add(&content);
+        content.show_all();
+        popover.popup();
     }
 
     fn set_selection_active(&self, active: bool) {
@@ -158,6 +546,14 @@ where
     Model: PlaylistModel + 'static,
 {
     fn on_event(&mut self, event: &AppEvent) {
+        if let Some((from, to)) = self.model.moved_song(event) {
+            self.move_row(from, to);
+            return;
+        }
+        if let Some(songs) = self.model.appended_songs(event) {
+            self.append_rows(songs);
+            return;
+        }
         match event {
             AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
                 self.update_list();
@@ -168,6 +564,9 @@ where
             AppEvent::SelectionEvent(SelectionEvent::SelectionModeChanged(active)) => {
                 self.set_selection_active(*active);
             }
+            AppEvent::BrowserEvent(BrowserEvent::SongCreditsShown(id, credits)) => {
+                self.show_credits(id, credits);
+            }
             _ if self.model.should_refresh_songs(event) => self.reset_list(),
             _ => {}
         }
@@ -1,17 +1,22 @@
+use super::PlaylistColumns;
 use crate::app::components::{screen_add_css_provider, Component};
 use crate::app::models::SongModel;
 use gio::MenuModel;
 use gladis::Gladis;
 use gtk::prelude::*;
-use gtk::{MenuButtonExt, WidgetExt};
+use gtk::{MenuButtonExt, ToggleButtonExt, WidgetExt};
 
 #[derive(Gladis, Clone)]
 struct SongWidget {
     root: gtk::Widget,
     song_index: gtk::Label,
+    song_labels: gtk::Box,
     song_title: gtk::Label,
     song_artist: gtk::Label,
+    song_album: gtk::Label,
     song_length: gtk::Label,
+    song_popularity: gtk::Label,
+    song_tags: gtk::Label,
     menu_btn: gtk::MenuButton,
 }
 
@@ -59,6 +64,16 @@ impl Song {
             .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
             .build();
 
+        model
+            .bind_property("album", &widget.song_album, "label")
+            .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE)
+            .build();
+
+        let popularity = model.get_popularity();
+        if popularity > 0 {
+            widget.song_popularity.set_text(&format!("{}%", popularity));
+        }
+
         SongWidget::set_playing(&widget.root, model.get_playing());
 
         model.connect_playing_local(clone!(@weak widget.root as root => move |song| {
@@ -72,6 +87,27 @@ impl Song {
         self.get_root_widget().insert_action_group("song", actions);
     }
 
+    pub fn set_tags(&self, tags: Option<&str>) {
+        let label = &self.widget.song_tags;
+        label.set_text(tags.unwrap_or(""));
+        label.set_visible(tags.is_some());
+    }
+
+    // Shows the album and popularity, and lines all columns up with the headers
+    pub fn show_columns(&self, columns: &PlaylistColumns) {
+        let widget = &self.widget;
+        widget.song_album.show();
+        widget.song_popularity.show();
+        columns.align(
+            &widget.song_index,
+            &widget.song_labels,
+            &widget.song_album,
+            &widget.song_popularity,
+            &widget.song_length,
+            &widget.menu_btn,
+        );
+    }
+
     pub fn set_menu(&self, menu: Option<&MenuModel>) {
         if menu.is_some() {
             let menu_btn = &self.widget.menu_btn;
@@ -81,6 +117,19 @@ impl Song {
                 .add_class("song__menu--enabled");
         }
     }
+
+    // Opens the song menu when the Menu key or Shift+F10 is pressed while the target has focus
+    pub fn bind_popup_menu<W: IsA<gtk::Widget>>(&self, target: &W) {
+        target.connect_popup_menu(
+            clone!(@weak self.widget.menu_btn as menu_btn => @default-return false, move |_| {
+                let has_menu = menu_btn.get_menu_model().is_some();
+                if has_menu {
+                    menu_btn.set_active(true);
+                }
+                has_menu
+            }),
+        );
+    }
 }
 
 impl Component for Song {
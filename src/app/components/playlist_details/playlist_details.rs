@@ -1,20 +1,44 @@
 use gladis::Gladis;
+use glib::ToVariant;
 use gtk::prelude::*;
+use std::path::Path;
 use std::rc::Rc;
 
 use super::PlaylistDetailsModel;
 
+use crate::app::components::utils::{
+    explicit_filter_button, format_duration, set_art_placeholder, Clock,
+};
 use crate::app::components::{screen_add_css_provider, Component, EventListener, Playlist};
 use crate::app::dispatch::Worker;
 use crate::app::loader::ImageLoader;
+use crate::app::models::{DurationRange, Mood};
+use crate::app::playlist_files::PlaylistFileFormat;
 use crate::app::{AppEvent, BrowserEvent};
 
 #[derive(Gladis, Clone)]
 struct PlaylistDetailsWidget {
     pub root: gtk::Widget,
+    pub header_stack: gtk::Stack,
     pub name_label: gtk::Label,
+    pub description_label: gtk::Label,
+    pub collaborative_badge: gtk::Label,
+    pub edit_button: gtk::Button,
+    pub export_button: gtk::Button,
+    pub cover_button: gtk::Button,
+    pub name_entry: gtk::Entry,
+    pub description_entry: gtk::Entry,
+    pub cancel_edit_button: gtk::Button,
+    pub save_edit_button: gtk::Button,
+    pub filter_entry: gtk::SearchEntry,
     pub tracks: gtk::ListBox,
     pub art: gtk::Image,
+    pub explicit_button: gtk::MenuButton,
+    pub mood_filter: gtk::ComboBoxText,
+    pub duration_label: gtk::Label,
+    pub duration_min: gtk::SpinButton,
+    pub duration_max: gtk::SpinButton,
+    pub duration_reset: gtk::Button,
 }
 
 impl PlaylistDetailsWidget {
@@ -23,16 +47,42 @@ impl PlaylistDetailsWidget {
         Self::from_resource(resource!("/components/playlist_details.ui")).unwrap()
     }
 
-    fn set_loaded(&self) {
-        let context = self.root.get_style_context();
+    // A bound set to 0 means no limit
+    fn duration_range(min: &gtk::SpinButton, max: &gtk::SpinButton) -> DurationRange {
+        let bound = |spin: &gtk::SpinButton| {
+            Some(spin.get_value_as_int().max(0) as u32 * 1000).filter(|&ms| ms > 0)
+        };
+        DurationRange {
+            min: bound(min),
+            max: bound(max),
+        }
+    }
+
+    fn describe_duration_range(range: DurationRange) -> String {
+        let format = |ms: u32| format_duration(ms as f64);
+        match (range.min, range.max) {
+            (None, None) => "Any length".to_string(),
+            (Some(min), None) => format!("≥ {}", format(min)),
+            (None, Some(max)) => format!("≤ {}", format(max)),
+            (Some(min), Some(max)) => format!("{} – {}", format(min), format(max)),
+        }
+    }
+
+    fn set_loaded(root: &gtk::Widget) {
+        let context = root.get_style_context();
         context.add_class("playlist_details--loaded");
     }
 }
 
+// How often collaborative playlists are checked for changes made by others
+const COLLABORATIVE_REFRESH_MS: u32 = 30_000;
+
 pub struct PlaylistDetails {
     model: Rc<PlaylistDetailsModel>,
     worker: Worker,
     widget: PlaylistDetailsWidget,
+    explicit_filter: gio::SimpleAction,
+    refresh_clock: Clock,
     children: Vec<Box<dyn EventListener>>,
 }
 
@@ -44,33 +94,266 @@ impl PlaylistDetails {
 
         let model = Rc::new(model);
         let widget = PlaylistDetailsWidget::new();
-        let playlist = Box::new(Playlist::new(widget.tracks.clone(), model.clone()));
+        let playlist = Playlist::new(widget.tracks.clone(), model.clone());
+        playlist.connect_filter_entry(&widget.filter_entry);
+
+        let explicit_filter = explicit_filter_button(
+            &widget.explicit_button,
+            model.explicit.get(),
+            clone!(@weak model => move |filter| {
+                model.explicit.set(filter);
+            }),
+        );
+
+        let tracks = &widget.tracks;
+        widget
+            .mood_filter
+            .connect_changed(clone!(@weak model, @weak tracks => move |combo| {
+                let mood = combo.get_active_id().and_then(|id| Mood::from_id(&id));
+                model.set_mood_filter(mood);
+                tracks.invalidate_filter();
+            }));
+
+        Self::connect_duration_filter(&widget, &model);
+        Self::connect_editing(&widget, &model);
+
+        widget
+            .export_button
+            .connect_clicked(clone!(@weak model => move |button| {
+                Self::prompt_export(button, model);
+            }));
+
+        widget
+            .cover_button
+            .connect_clicked(clone!(@weak model => move |button| {
+                Self::prompt_cover(button, model);
+            }));
 
         Self {
             model,
             worker,
             widget,
-            children: vec![playlist],
+            explicit_filter,
+            refresh_clock: Clock::with_interval(COLLABORATIVE_REFRESH_MS),
+            children: vec![Box::new(playlist)],
         }
     }
 
-    fn update_details(&self) {
+    fn connect_duration_filter(widget: &PlaylistDetailsWidget, model: &Rc<PlaylistDetailsModel>) {
+        let min = &widget.duration_min;
+        let max = &widget.duration_max;
+        let label = &widget.duration_label;
+        let tracks = &widget.tracks;
+        let on_change = clone!(@weak model, @weak min, @weak max, @weak label, @weak tracks => move |_: &gtk::SpinButton| {
+            let range = PlaylistDetailsWidget::duration_range(&min, &max);
+            model.set_duration_filter(range);
+            label.set_text(&PlaylistDetailsWidget::describe_duration_range(range));
+            tracks.invalidate_filter();
+        });
+        min.connect_value_changed(on_change.clone());
+        max.connect_value_changed(on_change);
+
+        widget
+            .duration_reset
+            .connect_clicked(clone!(@weak min, @weak max => move |_| {
+                min.set_value(0.0);
+                max.set_value(0.0);
+            }));
+    }
+
+    fn connect_editing(widget: &PlaylistDetailsWidget, model: &Rc<PlaylistDetailsModel>) {
+        let stack = &widget.header_stack;
+        let name = &widget.name_entry;
+        let description = &widget.description_entry;
+
+        widget.edit_button.connect_clicked(
+            clone!(@weak model, @weak stack, @weak name, @weak description => move |_| {
+                if let Some(info) = model.get_playlist_info() {
+                    name.set_text(&info.title);
+                    description.set_text(&info.description);
+                }
+                stack.set_visible_child_name("edit");
+                name.grab_focus();
+            }),
+        );
+
+        widget
+            .cancel_edit_button
+            .connect_clicked(clone!(@weak stack => move |_| {
+                stack.set_visible_child_name("view");
+            }));
+
+        let save = clone!(@weak model, @weak stack, @weak name, @weak description => move || {
+            model.update_details(name.get_text().as_str(), description.get_text().as_str());
+            stack.set_visible_child_name("view");
+        });
+        widget
+            .save_edit_button
+            .connect_clicked(clone!(@strong save => move |_| save()));
+        name.connect_activate(clone!(@strong save => move |_| save()));
+        description.connect_activate(move |_| save());
+
+        let save_button = &widget.save_edit_button;
+        name.connect_changed(clone!(@weak save_button => move |entry| {
+            save_button.set_sensitive(!entry.get_text().trim().is_empty());
+        }));
+    }
+
+    // The format is that of the extension, or of the filter picked if there's none
+    fn prompt_export(button: &gtk::Button, model: Rc<PlaylistDetailsModel>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::FileChooserDialog::with_buttons(
+            Some("Export playlist"),
+            window.as_ref(),
+            gtk::FileChooserAction::Save,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Export", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_do_overwrite_confirmation(true);
+
+        let title = model
+            .get_playlist_info()
+            .map(|info| info.title.replace('/', "-"))
+            .unwrap_or_else(|| "Playlist".to_string());
+        dialog.set_current_name(&format!("{}.m3u", title));
+
+        let filters = PlaylistFileFormat::ALL
+            .iter()
+            .map(|&format| {
+                let filter = gtk::FileFilter::new();
+                filter.set_name(Some(format.name()));
+                filter.add_pattern(&format!("*.{}", format.extension()));
+                dialog.add_filter(&filter);
+                (filter, format)
+            })
+            .collect::<Vec<(gtk::FileFilter, PlaylistFileFormat)>>();
+
+        let selected_format = move |dialog: &gtk::FileChooserDialog| {
+            let selected = dialog.get_filter()?;
+            filters
+                .iter()
+                .find(|(filter, _)| filter == &selected)
+                .map(|(_, format)| *format)
+        };
+
+        dialog.connect_property_filter_notify(clone!(@strong selected_format => move |dialog| {
+            let name = dialog.get_current_name();
+            if let (Some(name), Some(format)) = (name, selected_format(dialog)) {
+                let name = Path::new(name.as_str()).with_extension(format.extension());
+                dialog.set_current_name(&name);
+            }
+        }));
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(mut path) = dialog.get_filename() {
+                    let format = PlaylistFileFormat::from_path(&path).unwrap_or_else(|| {
+                        let format = selected_format(dialog).unwrap_or(PlaylistFileFormat::M3u);
+                        path.set_extension(format.extension());
+                        format
+                    });
+                    model.export(path, format);
+                }
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn prompt_cover(button: &gtk::Button, model: Rc<PlaylistDetailsModel>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::FileChooserDialog::with_buttons(
+            Some("Change cover"),
+            window.as_ref(),
+            gtk::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Upload", gtk::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images"));
+        filter.add_pixbuf_formats();
+        dialog.add_filter(&filter);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = dialog.get_filename() {
+                    model.upload_art(path);
+                }
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn update_header(&self) {
         if let Some(info) = self.model.get_playlist_info() {
-            let title = &info.title[..];
+            self.widget.name_label.set_label(&info.title[..]);
+            self.widget
+                .description_label
+                .set_label(&info.description[..]);
+            self.widget
+                .description_label
+                .set_visible(!info.description.is_empty());
+        }
+        self.widget.edit_button.set_visible(self.model.is_owned());
+        self.widget.cover_button.set_visible(self.model.is_owned());
+        self.widget
+            .collaborative_badge
+            .set_visible(self.model.is_collaborative());
+    }
 
-            self.widget.name_label.set_label(title);
+    fn watch_collaborative(&self) {
+        if !self.model.is_collaborative() || self.refresh_clock.is_running() {
+            return;
+        }
+        let model = Rc::downgrade(&self.model);
+        self.refresh_clock.start(move || {
+            if let Some(model) = model.upgrade() {
+                model.refresh_playlist_info();
+            }
+        });
+    }
 
-            let widget = self.widget.clone();
+    fn update_details(&self) {
+        self.update_header();
+        if let Some(info) = self.model.get_playlist_info() {
             if let Some(art) = info.art.clone() {
+                let model = Rc::downgrade(&self.model);
+                let image = self.widget.art.downgrade();
+                let root = self.widget.root.downgrade();
                 self.worker.send_local_task(async move {
                     let pixbuf = ImageLoader::new()
                         .load_remote(&art[..], "jpg", 100, 100)
                         .await;
-                    widget.art.set_from_pixbuf(pixbuf.as_ref());
-                    widget.set_loaded();
+                    // Drop covers that arrive after the page was left or its cover changed
+                    let (model, image, root) =
+                        match (model.upgrade(), image.upgrade(), root.upgrade()) {
+                            (Some(model), Some(image), Some(root)) => (model, image, root),
+                            _ => return,
+                        };
+                    let current_art = model.get_playlist_info().and_then(|info| info.art.clone());
+                    if current_art.as_ref() != Some(&art) {
+                        return;
+                    }
+                    if let Some(pixbuf) = pixbuf.as_ref() {
+                        image.set_from_pixbuf(Some(pixbuf));
+                    } else {
+                        set_art_placeholder(&image);
+                    }
+                    PlaylistDetailsWidget::set_loaded(&root);
                 });
             } else {
-                widget.set_loaded();
+                set_art_placeholder(&self.widget.art);
+                PlaylistDetailsWidget::set_loaded(&self.widget.root);
             }
         }
     }
@@ -92,7 +375,20 @@ impl EventListener for PlaylistDetails {
             AppEvent::BrowserEvent(BrowserEvent::PlaylistDetailsLoaded(id))
                 if id == &self.model.id =>
             {
-                self.update_details()
+                self.update_details();
+                self.model.load_audio_features();
+                self.watch_collaborative();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistDetailsUpdated(id))
+                if id == &self.model.id =>
+            {
+                self.update_details();
+            }
+            AppEvent::SettingsChanged => {
+                let filter = self.model.explicit.get();
+                self.explicit_filter
+                    .set_state(&filter.as_str().to_variant());
+                self.model.load_audio_features();
             }
             _ => {}
         }
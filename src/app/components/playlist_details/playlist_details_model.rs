@@ -1,30 +1,57 @@
 use gdk::SELECTION_CLIPBOARD;
+use gdk_pixbuf::Pixbuf;
 use gio::prelude::*;
-use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gio::{ActionMapExt, SimpleAction};
 use gtk::Clipboard;
-use std::cell::Ref;
+use std::cell::{Cell, Ref};
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
-use crate::app::components::{handle_error, PlaylistModel};
+use crate::app::components::{
+    append_song_menu, handle_error, song_actions, ExplicitFilterModel, PlaylistModel,
+};
 use crate::app::models::*;
+use crate::app::playlist_files::{export_playlist, PlaylistFileFormat};
 use crate::app::state::{
     BrowserAction, BrowserEvent, PlaybackAction, PlaylistSource, SelectionAction, SelectionState,
 };
 use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel, AppState};
 
+// The most the image upload endpoint accepts, once encoded
+const MAX_COVER_BYTES: usize = 256 * 1024;
+
+// Scaled down and compressed more and more until it is small enough
+fn encode_cover(path: &Path) -> Option<String> {
+    let pixbuf = Pixbuf::from_file_at_scale(path, 640, 640, true).ok()?;
+    [90, 75, 60, 45, 30].iter().find_map(|quality| {
+        let jpeg = pixbuf
+            .save_to_bufferv("jpeg", &[("quality", &quality.to_string())])
+            .ok()?;
+        glib::base64_encode(&jpeg)
+            .map(|image| image.to_string())
+            .filter(|image| image.len() <= MAX_COVER_BYTES)
+    })
+}
+
 pub struct PlaylistDetailsModel {
     pub id: String,
+    pub explicit: ExplicitFilterModel,
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    mood_filter: Cell<Option<Mood>>,
+    duration_filter: Cell<DurationRange>,
 }
 
 impl PlaylistDetailsModel {
     pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
         Self {
+            explicit: ExplicitFilterModel::new(id.clone(), Rc::clone(&app_model)),
             id,
             app_model,
             dispatcher,
+            mood_filter: Cell::new(None),
+            duration_filter: Cell::new(Default::default()),
         }
     }
 
@@ -45,6 +72,45 @@ impl PlaylistDetailsModel {
             .map_state_opt(|s| s.browser.playlist_details_state(&self.id)?.content.as_ref())
     }
 
+    pub fn is_collaborative(&self) -> bool {
+        self.get_playlist_info()
+            .map(|info| info.collaborative)
+            .unwrap_or(false)
+    }
+
+    // Refreshed details that didn't change are ignored by the state
+    pub fn refresh_playlist_info(&self) {
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.refresh_playlist(&id).await {
+                Ok(playlist) => Some(BrowserAction::SetPlaylistDetails(playlist).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    // Fetched again, every page of songs included, in case the page is still loading
+    pub fn export(&self, path: PathBuf, format: PlaylistFileFormat) {
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            let playlist = match api.get_playlist(&id).await {
+                Ok(playlist) => playlist,
+                Err(err) => return handle_error(err),
+            };
+            let message = match std::fs::write(&path, export_playlist(&playlist, format)) {
+                Ok(_) => format!(
+                    "Exported {} songs to {}",
+                    playlist.songs.len(),
+                    path.display()
+                ),
+                Err(err) => format!("Couldn't export {}: {}", playlist.title, err),
+            };
+            Some(AppAction::ShowNotification(message))
+        }));
+    }
+
     pub fn load_playlist_info(&self) {
         let api = self.app_model.get_spotify();
         let id = self.id.clone();
@@ -61,6 +127,146 @@ impl PlaylistDetailsModel {
     fn state(&self) -> Ref<'_, AppState> {
         self.app_model.get_state()
     }
+
+    pub fn is_owned(&self) -> bool {
+        self.state().owns_playlist(&self.id)
+    }
+
+    pub fn remove_songs(
+        dispatcher: &dyn ActionDispatcher,
+        app_model: &AppModel,
+        id: &str,
+        ids: Vec<String>,
+    ) {
+        let api = app_model.get_spotify();
+        let id = id.to_string();
+        let tracks = app_model
+            .get_state()
+            .browser
+            .playlist_details_state(&id)
+            .map(|state| state.positions_of(&ids))
+            .unwrap_or_default();
+        if tracks.is_empty() {
+            return;
+        }
+        let positions = tracks.iter().map(|(_, position)| *position).collect();
+        dispatcher.dispatch(BrowserAction::RemoveFromPlaylist(id.clone(), positions).into());
+        dispatcher.dispatch_async(Box::pin(async move {
+            match api.remove_from_playlist(&id, tracks).await {
+                Ok(_) => None,
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn update_details(&self, name: &str, description: &str) {
+        let name = name.trim().to_string();
+        let description = description.trim().to_string();
+        if name.is_empty() || !self.is_owned() {
+            return;
+        }
+
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        self.dispatcher.dispatch(
+            BrowserAction::UpdatePlaylistDetails(id.clone(), name.clone(), description.clone())
+                .into(),
+        );
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.update_playlist_details(&id, &name, &description).await {
+                Ok(_) => None,
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn upload_art(&self, path: PathBuf) {
+        if !self.is_owned() {
+            return;
+        }
+        let image = match encode_cover(&path) {
+            Some(image) => image,
+            None => {
+                let message = format!("Couldn't use {} as a cover", path.display());
+                self.dispatcher
+                    .dispatch(AppAction::ShowNotification(message));
+                return;
+            }
+        };
+
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        // Shows the new cover right away, without waiting for the upload
+        if let Ok(art) = glib::filename_to_uri(&path, None) {
+            self.dispatcher
+                .dispatch(BrowserAction::SetPlaylistArt(id.clone(), art.to_string()).into());
+        }
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.upload_playlist_image(&id, image).await {
+                Ok(_) => None,
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn set_mood_filter(&self, mood: Option<Mood>) {
+        self.mood_filter.set(mood);
+        self.load_audio_features();
+    }
+
+    pub fn duration_filter(&self) -> DurationRange {
+        self.duration_filter.get()
+    }
+
+    pub fn set_duration_filter(&self, range: DurationRange) {
+        self.duration_filter.set(range);
+    }
+
+    fn wants_audio_features(&self) -> bool {
+        self.state().settings.mood_tags || self.mood_filter.get().is_some()
+    }
+
+    pub fn load_audio_features(&self) {
+        if !self.wants_audio_features() {
+            return;
+        }
+        let ids: Vec<String> = {
+            let state = self.state();
+            self.songs_ref()
+                .map(|songs| {
+                    songs
+                        .iter()
+                        .filter(|s| !state.browser.has_fetched_audio_features(&s.id))
+                        .map(|s| s.id.clone())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        if ids.is_empty() {
+            return;
+        }
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_audio_features(ids).await {
+                Ok(features) => Some(BrowserAction::SetAudioFeatures(features).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    // Positions in the list skip hidden explicit songs, unlike those in the playlist
+    fn positions_in_playlist(&self, id: &str, position: usize) -> Option<(usize, usize)> {
+        let hides_explicit = self.explicit.hides_explicit();
+        let songs = self.songs_ref()?;
+        let from = songs.iter().position(|s| s.id == id)?;
+        let to = songs
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !(hides_explicit && s.explicit))
+            .nth(position)
+            .map(|(i, _)| i)?;
+        Some((from, to))
+    }
 }
 
 impl PlaylistModel for PlaylistDetailsModel {
@@ -71,8 +277,9 @@ impl PlaylistModel for PlaylistDetailsModel {
     fn songs(&self) -> Vec<SongModel> {
         let songs = self.songs_ref();
         match songs {
-            Some(songs) => songs
-                .iter()
+            Some(songs) => self
+                .explicit
+                .filter(songs.iter())
                 .enumerate()
                 .map(|(i, s)| s.to_song_model(i))
                 .collect(),
@@ -80,31 +287,141 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
     }
 
+    // Songs hidden by the duration filter are skipped, so the playlist is loaded again
+    // whenever the songs that would play differ from those loaded, e.g. once the filter changed
     fn play_song(&self, id: &str) {
         let source = PlaylistSource::Playlist(self.id.clone());
-        if self.app_model.get_state().playback.source != source {
-            let songs = self.songs_ref();
-            if let Some(songs) = songs {
+        let duration_filter = self.duration_filter.get();
+        let songs = self.songs_ref().map(|songs| {
+            self.explicit
+                .filter(songs.iter())
+                .filter(|s| s.id == id || duration_filter.contains(s.duration))
+                .cloned()
+                .collect::<Vec<SongDescription>>()
+        });
+        if let Some(songs) = songs {
+            if !self
+                .app_model
+                .get_state()
+                .playback
+                .plays_as_loaded_with(&source, &songs)
+            {
                 self.dispatcher
-                    .dispatch(PlaybackAction::LoadPlaylist(source, songs.clone()).into());
+                    .dispatch(PlaybackAction::LoadPlaylist(source, songs).into());
             }
         }
         self.dispatcher
             .dispatch(PlaybackAction::Load(id.to_string()).into());
     }
 
+    fn has_columns(&self) -> bool {
+        true
+    }
+
     fn should_refresh_songs(&self, event: &AppEvent) -> bool {
-        matches!(
-            event,
-            AppEvent::BrowserEvent(BrowserEvent::PlaylistDetailsLoaded(id)) if id == &self.id
-        )
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistDetailsLoaded(id)) => id == &self.id,
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistTracksRemoved(id)) => id == &self.id,
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistTrackMoved(id, _, _)) => id == &self.id,
+            AppEvent::SettingsChanged => true,
+            AppEvent::BrowserEvent(BrowserEvent::AudioFeaturesLoaded) => true,
+            _ => false,
+        }
+    }
+
+    fn song_tags(&self, id: &str) -> Option<String> {
+        let state = self.state();
+        let added_by = state
+            .browser
+            .playlist_details_state(&self.id)
+            .and_then(|s| s.content.as_ref()?.added_by.get(id))
+            .map(|name| format!("Added by {}", name));
+        let moods = if state.settings.mood_tags {
+            state
+                .browser
+                .audio_features(id)
+                .and_then(|features| features.describe())
+        } else {
+            None
+        };
+        match (added_by, moods) {
+            (Some(added_by), Some(moods)) => Some(format!("{} · {}", added_by, moods)),
+            (added_by, moods) => added_by.or(moods),
+        }
+    }
+
+    // Songs without features are hidden whenever a mood is picked
+    fn is_song_visible(&self, id: &str) -> bool {
+        let duration_filter = self.duration_filter.get();
+        let fits_duration = !duration_filter.is_active()
+            || self
+                .songs_ref()
+                .and_then(|songs| songs.iter().find(|s| s.id == id).map(|s| s.duration))
+                .map(|duration| duration_filter.contains(duration))
+                .unwrap_or(true);
+        let fits_mood = match self.mood_filter.get() {
+            Some(mood) => self
+                .state()
+                .browser
+                .audio_features(id)
+                .map(|f| f.has_mood(mood))
+                .unwrap_or(false),
+            None => true,
+        };
+        fits_duration && fits_mood
+    }
+
+    fn can_remove_song(&self, _id: &str) -> bool {
+        self.is_owned()
+    }
+
+    fn can_move_songs(&self) -> bool {
+        self.is_owned()
+    }
+
+    fn move_song(&self, id: &str, position: usize) {
+        let (from, to) = match self.positions_in_playlist(id, position) {
+            Some((from, to)) if from != to => (from, to),
+            _ => return,
+        };
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        self.dispatcher
+            .dispatch(BrowserAction::MovePlaylistTrack(id.clone(), from, to).into());
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.move_playlist_track(&id, from, to).await {
+                Ok(_) => None,
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    // With explicit songs hidden, the list is refreshed instead
+    fn moved_song(&self, event: &AppEvent) -> Option<(usize, usize)> {
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistTrackMoved(id, from, to))
+                if id == &self.id && !self.explicit.hides_explicit() =>
+            {
+                Some((*from, *to))
+            }
+            _ => None,
+        }
+    }
+
+    fn remove_song(&self, id: &str) {
+        Self::remove_songs(
+            &*self.dispatcher,
+            &self.app_model,
+            &self.id,
+            vec![id.to_string()],
+        );
     }
 
     fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
         let songs = self.songs_ref()?;
         let song = songs.iter().find(|&song| song.id == id)?;
 
-        let group = SimpleActionGroup::new();
+        let group = song_actions(self.dispatcher.as_ref(), song);
 
         let album_id = song.album.id.clone();
         let view_album = SimpleAction::new("view_album", None);
@@ -133,6 +450,23 @@ impl PlaylistModel for PlaylistDetailsModel {
         });
         group.add_action(&copy_link);
 
+        if self.is_owned() {
+            let remove = SimpleAction::new("remove", None);
+            let dispatcher = self.dispatcher.box_clone();
+            let app_model = Rc::clone(&self.app_model);
+            let playlist_id = self.id.clone();
+            let track_id = song.id.clone();
+            remove.connect_activate(move |_, _| {
+                Self::remove_songs(
+                    &*dispatcher,
+                    &app_model,
+                    &playlist_id,
+                    vec![track_id.clone()],
+                );
+            });
+            group.add_action(&remove);
+        }
+
         Some(group.upcast())
     }
 
@@ -150,6 +484,10 @@ impl PlaylistModel for PlaylistDetailsModel {
         }
 
         menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+        if self.is_owned() {
+            menu.append(Some("Remove from this playlist"), Some("song.remove"));
+        }
 
         Some(menu.upcast())
     }
@@ -1,5 +1,7 @@
 mod podcasts;
 mod podcasts_model;
+mod show_menu;
 
 pub use podcasts::*;
 pub use podcasts_model::*;
+pub use show_menu::*;
@@ -4,7 +4,7 @@ use gtk::ScrolledWindowExt;
 
 use std::rc::{Rc, Weak};
 
-use super::PodcastsModel;
+use super::{PodcastsModel, ShowMenu};
 use crate::app::components::{Album, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
@@ -30,10 +30,11 @@ pub struct Podcasts {
     widget: PodcastsWidget,
     worker: Worker,
     model: Rc<PodcastsModel>,
+    show_menu: Rc<ShowMenu>,
 }
 
 impl Podcasts {
-    pub fn new(worker: Worker, model: PodcastsModel) -> Self {
+    pub fn new(worker: Worker, model: PodcastsModel, show_menu: Rc<ShowMenu>) -> Self {
         let model = Rc::new(model);
 
         let widget = PodcastsWidget::new();
@@ -41,7 +42,7 @@ impl Podcasts {
         let weak_model = Rc::downgrade(&model);
         widget.scrolled_window.connect_edge_reached(move |_, pos| {
             if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
-                let _ = model.load_more_playlists();
+                let _ = model.load_more_podcasts();
             }
         });
 
@@ -49,16 +50,23 @@ impl Podcasts {
             widget,
             worker,
             model,
+            show_menu,
         }
     }
 
     fn bind_flowbox(&self, store: &gio::ListStore) {
         let weak_model = Rc::downgrade(&self.model);
         let worker_clone = self.worker.clone();
+        let show_menu = Rc::clone(&self.show_menu);
 
         self.widget.flowbox.bind_model(Some(store), move |item| {
             let item = item.downcast_ref::<AlbumModel>().unwrap();
-            let child = create_album_for(item, worker_clone.clone(), weak_model.clone());
+            let child = create_show_for(
+                item,
+                worker_clone.clone(),
+                weak_model.clone(),
+                Rc::clone(&show_menu),
+            );
             child.show_all();
             child.upcast::<gtk::Widget>()
         });
@@ -86,10 +94,11 @@ impl Component for Podcasts {
     }
 }
 
-fn create_album_for(
+fn create_show_for(
     album_model: &AlbumModel,
     worker: Worker,
     model: Weak<PodcastsModel>,
+    show_menu: Rc<ShowMenu>,
 ) -> gtk::FlowBoxChild {
     let child = gtk::FlowBoxChild::new();
 
@@ -98,9 +107,11 @@ fn create_album_for(
 
     album.connect_album_pressed(move |a| {
         if let (Some(model), Some(id)) = (model.upgrade(), a.uri()) {
-            model.open_playlist(id);
+            model.open_show(id);
         }
     });
+    // Unfollowing removes the card right away
+    album.connect_menu_requested(move |a, widget| show_menu.popup(a, widget));
 
     child
 }
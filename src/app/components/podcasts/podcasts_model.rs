@@ -25,16 +25,16 @@ impl PodcastsModel {
     }
 
     pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<AlbumModel>> + '_> {
-        Some(Ref::map(self.state()?, |s| &s.playlists))
+        Some(Ref::map(self.state()?, |s| &s.podcasts))
     }
 
     pub fn refresh_podcasts(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
-        let batch_size = self.state()?.next_playlists_page.batch_size;
+        let batch_size = self.state()?.next_podcasts_page.batch_size;
 
         self.dispatcher.dispatch_async(Box::pin(async move {
-            match api.get_saved_playlists(0, batch_size).await {
-                Ok(playlists) => Some(BrowserAction::SetPlaylistsContent(playlists).into()),
+            match api.get_saved_podcasts(0, batch_size).await {
+                Ok(podcasts) => Some(BrowserAction::SetPodcastsContent(podcasts).into()),
                 Err(err) => handle_error(err),
             }
         }));
@@ -42,16 +42,16 @@ impl PodcastsModel {
         Some(())
     }
 
-    pub fn load_more_playlists(&self) -> Option<()> {
+    pub fn load_more_podcasts(&self) -> Option<()> {
         let api = self.app_model.get_spotify();
 
-        let next_page = &self.state()?.next_playlists_page;
+        let next_page = &self.state()?.next_podcasts_page;
         let batch_size = next_page.batch_size;
         let offset = next_page.next_offset?;
 
         self.dispatcher.dispatch_async(Box::pin(async move {
-            match api.get_saved_playlists(offset, batch_size).await {
-                Ok(playlists) => Some(BrowserAction::AppendPlaylistsContent(playlists).into()),
+            match api.get_saved_podcasts(offset, batch_size).await {
+                Ok(podcasts) => Some(BrowserAction::AppendPodcastsContent(offset, podcasts).into()),
                 Err(err) => handle_error(err),
             }
         }));
@@ -59,7 +59,7 @@ impl PodcastsModel {
         Some(())
     }
 
-    pub fn open_playlist(&self, id: String) {
-        self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
+    pub fn open_show(&self, id: String) {
+        self.dispatcher.dispatch(AppAction::ViewShow(id));
     }
 }
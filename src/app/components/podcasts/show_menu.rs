@@ -0,0 +1,103 @@
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::handle_error;
+use crate::app::models::*;
+use crate::app::state::BrowserAction;
+use crate::app::{ActionDispatcher, AppModel};
+
+// The menu of podcast cards, to follow a show or unfollow it
+pub struct ShowMenu {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl ShowMenu {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    // Only the followed shows loaded so far are known, following a show twice is harmless
+    fn is_followed(&self, id: &str) -> bool {
+        self.app_model
+            .map_state_opt(|s| s.browser.home_state())
+            .map(|home| home.podcasts.iter().any(|p| p.uri().as_deref() == Some(id)))
+            .unwrap_or(false)
+    }
+
+    // Enough to list the show with the podcasts until the API call completes
+    fn describe(show: &AlbumModel, id: String) -> PodcastDescription {
+        PodcastDescription {
+            id,
+            name: show.title().unwrap_or_default(),
+            art: show.cover_url(),
+            publisher: show.artist().unwrap_or_default(),
+        }
+    }
+
+    fn follow_action(&self, show: &AlbumModel, id: String) -> SimpleAction {
+        let action = SimpleAction::new("follow", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        let podcast = Self::describe(show, id);
+        action.connect_activate(move |_, _| {
+            // Applied right away, before the API call completes
+            dispatcher.dispatch(BrowserAction::FollowShow(podcast.clone()).into());
+            let api = app_model.get_spotify();
+            let id = podcast.id.clone();
+            dispatcher.dispatch_async(Box::pin(async move {
+                match api.save_show(&id).await {
+                    Ok(podcast) => Some(BrowserAction::FollowShow(podcast).into()),
+                    Err(err) => handle_error(err),
+                }
+            }));
+        });
+        action
+    }
+
+    fn unfollow_action(&self, id: String) -> SimpleAction {
+        let action = SimpleAction::new("unfollow", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        action.connect_activate(move |_, _| {
+            // Applied right away, before the API call completes
+            dispatcher.dispatch(BrowserAction::UnfollowShow(id.clone()).into());
+            let api = app_model.get_spotify();
+            let id = id.clone();
+            dispatcher.dispatch_async(Box::pin(async move {
+                match api.remove_saved_show(&id).await {
+                    Ok(_) => None,
+                    Err(err) => handle_error(err),
+                }
+            }));
+        });
+        action
+    }
+
+    // Built when shown, as the show may have been followed or unfollowed in the meantime
+    pub fn popup(&self, show: &AlbumModel, relative_to: &gtk::Widget) {
+        let id = match show.uri() {
+            Some(id) => id,
+            None => return,
+        };
+
+        let group = SimpleActionGroup::new();
+        let menu = gio::Menu::new();
+        if self.is_followed(&id) {
+            group.add_action(&self.unfollow_action(id));
+            menu.append(Some("Unfollow this show"), Some("show.unfollow"));
+        } else {
+            group.add_action(&self.follow_action(show, id));
+            menu.append(Some("Follow this show"), Some("show.follow"));
+        }
+        relative_to.insert_action_group("show", Some(&group));
+
+        let popover = gtk::Popover::from_model(Some(relative_to), &menu);
+        popover.popup();
+    }
+}
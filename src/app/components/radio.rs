@@ -0,0 +1,59 @@
+use std::rc::Rc;
+
+use crate::app::components::{handle_error, EventListener};
+use crate::app::state::{PlaybackAction, PlaybackEvent, RadioSeed};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+const RECOMMENDATIONS: u32 = 50;
+
+// Replaces the queue with recommendations based on a song or an artist
+pub struct Radio {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl Radio {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn start(&self, seed: RadioSeed) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            let recommendations = match &seed {
+                RadioSeed::Song(song) => {
+                    api.get_recommendations(vec![song.id.clone()], RECOMMENDATIONS)
+                        .await
+                }
+                RadioSeed::Artist(id, _) => {
+                    api.get_artist_recommendations(id, RECOMMENDATIONS).await
+                }
+            };
+            match recommendations {
+                Ok(tracks) if tracks.is_empty() => Some(AppAction::ShowNotification(format!(
+                    "No recommendations found for {}",
+                    seed.name()
+                ))),
+                Ok(mut tracks) => {
+                    if let RadioSeed::Song(song) = &seed {
+                        tracks.retain(|track| track.id != song.id);
+                        tracks.insert(0, song.clone());
+                    }
+                    Some(PlaybackAction::LoadRadio(seed, tracks).into())
+                }
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+}
+
+impl EventListener for Radio {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::RadioRequested(seed)) = event {
+            self.start(seed.clone());
+        }
+    }
+}
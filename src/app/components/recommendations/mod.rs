@@ -0,0 +1,5 @@
+mod recommendations;
+pub use recommendations::*;
+
+mod recommendations_model;
+pub use recommendations_model::*;
@@ -0,0 +1,87 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use super::RecommendationsModel;
+use crate::app::components::{Component, EventListener, Playlist};
+use crate::app::{AppEvent, BrowserEvent};
+
+#[derive(Clone, Gladis)]
+struct RecommendationsWidget {
+    root: gtk::Widget,
+    title: gtk::Label,
+    subtitle: gtk::Label,
+    play: gtk::Button,
+    save: gtk::Button,
+    tracks: gtk::ListBox,
+}
+
+impl RecommendationsWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/recommendations.ui")).unwrap()
+    }
+}
+
+// Songs recommended after another one, only kept if saved as a playlist
+pub struct Recommendations {
+    widget: RecommendationsWidget,
+    model: Rc<RecommendationsModel>,
+    children: Vec<Box<dyn EventListener>>,
+}
+
+impl Recommendations {
+    pub fn new(model: RecommendationsModel) -> Self {
+        model.load_recommendations();
+
+        let model = Rc::new(model);
+        let widget = RecommendationsWidget::new();
+
+        widget.play.connect_clicked(clone!(@weak model => move |_| {
+            model.play();
+        }));
+
+        widget.save.connect_clicked(clone!(@weak model => move |_| {
+            model.save_as_playlist();
+        }));
+
+        let playlist = Playlist::new(widget.tracks.clone(), model.clone());
+
+        Self {
+            widget,
+            model,
+            children: vec![Box::new(playlist)],
+        }
+    }
+
+    fn update_details(&self) {
+        if let Some(title) = self.model.get_title() {
+            self.widget.title.set_label(&title);
+        }
+        if let Some(subtitle) = self.model.get_subtitle() {
+            self.widget.subtitle.set_label(&subtitle);
+        }
+        self.widget.play.set_sensitive(true);
+        self.widget.save.set_sensitive(true);
+    }
+}
+
+impl Component for Recommendations {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
+    }
+}
+
+impl EventListener for Recommendations {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::BrowserEvent(BrowserEvent::RecommendationsUpdated(id)) = event {
+            if id == &self.model.id {
+                self.update_details();
+            }
+        }
+        self.broadcast_event(event);
+    }
+}
@@ -0,0 +1,216 @@
+use gdk::SELECTION_CLIPBOARD;
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction};
+use gtk::Clipboard;
+use std::cell::Ref;
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
+use crate::app::models::*;
+use crate::app::state::{
+    BrowserAction, BrowserEvent, PlaybackAction, PlaylistSource, RecommendationsState,
+    SelectionAction, SelectionState,
+};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// The most songs the endpoint recommends at once
+const RECOMMENDATIONS: u32 = 100;
+
+pub struct RecommendationsModel {
+    pub id: String,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl RecommendationsModel {
+    pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            id,
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, RecommendationsState>> {
+        self.app_model
+            .map_state_opt(|s| s.browser.recommendations_state(&self.id))
+    }
+
+    // Also the name the songs are saved under
+    pub fn get_title(&self) -> Option<String> {
+        let state = self.state()?;
+        let seed = state.seed.as_ref()?;
+        Some(format!("More like {}", seed.title))
+    }
+
+    pub fn get_subtitle(&self) -> Option<String> {
+        let state = self.state()?;
+        let seed = state.seed.as_ref()?;
+        Some(seed.artists_name())
+    }
+
+    fn get_songs(&self) -> Vec<SongDescription> {
+        self.state()
+            .map(|state| state.songs.clone())
+            .unwrap_or_default()
+    }
+
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        self.state()?
+            .songs
+            .iter()
+            .find(|song| song.id == id)
+            .cloned()
+    }
+
+    // The song itself is fetched too, which the page is titled after
+    pub fn load_recommendations(&self) {
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            let seed = match api.get_tracks(vec![id.clone()]).await {
+                Ok(mut songs) if !songs.is_empty() => songs.remove(0),
+                Ok(_) => return None,
+                Err(err) => return handle_error(err),
+            };
+            match api.get_recommendations(vec![id], RECOMMENDATIONS).await {
+                Ok(songs) => Some(BrowserAction::SetRecommendations(seed, songs).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn play(&self) {
+        let first = self.state().and_then(|s| s.songs.first().cloned());
+        if let Some(first) = first {
+            self.play_song(&first.id);
+        }
+    }
+
+    pub fn save_as_playlist(&self) {
+        let name = match self.get_title() {
+            Some(name) => name,
+            None => return,
+        };
+        let ids = self
+            .get_songs()
+            .iter()
+            .map(|s| s.id.clone())
+            .collect::<Vec<String>>();
+        if ids.is_empty() {
+            return;
+        }
+
+        let api = self.app_model.get_spotify();
+        let notifier = self.dispatcher.box_clone();
+        self.dispatcher.dispatch_local_async(Box::pin(async move {
+            match api.create_playlist(&name, "", false, ids).await {
+                Ok(playlist) => {
+                    notifier.dispatch(AppAction::ShowNotification(format!(
+                        "Saved as “{}”",
+                        playlist.title
+                    )));
+                    Some(BrowserAction::AddPlaylist(playlist).into())
+                }
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+}
+
+impl PlaylistModel for RecommendationsModel {
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id.clone()
+    }
+
+    fn songs(&self) -> Vec<SongModel> {
+        self.get_songs()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.to_song_model(i))
+            .collect()
+    }
+
+    fn play_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(PlaybackAction::LoadPlaylist(PlaylistSource::None, self.get_songs()).into());
+        self.dispatcher
+            .dispatch(PlaybackAction::Load(id.to_string()).into());
+    }
+
+    fn should_refresh_songs(&self, event: &AppEvent) -> bool {
+        matches!(
+            event,
+            AppEvent::BrowserEvent(BrowserEvent::RecommendationsUpdated(id)) if id == &self.id
+        )
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song(id)?;
+        let group = song_actions(self.dispatcher.as_ref(), song);
+
+        let album_id = song.album.id.clone();
+        let view_album = SimpleAction::new("view_album", None);
+        let dispatcher = self.dispatcher.box_clone();
+        view_album.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+        });
+        group.add_action(&view_album);
+
+        for (i, artist) in song.artists.iter().enumerate() {
+            let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
+            let dispatcher = self.dispatcher.box_clone();
+            let id = artist.id.clone();
+            view_artist.connect_activate(move |_, _| {
+                dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+            });
+            group.add_action(&view_artist);
+        }
+
+        let track_id = song.id.clone();
+        let copy_link = SimpleAction::new("copy_link", None);
+        copy_link.connect_activate(move |_, _| {
+            let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+            clipboard.set_text(&format!("https://open.spotify.com/track/{}", &track_id));
+        });
+        group.add_action(&copy_link);
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song(id)?;
+
+        let menu = gio::Menu::new();
+        menu.append(Some("View album"), Some("song.view_album"));
+        for (i, artist) in song.artists.iter().enumerate() {
+            menu.append(
+                Some(&format!("More from {}", artist.name)),
+                Some(&format!("song.view_artist_{}", i)),
+            );
+        }
+
+        menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+
+        Some(menu.upcast())
+    }
+
+    fn select_song(&self, id: &str) {
+        if let Some(song) = self.song(id) {
+            self.dispatcher
+                .dispatch(SelectionAction::Select(song).into());
+        }
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(SelectionAction::Deselect(id.to_string()).into());
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+    }
+}
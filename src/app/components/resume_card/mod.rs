@@ -0,0 +1,5 @@
+mod resume_card;
+pub use resume_card::*;
+
+mod resume_card_model;
+pub use resume_card_model::*;
@@ -0,0 +1,120 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use super::ResumeCardModel;
+
+use crate::app::components::{screen_add_css_provider, Component, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::state::PlaybackEvent;
+use crate::app::AppEvent;
+
+#[derive(Gladis, Clone)]
+struct ResumeCardWidget {
+    pub root: gtk::Widget,
+    pub track_label: gtk::Label,
+    pub context_label: gtk::Label,
+    pub upcoming_label: gtk::Label,
+    pub resume_button: gtk::Button,
+    pub dismiss_button: gtk::Button,
+}
+
+impl ResumeCardWidget {
+    fn new() -> Self {
+        screen_add_css_provider(resource!("/components/resume_card.css"));
+        Self::from_resource(resource!("/components/resume_card.ui")).unwrap()
+    }
+}
+
+// Offers to pick up where the previous session left off
+pub struct ResumeCard {
+    model: Rc<ResumeCardModel>,
+    worker: Worker,
+    widget: ResumeCardWidget,
+    checked: bool,
+}
+
+impl ResumeCard {
+    pub fn new(model: ResumeCardModel, worker: Worker) -> Self {
+        let model = Rc::new(model);
+        let widget = ResumeCardWidget::new();
+
+        let root = &widget.root;
+        widget
+            .resume_button
+            .connect_clicked(clone!(@weak model, @weak root => move |_| {
+                model.resume();
+                root.hide();
+            }));
+
+        // Dismissing only lasts for the session
+        widget
+            .dismiss_button
+            .connect_clicked(clone!(@weak model, @weak root => move |_| {
+                model.set_candidate(None);
+                root.hide();
+            }));
+
+        Self {
+            model,
+            worker,
+            widget,
+            checked: false,
+        }
+    }
+
+    fn show_candidate(&mut self) {
+        self.checked = true;
+        if !self.model.is_enabled() {
+            return;
+        }
+
+        let model = Rc::clone(&self.model);
+        let widget = self.widget.clone();
+        self.worker.send_local_task(async move {
+            let candidate = model
+                .fetch_candidate()
+                .await
+                .filter(|_| !model.has_playback());
+            if let Some(candidate) = candidate.as_ref() {
+                let track = candidate.track_title().unwrap_or_default();
+                widget.track_label.set_text(&format!("Resume {}", track));
+                widget
+                    .context_label
+                    .set_text(&format!("from {}", candidate.context_name()));
+                let upcoming = candidate.upcoming_titles();
+                widget.upcoming_label.set_visible(!upcoming.is_empty());
+                widget
+                    .upcoming_label
+                    .set_text(&format!("Up next: {}", upcoming.join(", ")));
+                widget.root.show();
+            }
+            model.set_candidate(candidate);
+        });
+    }
+
+    fn hide(&self) {
+        self.model.set_candidate(None);
+        self.widget.root.hide();
+    }
+}
+
+impl Component for ResumeCard {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+}
+
+impl EventListener for ResumeCard {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::LoginCompleted(_) if !self.checked => self.show_candidate(),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) => {
+                self.model.save_last_played();
+                self.hide();
+            }
+            AppEvent::SettingsChanged if !self.model.is_enabled() => self.hide(),
+            _ => {}
+        }
+    }
+}
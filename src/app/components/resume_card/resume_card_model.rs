@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::app::models::SongDescription;
+use crate::app::settings::SpotSettings;
+use crate::app::state::{PlaybackAction, PlaylistSource, ScreenName};
+use crate::app::{ActionDispatcher, AppModel};
+
+// Number of upcoming songs listed on the card
+const UPCOMING_COUNT: usize = 3;
+
+// What was playing when Spot was last closed, if it can still be played
+pub struct ResumeCandidate {
+    source: PlaylistSource,
+    context_name: String,
+    songs: Vec<SongDescription>,
+    track_id: String,
+}
+
+impl ResumeCandidate {
+    fn position(&self) -> Option<usize> {
+        self.songs.iter().position(|s| s.id == self.track_id)
+    }
+
+    pub fn track_title(&self) -> Option<&str> {
+        Some(&self.songs[self.position()?].title[..])
+    }
+
+    pub fn context_name(&self) -> &str {
+        &self.context_name
+    }
+
+    pub fn upcoming_titles(&self) -> Vec<&str> {
+        self.position()
+            .map(|i| {
+                self.songs
+                    .iter()
+                    .skip(i + 1)
+                    .take(UPCOMING_COUNT)
+                    .map(|s| &s.title[..])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+pub struct ResumeCardModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    candidate: RefCell<Option<ResumeCandidate>>,
+}
+
+impl ResumeCardModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            candidate: RefCell::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.app_model.get_state().settings.resume_card
+    }
+
+    // No point in offering to resume once something else is playing
+    pub fn has_playback(&self) -> bool {
+        self.app_model
+            .get_state()
+            .playback
+            .current_song_id
+            .is_some()
+    }
+
+    pub fn set_candidate(&self, candidate: Option<ResumeCandidate>) {
+        self.candidate.replace(candidate);
+    }
+
+    // Resolves to None if nothing was saved or the context doesn't exist anymore
+    pub async fn fetch_candidate(&self) -> Option<ResumeCandidate> {
+        let (track_id, context) = SpotSettings::last_played()?;
+        let api = self.app_model.get_spotify();
+        let (source, context_name, songs) = match ScreenName::from_uri(&context)? {
+            ScreenName::AlbumDetails(id) => {
+                let album = api.get_album(&id).await.ok()?;
+                (PlaylistSource::Album(id), album.title, album.songs)
+            }
+            ScreenName::PlaylistDetails(id) => {
+                let playlist = api.get_playlist(&id).await.ok()?;
+                (PlaylistSource::Playlist(id), playlist.title, playlist.songs)
+            }
+            _ => return None,
+        };
+        let candidate = ResumeCandidate {
+            source,
+            context_name,
+            songs,
+            track_id,
+        };
+        candidate.position().map(|_| candidate)
+    }
+
+    pub fn resume(&self) {
+        if let Some(candidate) = self.candidate.borrow_mut().take() {
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadPlaylist(candidate.source, candidate.songs).into());
+            self.dispatcher
+                .dispatch(PlaybackAction::Load(candidate.track_id).into());
+        }
+    }
+
+    pub fn save_last_played(&self) {
+        let state = self.app_model.get_state();
+        let track_id = state.playback.current_song_id.as_ref();
+        if let (Some(track_id), Some(context)) = (track_id, state.playback.source.uri()) {
+            SpotSettings::save_last_played(track_id, &context);
+        }
+    }
+}
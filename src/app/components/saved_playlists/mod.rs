@@ -1,5 +1,7 @@
+mod playlist_import;
 mod saved_playlists;
 mod saved_playlists_model;
 
+pub use playlist_import::*;
 pub use saved_playlists::*;
 pub use saved_playlists_model::*;
@@ -0,0 +1,227 @@
+use gtk::prelude::*;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::api::SpotifyApiClient;
+use crate::app::components::handle_error;
+use crate::app::models::SongDescription;
+use crate::app::playlist_files::{import_playlist, ImportEntry, PlaylistFileFormat};
+use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction};
+
+// How many search results are offered when no exact match was found
+const CANDIDATES: u32 = 5;
+
+struct ImportMatch {
+    entry: ImportEntry,
+    candidates: Vec<SongDescription>,
+}
+
+impl ImportMatch {
+    fn resolved_id(&self) -> Option<String> {
+        self.entry.id.clone().or_else(|| {
+            self.candidates
+                .iter()
+                .find(|song| self.entry.is_exact_match(song))
+                .map(|song| song.id.clone())
+        })
+    }
+
+    fn needs_review(&self) -> bool {
+        self.resolved_id().is_none() && !self.candidates.is_empty()
+    }
+}
+
+pub struct PlaylistImport {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl PlaylistImport {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn notify(&self, message: String) {
+        self.dispatcher
+            .dispatch(AppAction::ShowNotification(message));
+    }
+
+    // Songs without a Spotify id are searched for, one after the other
+    pub fn import(&self, path: &Path, parent: Option<gtk::Window>) {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let format = match PlaylistFileFormat::from_path(path) {
+            Some(format) => format,
+            None => return self.notify(format!("{} isn't a supported playlist", file_name)),
+        };
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => return self.notify(format!("Couldn't read {}: {}", file_name, err)),
+        };
+        let entries = import_playlist(&contents, format);
+        if entries.is_empty() {
+            return self.notify(format!("No songs found in {}", file_name));
+        }
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or(file_name);
+
+        self.notify(format!("Looking up {} songs…", entries.len()));
+        let api = self.app_model.get_spotify();
+        let dispatcher = self.dispatcher.box_clone();
+        self.dispatcher.dispatch_local_async(Box::pin(async move {
+            let mut matches = vec![];
+            for entry in entries {
+                let candidates = if entry.id.is_some() {
+                    vec![]
+                } else {
+                    match api.search_tracks(&entry.query(), CANDIDATES).await {
+                        Ok(candidates) => candidates,
+                        Err(err) => return handle_error(err),
+                    }
+                };
+                matches.push(ImportMatch { entry, candidates });
+            }
+
+            if matches.iter().any(|m| m.needs_review()) {
+                Self::review(parent, dispatcher, api, name, matches);
+            } else {
+                let ids = matches.iter().filter_map(|m| m.resolved_id()).collect();
+                Self::create(dispatcher, api, name, ids, matches.len());
+            }
+            None
+        }));
+    }
+
+    // Each ambiguous entry gets the search results to pick from, or can be skipped
+    fn review(
+        parent: Option<gtk::Window>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        name: String,
+        matches: Vec<ImportMatch>,
+    ) {
+        let dialog = gtk::Dialog::with_buttons(
+            Some("Review matches"),
+            parent.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Create playlist", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+        dialog.set_default_size(560, 400);
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+
+        let reviewed = matches.iter().filter(|m| m.needs_review()).count();
+        let message = gtk::Label::new(Some(&format!(
+            "{} songs from {} couldn't be matched exactly, pick the right ones",
+            reviewed, name
+        )));
+        message.set_line_wrap(true);
+        message.set_xalign(0.0);
+        content.add(&message);
+
+        let grid = gtk::Grid::new();
+        grid.set_row_spacing(6);
+        grid.set_column_spacing(12);
+        let mut combos = vec![];
+        for (row, m) in matches.iter().filter(|m| m.needs_review()).enumerate() {
+            let label = gtk::Label::new(Some(&m.entry.describe()));
+            label.set_xalign(0.0);
+            label.set_line_wrap(true);
+            label.set_hexpand(true);
+            grid.attach(&label, 0, row as i32, 1, 1);
+
+            let combo = gtk::ComboBoxText::new();
+            for song in m.candidates.iter() {
+                let description = format!(
+                    "{} — {} ({})",
+                    song.title,
+                    song.artists_name(),
+                    song.album.name
+                );
+                combo.append(Some(&song.id), &description);
+            }
+            combo.append(Some(""), "Skip");
+            combo.set_active(Some(0));
+            grid.attach(&combo, 1, row as i32, 1, 1);
+            combos.push(combo);
+        }
+
+        let scrolled_window = gtk::ScrolledWindow::new(gtk::NONE_ADJUSTMENT, gtk::NONE_ADJUSTMENT);
+        scrolled_window.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        scrolled_window.set_vexpand(true);
+        scrolled_window.add(&grid);
+        content.add(&scrolled_window);
+
+        dialog.get_content_area().add(&content);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                // Picks come in the order of the file, like the entries needing them
+                let mut picks = combos.iter().map(|combo| {
+                    combo
+                        .get_active_id()
+                        .map(|id| id.to_string())
+                        .filter(|id| !id.is_empty())
+                });
+                let ids = matches
+                    .iter()
+                    .filter_map(|m| {
+                        if m.needs_review() {
+                            picks.next().flatten()
+                        } else {
+                            m.resolved_id()
+                        }
+                    })
+                    .collect();
+                Self::create(
+                    dispatcher.box_clone(),
+                    Arc::clone(&api),
+                    name.clone(),
+                    ids,
+                    matches.len(),
+                );
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn create(
+        dispatcher: Box<dyn ActionDispatcher>,
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        name: String,
+        ids: Vec<String>,
+        total: usize,
+    ) {
+        let notifier = dispatcher.box_clone();
+        dispatcher.dispatch_local_async(Box::pin(async move {
+            let count = ids.len();
+            match api.create_playlist(&name, "", false, ids).await {
+                Ok(playlist) => {
+                    notifier.dispatch(AppAction::ShowNotification(format!(
+                        "Imported {} of {} songs into {}",
+                        count, total, name
+                    )));
+                    Some(BrowserAction::AddPlaylist(playlist).into())
+                }
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+}
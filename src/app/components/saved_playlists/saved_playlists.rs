@@ -4,14 +4,18 @@ use gtk::ScrolledWindowExt;
 
 use std::rc::{Rc, Weak};
 
-use super::SavedPlaylistsModel;
+use super::{PlaylistImport, SavedPlaylistsModel};
 use crate::app::components::{Album, Component, EventListener};
 use crate::app::dispatch::Worker;
 use crate::app::models::AlbumModel;
-use crate::app::AppEvent;
+use crate::app::{AppEvent, BrowserEvent};
 
 #[derive(Clone, Gladis)]
 struct SavedPlaylistsWidget {
+    pub root: gtk::Widget,
+    pub folder: gtk::ComboBoxText,
+    pub new_playlist: gtk::Button,
+    pub import_playlist: gtk::Button,
     pub scrolled_window: gtk::ScrolledWindow,
     pub flowbox: gtk::FlowBox,
 }
@@ -20,10 +24,6 @@ impl SavedPlaylistsWidget {
     fn new() -> Self {
         Self::from_resource(resource!("/components/saved_playlists.ui")).unwrap()
     }
-
-    fn root(&self) -> &gtk::Widget {
-        self.scrolled_window.upcast_ref()
-    }
 }
 
 pub struct SavedPlaylists {
@@ -33,8 +33,9 @@ pub struct SavedPlaylists {
 }
 
 impl SavedPlaylists {
-    pub fn new(worker: Worker, model: SavedPlaylistsModel) -> Self {
+    pub fn new(worker: Worker, model: SavedPlaylistsModel, import: PlaylistImport) -> Self {
         let model = Rc::new(model);
+        let import = Rc::new(import);
 
         let widget = SavedPlaylistsWidget::new();
 
@@ -45,6 +46,40 @@ impl SavedPlaylists {
             }
         });
 
+        let weak_model = Rc::downgrade(&model);
+        widget.flowbox.set_filter_func(Some(Box::new(move |child| {
+            let model = match weak_model.upgrade() {
+                Some(model) => model,
+                None => return true,
+            };
+            let id = model
+                .get_list_store()
+                .and_then(|store| store.get(child.get_index() as u32).uri());
+            id.map(|id| model.is_playlist_visible(&id)).unwrap_or(true)
+        })));
+
+        let flowbox = &widget.flowbox;
+        widget
+            .folder
+            .connect_changed(clone!(@weak model, @weak flowbox => move |combo| {
+                let folder = combo
+                    .get_active_id()
+                    .map(|id| id.to_string())
+                    .filter(|id| id != "all");
+                model.set_folder(folder);
+                flowbox.invalidate_filter();
+            }));
+
+        widget
+            .new_playlist
+            .connect_clicked(clone!(@weak model => move |button| {
+                Self::prompt_new_playlist(button, model);
+            }));
+
+        widget.import_playlist.connect_clicked(move |button| {
+            Self::prompt_import(button, Rc::clone(&import));
+        });
+
         Self {
             widget,
             worker,
@@ -52,6 +87,111 @@ impl SavedPlaylists {
         }
     }
 
+    fn prompt_new_playlist(button: &gtk::Button, model: Rc<SavedPlaylistsModel>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::Dialog::with_buttons(
+            Some("New playlist"),
+            window.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Create", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+        dialog.set_response_sensitive(gtk::ResponseType::Accept, false);
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+
+        let name = gtk::Entry::new();
+        name.set_placeholder_text(Some("Playlist name"));
+        name.set_activates_default(true);
+        name.connect_changed(clone!(@weak dialog => move |entry| {
+            let valid = !entry.get_text().trim().is_empty();
+            dialog.set_response_sensitive(gtk::ResponseType::Accept, valid);
+        }));
+        content.add(&name);
+
+        let description = gtk::Entry::new();
+        description.set_placeholder_text(Some("Description (optional)"));
+        description.set_activates_default(true);
+        content.add(&description);
+
+        let public = gtk::CheckButton::with_label("Public");
+        public.set_tooltip_text(Some("Show this playlist on your profile"));
+        content.add(&public);
+
+        dialog.get_content_area().add(&content);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                model.create_playlist(
+                    name.get_text().as_str(),
+                    description.get_text().as_str(),
+                    public.get_active(),
+                );
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn prompt_import(button: &gtk::Button, import: Rc<PlaylistImport>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::FileChooserDialog::with_buttons(
+            Some("Import playlist"),
+            window.as_ref(),
+            gtk::FileChooserAction::Open,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Import", gtk::ResponseType::Accept),
+            ],
+        );
+
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Playlists"));
+        for pattern in &["*.m3u", "*.m3u8", "*.csv", "*.json"] {
+            filter.add_pattern(pattern);
+        }
+        dialog.add_filter(&filter);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                if let Some(path) = dialog.get_filename() {
+                    import.import(&path, window.clone());
+                }
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    // The selected folder is kept if it still exists
+    fn update_folders(&self) {
+        let combo = &self.widget.folder;
+        let folders = self.model.folders();
+        let selected = self
+            .model
+            .folder()
+            .filter(|folder| folders.iter().any(|(id, _)| id == folder));
+
+        combo.remove_all();
+        combo.append(Some("all"), "All playlists");
+        for (id, name) in folders.iter() {
+            combo.append(Some(id), name);
+        }
+        combo.set_active_id(Some(selected.as_deref().unwrap_or("all")));
+        combo.set_visible(!folders.is_empty());
+    }
+
     fn bind_flowbox(&self, store: &gio::ListStore) {
         let weak_model = Rc::downgrade(&self.model);
         let worker_clone = self.worker.clone();
@@ -69,12 +209,19 @@ impl EventListener for SavedPlaylists {
     fn on_event(&mut self, event: &AppEvent) {
         match event {
             AppEvent::Started => {
+                let _ = self.model.update_sort();
                 let _ = self.model.refresh_saved_playlists();
                 self.bind_flowbox(self.model.get_list_store().unwrap().unsafe_store())
             }
             AppEvent::LoginCompleted(_) => {
                 let _ = self.model.refresh_saved_playlists();
             }
+            AppEvent::SettingsChanged => {
+                let _ = self.model.update_sort();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::PlaylistFoldersUpdated) => {
+                self.update_folders();
+            }
             _ => {}
         }
     }
@@ -82,7 +229,7 @@ impl EventListener for SavedPlaylists {
 
 impl Component for SavedPlaylists {
     fn get_root_widget(&self) -> &gtk::Widget {
-        self.widget.root()
+        &self.widget.root
     }
 }
 
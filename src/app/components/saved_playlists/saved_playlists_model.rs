@@ -1,15 +1,17 @@
-use std::cell::Ref;
+use std::cell::{Ref, RefCell};
 use std::ops::Deref;
 use std::rc::Rc;
 
 use crate::app::components::handle_error;
 use crate::app::models::*;
+use crate::app::settings::PlaylistSort;
 use crate::app::state::HomeState;
 use crate::app::{ActionDispatcher, AppAction, AppModel, BrowserAction, ListStore};
 
 pub struct SavedPlaylistsModel {
     app_model: Rc<AppModel>,
     dispatcher: Box<dyn ActionDispatcher>,
+    folder: RefCell<Option<String>>,
 }
 
 impl SavedPlaylistsModel {
@@ -17,6 +19,7 @@ impl SavedPlaylistsModel {
         Self {
             app_model,
             dispatcher,
+            folder: RefCell::new(None),
         }
     }
 
@@ -59,6 +62,80 @@ impl SavedPlaylistsModel {
         Some(())
     }
 
+    pub fn update_sort(&self) -> Option<()> {
+        let sort = self.app_model.get_state().settings.playlists_sort;
+        if sort == self.state()?.playlists_sort {
+            return Some(());
+        }
+        self.dispatcher
+            .dispatch(BrowserAction::SetPlaylistsSort(sort).into());
+        if sort == PlaylistSort::Default {
+            self.refresh_saved_playlists()?;
+        }
+        Some(())
+    }
+
+    pub fn create_playlist(&self, name: &str, description: &str, public: bool) {
+        let name = name.trim().to_string();
+        let description = description.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api
+                .create_playlist(&name, &description, public, vec![])
+                .await
+            {
+                Ok(playlist) => Some(BrowserAction::AddPlaylist(playlist).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    // Ids and names of all folders, nested ones being indented
+    pub fn folders(&self) -> Vec<(String, String)> {
+        match self.state() {
+            Some(state) => state
+                .playlist_folders
+                .iter()
+                .map(|f| {
+                    (
+                        f.id.clone(),
+                        format!("{}{}", "    ".repeat(f.depth), f.name),
+                    )
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn folder(&self) -> Option<String> {
+        self.folder.borrow().clone()
+    }
+
+    pub fn set_folder(&self, folder: Option<String>) {
+        self.folder.replace(folder);
+    }
+
+    pub fn is_playlist_visible(&self, id: &str) -> bool {
+        let folder = self.folder.borrow();
+        let folder = match folder.as_ref() {
+            Some(folder) => folder,
+            None => return true,
+        };
+        self.state()
+            .and_then(|state| {
+                state
+                    .playlist_folders
+                    .iter()
+                    .find(|f| &f.id == folder)
+                    .map(|f| f.playlist_ids.iter().any(|p| p == id))
+            })
+            .unwrap_or(true)
+    }
+
     pub fn open_playlist(&self, id: String) {
         self.dispatcher.dispatch(AppAction::ViewPlaylist(id));
     }
@@ -0,0 +1,5 @@
+mod saved_tracks;
+pub use saved_tracks::*;
+
+mod saved_tracks_model;
+pub use saved_tracks_model::*;
@@ -0,0 +1,122 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use gtk::ScrolledWindowExt;
+use std::rc::Rc;
+
+use super::SavedTracksModel;
+use crate::app::components::{Component, EventListener, Playlist};
+use crate::app::models::SongSort;
+use crate::app::{AppEvent, BrowserEvent};
+
+#[derive(Clone, Gladis)]
+struct SavedTracksWidget {
+    root: gtk::Widget,
+    scrolled_window: gtk::ScrolledWindow,
+    search: gtk::SearchEntry,
+    sort: gtk::ComboBoxText,
+    tracks: gtk::ListBox,
+}
+
+impl SavedTracksWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/saved_tracks.ui")).unwrap()
+    }
+
+    // Pages are otherwise only loaded when scrolling down, which isn't possible
+    // until there are enough songs to fill the window
+    fn fill_window(
+        root: &gtk::Widget,
+        scrolled_window: &gtk::ScrolledWindow,
+        model: &SavedTracksModel,
+    ) {
+        let adjustment = scrolled_window.get_vadjustment();
+        if let Some(adjustment) = adjustment.filter(|_| root.get_mapped()) {
+            let page_size = adjustment.get_page_size();
+            if page_size > 0.0 && adjustment.get_upper() <= page_size {
+                let _ = model.load_more_tracks();
+            }
+        }
+    }
+}
+
+pub struct SavedTracks {
+    widget: SavedTracksWidget,
+    model: Rc<SavedTracksModel>,
+    children: Vec<Box<dyn EventListener>>,
+}
+
+impl SavedTracks {
+    pub fn new(model: SavedTracksModel) -> Self {
+        let model = Rc::new(model);
+        let widget = SavedTracksWidget::new();
+
+        let weak_model = Rc::downgrade(&model);
+        widget.scrolled_window.connect_edge_reached(move |_, pos| {
+            if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
+                let _ = model.load_more_tracks();
+            }
+        });
+
+        widget.search.connect_search_changed(
+            clone!(@weak model, @weak widget.tracks as tracks => move |entry| {
+                model.set_search(entry.get_text().as_str());
+                tracks.invalidate_filter();
+            }),
+        );
+
+        widget
+            .sort
+            .connect_changed(clone!(@weak model => move |combo| {
+                let sort = combo
+                    .get_active_id()
+                    .map(|id| SongSort::from_id(&id))
+                    .unwrap_or(SongSort::Default);
+                model.set_sort(sort);
+            }));
+
+        // The page may be shown for the first time once songs are already there
+        widget.root.connect_map(
+            clone!(@weak model, @weak widget.scrolled_window as scrolled_window => move |root| {
+                SavedTracksWidget::fill_window(root, &scrolled_window, &model);
+            }),
+        );
+
+        let playlist = Playlist::new(widget.tracks.clone(), model.clone());
+
+        Self {
+            widget,
+            model,
+            children: vec![Box::new(playlist)],
+        }
+    }
+}
+
+impl Component for SavedTracks {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
+    }
+}
+
+impl EventListener for SavedTracks {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started | AppEvent::LoginCompleted(_) => {
+                let _ = self.model.refresh_saved_tracks();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated)
+            | AppEvent::BrowserEvent(BrowserEvent::SavedTracksAppended(_)) => {
+                SavedTracksWidget::fill_window(
+                    &self.widget.root,
+                    &self.widget.scrolled_window,
+                    &self.model,
+                );
+            }
+            _ => {}
+        }
+        self.broadcast_event(event);
+    }
+}
@@ -0,0 +1,266 @@
+use gdk::SELECTION_CLIPBOARD;
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction};
+use gtk::Clipboard;
+use std::cell::{Ref, RefCell};
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
+use crate::app::models::*;
+use crate::app::state::{
+    BrowserAction, BrowserEvent, HomeState, PlaybackAction, PlaylistSource, SelectionAction,
+    SelectionState,
+};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+pub struct SavedTracksModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    search: RefCell<String>,
+}
+
+impl SavedTracksModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            search: RefCell::new(String::new()),
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, HomeState>> {
+        self.app_model.map_state_opt(|s| s.browser.home_state())
+    }
+
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        self.state()?
+            .saved_tracks
+            .iter()
+            .find(|song| song.id == id)
+            .cloned()
+    }
+
+    // Saved tracks in the order they are displayed
+    fn sorted_songs(&self) -> Vec<SongDescription> {
+        match self.state() {
+            Some(state) => {
+                let mut songs = state.saved_tracks.iter().collect::<Vec<&SongDescription>>();
+                state.saved_tracks_sort.sort(&mut songs);
+                songs.into_iter().cloned().collect()
+            }
+            None => vec![],
+        }
+    }
+
+    pub fn set_sort(&self, sort: SongSort) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetSavedTracksSort(sort).into());
+    }
+
+    pub fn set_search(&self, search: &str) {
+        self.search.replace(search.trim().to_lowercase());
+    }
+
+    pub fn refresh_saved_tracks(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let batch_size = self.state()?.next_saved_tracks_page.batch_size;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_saved_tracks(0, batch_size).await {
+                Ok(tracks) => Some(BrowserAction::SetSavedTracks(tracks).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    pub fn load_more_tracks(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+
+        let next_page = &self.state()?.next_saved_tracks_page;
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_saved_tracks(offset, batch_size).await {
+                Ok(tracks) => Some(BrowserAction::AppendSavedTracks(offset, tracks).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    fn unlike(dispatcher: &dyn ActionDispatcher, app_model: &AppModel, id: &str) {
+        let api = app_model.get_spotify();
+        let id = id.to_string();
+        dispatcher.dispatch(BrowserAction::RemoveSavedTrack(id.clone()).into());
+        dispatcher.dispatch_async(Box::pin(async move {
+            match api.remove_saved_track(&id).await {
+                Ok(_) => None,
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+}
+
+impl PlaylistModel for SavedTracksModel {
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id.clone()
+    }
+
+    fn songs(&self) -> Vec<SongModel> {
+        self.sorted_songs()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.to_song_model(i))
+            .collect()
+    }
+
+    // Songs are played in the order they are displayed, so the playlist is
+    // loaded again whenever it is sorted
+    fn play_song(&self, id: &str) {
+        let source = PlaylistSource::SavedTracks;
+        let is_sorted = self
+            .state()
+            .map(|s| s.saved_tracks_sort != SongSort::Default)
+            .unwrap_or(false);
+        if !self.app_model.get_state().playback.plays_as_loaded(&source) || is_sorted {
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadPlaylist(source, self.sorted_songs()).into());
+        }
+        self.dispatcher
+            .dispatch(PlaybackAction::Load(id.to_string()).into());
+    }
+
+    // Pages are appended as they are, as long as the songs are shown in the default order
+    fn appended_songs(&self, event: &AppEvent) -> Option<Vec<SongModel>> {
+        let position = match event {
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksAppended(position)) => *position,
+            _ => return None,
+        };
+        let state = self.state()?;
+        if state.saved_tracks_sort != SongSort::Default {
+            return None;
+        }
+        Some(
+            state
+                .saved_tracks
+                .iter()
+                .enumerate()
+                .skip(position)
+                .map(|(i, s)| s.to_song_model(i))
+                .collect(),
+        )
+    }
+
+    fn should_refresh_songs(&self, event: &AppEvent) -> bool {
+        matches!(
+            event,
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated)
+                | AppEvent::BrowserEvent(BrowserEvent::SavedTracksAppended(_))
+        )
+    }
+
+    fn is_song_visible(&self, id: &str) -> bool {
+        let search = self.search.borrow();
+        if search.is_empty() {
+            return true;
+        }
+        self.song(id)
+            .map(|song| {
+                song.title.to_lowercase().contains(&*search)
+                    || song.artists_name().to_lowercase().contains(&*search)
+                    || song.album.name.to_lowercase().contains(&*search)
+            })
+            .unwrap_or(true)
+    }
+
+    fn can_remove_song(&self, _id: &str) -> bool {
+        true
+    }
+
+    fn remove_song(&self, id: &str) {
+        Self::unlike(&*self.dispatcher, &self.app_model, id);
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song(id)?;
+        let group = song_actions(self.dispatcher.as_ref(), song);
+
+        let album_id = song.album.id.clone();
+        let view_album = SimpleAction::new("view_album", None);
+        let dispatcher = self.dispatcher.box_clone();
+        view_album.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+        });
+
+        group.add_action(&view_album);
+
+        for (i, artist) in song.artists.iter().enumerate() {
+            let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
+            let dispatcher = self.dispatcher.box_clone();
+            let id = artist.id.clone();
+            view_artist.connect_activate(move |_, _| {
+                dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+            });
+            group.add_action(&view_artist);
+        }
+
+        let track_id = song.id.clone();
+        let copy_link = SimpleAction::new("copy_link", None);
+        copy_link.connect_activate(move |_, _| {
+            let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+            clipboard.set_text(&format!("https://open.spotify.com/track/{}", &track_id));
+        });
+        group.add_action(&copy_link);
+
+        let unlike = SimpleAction::new("unlike", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        let track_id = song.id.clone();
+        unlike.connect_activate(move |_, _| {
+            Self::unlike(&*dispatcher, &app_model, &track_id);
+        });
+        group.add_action(&unlike);
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song(id)?;
+
+        let menu = gio::Menu::new();
+        menu.append(Some("View album"), Some("song.view_album"));
+        for (i, artist) in song.artists.iter().enumerate() {
+            menu.append(
+                Some(&format!("More from {}", artist.name)),
+                Some(&format!("song.view_artist_{}", i)),
+            );
+        }
+
+        menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+        menu.append(Some("Remove from Liked songs"), Some("song.unlike"));
+
+        Some(menu.upcast())
+    }
+
+    fn select_song(&self, id: &str) {
+        if let Some(song) = self.song(id) {
+            self.dispatcher
+                .dispatch(SelectionAction::Select(song).into());
+        }
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(SelectionAction::Deselect(id.to_string()).into());
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+    }
+}
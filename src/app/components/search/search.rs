@@ -1,28 +1,82 @@
 use gio::prelude::*;
 use gladis::Gladis;
 use gtk::prelude::*;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 use crate::app::components::utils::{wrap_flowbox_item, Debouncer};
-use crate::app::components::{Album, Artist, Component, EventListener};
+use crate::app::components::{
+    Album, AlbumMenu, Artist, Component, Episode, EventListener, Playlist, ShowMenu,
+};
 use crate::app::dispatch::Worker;
-use crate::app::models::{AlbumModel, ArtistModel};
+use crate::app::models::{AlbumModel, ArtistModel, EpisodeModel, SearchFilter};
 use crate::app::state::{AppEvent, BrowserEvent};
 
 use super::SearchResultsModel;
 
 #[derive(Gladis, Clone)]
 struct SearchResultsWidget {
-    search_root: gtk::Widget,
+    search_root: gtk::ScrolledWindow,
     results_label: gtk::Label,
+    filter_all: gtk::RadioButton,
+    filter_songs: gtk::RadioButton,
+    filter_albums: gtk::RadioButton,
+    filter_artists: gtk::RadioButton,
+    filter_playlists: gtk::RadioButton,
+    filter_podcasts: gtk::RadioButton,
+    filter_episodes: gtk::RadioButton,
+    songs_section: gtk::Expander,
+    song_results: gtk::ListBox,
+    albums_section: gtk::Expander,
     albums_results: gtk::FlowBox,
+    artists_section: gtk::Expander,
     artist_results: gtk::FlowBox,
+    playlists_section: gtk::Expander,
+    playlist_results: gtk::FlowBox,
+    podcasts_section: gtk::Expander,
+    podcast_results: gtk::FlowBox,
+    episodes_section: gtk::Expander,
+    episode_results: gtk::ListBox,
+    empty_label: gtk::Label,
 }
 
 impl SearchResultsWidget {
     fn new() -> Self {
         Self::from_resource(resource!("/components/search.ui")).unwrap()
     }
+
+    fn filters(&self) -> [(&gtk::RadioButton, SearchFilter); 7] {
+        [
+            (&self.filter_all, SearchFilter::All),
+            (&self.filter_songs, SearchFilter::Songs),
+            (&self.filter_albums, SearchFilter::Albums),
+            (&self.filter_artists, SearchFilter::Artists),
+            (&self.filter_playlists, SearchFilter::Playlists),
+            (&self.filter_podcasts, SearchFilter::Podcasts),
+            (&self.filter_episodes, SearchFilter::Episodes),
+        ]
+    }
+}
+
+// Cards opening their own page, with no menu
+fn bind_cards(
+    flowbox: &gtk::FlowBox,
+    store: &gio::ListStore,
+    worker: Worker,
+    model: Weak<SearchResultsModel>,
+    open: fn(&SearchResultsModel, &str),
+) {
+    flowbox.bind_model(Some(store), move |item| {
+        wrap_flowbox_item(item, |item: &AlbumModel| {
+            let album = Album::new(item, worker.clone());
+            let weak = model.clone();
+            album.connect_album_pressed(move |a| {
+                if let (Some(id), Some(m)) = (a.uri().as_ref(), weak.upgrade()) {
+                    open(&m, id);
+                }
+            });
+            album.get_root_widget().clone()
+        })
+    });
 }
 
 pub struct SearchResults {
@@ -30,16 +84,44 @@ pub struct SearchResults {
     model: Rc<SearchResultsModel>,
     album_results_model: gio::ListStore,
     artist_results_model: gio::ListStore,
+    playlist_results_model: gio::ListStore,
+    podcast_results_model: gio::ListStore,
     debouncer: Debouncer,
+    children: Vec<Box<dyn EventListener>>,
 }
 
 impl SearchResults {
-    pub fn new(model: SearchResultsModel, worker: Worker) -> Self {
+    pub fn new(
+        model: SearchResultsModel,
+        worker: Worker,
+        album_menu: Rc<AlbumMenu>,
+        show_menu: Rc<ShowMenu>,
+    ) -> Self {
         let model = Rc::new(model);
         let widget = SearchResultsWidget::new();
 
         let album_results_model = gio::ListStore::new(AlbumModel::static_type());
         let artist_results_model = gio::ListStore::new(ArtistModel::static_type());
+        let playlist_results_model = gio::ListStore::new(AlbumModel::static_type());
+        let podcast_results_model = gio::ListStore::new(AlbumModel::static_type());
+
+        for (button, filter) in widget.filters().iter() {
+            let filter = *filter;
+            let weak_model = Rc::downgrade(&model);
+            button.connect_toggled(move |button| {
+                if let (true, Some(model)) = (button.get_active(), weak_model.upgrade()) {
+                    model.set_filter(filter);
+                }
+            });
+        }
+
+        // Only the filtered type of results is paged
+        let weak_model = Rc::downgrade(&model);
+        widget.search_root.connect_edge_reached(move |_, pos| {
+            if let (gtk::PositionType::Bottom, Some(model)) = (pos, weak_model.upgrade()) {
+                let _ = model.load_more();
+            }
+        });
 
         let model_clone = Rc::downgrade(&model);
         let worker_clone = worker.clone();
@@ -54,16 +136,19 @@ impl SearchResults {
                             m.open_album(id);
                         }
                     });
+                    let album_menu = Rc::clone(&album_menu);
+                    album.connect_menu_requested(move |a, widget| album_menu.popup(a, widget));
                     album.get_root_widget().clone()
                 })
             });
 
         let model_clone = Rc::downgrade(&model);
+        let worker_clone = worker.clone();
         widget
             .artist_results
             .bind_model(Some(&artist_results_model), move |item| {
                 wrap_flowbox_item(item, |item: &ArtistModel| {
-                    let artist = Artist::new(item, worker.clone());
+                    let artist = Artist::new(item, worker_clone.clone());
                     let weak = model_clone.clone();
                     artist.connect_artist_pressed(move |a| {
                         if let (Some(id), Some(m)) = (a.id().as_ref(), weak.upgrade()) {
@@ -74,19 +159,56 @@ impl SearchResults {
                 })
             });
 
+        bind_cards(
+            &widget.playlist_results,
+            &playlist_results_model,
+            worker.clone(),
+            Rc::downgrade(&model),
+            SearchResultsModel::open_playlist,
+        );
+
+        // Shows can be followed right from the results
+        let model_clone = Rc::downgrade(&model);
+        widget
+            .podcast_results
+            .bind_model(Some(&podcast_results_model), move |item| {
+                wrap_flowbox_item(item, |item: &AlbumModel| {
+                    let show = Album::new(item, worker.clone());
+                    let weak = model_clone.clone();
+                    show.connect_album_pressed(move |a| {
+                        if let (Some(id), Some(m)) = (a.uri().as_ref(), weak.upgrade()) {
+                            m.open_show(id);
+                        }
+                    });
+                    let show_menu = Rc::clone(&show_menu);
+                    show.connect_menu_requested(move |a, widget| show_menu.popup(a, widget));
+                    show.get_root_widget().clone()
+                })
+            });
+
+        widget
+            .episode_results
+            .set_selection_mode(gtk::SelectionMode::None);
+
+        let songs = Playlist::new(widget.song_results.clone(), model.clone());
+
         Self {
             widget,
             model,
             album_results_model,
             artist_results_model,
+            playlist_results_model,
+            podcast_results_model,
             debouncer: Debouncer::new(),
+            children: vec![Box::new(songs)],
         }
     }
 
     fn update_results(&self) {
+        let count = self.model.shown_count();
         if let Some(results) = self.model.get_album_results() {
             self.album_results_model.remove_all();
-            for album in results.iter() {
+            for album in results.iter().take(count) {
                 self.album_results_model.append(&AlbumModel::new(
                     &album.artists_name(),
                     &album.title,
@@ -94,17 +216,76 @@ impl SearchResults {
                     &album.id,
                 ));
             }
+            let visible = self.model.shows(SearchFilter::Albums) && !results.is_empty();
+            self.widget.albums_section.set_visible(visible);
         }
         if let Some(results) = self.model.get_artist_results() {
             self.artist_results_model.remove_all();
-            for artist in results.iter() {
+            for artist in results.iter().take(count) {
                 self.artist_results_model.append(&ArtistModel::new(
                     &artist.name,
                     &artist.photo,
                     &artist.id,
                 ));
             }
+            let visible = self.model.shows(SearchFilter::Artists) && !results.is_empty();
+            self.widget.artists_section.set_visible(visible);
+        }
+        if let Some(results) = self.model.get_playlist_results() {
+            self.playlist_results_model.remove_all();
+            for playlist in results.iter().take(count) {
+                self.playlist_results_model
+                    .append(&AlbumModel::from(playlist));
+            }
+            let visible = self.model.shows(SearchFilter::Playlists) && !results.is_empty();
+            self.widget.playlists_section.set_visible(visible);
         }
+        if let Some(results) = self.model.get_podcast_results() {
+            self.podcast_results_model.remove_all();
+            for podcast in results.iter().take(count) {
+                self.podcast_results_model.append(&AlbumModel::new(
+                    &podcast.publisher,
+                    &podcast.name,
+                    &podcast.art,
+                    &podcast.id,
+                ));
+            }
+            let visible = self.model.shows(SearchFilter::Podcasts) && !results.is_empty();
+            self.widget.podcasts_section.set_visible(visible);
+        }
+        if let Some(results) = self.model.get_episode_results() {
+            let listbox = &self.widget.episode_results;
+            listbox.foreach(|row| listbox.remove(row));
+            for episode in results.iter().take(count) {
+                let episode_model = EpisodeModel::from(episode);
+                if let Some(played) = self.model.played_mark(&episode.id) {
+                    episode_model.set_played(played);
+                }
+                let weak_model = Rc::downgrade(&self.model);
+                let episode = Episode::new(&episode_model, move |id, played| {
+                    if let Some(model) = weak_model.upgrade() {
+                        model.mark_played(id, played);
+                    }
+                });
+                listbox.add(episode.get_root_widget());
+            }
+            let visible = self.model.shows(SearchFilter::Episodes) && !results.is_empty();
+            self.widget.episodes_section.set_visible(visible);
+        }
+        self.widget
+            .songs_section
+            .set_visible(self.model.has_song_results());
+
+        let sections = [
+            &self.widget.songs_section,
+            &self.widget.albums_section,
+            &self.widget.artists_section,
+            &self.widget.playlists_section,
+            &self.widget.podcasts_section,
+            &self.widget.episodes_section,
+        ];
+        let empty = !sections.iter().any(|section| section.get_visible());
+        self.widget.empty_label.set_visible(empty);
     }
 
     fn update_search_query(&self) {
@@ -126,7 +307,11 @@ impl SearchResults {
 
 impl Component for SearchResults {
     fn get_root_widget(&self) -> &gtk::Widget {
-        &self.widget.search_root
+        self.widget.search_root.upcast_ref()
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
     }
 }
 
@@ -136,10 +321,12 @@ impl EventListener for SearchResults {
             AppEvent::BrowserEvent(BrowserEvent::SearchUpdated) => {
                 self.update_search_query();
             }
-            AppEvent::BrowserEvent(BrowserEvent::SearchResultsUpdated) => {
+            AppEvent::BrowserEvent(BrowserEvent::SearchResultsUpdated)
+            | AppEvent::BrowserEvent(BrowserEvent::SearchFilterChanged) => {
                 self.update_results();
             }
             _ => {}
         }
+        self.broadcast_event(app_event);
     }
 }
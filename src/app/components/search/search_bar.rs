@@ -5,8 +5,51 @@ use std::rc::Rc;
 
 use super::SearchBarModel;
 use crate::app::components::EventListener;
+use crate::app::{AppEvent, BrowserEvent};
 
-pub struct SearchBar;
+// Recent searches and completions, under the search entry
+#[derive(Clone)]
+pub struct SearchSuggestions {
+    pub popover: gtk::Popover,
+    pub list: gtk::ListBox,
+    pub clear_history: gtk::Button,
+}
+
+impl SearchSuggestions {
+    // Nothing in the popover can take the focus, so that typing goes on in the entry
+    fn update(&self, model: &SearchBarModel, query: &str) {
+        self.list.foreach(|row| self.list.remove(row));
+
+        let suggestions = model.suggestions(query);
+        for suggestion in suggestions.iter() {
+            let label = gtk::Label::new(Some(suggestion));
+            label.set_xalign(0.0);
+            label.set_margin_start(6);
+            label.set_margin_end(6);
+            label.set_margin_top(6);
+            label.set_margin_bottom(6);
+
+            let row = gtk::ListBoxRow::new();
+            row.set_can_focus(false);
+            row.add(&label);
+            row.show_all();
+            self.list.add(&row);
+        }
+
+        self.clear_history.set_visible(model.has_history());
+        if suggestions.is_empty() {
+            self.popover.popdown();
+        } else {
+            self.popover.popup();
+        }
+    }
+}
+
+pub struct SearchBar {
+    model: Rc<SearchBarModel>,
+    search_entry: gtk::SearchEntry,
+    suggestions: SearchSuggestions,
+}
 
 impl SearchBar {
     pub fn new(
@@ -14,26 +57,70 @@ impl SearchBar {
         search_button: gtk::ToggleButton,
         search_bar: libhandy::SearchBar,
         search_entry: gtk::SearchEntry,
+        suggestions: SearchSuggestions,
     ) -> Self {
         let model = Rc::new(model);
 
         {
             let model = model.clone();
+            let suggestions = suggestions.clone();
             search_entry.connect_changed(move |s| {
                 let query = s.get_text().as_str().to_string();
+                if s.has_focus() {
+                    suggestions.update(&model, &query);
+                }
                 if !query.is_empty() {
                     model.search(query);
                 }
             });
         }
 
-        search_entry.connect_focus_in_event(move |s, _| {
-            let query = s.get_text().as_str().to_string();
-            if !query.is_empty() {
-                model.search(query);
-            }
-            Inhibit(false)
-        });
+        {
+            let model = model.clone();
+            let suggestions = suggestions.clone();
+            search_entry.connect_focus_in_event(move |s, _| {
+                let query = s.get_text().as_str().to_string();
+                suggestions.update(&model, &query);
+                if !query.is_empty() {
+                    model.search(query);
+                }
+                Inhibit(false)
+            });
+        }
+
+        {
+            let popover = suggestions.popover.clone();
+            search_entry.connect_focus_out_event(move |_, _| {
+                popover.popdown();
+                Inhibit(false)
+            });
+        }
+
+        search_entry.connect_activate(clone!(@weak suggestions.popover as popover => move |_| {
+            popover.popdown();
+        }));
+
+        suggestions.list.connect_row_activated(
+            clone!(@weak search_entry, @weak suggestions.popover as popover => move |_, row| {
+                let label = row.get_child().and_then(|w| w.downcast::<gtk::Label>().ok());
+                if let Some(label) = label {
+                    search_entry.set_text(label.get_text().as_str());
+                    search_entry.set_position(-1);
+                }
+                popover.popdown();
+            }),
+        );
+
+        {
+            let model = model.clone();
+            let suggestions_clone = suggestions.clone();
+            suggestions
+                .clear_history
+                .connect_clicked(clone!(@weak search_entry => move |_| {
+                    model.clear_history();
+                    suggestions_clone.update(&model, search_entry.get_text().as_str());
+                }));
+        }
 
         search_button.connect_clicked(clone!(@weak search_bar => move |b| {
             search_bar.set_search_mode(b.get_active());
@@ -50,8 +137,22 @@ impl SearchBar {
 
         search_bar.connect_entry(&search_entry);
 
-        Self
+        Self {
+            model,
+            search_entry,
+            suggestions,
+        }
     }
 }
 
-impl EventListener for SearchBar {}
+impl EventListener for SearchBar {
+    fn on_event(&mut self, event: &AppEvent) {
+        // Completions come from the results, which arrive after typing
+        if let AppEvent::BrowserEvent(BrowserEvent::SearchResultsUpdated) = event {
+            if self.search_entry.has_focus() {
+                let query = self.search_entry.get_text();
+                self.suggestions.update(&self.model, query.as_str());
+            }
+        }
+    }
+}
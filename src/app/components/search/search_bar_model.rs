@@ -1,9 +1,75 @@
-use crate::app::{ActionDispatcher, BrowserAction};
+use std::rc::Rc;
 
-pub struct SearchBarModel(pub Box<dyn ActionDispatcher>);
+use crate::app::settings::SpotSettings;
+use crate::app::{ActionDispatcher, AppModel, BrowserAction};
+
+// Suggestions listed under the search entry, at most
+const SUGGESTIONS_LIMIT: usize = 8;
+
+pub struct SearchBarModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
 
 impl SearchBarModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
     pub fn search(&self, query: String) {
-        self.0.dispatch(BrowserAction::Search(query).into());
+        self.dispatcher
+            .dispatch(BrowserAction::Search(query).into());
+    }
+
+    // Names found in the current results complete the query, once something was typed
+    fn completions(&self, query: &str) -> Vec<String> {
+        if query.is_empty() {
+            return vec![];
+        }
+        let state = self.app_model.get_state();
+        let search = match state.browser.search_state() {
+            Some(search) => search,
+            None => return vec![],
+        };
+        search
+            .artist_results
+            .iter()
+            .map(|artist| artist.name.clone())
+            .chain(search.album_results.iter().map(|album| album.title.clone()))
+            .chain(search.song_results.iter().map(|song| song.title.clone()))
+            .chain(search.playlist_results.iter().map(|p| p.title.clone()))
+            .collect()
+    }
+
+    // Recent searches first, then completions, all starting with what was typed
+    pub fn suggestions(&self, query: &str) -> Vec<String> {
+        let query = query.trim().to_lowercase();
+        let candidates = SpotSettings::search_history()
+            .into_iter()
+            .chain(self.completions(&query));
+
+        let mut suggestions: Vec<String> = vec![];
+        for candidate in candidates {
+            let lowercase = candidate.to_lowercase();
+            let is_new = !suggestions.iter().any(|s| s.to_lowercase() == lowercase);
+            if is_new && lowercase != query && lowercase.starts_with(&query) {
+                suggestions.push(candidate);
+            }
+            if suggestions.len() == SUGGESTIONS_LIMIT {
+                break;
+            }
+        }
+        suggestions
+    }
+
+    pub fn has_history(&self) -> bool {
+        !SpotSettings::search_history().is_empty()
+    }
+
+    pub fn clear_history(&self) {
+        SpotSettings::clear_search_history();
     }
 }
@@ -1,10 +1,21 @@
+use gdk::SELECTION_CLIPBOARD;
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction};
+use gtk::Clipboard;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use crate::app::components::handle_error;
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
 use crate::app::dispatch::ActionDispatcher;
 use crate::app::models::*;
-use crate::app::state::{AppAction, AppModel, BrowserAction};
+use crate::app::settings::SpotSettings;
+use crate::app::state::{
+    AppAction, AppEvent, AppModel, BrowserAction, BrowserEvent, PlaybackAction, PlaylistSource,
+    SelectionAction, SelectionState,
+};
+
+// Results of each type shown when not filtering
+const PREVIEW_SIZE: usize = 5;
 
 pub struct SearchResultsModel {
     app_model: Rc<AppModel>,
@@ -28,15 +39,74 @@ impl SearchResultsModel {
         let api = self.app_model.get_spotify();
         if let Some(query) = self.get_query() {
             let query = query.to_owned();
+            SpotSettings::save_search(&query);
+            // The first page of every type comes at once
+            let batch_size = self.page_size();
             self.dispatcher.dispatch_async(Box::pin(async move {
-                match api.search(&query[..], 0, 5).await {
-                    Ok(albums) => Some(BrowserAction::SetSearchResults(albums).into()),
+                match api
+                    .search(&query[..], SearchFilter::All, 0, batch_size)
+                    .await
+                {
+                    Ok(results) => Some(BrowserAction::SetSearchResults(results).into()),
                     Err(err) => handle_error(err),
                 }
             }))
         }
     }
 
+    fn page_size(&self) -> u32 {
+        self.app_model
+            .map_state_opt(|s| s.browser.search_state()?.next_pages.first())
+            .map(|page| page.batch_size)
+            .unwrap_or(20)
+    }
+
+    pub fn load_more(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let filter = self.get_filter();
+        let query = self.get_query()?.to_owned();
+
+        let state = self.app_model.map_state_opt(|s| s.browser.search_state())?;
+        let next_page = state.next_page(filter)?;
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.search(&query[..], filter, offset, batch_size).await {
+                Ok(results) => Some(BrowserAction::AppendSearchResults(filter, results).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    pub fn get_filter(&self) -> SearchFilter {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.filter))
+            .map(|filter| *filter)
+            .unwrap_or(SearchFilter::All)
+    }
+
+    pub fn set_filter(&self, filter: SearchFilter) {
+        self.dispatcher
+            .dispatch(BrowserAction::SetSearchFilter(filter).into());
+    }
+
+    // Whether results of that type are shown at all with the current filter
+    pub fn shows(&self, filter: SearchFilter) -> bool {
+        let current = self.get_filter();
+        current == SearchFilter::All || current == filter
+    }
+
+    // Every loaded result once filtered, only the first ones otherwise
+    pub fn shown_count(&self) -> usize {
+        match self.get_filter() {
+            SearchFilter::All => PREVIEW_SIZE,
+            _ => usize::MAX,
+        }
+    }
+
     pub fn get_album_results(&self) -> Option<impl Deref<Target = Vec<AlbumDescription>> + '_> {
         self.app_model
             .map_state_opt(|s| Some(&s.browser.search_state()?.album_results))
@@ -47,6 +117,43 @@ impl SearchResultsModel {
             .map_state_opt(|s| Some(&s.browser.search_state()?.artist_results))
     }
 
+    fn get_song_results(&self) -> Option<impl Deref<Target = Vec<SongDescription>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.song_results))
+    }
+
+    pub fn get_playlist_results(
+        &self,
+    ) -> Option<impl Deref<Target = Vec<PlaylistDescription>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.playlist_results))
+    }
+
+    pub fn get_podcast_results(&self) -> Option<impl Deref<Target = Vec<PodcastDescription>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.podcast_results))
+    }
+
+    pub fn get_episode_results(&self) -> Option<impl Deref<Target = Vec<EpisodeDescription>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.search_state()?.episode_results))
+    }
+
+    pub fn has_song_results(&self) -> bool {
+        self.shows(SearchFilter::Songs)
+            && self
+                .get_song_results()
+                .map(|songs| !songs.is_empty())
+                .unwrap_or(false)
+    }
+
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        self.get_song_results()?
+            .iter()
+            .find(|song| song.id == id)
+            .cloned()
+    }
+
     pub fn open_album(&self, id: &str) {
         self.dispatcher
             .dispatch(AppAction::ViewAlbum(id.to_string()));
@@ -56,4 +163,125 @@ impl SearchResultsModel {
         self.dispatcher
             .dispatch(AppAction::ViewArtist(id.to_string()));
     }
+
+    pub fn open_playlist(&self, id: &str) {
+        self.dispatcher
+            .dispatch(AppAction::ViewPlaylist(id.to_string()));
+    }
+
+    pub fn open_show(&self, id: &str) {
+        self.dispatcher
+            .dispatch(AppAction::ViewShow(id.to_string()));
+    }
+
+    pub fn played_mark(&self, episode_id: &str) -> Option<bool> {
+        self.app_model.get_state().settings.played_mark(episode_id)
+    }
+
+    pub fn mark_played(&self, episode_id: &str, played: bool) {
+        SpotSettings::save_episode_played(episode_id, played);
+    }
+}
+
+impl PlaylistModel for SearchResultsModel {
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id.clone()
+    }
+
+    fn songs(&self) -> Vec<SongModel> {
+        match self.get_song_results() {
+            Some(songs) => songs
+                .iter()
+                .take(self.shown_count())
+                .enumerate()
+                .map(|(i, s)| s.to_song_model(i))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    fn play_song(&self, id: &str) {
+        let songs = self.get_song_results().map(|songs| songs.clone());
+        if let Some(songs) = songs {
+            self.dispatcher
+                .dispatch(PlaybackAction::LoadPlaylist(PlaylistSource::None, songs).into());
+            self.dispatcher
+                .dispatch(PlaybackAction::Load(id.to_string()).into());
+        }
+    }
+
+    fn should_refresh_songs(&self, event: &AppEvent) -> bool {
+        matches!(
+            event,
+            AppEvent::BrowserEvent(BrowserEvent::SearchResultsUpdated)
+                | AppEvent::BrowserEvent(BrowserEvent::SearchFilterChanged)
+        )
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song(id)?;
+        let group = song_actions(self.dispatcher.as_ref(), song);
+
+        let album_id = song.album.id.clone();
+        let view_album = SimpleAction::new("view_album", None);
+        let dispatcher = self.dispatcher.box_clone();
+        view_album.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+        });
+        group.add_action(&view_album);
+
+        for (i, artist) in song.artists.iter().enumerate() {
+            let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
+            let dispatcher = self.dispatcher.box_clone();
+            let id = artist.id.clone();
+            view_artist.connect_activate(move |_, _| {
+                dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+            });
+            group.add_action(&view_artist);
+        }
+
+        let track_id = song.id.clone();
+        let copy_link = SimpleAction::new("copy_link", None);
+        copy_link.connect_activate(move |_, _| {
+            let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+            clipboard.set_text(&format!("https://open.spotify.com/track/{}", &track_id));
+        });
+        group.add_action(&copy_link);
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song(id)?;
+
+        let menu = gio::Menu::new();
+        menu.append(Some("View album"), Some("song.view_album"));
+        for (i, artist) in song.artists.iter().enumerate() {
+            menu.append(
+                Some(&format!("More from {}", artist.name)),
+                Some(&format!("song.view_artist_{}", i)),
+            );
+        }
+
+        menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+
+        Some(menu.upcast())
+    }
+
+    fn select_song(&self, id: &str) {
+        if let Some(song) = self.song(id) {
+            self.dispatcher
+                .dispatch(SelectionAction::Select(song).into());
+        }
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(SelectionAction::Deselect(id.to_string()).into());
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+    }
 }
@@ -1,10 +1,14 @@
 use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use glib::StaticVariantType;
 use gtk::prelude::*;
 use gtk::ButtonExt;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::app::components::EventListener;
-use crate::app::state::{SelectionAction, SelectionEvent};
+use crate::api::SpotifyApiClient;
+use crate::app::components::{handle_error, EventListener, PlaylistDetailsModel};
+use crate::app::models::PlaylistDescription;
+use crate::app::state::{BrowserAction, SelectionAction, SelectionEvent};
 use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
 
 pub struct SelectionEditorModel {
@@ -33,7 +37,167 @@ impl SelectionEditorModel {
         self.app_model.get_state().selection_is_from_queue()
     }
 
-    fn make_actions(&self) -> SimpleActionGroup {
+    fn owned_playlist(&self) -> Option<String> {
+        self.app_model.get_state().selection_owned_playlist()
+    }
+
+    fn remove_selection_from_playlist(dispatcher: &dyn ActionDispatcher, app_model: &AppModel) {
+        let selection = {
+            let state = app_model.get_state();
+            state.selection_owned_playlist().map(|id| {
+                let ids = state
+                    .selection
+                    .peek_selection()
+                    .iter()
+                    .map(|s| s.id.clone())
+                    .collect::<Vec<String>>();
+                (id, ids)
+            })
+        };
+        if let Some((id, ids)) = selection {
+            PlaylistDetailsModel::remove_songs(dispatcher, app_model, &id, ids);
+            dispatcher.dispatch(SelectionAction::ChangeSelectionMode(false).into());
+        }
+    }
+
+    // The ids and titles of saved playlists, as far as they were loaded
+    fn saved_playlists(&self) -> Vec<(String, String)> {
+        self.app_model
+            .map_state_opt(|s| s.browser.home_state())
+            .map(|home| {
+                home.playlists
+                    .iter()
+                    .filter_map(|p| Some((p.uri()?, p.title()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // The playlist's songs are checked first, songs already in it are only added again if confirmed
+    fn add_selection_to_playlist(
+        dispatcher: &dyn ActionDispatcher,
+        app_model: &AppModel,
+        id: String,
+        parent: Option<gtk::Window>,
+    ) {
+        let (user, ids) = {
+            let state = app_model.get_state();
+            let ids = state
+                .selection
+                .peek_selection()
+                .iter()
+                .map(|s| s.id.clone())
+                .collect::<Vec<String>>();
+            (state.user.clone(), ids)
+        };
+        dispatcher.dispatch(SelectionAction::ChangeSelectionMode(false).into());
+
+        let api = app_model.get_spotify();
+        let owned_dispatcher = dispatcher.box_clone();
+        dispatcher.dispatch_local_async(Box::pin(async move {
+            let playlist = match api.get_playlist(&id).await {
+                Ok(playlist) => playlist,
+                Err(err) => return handle_error(err),
+            };
+            if !playlist.collaborative && user.as_ref() != Some(&playlist.owner.id) {
+                return Some(AppAction::ShowNotification(format!(
+                    "Songs can't be added to {}",
+                    playlist.title
+                )));
+            }
+
+            let new = ids
+                .iter()
+                .filter(|id| !playlist.songs.iter().any(|s| &s.id == *id))
+                .cloned()
+                .collect::<Vec<String>>();
+            if new.len() == ids.len() {
+                Self::add_songs(owned_dispatcher, api, playlist, ids);
+            } else {
+                Self::confirm_duplicates(parent, owned_dispatcher, api, playlist, ids, new);
+            }
+            None
+        }));
+    }
+
+    fn confirm_duplicates(
+        parent: Option<gtk::Window>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        playlist: PlaylistDescription,
+        ids: Vec<String>,
+        new: Vec<String>,
+    ) {
+        let duplicates = ids.len() - new.len();
+        let message = if ids.len() == 1 {
+            format!("This song is already in {}", playlist.title)
+        } else {
+            format!(
+                "{} of these songs are already in {}",
+                duplicates, playlist.title
+            )
+        };
+        let dialog = gtk::MessageDialog::new(
+            parent.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            gtk::MessageType::Question,
+            gtk::ButtonsType::None,
+            &message,
+        );
+        dialog.add_button("Cancel", gtk::ResponseType::Cancel);
+        if !new.is_empty() {
+            dialog.add_button("Skip duplicates", gtk::ResponseType::Reject);
+        }
+        dialog.add_button("Add anyway", gtk::ResponseType::Accept);
+        dialog.set_default_response(gtk::ResponseType::Accept);
+
+        dialog.connect_response(move |dialog, response| {
+            let songs = match response {
+                gtk::ResponseType::Accept => Some(ids.clone()),
+                gtk::ResponseType::Reject => Some(new.clone()),
+                _ => None,
+            };
+            if let Some(songs) = songs {
+                Self::add_songs(
+                    dispatcher.box_clone(),
+                    Arc::clone(&api),
+                    playlist.clone(),
+                    songs,
+                );
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn add_songs(
+        dispatcher: Box<dyn ActionDispatcher>,
+        api: Arc<dyn SpotifyApiClient + Send + Sync>,
+        playlist: PlaylistDescription,
+        ids: Vec<String>,
+    ) {
+        if ids.is_empty() {
+            return;
+        }
+        let notifier = dispatcher.box_clone();
+        dispatcher.dispatch_local_async(Box::pin(async move {
+            let count = ids.len();
+            if let Err(err) = api.add_to_playlist(&playlist.id, ids).await {
+                return handle_error(err);
+            }
+            notifier.dispatch(AppAction::ShowNotification(format!(
+                "Added {} songs to {}",
+                count, playlist.title
+            )));
+            // In case the playlist is on screen
+            match api.refresh_playlist(&playlist.id).await {
+                Ok(playlist) => Some(BrowserAction::SetPlaylistDetails(playlist).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    fn make_actions(&self, button: &gtk::MenuButton) -> SimpleActionGroup {
         let group = SimpleActionGroup::new();
 
         let queue_selection = SimpleAction::new("queue", None);
@@ -50,6 +214,30 @@ impl SelectionEditorModel {
         });
         group.add_action(&dequeue_selection);
 
+        let remove_from_playlist = SimpleAction::new("remove_from_playlist", None);
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        remove_from_playlist.connect_activate(move |_, _| {
+            Self::remove_selection_from_playlist(&*dispatcher, &app_model);
+        });
+        group.add_action(&remove_from_playlist);
+
+        let add_to_playlist = SimpleAction::new(
+            "add_to_playlist",
+            Some(String::static_variant_type().as_ref()),
+        );
+        let dispatcher = self.dispatcher.box_clone();
+        let app_model = Rc::clone(&self.app_model);
+        add_to_playlist.connect_activate(clone!(@weak button => move |_, param| {
+            if let Some(id) = param.and_then(|p| p.get_str()) {
+                let parent = button
+                    .get_toplevel()
+                    .and_then(|w| w.downcast::<gtk::Window>().ok());
+                Self::add_selection_to_playlist(&*dispatcher, &app_model, id.to_string(), parent);
+            }
+        }));
+        group.add_action(&add_to_playlist);
+
         group
     }
 }
@@ -75,7 +263,8 @@ impl SelectionEditor {
             model.set_selection_mode(t.get_active());
         }));
 
-        selection_button.insert_action_group("selection", Some(&model.make_actions()));
+        selection_button
+            .insert_action_group("selection", Some(&model.make_actions(&selection_button)));
 
         Self {
             model,
@@ -122,9 +311,29 @@ impl SelectionEditor {
 
         let menu = gio::Menu::new();
         if self.model.all_selected_from_queue() {
-            menu.append(Some("Dequeue selected"), Some("selection.dequeue"));
+            menu.append(
+                Some("Remove selection from queue"),
+                Some("selection.dequeue"),
+            );
         } else {
-            menu.append(Some("Queue selected"), Some("selection.queue"));
+            menu.append(Some("Add selection to queue"), Some("selection.queue"));
+        }
+        if self.model.owned_playlist().is_some() {
+            menu.append(
+                Some("Remove selection from playlist"),
+                Some("selection.remove_from_playlist"),
+            );
+        }
+
+        let playlists = gio::Menu::new();
+        for (id, title) in self.model.saved_playlists() {
+            playlists.append(
+                Some(&title),
+                Some(&format!("selection.add_to_playlist::{}", id)),
+            );
+        }
+        if playlists.get_n_items() > 0 {
+            menu.append_submenu(Some("Add selection to playlist"), &playlists);
         }
         self.selection_button.set_menu_model(Some(&menu));
     }
@@ -0,0 +1,146 @@
+use gio::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::app::components::{handle_error, EventListener};
+use crate::app::settings::{SavedSession, SpotSettings};
+use crate::app::state::{PlaybackAction, PlaybackEvent, PlaylistSource};
+use crate::app::{ActionDispatcher, AppEvent, AppModel};
+
+// At most this many tracks around the current one are saved
+const MAX_SAVED_TRACKS: usize = 500;
+
+// Where playback is in the current track, as of the last playback event
+#[derive(Clone, Copy, Default)]
+struct Position {
+    at: u32,
+    // Set while playing
+    since: Option<Instant>,
+}
+
+impl Position {
+    fn current(self) -> u32 {
+        let elapsed = self.since.map(|since| since.elapsed().as_millis() as u32);
+        self.at + elapsed.unwrap_or(0)
+    }
+}
+
+// Saves the queue and what was playing on exit, and restores it on the next startup
+pub struct SessionKeeper {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    position: Rc<Cell<Position>>,
+    restored: bool,
+}
+
+impl SessionKeeper {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            position: Rc::new(Cell::new(Position::default())),
+            restored: false,
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.app_model.get_state().playback.is_playing()
+    }
+
+    fn session(app_model: &AppModel, position: u32) -> Option<SavedSession> {
+        let state = app_model.get_state();
+        let playback = &state.playback;
+        let track_id = playback.current_song_id.clone()?;
+        let index = playback
+            .songs()
+            .position(|song| song.id == track_id)
+            .unwrap_or(0);
+        let mut track_ids: Vec<String> = playback
+            .songs()
+            .skip(index.saturating_sub(MAX_SAVED_TRACKS / 2))
+            .take(MAX_SAVED_TRACKS)
+            .map(|song| song.id.clone())
+            .collect();
+        // e.g. when a pinned song was playing
+        if !track_ids.contains(&track_id) {
+            track_ids.insert(0, track_id.clone());
+        }
+        Some(SavedSession {
+            context_uri: playback.source.uri().unwrap_or_default(),
+            track_ids,
+            track_id,
+            position,
+        })
+    }
+
+    // Nothing is saved when nothing plays, so that an old session isn't restored
+    fn save_on_shutdown(&self) {
+        if let Some(app) = gio::Application::get_default() {
+            let app_model = Rc::clone(&self.app_model);
+            let position = Rc::clone(&self.position);
+            app.connect_shutdown(move |_| {
+                let session = Self::session(&app_model, position.get().current());
+                SpotSettings::save_session(&session.unwrap_or_default());
+                // The process exits right after, before settings would otherwise be written
+                gio::Settings::sync();
+            });
+        }
+    }
+
+    fn restore(&mut self) {
+        self.restored = true;
+        if !self.app_model.get_state().settings.restore_session {
+            return;
+        }
+        let session = match SpotSettings::saved_session() {
+            Some(session) => session,
+            None => return,
+        };
+
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_tracks(session.track_ids).await {
+                Ok(tracks) => Some(
+                    PlaybackAction::RestoreSession(
+                        PlaylistSource::from_uri(&session.context_uri),
+                        tracks,
+                        session.track_id,
+                        session.position,
+                    )
+                    .into(),
+                ),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    fn set_position(&self, at: u32) {
+        let since = if self.is_playing() {
+            Some(Instant::now())
+        } else {
+            None
+        };
+        self.position.set(Position { at, since });
+    }
+}
+
+impl EventListener for SessionKeeper {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started => self.save_on_shutdown(),
+            AppEvent::LoginCompleted(_) if !self.restored => self.restore(),
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_))
+            | AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => self.set_position(0),
+            AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(position))
+            | AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(position)) => {
+                self.set_position(*position)
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused)
+            | AppEvent::PlaybackEvent(PlaybackEvent::PlaybackResumed) => {
+                self.set_position(self.position.get().current())
+            }
+            _ => {}
+        }
+    }
+}
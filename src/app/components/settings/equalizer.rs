@@ -0,0 +1,130 @@
+use gio::SettingsExt;
+use gladis::Gladis;
+use gtk::prelude::*;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::app::components::utils::Debouncer;
+use crate::app::settings::{EqualizerPreset, SpotSettings};
+
+const BAND_LABELS: [&str; 10] = [
+    "31", "62", "125", "250", "500", "1k", "2k", "4k", "8k", "16k",
+];
+const MAX_GAIN_DB: f64 = 12.0;
+
+#[derive(Clone, Gladis)]
+struct EqualizerWidget {
+    root: gtk::Window,
+    preset: gtk::ComboBoxText,
+    bands: gtk::Box,
+}
+
+impl EqualizerWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/equalizer.ui")).unwrap()
+    }
+
+    fn make_band(adjustment: &gtk::Adjustment, label: &str) -> gtk::Box {
+        let band = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        let scale = gtk::Scale::new(gtk::Orientation::Vertical, Some(adjustment));
+        scale.set_inverted(true);
+        scale.set_draw_value(false);
+        scale.add_mark(0.0, gtk::PositionType::Left, None);
+        band.pack_start(&scale, true, true, 0);
+        band.pack_start(&gtk::Label::new(Some(label)), false, false, 0);
+        band
+    }
+}
+
+pub struct EqualizerWindow {
+    widget: EqualizerWidget,
+}
+
+impl EqualizerWindow {
+    pub fn new(parent: &gtk::Window, settings: &gio::Settings) -> Self {
+        let widget = EqualizerWidget::new();
+
+        widget.root.set_transient_for(Some(parent));
+        widget.root.connect_delete_event(|window, _| {
+            window.hide();
+            Inhibit(true)
+        });
+
+        let gains = SpotSettings::equalizer_bands(settings);
+        let adjustments = BAND_LABELS
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let gain = gains.get(i).copied().unwrap_or(0.0);
+                let adjustment =
+                    gtk::Adjustment::new(gain, -MAX_GAIN_DB, MAX_GAIN_DB, 1.0, 3.0, 0.0);
+                let band = EqualizerWidget::make_band(&adjustment, label);
+                widget.bands.pack_start(&band, true, true, 0);
+                adjustment
+            })
+            .collect::<Vec<gtk::Adjustment>>();
+        widget.bands.show_all();
+
+        settings.bind(
+            "equalizer-preset",
+            &widget.preset,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+
+        // Set while a preset is applied, so that moving the bands doesn't switch to custom
+        let applying_preset = Rc::new(Cell::new(false));
+        Self::connect_bands(&widget, &adjustments, &applying_preset);
+
+        widget.preset.connect_changed(move |combo| {
+            let gains = combo
+                .get_active_id()
+                .and_then(|id| EqualizerPreset::from_id(&id).gains());
+            if let Some(gains) = gains {
+                applying_preset.set(true);
+                for (adjustment, &gain) in adjustments.iter().zip(gains.iter()) {
+                    adjustment.set_value(gain);
+                }
+                applying_preset.set(false);
+            }
+        });
+
+        Self { widget }
+    }
+
+    // Bands are saved shortly after they stop moving, the player picks up the change from there
+    fn connect_bands(
+        widget: &EqualizerWidget,
+        adjustments: &[gtk::Adjustment],
+        applying_preset: &Rc<Cell<bool>>,
+    ) {
+        let debouncer = Debouncer::new();
+        let weak_adjustments = adjustments
+            .iter()
+            .map(|adjustment| adjustment.downgrade())
+            .collect::<Vec<_>>();
+        for adjustment in adjustments {
+            let debouncer = debouncer.clone();
+            let weak_adjustments = weak_adjustments.clone();
+            adjustment.connect_value_changed(
+                clone!(@weak widget.preset as preset, @weak applying_preset => move |_| {
+                    if !applying_preset.get() {
+                        preset.set_active_id(Some("custom"));
+                    }
+                    let bands = weak_adjustments
+                        .iter()
+                        .filter_map(|adjustment| adjustment.upgrade())
+                        .map(|adjustment| adjustment.get_value().round())
+                        .collect::<Vec<f64>>();
+                    debouncer.debounce(100, move || {
+                        SpotSettings::save_equalizer_bands(&bands);
+                    });
+                }),
+            );
+        }
+    }
+
+    pub fn present(&self) {
+        self.widget.root.present();
+    }
+}
@@ -0,0 +1,8 @@
+mod equalizer;
+use equalizer::EqualizerWindow;
+
+mod settings;
+pub use settings::*;
+
+mod settings_model;
+pub use settings_model::*;
@@ -0,0 +1,339 @@
+use gio::SettingsExt;
+use gladis::Gladis;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use super::{EqualizerWindow, SettingsModel};
+use crate::app::components::EventListener;
+use crate::app::loader::ImageLoader;
+use crate::app::settings::SpotSettings;
+use crate::app::AppEvent;
+
+#[derive(Clone, Gladis)]
+struct SettingsWidget {
+    root: gtk::Window,
+    no_images: gtk::Switch,
+    mpris_combined_title: gtk::Switch,
+    startup_view: gtk::ComboBoxText,
+    startup_context: gtk::Entry,
+    resume_card: gtk::Switch,
+    restore_session: gtk::Switch,
+    hide_explicit: gtk::Switch,
+    mood_tags: gtk::Switch,
+    playlists_sort: gtk::ComboBoxText,
+    swap_resumes_position: gtk::Switch,
+    previous_restarts: gtk::Switch,
+    shuffle_per_context: gtk::Switch,
+    shuffle_default: gtk::Switch,
+    shuffle_default_row: gtk::Widget,
+    shuffle_mode: gtk::ComboBoxText,
+    clear_queue_on_new_context: gtk::Switch,
+    autoplay: gtk::Switch,
+    sleep_timer_quit: gtk::Switch,
+    up_next_toast: gtk::Switch,
+    up_next_toast_fullscreen: gtk::Switch,
+    up_next_toast_fullscreen_row: gtk::Widget,
+    auto_show_now_playing: gtk::Switch,
+    auto_hide_now_playing: gtk::Switch,
+    auto_hide_now_playing_row: gtk::Widget,
+    auto_hide_now_playing_delay: gtk::Adjustment,
+    auto_hide_now_playing_delay_row: gtk::Widget,
+    force_mono: gtk::Switch,
+    audio_balance: gtk::Adjustment,
+    audio_balance_reset: gtk::Button,
+    volume_normalization: gtk::Switch,
+    normalization_level: gtk::ComboBoxText,
+    normalization_level_row: gtk::Widget,
+    equalizer_enabled: gtk::Switch,
+    equalizer_button: gtk::Button,
+    visualizer: gtk::Switch,
+    fade_duration: gtk::Adjustment,
+}
+
+impl SettingsWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/settings.ui")).unwrap()
+    }
+}
+
+pub struct Settings {
+    widget: SettingsWidget,
+    model: Rc<SettingsModel>,
+    _equalizer: Rc<EqualizerWindow>,
+    // Kept around so that we keep getting notified of changes
+    _settings: gio::Settings,
+}
+
+impl Settings {
+    pub fn new(parent: gtk::Window, model: SettingsModel) -> Self {
+        let widget = SettingsWidget::new();
+        let model = Rc::new(model);
+
+        widget.root.set_transient_for(Some(&parent));
+        widget.root.connect_delete_event(|window, _| {
+            window.hide();
+            Inhibit(true)
+        });
+
+        let settings = SpotSettings::gsettings();
+        settings.bind(
+            "no-images",
+            &widget.no_images,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "mpris-combined-title",
+            &widget.mpris_combined_title,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "startup-view",
+            &widget.startup_view,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "startup-context",
+            &widget.startup_context,
+            "text",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "resume-card",
+            &widget.resume_card,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "restore-session",
+            &widget.restore_session,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "hide-explicit",
+            &widget.hide_explicit,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "mood-tags",
+            &widget.mood_tags,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "playlists-sort",
+            &widget.playlists_sort,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "swap-resumes-position",
+            &widget.swap_resumes_position,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "previous-restarts",
+            &widget.previous_restarts,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "shuffle-per-context",
+            &widget.shuffle_per_context,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "shuffle-default",
+            &widget.shuffle_default,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "shuffle-per-context",
+            &widget.shuffle_default_row,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "shuffle-mode",
+            &widget.shuffle_mode,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "clear-queue-on-new-context",
+            &widget.clear_queue_on_new_context,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "autoplay",
+            &widget.autoplay,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "sleep-timer-quit",
+            &widget.sleep_timer_quit,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "up-next-toast",
+            &widget.up_next_toast,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "up-next-toast-fullscreen",
+            &widget.up_next_toast_fullscreen,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "up-next-toast",
+            &widget.up_next_toast_fullscreen_row,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "auto-show-now-playing",
+            &widget.auto_show_now_playing,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "auto-hide-now-playing",
+            &widget.auto_hide_now_playing,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "auto-show-now-playing",
+            &widget.auto_hide_now_playing_row,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "auto-hide-now-playing-delay",
+            &widget.auto_hide_now_playing_delay,
+            "value",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "auto-hide-now-playing",
+            &widget.auto_hide_now_playing_delay_row,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "force-mono",
+            &widget.force_mono,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "audio-balance",
+            &widget.audio_balance,
+            "value",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "volume-normalization",
+            &widget.volume_normalization,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "normalization-level",
+            &widget.normalization_level,
+            "active-id",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "volume-normalization",
+            &widget.normalization_level_row,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "equalizer-enabled",
+            &widget.equalizer_enabled,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "equalizer-enabled",
+            &widget.equalizer_button,
+            "sensitive",
+            gio::SettingsBindFlags::GET,
+        );
+        settings.bind(
+            "visualizer",
+            &widget.visualizer,
+            "active",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        settings.bind(
+            "fade-duration",
+            &widget.fade_duration,
+            "value",
+            gio::SettingsBindFlags::DEFAULT,
+        );
+        let equalizer = Rc::new(EqualizerWindow::new(&widget.root, &settings));
+        widget
+            .equalizer_button
+            .connect_clicked(clone!(@weak equalizer => move |_| {
+                equalizer.present();
+            }));
+        widget.audio_balance_reset.connect_clicked(
+            clone!(@weak widget.audio_balance as balance => move |_| {
+                balance.set_value(0.0);
+            }),
+        );
+        widget.startup_view.connect_changed(
+            clone!(@weak widget.startup_context as entry => move |combo| {
+                let is_context = combo.get_active_id().map(|id| id == "context").unwrap_or(false);
+                entry.set_sensitive(is_context);
+            }),
+        );
+        widget.startup_context.set_sensitive(
+            widget
+                .startup_view
+                .get_active_id()
+                .map(|id| id == "context")
+                .unwrap_or(false),
+        );
+
+        settings.connect_changed(clone!(@weak model => move |_, _| {
+            model.update_settings();
+        }));
+
+        Self {
+            widget,
+            model,
+            _equalizer: equalizer,
+            _settings: settings,
+        }
+    }
+
+    fn apply_settings(&self) {
+        ImageLoader::set_network_enabled(!self.model.settings().no_images);
+    }
+}
+
+impl EventListener for Settings {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started | AppEvent::SettingsChanged => self.apply_settings(),
+            AppEvent::SettingsShown => self.widget.root.present(),
+            _ => {}
+        }
+    }
+}
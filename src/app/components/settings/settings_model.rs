@@ -0,0 +1,28 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::settings::SpotSettings;
+use crate::app::{ActionDispatcher, AppAction, AppModel};
+
+pub struct SettingsModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl SettingsModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    pub fn settings(&self) -> impl Deref<Target = SpotSettings> + '_ {
+        self.app_model.map_state(|s| &s.settings)
+    }
+
+    pub fn update_settings(&self) {
+        self.dispatcher
+            .dispatch(AppAction::UpdateSettings(SpotSettings::new_from_gsettings()));
+    }
+}
@@ -0,0 +1,237 @@
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction, SimpleActionGroup};
+use gladis::Gladis;
+use glib::{StaticVariantType, ToVariant};
+use gtk::prelude::*;
+use gtk::ScrolledWindowExt;
+use std::rc::Rc;
+
+use super::ShowDetailsModel;
+use crate::app::components::{screen_add_css_provider, Component, Episode, EventListener};
+use crate::app::dispatch::Worker;
+use crate::app::loader::ImageLoader;
+use crate::app::models::EpisodeModel;
+use crate::app::{AppEvent, BrowserEvent};
+
+#[derive(Gladis, Clone)]
+struct ShowDetailsWidget {
+    pub root: gtk::ScrolledWindow,
+    pub show_art: gtk::Image,
+    pub show_title: gtk::Label,
+    pub publisher: gtk::Label,
+    pub follow_button: gtk::Button,
+    pub description: gtk::Label,
+    pub view_button: gtk::MenuButton,
+    pub episodes: gtk::ListBox,
+}
+
+impl ShowDetailsWidget {
+    fn new() -> Self {
+        screen_add_css_provider(resource!("/components/show_details.css"));
+        Self::from_resource(resource!("/components/show_details.ui")).unwrap()
+    }
+
+    fn set_loaded(&self) {
+        let context = self.root.get_style_context();
+        context.add_class("show--loaded");
+    }
+}
+
+// A podcast with its episodes, most recent first unless sorted otherwise
+pub struct ShowDetails {
+    model: Rc<ShowDetailsModel>,
+    worker: Worker,
+    widget: ShowDetailsWidget,
+    // The loaded episodes as sorted and filtered
+    episodes_view: gio::ListStore,
+}
+
+impl ShowDetails {
+    pub fn new(model: ShowDetailsModel, worker: Worker) -> Self {
+        model.load_show();
+
+        let model = Rc::new(model);
+        let widget = ShowDetailsWidget::new();
+
+        // Older episodes come at the top when the oldest are listed first
+        let weak_model = Rc::downgrade(&model);
+        widget.root.connect_edge_reached(move |_, pos| {
+            if let Some(model) = weak_model.upgrade() {
+                let edge = if model.episode_view().oldest_first {
+                    gtk::PositionType::Top
+                } else {
+                    gtk::PositionType::Bottom
+                };
+                if pos == edge {
+                    let _ = model.load_more();
+                }
+            }
+        });
+
+        widget
+            .follow_button
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.toggle_follow();
+            }));
+
+        let episodes_view = gio::ListStore::new(EpisodeModel::static_type());
+        let weak_model = Rc::downgrade(&model);
+        widget
+            .episodes
+            .bind_model(Some(&episodes_view), move |item| {
+                let item = item.downcast_ref::<EpisodeModel>().unwrap();
+                let weak_model = weak_model.clone();
+                let episode = Episode::new(item, move |id, played| {
+                    if let Some(model) = weak_model.upgrade() {
+                        model.mark_played(id, played);
+                    }
+                });
+                episode.get_root_widget().clone()
+            });
+
+        Self::connect_view_menu(&widget.view_button, &model);
+
+        Self {
+            model,
+            worker,
+            widget,
+            episodes_view,
+        }
+    }
+
+    // Changes are saved right away, the list is updated once the settings change
+    fn connect_view_menu(button: &gtk::MenuButton, model: &Rc<ShowDetailsModel>) {
+        let view = model.episode_view();
+        let current_order = if view.oldest_first {
+            "oldest"
+        } else {
+            "newest"
+        };
+
+        let order = SimpleAction::new_stateful(
+            "order",
+            Some(String::static_variant_type().as_ref()),
+            &current_order.to_variant(),
+        );
+        order.connect_activate(clone!(@weak model => move |action, param| {
+            if let Some(param) = param {
+                action.set_state(param);
+                let mut view = model.episode_view();
+                view.oldest_first = param.get_str() == Some("oldest");
+                model.set_episode_view(view);
+            }
+        }));
+
+        let hide_played =
+            SimpleAction::new_stateful("hide_played", None, &view.hide_played.to_variant());
+        hide_played.connect_activate(clone!(@weak model => move |action, _| {
+            let mut view = model.episode_view();
+            view.hide_played = !view.hide_played;
+            action.set_state(&view.hide_played.to_variant());
+            model.set_episode_view(view);
+        }));
+
+        let group = SimpleActionGroup::new();
+        group.add_action(&order);
+        group.add_action(&hide_played);
+        button.insert_action_group("episodes", Some(&group));
+
+        let sort = gio::Menu::new();
+        sort.append(Some("Newest first"), Some("episodes.order::newest"));
+        sort.append(Some("Oldest first"), Some("episodes.order::oldest"));
+        let filter = gio::Menu::new();
+        filter.append(Some("Hide played episodes"), Some("episodes.hide_played"));
+        let menu = gio::Menu::new();
+        menu.append_section(None, &sort);
+        menu.append_section(None, &filter);
+        button.set_menu_model(Some(&menu));
+    }
+
+    fn update_episodes(&self) {
+        self.model.apply_played_marks();
+        let episodes: Vec<glib::Object> = self
+            .model
+            .episodes_view()
+            .into_iter()
+            .map(|episode| episode.upcast::<glib::Object>())
+            .collect();
+        self.episodes_view
+            .splice(0, self.episodes_view.get_n_items(), &episodes[..]);
+    }
+
+    fn update_followed(&self) {
+        let is_followed = self.model.is_followed();
+        let button = &self.widget.follow_button;
+        button.set_sensitive(true);
+        button.set_label(if is_followed { "Following" } else { "Follow" });
+        button.set_tooltip_text(if is_followed {
+            Some("Unfollow this show")
+        } else {
+            None
+        });
+        let context = button.get_style_context();
+        if is_followed {
+            context.remove_class("suggested-action");
+        } else {
+            context.add_class("suggested-action");
+        }
+    }
+
+    fn update_details(&self) {
+        if let Some(show) = self.model.get_show() {
+            self.widget.show_title.set_label(&show.name);
+            self.widget.publisher.set_label(&show.publisher);
+            self.widget.description.set_label(&show.description);
+
+            let widget = self.widget.clone();
+            if let Some(art) = show.art.clone() {
+                self.worker.send_local_task(async move {
+                    let pixbuf = ImageLoader::new()
+                        .load_remote(&art[..], "jpg", 150, 150)
+                        .await;
+                    if let Some(pixbuf) = pixbuf.as_ref() {
+                        widget.show_art.set_from_pixbuf(Some(pixbuf));
+                    }
+                    widget.set_loaded();
+                });
+            } else {
+                widget.set_loaded();
+            }
+        }
+    }
+}
+
+impl Component for ShowDetails {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        self.widget.root.upcast_ref()
+    }
+}
+
+impl EventListener for ShowDetails {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::BrowserEvent(BrowserEvent::ShowDetailsUpdated(id))
+                if id == &self.model.id =>
+            {
+                self.update_details();
+                self.update_followed();
+                self.update_episodes();
+            }
+            AppEvent::BrowserEvent(BrowserEvent::ShowEpisodesAppended(id))
+                if id == &self.model.id =>
+            {
+                self.update_episodes();
+            }
+            AppEvent::SettingsChanged | AppEvent::RememberedSettingsChanged => {
+                self.update_episodes()
+            }
+            AppEvent::BrowserEvent(BrowserEvent::ShowFollowed(id))
+            | AppEvent::BrowserEvent(BrowserEvent::ShowUnfollowed(id))
+                if id == &self.model.id =>
+            {
+                self.update_followed();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::ops::Deref;
+use std::rc::Rc;
+
+use crate::app::components::handle_error;
+use crate::app::models::*;
+use crate::app::settings::{EpisodeView, SpotSettings};
+use crate::app::{ActionDispatcher, AppModel, BrowserAction, ListStore};
+
+pub struct ShowDetailsModel {
+    pub id: String,
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl ShowDetailsModel {
+    pub fn new(id: String, app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            id,
+            app_model,
+            dispatcher,
+        }
+    }
+
+    pub fn get_show(&self) -> Option<impl Deref<Target = ShowDescription> + '_> {
+        self.app_model
+            .map_state_opt(|s| s.browser.show_state(&self.id)?.show.as_ref())
+    }
+
+    pub fn get_list_store(&self) -> Option<impl Deref<Target = ListStore<EpisodeModel>> + '_> {
+        self.app_model
+            .map_state_opt(|s| Some(&s.browser.show_state(&self.id)?.episodes))
+    }
+
+    pub fn episode_view(&self) -> EpisodeView {
+        self.app_model.get_state().settings.episode_view(&self.id)
+    }
+
+    pub fn set_episode_view(&self, view: EpisodeView) {
+        SpotSettings::save_episode_view(&self.id, view);
+    }
+
+    // The loaded episodes as listed, Spotify gives them newest first
+    pub fn episodes_view(&self) -> Vec<EpisodeModel> {
+        let view = self.episode_view();
+        let mut episodes: Vec<EpisodeModel> = match self.get_list_store() {
+            Some(store) => store
+                .iter()
+                .filter(|episode| !view.hide_played || !episode.get_played())
+                .collect(),
+            None => vec![],
+        };
+        if view.oldest_first {
+            episodes.reverse();
+        }
+        episodes
+    }
+
+    pub fn is_followed(&self) -> bool {
+        self.get_show().map(|s| s.is_followed).unwrap_or(false)
+    }
+
+    // Marks are kept locally and win over what Spotify reports
+    pub fn apply_played_marks(&self) {
+        let state = self.app_model.get_state();
+        if let Some(show) = state.browser.show_state(&self.id) {
+            for episode in show.episodes.iter() {
+                let mark = episode.id().and_then(|id| state.settings.played_mark(&id));
+                if let Some(played) = mark {
+                    episode.set_played(played);
+                }
+            }
+        }
+    }
+
+    pub fn mark_played(&self, episode_id: &str, played: bool) {
+        SpotSettings::save_episode_played(episode_id, played);
+    }
+
+    pub fn load_show(&self) {
+        let api = self.app_model.get_spotify();
+        let id = self.id.clone();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_show(&id).await {
+                Ok(show) => Some(BrowserAction::SetShowDetails(show).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+    }
+
+    pub fn load_more(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+        let state = self.app_model.get_state();
+        let next_page = &state.browser.show_state(&self.id)?.next_page;
+
+        let id = next_page.data.clone();
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_show_episodes(&id, offset, batch_size).await {
+                Ok(episodes) => Some(BrowserAction::AppendShowEpisodes(id, episodes).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+
+    // Following a show saves it to the library, where it's listed with the podcasts
+    pub fn toggle_follow(&self) {
+        let id = self.id.clone();
+        let is_followed = self.is_followed();
+        let api = self.app_model.get_spotify();
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            if !is_followed {
+                match api.save_show(&id).await {
+                    Ok(podcast) => Some(BrowserAction::FollowShow(podcast).into()),
+                    Err(err) => handle_error(err),
+                }
+            } else {
+                match api.remove_saved_show(&id).await {
+                    Ok(_) => Some(BrowserAction::UnfollowShow(id).into()),
+                    Err(err) => handle_error(err),
+                }
+            }
+        }));
+    }
+}
@@ -0,0 +1,35 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::settings::SpotSettings;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
+// Remembers whether each album or playlist is shuffled as soon as it's toggled,
+// from the app or MPRIS, so that it's shuffled the same way when played again
+pub struct ShuffleKeeper {
+    app_model: Rc<AppModel>,
+}
+
+impl ShuffleKeeper {
+    pub fn new(app_model: Rc<AppModel>) -> Self {
+        Self { app_model }
+    }
+
+    fn save_shuffle(&self) {
+        let state = self.app_model.get_state();
+        if state.settings.shuffle_per_context {
+            if let Some(uri) = state.playback.source.uri() {
+                SpotSettings::save_shuffle(&uri, state.playback.is_shuffled());
+            }
+        }
+    }
+}
+
+impl EventListener for ShuffleKeeper {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::ShuffleToggled) = event {
+            self.save_shuffle();
+        }
+    }
+}
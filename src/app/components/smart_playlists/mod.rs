@@ -0,0 +1,5 @@
+mod smart_playlists;
+pub use smart_playlists::*;
+
+mod smart_playlists_model;
+pub use smart_playlists_model::*;
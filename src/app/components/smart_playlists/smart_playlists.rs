@@ -0,0 +1,209 @@
+use gladis::Gladis;
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use super::SmartPlaylistsModel;
+use crate::app::components::{Component, EventListener, Playlist};
+use crate::app::models::SmartRule;
+use crate::app::{AppEvent, BrowserEvent};
+
+#[derive(Clone, Gladis)]
+struct SmartPlaylistsWidget {
+    root: gtk::Widget,
+    playlist: gtk::ComboBoxText,
+    empty_label: gtk::Label,
+    new_playlist: gtk::Button,
+    remove_playlist: gtk::Button,
+    tracks: gtk::ListBox,
+}
+
+impl SmartPlaylistsWidget {
+    fn new() -> Self {
+        Self::from_resource(resource!("/components/smart_playlists.ui")).unwrap()
+    }
+}
+
+// A check button enabling a rule, followed by the value of the rule if it has one
+fn rule_row(label: &str, value: Option<(&gtk::SpinButton, &str)>) -> (gtk::Box, gtk::CheckButton) {
+    let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+    let check = gtk::CheckButton::with_label(label);
+    row.add(&check);
+    if let Some((spin, unit)) = value {
+        spin.set_sensitive(false);
+        check.connect_toggled(clone!(@weak spin => move |check| {
+            spin.set_sensitive(check.get_active());
+        }));
+        row.add(spin);
+        row.add(&gtk::Label::new(Some(unit)));
+    }
+    (row, check)
+}
+
+pub struct SmartPlaylists {
+    widget: SmartPlaylistsWidget,
+    model: Rc<SmartPlaylistsModel>,
+    children: Vec<Box<dyn EventListener>>,
+}
+
+impl SmartPlaylists {
+    pub fn new(model: SmartPlaylistsModel) -> Self {
+        let model = Rc::new(model);
+        let widget = SmartPlaylistsWidget::new();
+
+        widget
+            .playlist
+            .connect_changed(clone!(@weak model => move |combo| {
+                if let Some(name) = combo.get_active_id() {
+                    model.select(name.as_str());
+                }
+            }));
+
+        widget
+            .new_playlist
+            .connect_clicked(clone!(@weak model => move |button| {
+                Self::prompt_new_playlist(button, model);
+            }));
+
+        widget
+            .remove_playlist
+            .connect_clicked(clone!(@weak model => move |_| {
+                model.remove_selected();
+            }));
+
+        // All liked songs are needed, but only loaded once the page is visited
+        widget.root.connect_map(clone!(@weak model => move |_| {
+            let _ = model.load_more_tracks();
+        }));
+
+        let playlist = Playlist::new(widget.tracks.clone(), model.clone());
+
+        Self {
+            widget,
+            model,
+            children: vec![Box::new(playlist)],
+        }
+    }
+
+    fn prompt_new_playlist(button: &gtk::Button, model: Rc<SmartPlaylistsModel>) {
+        let window = button
+            .get_toplevel()
+            .and_then(|w| w.downcast::<gtk::Window>().ok());
+        let dialog = gtk::Dialog::with_buttons(
+            Some("New smart playlist"),
+            window.as_ref(),
+            gtk::DialogFlags::MODAL | gtk::DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", gtk::ResponseType::Cancel),
+                ("Create", gtk::ResponseType::Accept),
+            ],
+        );
+        dialog.set_default_response(gtk::ResponseType::Accept);
+        dialog.set_response_sensitive(gtk::ResponseType::Accept, false);
+
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+
+        let name = gtk::Entry::new();
+        name.set_placeholder_text(Some("Playlist name"));
+        name.set_activates_default(true);
+        name.connect_changed(clone!(@weak dialog => move |entry| {
+            let valid = !entry.get_text().trim().is_empty();
+            dialog.set_response_sensitive(gtk::ResponseType::Accept, valid);
+        }));
+        content.add(&name);
+
+        let days = gtk::SpinButton::with_range(1.0, 3650.0, 1.0);
+        days.set_value(30.0);
+        let (row, liked_within) = rule_row("Liked in the last", Some((&days, "days")));
+        content.add(&row);
+
+        let (row, by_followed) = rule_row("By artists you follow", None);
+        content.add(&row);
+
+        let shorter_minutes = gtk::SpinButton::with_range(1.0, 60.0, 1.0);
+        shorter_minutes.set_value(3.0);
+        let (row, shorter) = rule_row("Shorter than", Some((&shorter_minutes, "minutes")));
+        content.add(&row);
+
+        let longer_minutes = gtk::SpinButton::with_range(1.0, 60.0, 1.0);
+        longer_minutes.set_value(5.0);
+        let (row, longer) = rule_row("Longer than", Some((&longer_minutes, "minutes")));
+        content.add(&row);
+
+        dialog.get_content_area().add(&content);
+
+        dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Accept {
+                let mut rules = vec![];
+                if liked_within.get_active() {
+                    rules.push(SmartRule::LikedWithinDays(days.get_value_as_int() as u32));
+                }
+                if by_followed.get_active() {
+                    rules.push(SmartRule::ByFollowedArtist);
+                }
+                if shorter.get_active() {
+                    let seconds = shorter_minutes.get_value_as_int() as u32 * 60;
+                    rules.push(SmartRule::ShorterThan(seconds));
+                }
+                if longer.get_active() {
+                    let seconds = longer_minutes.get_value_as_int() as u32 * 60;
+                    rules.push(SmartRule::LongerThan(seconds));
+                }
+                model.save_playlist(name.get_text().trim(), rules);
+            }
+            dialog.close();
+        });
+        dialog.show_all();
+    }
+
+    fn update_playlists(&self) {
+        let combo = &self.widget.playlist;
+        let names = self.model.names();
+        let selected = self.model.selected().map(|p| p.name);
+
+        combo.remove_all();
+        for name in names.iter() {
+            combo.append(Some(name), name);
+        }
+        if let Some(selected) = selected {
+            combo.set_active_id(Some(&selected));
+        }
+        combo.set_visible(!names.is_empty());
+        self.widget.remove_playlist.set_visible(!names.is_empty());
+        self.widget.empty_label.set_visible(names.is_empty());
+    }
+
+    // Pages keep being loaded while the page is shown, until all liked songs are there
+    fn load_more_tracks(&self) {
+        if self.widget.root.get_mapped() {
+            let _ = self.model.load_more_tracks();
+        }
+    }
+}
+
+impl Component for SmartPlaylists {
+    fn get_root_widget(&self) -> &gtk::Widget {
+        &self.widget.root
+    }
+
+    fn get_children(&mut self) -> Option<&mut Vec<Box<dyn EventListener>>> {
+        Some(&mut self.children)
+    }
+}
+
+impl EventListener for SmartPlaylists {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started | AppEvent::SettingsChanged => self.update_playlists(),
+            AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated)
+            | AppEvent::BrowserEvent(BrowserEvent::SavedTracksAppended(_)) => {
+                self.load_more_tracks();
+            }
+            _ => {}
+        }
+        self.broadcast_event(event);
+    }
+}
@@ -0,0 +1,215 @@
+use gdk::SELECTION_CLIPBOARD;
+use gio::prelude::*;
+use gio::{ActionMapExt, SimpleAction};
+use gtk::Clipboard;
+use std::cell::Ref;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use crate::app::components::{append_song_menu, handle_error, song_actions, PlaylistModel};
+use crate::app::models::*;
+use crate::app::settings::SpotSettings;
+use crate::app::state::{
+    BrowserAction, BrowserEvent, HomeState, PlaybackAction, PlaylistSource, SelectionAction,
+    SelectionState,
+};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+pub struct SmartPlaylistsModel {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl SmartPlaylistsModel {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn state(&self) -> Option<Ref<'_, HomeState>> {
+        self.app_model.map_state_opt(|s| s.browser.home_state())
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.app_model
+            .get_state()
+            .settings
+            .smart_playlists
+            .iter()
+            .map(|p| p.name.clone())
+            .collect()
+    }
+
+    pub fn selected(&self) -> Option<SmartPlaylist> {
+        let state = self.app_model.get_state();
+        let playlists = &state.settings.smart_playlists;
+        let name = state.browser.home_state()?.smart_playlist.clone();
+        name.and_then(|name| playlists.iter().find(|p| p.name == name))
+            .or_else(|| playlists.first())
+            .cloned()
+    }
+
+    pub fn select(&self, name: &str) {
+        self.dispatcher
+            .dispatch(BrowserAction::SelectSmartPlaylist(name.to_string()).into());
+    }
+
+    // Saving to the settings is what updates the state
+    pub fn save_playlist(&self, name: &str, rules: Vec<SmartRule>) {
+        SpotSettings::save_smart_playlist(&SmartPlaylist {
+            name: name.to_string(),
+            rules,
+        });
+        self.select(name);
+    }
+
+    pub fn remove_selected(&self) {
+        if let Some(playlist) = self.selected() {
+            SpotSettings::remove_smart_playlist(&playlist.name);
+        }
+    }
+
+    fn matching_songs(&self) -> Vec<SongDescription> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        match (self.selected(), self.state()) {
+            (Some(playlist), Some(state)) => state.smart_playlist_songs(&playlist, now),
+            _ => vec![],
+        }
+    }
+
+    fn song(&self, id: &str) -> Option<SongDescription> {
+        self.state()?
+            .saved_tracks
+            .iter()
+            .find(|song| song.id == id)
+            .cloned()
+    }
+
+    // Rules are evaluated against the liked songs, which are loaded page after page
+    pub fn load_more_tracks(&self) -> Option<()> {
+        let api = self.app_model.get_spotify();
+
+        let next_page = &self.state()?.next_saved_tracks_page;
+        let batch_size = next_page.batch_size;
+        let offset = next_page.next_offset?;
+
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_saved_tracks(offset, batch_size).await {
+                Ok(tracks) => Some(BrowserAction::AppendSavedTracks(offset, tracks).into()),
+                Err(err) => handle_error(err),
+            }
+        }));
+
+        Some(())
+    }
+}
+
+impl PlaylistModel for SmartPlaylistsModel {
+    fn current_song_id(&self) -> Option<String> {
+        self.app_model.get_state().playback.current_song_id.clone()
+    }
+
+    fn songs(&self) -> Vec<SongModel> {
+        self.matching_songs()
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s.to_song_model(i))
+            .collect()
+    }
+
+    fn play_song(&self, id: &str) {
+        self.dispatcher.dispatch(
+            PlaybackAction::LoadPlaylist(PlaylistSource::None, self.matching_songs()).into(),
+        );
+        self.dispatcher
+            .dispatch(PlaybackAction::Load(id.to_string()).into());
+    }
+
+    fn should_refresh_songs(&self, event: &AppEvent) -> bool {
+        matches!(
+            event,
+            AppEvent::SettingsChanged
+                | AppEvent::BrowserEvent(BrowserEvent::SavedTracksUpdated)
+                | AppEvent::BrowserEvent(BrowserEvent::SavedTracksAppended(_))
+                | AppEvent::BrowserEvent(BrowserEvent::FollowedArtistsUpdated)
+                | AppEvent::BrowserEvent(BrowserEvent::SmartPlaylistSelected)
+        )
+    }
+
+    fn has_columns(&self) -> bool {
+        true
+    }
+
+    fn actions_for(&self, id: &str) -> Option<gio::ActionGroup> {
+        let song = self.song(id)?;
+        let group = song_actions(self.dispatcher.as_ref(), song);
+
+        let album_id = song.album.id.clone();
+        let view_album = SimpleAction::new("view_album", None);
+        let dispatcher = self.dispatcher.box_clone();
+        view_album.connect_activate(move |_, _| {
+            dispatcher.dispatch(AppAction::ViewAlbum(album_id.clone()));
+        });
+        group.add_action(&view_album);
+
+        for (i, artist) in song.artists.iter().enumerate() {
+            let view_artist = SimpleAction::new(&format!("view_artist_{}", i), None);
+            let dispatcher = self.dispatcher.box_clone();
+            let id = artist.id.clone();
+            view_artist.connect_activate(move |_, _| {
+                dispatcher.dispatch(AppAction::ViewArtist(id.clone()));
+            });
+            group.add_action(&view_artist);
+        }
+
+        let track_id = song.id.clone();
+        let copy_link = SimpleAction::new("copy_link", None);
+        copy_link.connect_activate(move |_, _| {
+            let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+            clipboard.set_text(&format!("https://open.spotify.com/track/{}", &track_id));
+        });
+        group.add_action(&copy_link);
+
+        Some(group.upcast())
+    }
+
+    fn menu_for(&self, id: &str) -> Option<gio::MenuModel> {
+        let song = self.song(id)?;
+
+        let menu = gio::Menu::new();
+        menu.append(Some("View album"), Some("song.view_album"));
+        for (i, artist) in song.artists.iter().enumerate() {
+            menu.append(
+                Some(&format!("More from {}", artist.name)),
+                Some(&format!("song.view_artist_{}", i)),
+            );
+        }
+
+        menu.append(Some("Copy link"), Some("song.copy_link"));
+        append_song_menu(&menu, &self.app_model.get_state(), false);
+
+        Some(menu.upcast())
+    }
+
+    fn select_song(&self, id: &str) {
+        if let Some(song) = self.song(id) {
+            self.dispatcher
+                .dispatch(SelectionAction::Select(song).into());
+        }
+    }
+
+    fn deselect_song(&self, id: &str) {
+        self.dispatcher
+            .dispatch(SelectionAction::Deselect(id.to_string()).into());
+    }
+
+    fn selection(&self) -> Option<Box<dyn Deref<Target = SelectionState> + '_>> {
+        Some(Box::new(self.app_model.map_state(|s| &s.selection)))
+    }
+}
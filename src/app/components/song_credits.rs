@@ -0,0 +1,46 @@
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::{BrowserAction, BrowserEvent};
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// Fetches the credits of a song asked for from its menu, the song list then shows them.
+// They come from an endpoint outside of the public API, which may fail or go away at any
+// time: credits are then no longer offered, rather than reporting an error.
+pub struct SongCreditsLoader {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+}
+
+impl SongCreditsLoader {
+    pub fn new(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+        }
+    }
+
+    fn load(&self, id: String) {
+        let api = self.app_model.get_spotify();
+        self.dispatcher.dispatch_async(Box::pin(async move {
+            match api.get_track_credits(&id).await {
+                Ok(credits) if credits.is_empty() => Some(AppAction::ShowNotification(
+                    "No credits available for this song".to_string(),
+                )),
+                Ok(credits) => Some(BrowserAction::SetSongCredits(id, credits).into()),
+                Err(err) => {
+                    println!("Credits unavailable: {:?}", err);
+                    Some(BrowserAction::DisableSongCredits.into())
+                }
+            }
+        }));
+    }
+}
+
+impl EventListener for SongCreditsLoader {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::BrowserEvent(BrowserEvent::SongCreditsRequested(id)) = event {
+            self.load(id.clone());
+        }
+    }
+}
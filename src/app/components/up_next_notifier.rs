@@ -0,0 +1,61 @@
+use gdk::{WindowExt, WindowState};
+use gtk::prelude::*;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::PlaybackEvent;
+use crate::app::{ActionDispatcher, AppAction, AppEvent, AppModel};
+
+// Briefly shows the current and next songs whenever the track changes
+pub struct UpNextNotifier {
+    app_model: Rc<AppModel>,
+    dispatcher: Box<dyn ActionDispatcher>,
+    window: gtk::Window,
+}
+
+impl UpNextNotifier {
+    pub fn new(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+        window: gtk::Window,
+    ) -> Self {
+        Self {
+            app_model,
+            dispatcher,
+            window,
+        }
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.window
+            .get_window()
+            .map(|w| w.get_state().contains(WindowState::FULLSCREEN))
+            .unwrap_or(false)
+    }
+
+    fn message(&self) -> Option<String> {
+        let state = self.app_model.get_state();
+        let settings = &state.settings;
+        if !settings.up_next_toast || (self.is_fullscreen() && !settings.up_next_toast_fullscreen) {
+            return None;
+        }
+        let current = state.playback.current_song()?;
+        let next = state
+            .playback
+            .next_song()
+            .map(|s| s.title.clone())
+            .unwrap_or_else(|| "(end)".to_string());
+        Some(format!("Now: {} — Up next: {}", current.title, next))
+    }
+}
+
+impl EventListener for UpNextNotifier {
+    fn on_event(&mut self, event: &AppEvent) {
+        if let AppEvent::PlaybackEvent(PlaybackEvent::TrackChanged(_)) = event {
+            if let Some(message) = self.message() {
+                self.dispatcher
+                    .dispatch(AppAction::ShowNotification(message));
+            }
+        }
+    }
+}
@@ -41,6 +41,14 @@ impl UserMenu {
             logout
         });
 
+        action_group.add_action(&{
+            let settings = SimpleAction::new("settings", None);
+            settings.connect_activate(clone!(@weak model => move |_, _| {
+                model.show_settings();
+            }));
+            settings
+        });
+
         action_group.add_action(&{
             let about_action = SimpleAction::new("about", None);
             about_action.connect_activate(clone!(@weak about => move |_, _| {
@@ -57,6 +65,7 @@ impl UserMenu {
     fn update_menu(&self) {
         let menu = gio::Menu::new();
         menu.insert(0, Some("About"), Some("menu.about"));
+        menu.insert(0, Some("Preferences"), Some("menu.settings"));
 
         if let Some(username) = self.model.username() {
             let user_menu = gio::Menu::new();
@@ -20,6 +20,10 @@ impl UserMenuModel {
         self.app_model.map_state_opt(|s| s.user.as_ref())
     }
 
+    pub fn show_settings(&self) {
+        self.dispatcher.dispatch(AppAction::ShowSettings);
+    }
+
     pub fn logout(&self) {
         if credentials::logout().is_ok() {
             self.dispatcher.dispatch(AppAction::Logout);
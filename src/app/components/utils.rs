@@ -1,7 +1,11 @@
+use gio::prelude::*;
+use glib::{StaticVariantType, ToVariant};
 use gtk::prelude::*;
 use std::cell::Cell;
 use std::rc::Rc;
 
+use crate::app::settings::ExplicitFilter;
+
 pub struct Clock {
     interval_ms: u32,
     source: Cell<Option<glib::source::SourceId>>,
@@ -9,12 +13,23 @@ pub struct Clock {
 
 impl Clock {
     pub fn new() -> Self {
+        Self::with_interval(1000)
+    }
+
+    pub fn with_interval(interval_ms: u32) -> Self {
         Self {
-            interval_ms: 1000,
+            interval_ms,
             source: Cell::new(None),
         }
     }
 
+    pub fn is_running(&self) -> bool {
+        let source = self.source.take();
+        let running = source.is_some();
+        self.source.set(source);
+        running
+    }
+
     pub fn start<F: Fn() + 'static>(&self, tick: F) {
         let new_source = Some(glib::timeout_add_local(self.interval_ms, move || {
             tick();
@@ -33,6 +48,13 @@ impl Clock {
     }
 }
 
+// Ticking stops along with whatever owns the clock
+impl Drop for Clock {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 #[derive(Clone)]
 pub struct Debouncer(Rc<Cell<Option<glib::source::SourceId>>>);
 
@@ -72,9 +94,72 @@ pub fn wrap_flowbox_item<
     child.upcast::<gtk::Widget>()
 }
 
+// For covers that are missing, or can't be loaded
+pub fn set_art_placeholder(image: &gtk::Image) {
+    image.set_from_icon_name(Some("emblem-music-symbolic"), gtk::IconSize::Dialog);
+}
+
+pub fn scroll_to_row(listbox: &gtk::ListBox, adjustment: &gtk::Adjustment, index: i32) -> bool {
+    if let Some(row) = listbox.get_row_at_index(index) {
+        adjustment.set_value(row.get_allocation().y as f64);
+        row.grab_focus();
+        true
+    } else {
+        false
+    }
+}
+
+// Sets up a menu on the button to pick the explicit content filter of a context,
+// the returned action's state should be kept in sync with the settings
+pub fn explicit_filter_button<F: Fn(ExplicitFilter) + 'static>(
+    button: &gtk::MenuButton,
+    current: ExplicitFilter,
+    on_change: F,
+) -> gio::SimpleAction {
+    let action = gio::SimpleAction::new_stateful(
+        "filter",
+        Some(String::static_variant_type().as_ref()),
+        &current.as_str().to_variant(),
+    );
+    action.connect_activate(move |action, param| {
+        if let Some(param) = param {
+            action.set_state(param);
+            on_change(ExplicitFilter::from_str(param.get_str().unwrap_or("")));
+        }
+    });
+
+    let group = gio::SimpleActionGroup::new();
+    group.add_action(&action);
+    button.insert_action_group("explicit", Some(&group));
+
+    let menu = gio::Menu::new();
+    menu.append(Some("Use default"), Some("explicit.filter::inherit"));
+    menu.append(
+        Some("Always hide explicit songs"),
+        Some("explicit.filter::hide"),
+    );
+    menu.append(
+        Some("Always show explicit songs"),
+        Some("explicit.filter::show"),
+    );
+    button.set_menu_model(Some(&menu));
+
+    action
+}
+
 pub fn format_duration(duration: f64) -> String {
     let seconds = (duration / 1000.0) as i32;
     let minutes = seconds.div_euclid(60);
     let seconds = seconds.rem_euclid(60);
     format!("{}:{:02}", minutes, seconds)
 }
+
+// For whole albums or playlists, e.g. "1 h 12 min"
+pub fn format_total_duration(duration: u32) -> String {
+    let minutes = duration / 60_000;
+    if minutes >= 60 {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    } else {
+        format!("{} min", minutes)
+    }
+}
@@ -0,0 +1,2 @@
+mod visualizer;
+pub use visualizer::*;
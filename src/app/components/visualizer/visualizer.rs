@@ -0,0 +1,108 @@
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::app::components::EventListener;
+use crate::app::state::PlaybackEvent;
+use crate::app::{AppEvent, AppModel};
+
+// How much of the previous level is kept on each frame, so that bars fall smoothly
+const DECAY: f32 = 0.85;
+const BAR_SPACING: f64 = 2.0;
+
+pub struct VisualizerModel {
+    app_model: Rc<AppModel>,
+}
+
+impl VisualizerModel {
+    pub fn new(app_model: Rc<AppModel>) -> Self {
+        Self { app_model }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.app_model.get_state().settings.visualizer
+    }
+}
+
+pub struct Visualizer {
+    model: VisualizerModel,
+    area: gtk::DrawingArea,
+    levels: Rc<RefCell<Vec<f32>>>,
+}
+
+impl Visualizer {
+    pub fn new(model: VisualizerModel, area: gtk::DrawingArea) -> Self {
+        let levels = Rc::new(RefCell::new(Vec::<f32>::new()));
+
+        area.connect_draw(
+            clone!(@weak levels => @default-return Inhibit(false), move |area, cr| {
+                let levels = levels.borrow();
+                if levels.is_empty() {
+                    return Inhibit(false);
+                }
+                let width = area.get_allocated_width() as f64;
+                let height = area.get_allocated_height() as f64;
+                let color = area.get_style_context().get_color(gtk::StateFlags::NORMAL);
+                cr.set_source_rgba(color.red, color.green, color.blue, 0.6);
+                let bar_width = width / levels.len() as f64;
+                for (i, &level) in levels.iter().enumerate() {
+                    let bar_height = height * level as f64;
+                    cr.rectangle(
+                        i as f64 * bar_width + BAR_SPACING / 2.0,
+                        height - bar_height,
+                        (bar_width - BAR_SPACING).max(1.0),
+                        bar_height,
+                    );
+                }
+                cr.fill();
+                Inhibit(false)
+            }),
+        );
+
+        Self {
+            model,
+            area,
+            levels,
+        }
+    }
+
+    fn update_visibility(&self) {
+        if self.model.is_enabled() {
+            self.area.show();
+        } else {
+            self.area.hide();
+            self.clear();
+        }
+    }
+
+    fn update_levels(&self, new_levels: &[f32]) {
+        if !self.area.is_visible() {
+            return;
+        }
+        let mut levels = self.levels.borrow_mut();
+        levels.resize(new_levels.len(), 0.0);
+        for (level, &new_level) in levels.iter_mut().zip(new_levels.iter()) {
+            *level = new_level.max(*level * DECAY);
+        }
+        self.area.queue_draw();
+    }
+
+    fn clear(&self) {
+        self.levels.borrow_mut().clear();
+        self.area.queue_draw();
+    }
+}
+
+impl EventListener for Visualizer {
+    fn on_event(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Started | AppEvent::SettingsChanged => self.update_visibility(),
+            AppEvent::PlaybackEvent(PlaybackEvent::SpectrumSynced(levels)) => {
+                self.update_levels(levels);
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaybackPaused)
+            | AppEvent::PlaybackEvent(PlaybackEvent::PlaybackStopped) => self.clear(),
+            _ => {}
+        }
+    }
+}
@@ -4,6 +4,8 @@ use std::thread;
 use zbus::fdo;
 
 use crate::app::components::EventListener;
+use crate::app::dispatch::Worker;
+use crate::app::loader::ImageLoader;
 use crate::app::state::PlaybackEvent;
 use crate::app::{models::SongDescription, AppAction, AppEvent, AppModel};
 
@@ -15,8 +17,9 @@ use types::*;
 
 // This one wraps a connection and reads the app state
 pub struct AppPlaybackStateListener {
-    object_server: zbus::ObjectServer,
+    object_server: Rc<zbus::ObjectServer>,
     app_model: Rc<AppModel>,
+    worker: Worker,
 }
 
 impl AppPlaybackStateListener {
@@ -25,47 +28,96 @@ impl AppPlaybackStateListener {
         mpris: SpotMpris,
         player: SpotMprisPlayer,
         app_model: Rc<AppModel>,
+        worker: Worker,
     ) -> Result<Self, zbus::Error> {
         let object_server = register_mpris(&connection, mpris, player)?;
         Ok(Self {
-            object_server,
+            object_server: Rc::new(object_server),
             app_model,
+            worker,
         })
     }
 
     fn with_player<F: Fn(&SpotMprisPlayer) -> zbus::Result<()>>(&self, f: F) -> zbus::Result<()> {
-        self.object_server
-            .with("/org/mpris/MediaPlayer2", |iface: &SpotMprisPlayer| {
-                f(iface)
-            })
+        with_player(&self.object_server, f)
+    }
+
+    // Only covers that were cached already, see cache_art
+    fn cached_art_url(url: &str) -> Option<String> {
+        let path = ImageLoader::new().cached_path(url, "jpg");
+        if path.exists() {
+            glib::filename_to_uri(path, None)
+                .ok()
+                .map(|uri| uri.to_string())
+        } else {
+            None
+        }
+    }
+
+    // The track is announced right away, and again with its cover once downloaded
+    fn cache_art(&self) {
+        let state = self.app_model.get_state();
+        let song = match state.playback.current_song() {
+            Some(song) => song,
+            None => return,
+        };
+        let url = match song.art.clone() {
+            Some(url) if Self::cached_art_url(&url).is_none() => url,
+            _ => return,
+        };
+        let track_id = track_path(&song.id);
+        let object_server = Rc::clone(&self.object_server);
+        self.worker.send_local_task(async move {
+            let path = ImageLoader::new().cache_remote(&url, "jpg").await;
+            let art_url = path.and_then(|path| glib::filename_to_uri(path, None).ok());
+            if let Some(art_url) = art_url {
+                with_player(&object_server, |player| {
+                    if player.state.set_art_url(&track_id, art_url.to_string()) {
+                        player.notify_metadata()?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
+        });
     }
 
     fn make_track_meta(&self) -> Option<TrackMetadata> {
-        self.app_model
-            .get_state()
-            .playback
-            .current_song()
-            .cloned()
-            .map(
-                |SongDescription {
-                     id,
-                     title,
-                     artists,
-                     duration,
-                     ..
-                 }| TrackMetadata {
-                    id: format!("/dev/alextren/Spot/Track/{}", id),
+        let state = self.app_model.get_state();
+        let combined_title = state.settings.mpris_combined_title;
+        state.playback.current_song().cloned().map(
+            |SongDescription {
+                 id,
+                 title,
+                 artists,
+                 album,
+                 duration,
+                 art,
+                 ..
+             }| {
+                let artist: Vec<String> = artists.into_iter().map(|a| a.name).collect();
+                let title = if combined_title {
+                    truncate_title(&format!("{} - {}", artist.join(", "), title))
+                } else {
+                    title
+                };
+                TrackMetadata {
+                    id: track_path(&id),
                     length: 1000 * duration as u64,
                     title,
-                    artist: artists.into_iter().map(|a| a.name).collect(),
-                },
-            )
+                    artist,
+                    album: album.name,
+                    art_url: art.as_deref().and_then(Self::cached_art_url),
+                }
+            },
+        )
     }
 
     fn has_prev_next(&self) -> (bool, bool) {
         let state = self.app_model.get_state();
         (
-            state.playback.prev_song().is_some(),
+            state.playback.prev_song().is_some()
+                || (state.settings.previous_restarts && state.playback.current_song().is_some()),
             state.playback.next_song().is_some(),
         )
     }
@@ -109,9 +161,52 @@ impl EventListener for AppPlaybackStateListener {
                     Ok(())
                 })
                 .unwrap();
+                self.cache_art();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::PlaylistChanged) => {
+                self.with_player(|player| {
+                    let (has_prev, has_next) = self.has_prev_next();
+                    let shuffle = self.app_model.get_state().playback.is_shuffled();
+                    player.state.set_shuffle(shuffle);
+                    player.state.set_has_prev(has_prev);
+                    player.state.set_has_next(has_next);
+                    player.notify_shuffle_and_prev_next()?;
+                    Ok(())
+                })
+                .unwrap();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::VolumeChanged) => {
+                self.with_player(|player| {
+                    let volume = self.app_model.get_state().playback.volume;
+                    player.state.set_volume(volume);
+                    player.notify_volume()?;
+                    Ok(())
+                })
+                .unwrap();
+            }
+            AppEvent::PlaybackEvent(PlaybackEvent::RepeatModeChanged) => {
+                self.with_player(|player| {
+                    let (has_prev, has_next) = self.has_prev_next();
+                    let repeat_mode = self.app_model.get_state().playback.repeat_mode;
+                    player.state.set_loop_status(repeat_mode.into());
+                    player.state.set_has_prev(has_prev);
+                    player.state.set_has_next(has_next);
+                    player.notify_loop_status_and_prev_next()?;
+                    Ok(())
+                })
+                .unwrap();
+            }
+            AppEvent::SettingsChanged => {
+                self.with_player(|player| {
+                    let (has_prev, _) = self.has_prev_next();
+                    player.state.update_current_track(self.make_track_meta());
+                    player.state.set_has_prev(has_prev);
+                    player.notify_metadata_and_prev_next()?;
+                    Ok(())
+                })
+                .unwrap();
             }
-            AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(pos))
-            | AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(pos)) => {
+            AppEvent::PlaybackEvent(PlaybackEvent::TrackSeeked(pos)) => {
                 self.with_player(|player| {
                     let pos = 1000 * (*pos as u128);
                     player.state.set_position(pos);
@@ -120,11 +215,35 @@ impl EventListener for AppPlaybackStateListener {
                 })
                 .unwrap();
             }
+            // Clients estimate the position themselves, they only need to know when it jumped
+            AppEvent::PlaybackEvent(PlaybackEvent::SeekSynced(pos)) => {
+                self.with_player(|player| {
+                    let pos = 1000 * (*pos as u128);
+                    if player.state.sync_position(pos) {
+                        player.seeked(pos as i64)?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
             _ => {}
         }
     }
 }
 
+fn track_path(id: &str) -> String {
+    format!("/dev/alextren/Spot/Track/{}", id)
+}
+
+fn with_player<F: Fn(&SpotMprisPlayer) -> zbus::Result<()>>(
+    object_server: &zbus::ObjectServer,
+    f: F,
+) -> zbus::Result<()> {
+    object_server.with("/org/mpris/MediaPlayer2", |iface: &SpotMprisPlayer| {
+        f(iface)
+    })
+}
+
 fn register_mpris(
     connection: &zbus::Connection,
     mpris: SpotMpris,
@@ -139,6 +258,7 @@ fn register_mpris(
 pub fn start_dbus_server(
     app_model: Rc<AppModel>,
     sender: UnboundedSender<AppAction>,
+    worker: Worker,
 ) -> Result<AppPlaybackStateListener, zbus::Error> {
     let state = SharedMprisState::new();
 
@@ -164,5 +284,5 @@ pub fn start_dbus_server(
         }
     });
 
-    AppPlaybackStateListener::new(connection, mpris, player, app_model)
+    AppPlaybackStateListener::new(connection, mpris, player, app_model, worker)
 }
@@ -8,7 +8,7 @@ use zbus::fdo::{Error, Result};
 use zbus::ObjectServer;
 
 use super::types::*;
-use crate::app::{state::PlaybackAction, AppAction};
+use crate::app::{state::PlaybackAction, state::RepeatMode, AppAction};
 
 #[derive(Clone)]
 pub struct SpotMpris {
@@ -74,6 +74,27 @@ impl SpotMprisPlayer {
     pub fn new(state: SharedMprisState, sender: UnboundedSender<AppAction>) -> Self {
         Self { state, sender }
     }
+
+    // Positions are in microseconds here, in milliseconds for the app
+    fn seek_to(&self, position: i64) -> Result<()> {
+        let position = (position.max(0) / 1000) as u32;
+        self.sender
+            .unbounded_send(PlaybackAction::Seek(position).into())
+            .map_err(|_| Error::Failed("Could not send action".to_string()))
+    }
+
+    fn notify(
+        &self,
+        changed: std::collections::HashMap<&str, zvariant::Value>,
+    ) -> zbus::Result<()> {
+        let invalidated: Vec<String> = vec![];
+        ObjectServer::local_node_emit_signal(
+            None,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+            &("org.mpris.MediaPlayer2.Player", changed, invalidated),
+        )
+    }
 }
 
 #[dbus_interface(interface = "org.mpris.MediaPlayer2.Player")]
@@ -107,35 +128,60 @@ impl SpotMprisPlayer {
     }
 
     pub fn notify_playback_status(&self) -> zbus::Result<()> {
-        let invalidated: Vec<String> = vec![];
         let mut changed = std::collections::HashMap::new();
         changed.insert(
             "PlaybackStatus",
             zvariant::Value::from(self.playback_status()),
         );
-        ObjectServer::local_node_emit_signal(
-            None,
-            "org.freedesktop.DBus.Properties",
-            "PropertiesChanged",
-            &("org.mpris.MediaPlayer2.Player", changed, invalidated),
-        )
+        self.notify(changed)
     }
 
     pub fn notify_metadata_and_prev_next(&self) -> zbus::Result<()> {
-        let invalidated: Vec<String> = vec![];
         let mut changed = std::collections::HashMap::new();
         changed.insert("Metadata", zvariant::Value::from(self.metadata()));
+        changed.insert("CanSeek", zvariant::Value::from(self.can_seek()));
         changed.insert("CanGoNext", zvariant::Value::from(self.can_go_next()));
         changed.insert(
             "CanGoPrevious",
             zvariant::Value::from(self.can_go_previous()),
         );
-        ObjectServer::local_node_emit_signal(
-            None,
-            "org.freedesktop.DBus.Properties",
-            "PropertiesChanged",
-            &("org.mpris.MediaPlayer2.Player", changed, invalidated),
-        )
+        self.notify(changed)
+    }
+
+    pub fn notify_metadata(&self) -> zbus::Result<()> {
+        let mut changed = std::collections::HashMap::new();
+        changed.insert("Metadata", zvariant::Value::from(self.metadata()));
+        self.notify(changed)
+    }
+
+    // Changing the loop status also changes whether there is a next or previous song
+    pub fn notify_loop_status_and_prev_next(&self) -> zbus::Result<()> {
+        let mut changed = std::collections::HashMap::new();
+        changed.insert("LoopStatus", zvariant::Value::from(self.loop_status()));
+        changed.insert("CanGoNext", zvariant::Value::from(self.can_go_next()));
+        changed.insert(
+            "CanGoPrevious",
+            zvariant::Value::from(self.can_go_previous()),
+        );
+        self.notify(changed)
+    }
+
+    // Shuffling changes which songs come before and after the current one too
+    pub fn notify_shuffle_and_prev_next(&self) -> zbus::Result<()> {
+        let mut changed = std::collections::HashMap::new();
+        changed.insert("Shuffle", zvariant::Value::from(self.shuffle()));
+        changed.insert("CanGoNext", zvariant::Value::from(self.can_go_next()));
+        changed.insert(
+            "CanGoPrevious",
+            zvariant::Value::from(self.can_go_previous()),
+        );
+        self.notify(changed)
+    }
+
+    pub fn notify_volume(&self) -> zbus::Result<()> {
+        let mut changed = std::collections::HashMap::new();
+        changed.insert("Volume", zvariant::Value::from(self.volume()));
+        self.notify(changed)
     }
 
     fn previous(&mut self) -> Result<()> {
@@ -144,12 +190,34 @@ impl SpotMprisPlayer {
             .map_err(|_| zbus::fdo::Error::Failed("Could not send action".to_string()))
     }
 
+    // Seeking past the end skips to the next track, as the spec requires
     pub fn seek(&self, Offset: i64) -> Result<()> {
-        Ok(())
-    }
-
-    fn set_position(&self, TrackId: String, Position: i64) -> Result<()> {
-        Ok(())
+        let length = match self.state.current_track() {
+            Some(track) => track.length as i64,
+            None => return Ok(()),
+        };
+        let position = self.state.position() as i64 + Offset;
+        if position >= length {
+            self.sender
+                .unbounded_send(PlaybackAction::Next.into())
+                .map_err(|_| Error::Failed("Could not send action".to_string()))
+        } else {
+            self.seek_to(position)
+        }
+    }
+
+    // Ignored when meant for another track or out of range, as the spec requires
+    fn set_position(&self, TrackId: zvariant::ObjectPath<'_>, Position: i64) -> Result<()> {
+        match self.state.current_track() {
+            Some(track)
+                if track.id == TrackId.as_str()
+                    && Position >= 0
+                    && Position <= track.length as i64 =>
+            {
+                self.seek_to(Position)
+            }
+            _ => Ok(()),
+        }
     }
 
     pub fn stop(&self) -> Result<()> {
@@ -187,7 +255,22 @@ impl SpotMprisPlayer {
 
     #[dbus_interface(property)]
     pub fn can_seek(&self) -> bool {
-        false
+        self.state.current_track().is_some()
+    }
+
+    #[dbus_interface(property)]
+    pub fn loop_status(&self) -> LoopStatus {
+        self.state.loop_status()
+    }
+
+    #[dbus_interface(property)]
+    pub fn set_loop_status(&self, value: String) {
+        if let Some(status) = LoopStatus::from_name(&value) {
+            let mode: RepeatMode = status.into();
+            let _ = self
+                .sender
+                .unbounded_send(PlaybackAction::SetRepeatMode(mode).into());
+        }
     }
 
     #[dbus_interface(property)]
@@ -198,10 +281,12 @@ impl SpotMprisPlayer {
     #[dbus_interface(property)]
     pub fn metadata(&self) -> TrackMetadata {
         self.state.current_track().unwrap_or(TrackMetadata {
-            id: String::new(),
+            id: "/org/mpris/MediaPlayer2/TrackList/NoTrack".to_string(),
             length: 0,
             title: "Not playing".to_string(),
             artist: vec![],
+            album: String::new(),
+            art_url: None,
         })
     }
 
@@ -230,17 +315,27 @@ impl SpotMprisPlayer {
 
     #[dbus_interface(property)]
     pub fn shuffle(&self) -> bool {
-        false
+        self.state.shuffle()
     }
 
     #[dbus_interface(property)]
-    pub fn set_shuffle(&self, value: bool) {}
+    pub fn set_shuffle(&self, value: bool) {
+        if value != self.state.shuffle() {
+            let _ = self
+                .sender
+                .unbounded_send(PlaybackAction::ToggleShuffle.into());
+        }
+    }
 
     #[dbus_interface(property)]
     pub fn volume(&self) -> f64 {
-        0f64
+        self.state.volume()
     }
 
     #[dbus_interface(property)]
-    pub fn set_volume(&self, value: f64) {}
+    pub fn set_volume(&self, value: f64) {
+        let _ = self
+            .sender
+            .unbounded_send(PlaybackAction::SetVolume(value).into());
+    }
 }
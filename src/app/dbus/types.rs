@@ -1,8 +1,10 @@
-use std::convert::Into;
+use std::convert::{Into, TryFrom};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use zvariant::Type;
-use zvariant::{Dict, Signature, Str, Value};
+use zvariant::{Dict, ObjectPath, Signature, Str, Value};
+
+use crate::app::state::RepeatMode;
 
 fn boxed_value<'a, V: Into<Value<'a>>>(v: V) -> Value<'a> {
     Value::new(v.into())
@@ -31,6 +33,60 @@ impl From<PlaybackStatus> for Value<'_> {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopStatus {
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopStatus {
+    pub fn from_name(status: &str) -> Option<Self> {
+        match status {
+            "None" => Some(Self::None),
+            "Track" => Some(Self::Track),
+            "Playlist" => Some(Self::Playlist),
+            _ => None,
+        }
+    }
+}
+
+impl Type for LoopStatus {
+    fn signature() -> Signature<'static> {
+        Str::signature()
+    }
+}
+
+impl From<LoopStatus> for Value<'_> {
+    fn from(status: LoopStatus) -> Self {
+        match status {
+            LoopStatus::None => "None".into(),
+            LoopStatus::Track => "Track".into(),
+            LoopStatus::Playlist => "Playlist".into(),
+        }
+    }
+}
+
+impl From<RepeatMode> for LoopStatus {
+    fn from(mode: RepeatMode) -> Self {
+        match mode {
+            RepeatMode::Off => Self::None,
+            RepeatMode::All => Self::Playlist,
+            RepeatMode::One => Self::Track,
+        }
+    }
+}
+
+impl From<LoopStatus> for RepeatMode {
+    fn from(status: LoopStatus) -> Self {
+        match status {
+            LoopStatus::None => Self::Off,
+            LoopStatus::Playlist => Self::All,
+            LoopStatus::Track => Self::One,
+        }
+    }
+}
+
 struct PositionMicros {
     last_known_position: u128,
     last_resume_instant: Option<Instant>,
@@ -69,12 +125,30 @@ impl PositionMicros {
     }
 }
 
+// How far off the estimated position can be before clients are told about it
+const POSITION_TOLERANCE: u128 = 1_000_000;
+
+// Some displays choke on very long fields
+const MAX_TITLE_LEN: usize = 100;
+
+pub fn truncate_title(title: &str) -> String {
+    if title.chars().count() > MAX_TITLE_LEN {
+        let truncated = title.chars().take(MAX_TITLE_LEN - 1).collect::<String>();
+        format!("{}…", truncated.trim_end())
+    } else {
+        title.to_string()
+    }
+}
+
 #[derive(Clone)]
 pub struct TrackMetadata {
     pub id: String,
     pub length: u64,
     pub artist: Vec<String>,
     pub title: String,
+    pub album: String,
+    // A local file, clients don't all load remote images
+    pub art_url: Option<String>,
 }
 
 impl Type for TrackMetadata {
@@ -86,8 +160,11 @@ impl Type for TrackMetadata {
 impl From<TrackMetadata> for Value<'_> {
     fn from(meta: TrackMetadata) -> Self {
         let mut d = Dict::new(Str::signature(), Value::signature());
-        d.append("mpris:trackid".into(), boxed_value(meta.id))
-            .unwrap();
+        d.append(
+            "mpris:trackid".into(),
+            boxed_value(ObjectPath::try_from(meta.id).unwrap()),
+        )
+        .unwrap();
         d.append("mpris:length".into(), boxed_value(meta.length))
             .unwrap();
         d.append("xesam:title".into(), boxed_value(meta.title))
@@ -96,6 +173,12 @@ impl From<TrackMetadata> for Value<'_> {
             .unwrap();
         d.append("xesam:albumArtist".into(), boxed_value(meta.artist))
             .unwrap();
+        d.append("xesam:album".into(), boxed_value(meta.album))
+            .unwrap();
+        if let Some(art_url) = meta.art_url {
+            d.append("mpris:artUrl".into(), boxed_value(art_url))
+                .unwrap();
+        }
         Value::Dict(d)
     }
 }
@@ -106,6 +189,9 @@ struct MprisState {
     metadata: Option<TrackMetadata>,
     has_prev: bool,
     has_next: bool,
+    loop_status: LoopStatus,
+    shuffle: bool,
+    volume: f64,
 }
 
 #[derive(Clone)]
@@ -119,6 +205,9 @@ impl SharedMprisState {
             metadata: None,
             has_prev: false,
             has_next: false,
+            loop_status: LoopStatus::None,
+            shuffle: false,
+            volume: 1.0,
         })))
     }
 
@@ -153,6 +242,39 @@ impl SharedMprisState {
         }
     }
 
+    pub fn loop_status(&self) -> LoopStatus {
+        self.0
+            .lock()
+            .map(|s| s.loop_status)
+            .unwrap_or(LoopStatus::None)
+    }
+
+    pub fn set_loop_status(&self, loop_status: LoopStatus) {
+        if let Ok(mut state) = self.0.lock() {
+            (*state).loop_status = loop_status;
+        }
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.0.lock().ok().map(|s| s.shuffle).unwrap_or(false)
+    }
+
+    pub fn set_shuffle(&self, shuffle: bool) {
+        if let Ok(mut state) = self.0.lock() {
+            (*state).shuffle = shuffle;
+        }
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.0.lock().ok().map(|s| s.volume).unwrap_or(1.0)
+    }
+
+    pub fn set_volume(&self, volume: f64) {
+        if let Ok(mut state) = self.0.lock() {
+            (*state).volume = volume;
+        }
+    }
+
     pub fn set_current_track(&self, track: Option<TrackMetadata>) {
         if let Ok(mut state) = self.0.lock() {
             let playing = state.status == PlaybackStatus::Playing;
@@ -161,6 +283,24 @@ impl SharedMprisState {
         }
     }
 
+    // Unlike set_current_track, this keeps the current position
+    pub fn update_current_track(&self, track: Option<TrackMetadata>) {
+        if let Ok(mut state) = self.0.lock() {
+            (*state).metadata = track;
+        }
+    }
+
+    // The cover is cached after the track changed, by then another track might be playing
+    pub fn set_art_url(&self, track_id: &str, art_url: String) -> bool {
+        if let Ok(mut state) = self.0.lock() {
+            if let Some(metadata) = (*state).metadata.as_mut().filter(|m| m.id == track_id) {
+                metadata.art_url = Some(art_url);
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn position(&self) -> u128 {
         self.0
             .lock()
@@ -176,6 +316,20 @@ impl SharedMprisState {
         }
     }
 
+    // The player reports its position from time to time, which is only a seek
+    // as far as clients are concerned when the estimate was way off
+    pub fn sync_position(&self, position: u128) -> bool {
+        if let Ok(mut state) = self.0.lock() {
+            let estimate = state.position.current();
+            let playing = state.status == PlaybackStatus::Playing;
+            (*state).position.set(position, playing);
+            let drift = estimate.max(position) - estimate.min(position);
+            drift > POSITION_TOLERANCE
+        } else {
+            false
+        }
+    }
+
     pub fn set_playing(&self, status: PlaybackStatus) {
         if let Ok(mut state) = self.0.lock() {
             (*state).status = status;
@@ -193,3 +347,31 @@ impl SharedMprisState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_truncate_short_title() {
+        assert_eq!(truncate_title("Artist - Title"), "Artist - Title");
+    }
+
+    #[test]
+    fn test_truncate_long_title() {
+        let title = "é".repeat(150);
+        let truncated = truncate_title(&title);
+        assert_eq!(truncated.chars().count(), MAX_TITLE_LEN);
+        assert!(truncated.ends_with('…'));
+    }
+
+    #[test]
+    fn test_sync_position() {
+        let state = SharedMprisState::new();
+        state.set_position(5_000_000);
+        assert!(!state.sync_position(5_200_000));
+        assert!(state.sync_position(60_000_000));
+        assert_eq!(state.position(), 60_000_000);
+    }
+}
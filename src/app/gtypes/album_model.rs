@@ -38,6 +38,26 @@ impl AlbumModel {
             .map(|s| s.to_string())
     }
 
+    pub fn artist(&self) -> Option<String> {
+        self.get_property("artist")
+            .unwrap()
+            .get::<&str>()
+            .unwrap()
+            .map(|s| s.to_string())
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.get_property("album")
+            .unwrap()
+            .get::<&str>()
+            .unwrap()
+            .map(|s| s.to_string())
+    }
+
+    pub fn set_title(&self, title: &str) {
+        self.set_property("album", &title).unwrap();
+    }
+
     pub fn uri(&self) -> Option<String> {
         self.get_property("uri")
             .unwrap()
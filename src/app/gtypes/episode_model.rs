@@ -0,0 +1,273 @@
+use gio::prelude::*;
+use glib::glib_wrapper;
+use glib::subclass;
+use glib::subclass::prelude::*;
+use glib::translate::*;
+use glib::Value;
+
+use crate::app::components::utils::format_duration;
+
+glib_wrapper! {
+    pub struct EpisodeModel(Object<subclass::simple::InstanceStruct<imp::EpisodeModel>, subclass::simple::ClassStruct<imp::EpisodeModel>, EpisodeModelClass>);
+
+    match fn {
+        get_type => || imp::EpisodeModel::get_type().to_glib(),
+    }
+}
+
+// Constructor for new instances. This simply calls glib::Object::new() with
+// initial values for our properties and then returns the new instance
+impl EpisodeModel {
+    // The length is in milliseconds, the show notes are Pango markup
+    pub fn new(
+        id: &str,
+        title: &str,
+        description: &str,
+        show_notes: &str,
+        release_date: &str,
+        length: u32,
+        played: bool,
+    ) -> EpisodeModel {
+        glib::Object::new(
+            Self::static_type(),
+            &[
+                ("id", &id),
+                ("title", &title),
+                ("description", &description),
+                ("show-notes", &show_notes),
+                ("release-date", &release_date),
+                ("duration", &format_duration(length.into())),
+                ("played", &played),
+            ],
+        )
+        .expect("Failed to create")
+        .downcast()
+        .expect("Created with wrong type")
+    }
+
+    pub fn id(&self) -> Option<String> {
+        self.get_property("id")
+            .unwrap()
+            .get::<&str>()
+            .unwrap()
+            .map(|s| s.to_string())
+    }
+
+    pub fn set_played(&self, played: bool) {
+        self.set_property("played", &Value::from(&played))
+            .expect("set 'played' failed");
+    }
+
+    pub fn get_played(&self) -> bool {
+        self.get_property("played")
+            .unwrap()
+            .get::<bool>()
+            .unwrap()
+            .unwrap()
+    }
+
+    pub fn connect_played_local<F: Fn(&Self) + 'static>(&self, handler: F) {
+        self.connect_local("notify::played", true, move |values| {
+            if let Ok(Some(_self)) = values[0].get::<Self>() {
+                handler(&_self);
+            }
+            None
+        })
+        .expect("connecting to prop 'played' failed");
+    }
+}
+
+mod imp {
+
+    use super::*;
+    use glib::{glib_object_impl, glib_object_subclass};
+
+    use std::cell::RefCell;
+
+    // Static array for defining the properties of the new type.
+    static PROPERTIES: [subclass::Property; 7] = [
+        subclass::Property("id", |id| {
+            glib::ParamSpec::string(id, "id", "id", None, glib::ParamFlags::READWRITE)
+        }),
+        subclass::Property("title", |title| {
+            glib::ParamSpec::string(title, "Title", "Title", None, glib::ParamFlags::READWRITE)
+        }),
+        subclass::Property("description", |description| {
+            glib::ParamSpec::string(
+                description,
+                "Description",
+                "Description",
+                None,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+        subclass::Property("show-notes", |show_notes| {
+            glib::ParamSpec::string(
+                show_notes,
+                "Show notes",
+                "Show notes",
+                None,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+        subclass::Property("release-date", |release_date| {
+            glib::ParamSpec::string(
+                release_date,
+                "Release date",
+                "Release date",
+                None,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+        subclass::Property("duration", |duration| {
+            glib::ParamSpec::string(
+                duration,
+                "Duration",
+                "Duration",
+                None,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+        subclass::Property("played", |played| {
+            glib::ParamSpec::boolean(
+                played,
+                "Played",
+                "Played",
+                false,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+    ];
+
+    // This is the struct containing all state carried with
+    // the new type. Generally this has to make use of
+    // interior mutability.
+    pub struct EpisodeModel {
+        id: RefCell<Option<String>>,
+        title: RefCell<Option<String>>,
+        description: RefCell<Option<String>>,
+        show_notes: RefCell<Option<String>>,
+        release_date: RefCell<Option<String>>,
+        duration: RefCell<Option<String>>,
+        played: RefCell<bool>,
+    }
+
+    // ObjectSubclass is the trait that defines the new type and
+    // contains all information needed by the GObject type system,
+    // including the new type's name, parent type, etc.
+    impl ObjectSubclass for EpisodeModel {
+        // This type name must be unique per process.
+        const NAME: &'static str = "EpisodeModel";
+
+        // The parent type this one is inheriting from.
+        type ParentType = glib::Object;
+
+        // The C/FFI instance and class structs. The simple ones
+        // are enough in most cases and more is only needed to
+        // expose public instance fields to C APIs or to provide
+        // new virtual methods for subclasses of this type.
+        type Instance = subclass::simple::InstanceStruct<Self>;
+        type Class = subclass::simple::ClassStruct<Self>;
+
+        // This macro defines some boilerplate.
+        glib_object_subclass!();
+
+        // Called right before the first time an instance of the new
+        // type is created. Here class specific settings can be performed,
+        // including installation of properties and registration of signals
+        // for the new type.
+        fn class_init(klass: &mut Self::Class) {
+            klass.install_properties(&PROPERTIES);
+        }
+
+        // Called every time a new instance is created. This should return
+        // a new instance of our type with its basic values.
+        fn new() -> Self {
+            Self {
+                id: RefCell::new(None),
+                title: RefCell::new(None),
+                description: RefCell::new(None),
+                show_notes: RefCell::new(None),
+                release_date: RefCell::new(None),
+                duration: RefCell::new(None),
+                played: RefCell::new(false),
+            }
+        }
+    }
+
+    // Trait that is used to override virtual methods of glib::Object.
+    impl ObjectImpl for EpisodeModel {
+        // This macro defines some boilerplate.
+        glib_object_impl!();
+
+        // Called whenever a property is set on this instance. The id
+        // is the same as the index of the property in the PROPERTIES array.
+        fn set_property(&self, _obj: &glib::Object, id: usize, value: &glib::Value) {
+            let prop = &PROPERTIES[id];
+
+            match *prop {
+                subclass::Property("id", ..) => {
+                    let id = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.id.replace(id);
+                }
+                subclass::Property("title", ..) => {
+                    let title = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.title.replace(title);
+                }
+                subclass::Property("description", ..) => {
+                    let description = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.description.replace(description);
+                }
+                subclass::Property("show-notes", ..) => {
+                    let show_notes = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.show_notes.replace(show_notes);
+                }
+                subclass::Property("release-date", ..) => {
+                    let release_date = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.release_date.replace(release_date);
+                }
+                subclass::Property("duration", ..) => {
+                    let duration = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.duration.replace(duration);
+                }
+                subclass::Property("played", ..) => {
+                    let played = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`")
+                        .unwrap();
+                    self.played.replace(played);
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        // Called whenever a property is retrieved from this instance. The id
+        // is the same as the index of the property in the PROPERTIES array.
+        fn get_property(&self, _obj: &glib::Object, id: usize) -> Result<glib::Value, ()> {
+            let prop = &PROPERTIES[id];
+
+            match *prop {
+                subclass::Property("id", ..) => Ok(self.id.borrow().to_value()),
+                subclass::Property("title", ..) => Ok(self.title.borrow().to_value()),
+                subclass::Property("description", ..) => Ok(self.description.borrow().to_value()),
+                subclass::Property("show-notes", ..) => Ok(self.show_notes.borrow().to_value()),
+                subclass::Property("release-date", ..) => Ok(self.release_date.borrow().to_value()),
+                subclass::Property("duration", ..) => Ok(self.duration.borrow().to_value()),
+                subclass::Property("played", ..) => Ok(self.played.borrow().to_value()),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
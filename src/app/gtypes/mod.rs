@@ -6,3 +6,6 @@ pub use album_model::AlbumModel;
 
 mod artist_model;
 pub use artist_model::ArtistModel;
+
+mod episode_model;
+pub use episode_model::EpisodeModel;
@@ -5,6 +5,8 @@ use glib::subclass::prelude::*;
 use glib::translate::*;
 use glib::Value;
 
+use crate::app::components::utils::format_duration;
+
 glib_wrapper! {
     pub struct SongModel(Object<subclass::simple::InstanceStruct<imp::SongModel>, subclass::simple::ClassStruct<imp::SongModel>, SongModelClass>);
 
@@ -16,15 +18,27 @@ glib_wrapper! {
 // Constructor for new instances. This simply calls glib::Object::new() with
 // initial values for our two properties and then returns the new instance
 impl SongModel {
-    pub fn new(id: &str, index: u32, title: &str, artist: &str, duration: &str) -> SongModel {
+    // The length is in milliseconds, and a popularity of 0 means it's unknown
+    pub fn new(
+        id: &str,
+        index: u32,
+        title: &str,
+        artist: &str,
+        album: &str,
+        length: u32,
+        popularity: u32,
+    ) -> SongModel {
         glib::Object::new(
             Self::static_type(),
             &[
                 ("index", &index),
                 ("title", &title),
                 ("artist", &artist),
+                ("album", &album),
                 ("id", &id),
-                ("duration", &duration),
+                ("duration", &format_duration(length.into())),
+                ("length", &length),
+                ("popularity", &popularity),
             ],
         )
         .expect("Failed to create")
@@ -32,6 +46,11 @@ impl SongModel {
         .expect("Created with wrong type")
     }
 
+    pub fn set_index(&self, index: u32) {
+        self.set_property("index", &Value::from(&index))
+            .expect("set 'index' failed");
+    }
+
     pub fn set_playing(&self, is_playing: bool) {
         self.set_property("playing", &Value::from(&is_playing))
             .expect("set 'playing' failed");
@@ -45,6 +64,55 @@ impl SongModel {
             .unwrap()
     }
 
+    pub fn get_index(&self) -> u32 {
+        self.get_uint("index")
+    }
+
+    pub fn get_title(&self) -> String {
+        self.get_string("title")
+    }
+
+    pub fn get_artist(&self) -> String {
+        self.get_string("artist")
+    }
+
+    pub fn get_album(&self) -> String {
+        self.get_string("album")
+    }
+
+    pub fn get_length(&self) -> u32 {
+        self.get_uint("length")
+    }
+
+    pub fn get_popularity(&self) -> u32 {
+        self.get_uint("popularity")
+    }
+
+    fn get_uint(&self, name: &str) -> u32 {
+        self.get_property(name)
+            .unwrap()
+            .get::<u32>()
+            .unwrap()
+            .unwrap_or(0)
+    }
+
+    fn get_string(&self, name: &str) -> String {
+        self.get_property(name)
+            .unwrap()
+            .get::<&str>()
+            .unwrap()
+            .unwrap_or("")
+            .to_string()
+    }
+
+    // Case-insensitive, against the title, artist and album
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [self.get_title(), self.get_artist(), self.get_album()]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
+    }
+
     pub fn get_id(&self) -> String {
         self.get_property("id")
             .unwrap()
@@ -73,7 +141,7 @@ mod imp {
     use std::cell::RefCell;
 
     // Static array for defining the properties of the new type.
-    static PROPERTIES: [subclass::Property; 6] = [
+    static PROPERTIES: [subclass::Property; 9] = [
         subclass::Property("index", |index| {
             glib::ParamSpec::uint(
                 index,
@@ -97,6 +165,9 @@ mod imp {
                 glib::ParamFlags::READWRITE,
             )
         }),
+        subclass::Property("album", |album| {
+            glib::ParamSpec::string(album, "Album", "Album", None, glib::ParamFlags::READWRITE)
+        }),
         subclass::Property("id", |id| {
             glib::ParamSpec::string(id, "id", "id", None, glib::ParamFlags::READWRITE)
         }),
@@ -109,6 +180,28 @@ mod imp {
                 glib::ParamFlags::READWRITE,
             )
         }),
+        subclass::Property("length", |length| {
+            glib::ParamSpec::uint(
+                length,
+                "Length",
+                "Length in milliseconds",
+                0,
+                u32::MAX,
+                0,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
+        subclass::Property("popularity", |popularity| {
+            glib::ParamSpec::uint(
+                popularity,
+                "Popularity",
+                "Popularity",
+                0,
+                100,
+                0,
+                glib::ParamFlags::READWRITE,
+            )
+        }),
         subclass::Property("playing", |playing| {
             glib::ParamSpec::boolean(
                 playing,
@@ -127,8 +220,11 @@ mod imp {
         index: RefCell<u32>,
         title: RefCell<Option<String>>,
         artist: RefCell<Option<String>>,
+        album: RefCell<Option<String>>,
         id: RefCell<Option<String>>,
         duration: RefCell<Option<String>>,
+        length: RefCell<u32>,
+        popularity: RefCell<u32>,
         playing: RefCell<bool>,
     }
 
@@ -167,9 +263,12 @@ mod imp {
                 index: RefCell::new(1),
                 title: RefCell::new(None),
                 artist: RefCell::new(None),
+                album: RefCell::new(None),
                 id: RefCell::new(None),
                 playing: RefCell::new(false),
                 duration: RefCell::new(None),
+                length: RefCell::new(0),
+                popularity: RefCell::new(0),
             }
         }
     }
@@ -204,6 +303,12 @@ mod imp {
                         .expect("type conformity checked by `Object::set_property`");
                     self.artist.replace(artist);
                 }
+                subclass::Property("album", ..) => {
+                    let album = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`");
+                    self.album.replace(album);
+                }
                 subclass::Property("id", ..) => {
                     let id = value
                         .get()
@@ -216,6 +321,20 @@ mod imp {
                         .expect("type conformity checked by `Object::set_property`");
                     self.duration.replace(dur);
                 }
+                subclass::Property("length", ..) => {
+                    let length = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`")
+                        .unwrap();
+                    self.length.replace(length);
+                }
+                subclass::Property("popularity", ..) => {
+                    let popularity = value
+                        .get()
+                        .expect("type conformity checked by `Object::set_property`")
+                        .unwrap();
+                    self.popularity.replace(popularity);
+                }
                 subclass::Property("playing", ..) => {
                     let playing = value
                         .get()
@@ -236,8 +355,11 @@ mod imp {
                 subclass::Property("index", ..) => Ok(self.index.borrow().to_value()),
                 subclass::Property("title", ..) => Ok(self.title.borrow().to_value()),
                 subclass::Property("artist", ..) => Ok(self.artist.borrow().to_value()),
+                subclass::Property("album", ..) => Ok(self.album.borrow().to_value()),
                 subclass::Property("id", ..) => Ok(self.id.borrow().to_value()),
                 subclass::Property("duration", ..) => Ok(self.duration.borrow().to_value()),
+                subclass::Property("length", ..) => Ok(self.length.borrow().to_value()),
+                subclass::Property("popularity", ..) => Ok(self.popularity.borrow().to_value()),
                 subclass::Property("playing", ..) => Ok(self.playing.borrow().to_value()),
                 _ => unimplemented!(),
             }
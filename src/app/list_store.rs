@@ -1,5 +1,6 @@
 use gio::prelude::*;
 use gio::ListModelExt;
+use std::cmp::Ordering;
 use std::iter::Iterator;
 use std::marker::PhantomData;
 
@@ -31,6 +32,16 @@ where
         self.store.append(&element);
     }
 
+    // Appended at once, so that views bound to the store only update once
+    pub fn extend(&mut self, content: Vec<GType>) {
+        let upcast_vec: Vec<glib::Object> = content
+            .into_iter()
+            .map(|e| e.upcast::<glib::Object>())
+            .collect();
+        let position = self.store.get_n_items();
+        self.store.splice(position, 0, &upcast_vec[..]);
+    }
+
     pub fn replace_all(&mut self, content: Vec<GType>) {
         let upcast_vec: Vec<glib::Object> = content
             .into_iter()
@@ -48,6 +59,18 @@ where
         self.store.remove(position);
     }
 
+    pub fn sort_by<F>(&mut self, comparison: F)
+    where
+        F: Fn(&GType, &GType) -> Ordering,
+    {
+        self.store.sort(|a, b| {
+            comparison(
+                a.downcast_ref::<GType>().unwrap(),
+                b.downcast_ref::<GType>().unwrap(),
+            )
+        });
+    }
+
     pub fn get(&self, index: u32) -> GType {
         self.store
             .get_object(index)
@@ -5,6 +5,11 @@ use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient, Response};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hasher;
 use std::io::{Error, ErrorKind, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Shared by all loaders, so that requests already started are affected too
+static NETWORK_ENABLED: AtomicBool = AtomicBool::new(true);
 
 struct LocalPixbufLoader<'a>(&'a PixbufLoader);
 
@@ -35,6 +40,15 @@ impl ImageLoader {
         }
     }
 
+    // When disabled, only images that are already cached get loaded
+    pub fn set_network_enabled(enabled: bool) {
+        NETWORK_ENABLED.store(enabled, Ordering::Relaxed);
+    }
+
+    fn network_enabled() -> bool {
+        NETWORK_ENABLED.load(Ordering::Relaxed)
+    }
+
     fn resource_for(url: &str, ext: &str) -> String {
         let mut hasher = DefaultHasher::new();
         hasher.write(url.as_bytes());
@@ -51,6 +65,39 @@ impl ImageLoader {
         client.get_async(url).await.ok()
     }
 
+    // Downloads the image and caches it, unless only cached images may be loaded
+    async fn fetch(&self, url: &str, resource: &str) -> Option<Vec<u8>> {
+        if !Self::network_enabled() {
+            return None;
+        }
+        let mut resp = Self::get_image(url).await?;
+        // The setting might have changed while waiting for the response
+        if !Self::network_enabled() {
+            return None;
+        }
+        let mut buffer = vec![];
+        resp.copy_to(&mut buffer).await.ok()?;
+        self.cache
+            .write_cache_file(resource, &buffer[..], CacheExpiry::Never)
+            .await
+            .ok()?;
+        Some(buffer)
+    }
+
+    // Where the image is once loaded, it might not be there yet
+    pub fn cached_path(&self, url: &str, ext: &str) -> PathBuf {
+        self.cache.cache_path(&Self::resource_for(url, ext))
+    }
+
+    // For those that read images from disk rather than as a Pixbuf
+    pub async fn cache_remote(&self, url: &str, ext: &str) -> Option<PathBuf> {
+        let path = self.cached_path(url, ext);
+        if !path.exists() {
+            self.fetch(url, &Self::resource_for(url, ext)).await?;
+        }
+        Some(path)
+    }
+
     pub async fn load_remote(
         &self,
         url: &str,
@@ -63,6 +110,15 @@ impl ImageLoader {
         pixbuf_loader.set_size(width, height);
         let mut loader = LocalPixbufLoader(&pixbuf_loader);
 
+        // Local images, such as a cover that is being uploaded, are read as they are
+        if url.starts_with("file://") {
+            let (path, _) = glib::filename_from_uri(url).ok()?;
+            let buffer = async_std::fs::read(path).await.ok()?;
+            loader.write_all(&buffer[..]).ok()?;
+            pixbuf_loader.close().ok()?;
+            return pixbuf_loader.get_pixbuf();
+        }
+
         match self
             .cache
             .read_cache_file(&resource[..], CachePolicy::IgnoreExpiry)
@@ -72,15 +128,8 @@ impl ImageLoader {
                 loader.write_all(&buffer[..]).ok()?;
             }
             _ => {
-                if let Some(mut resp) = Self::get_image(url).await {
-                    let mut buffer = vec![];
-                    resp.copy_to(&mut buffer).await.ok()?;
-                    loader.write_all(&buffer[..]).ok()?;
-                    self.cache
-                        .write_cache_file(&resource[..], &buffer[..], CacheExpiry::Never)
-                        .await
-                        .ok()?;
-                }
+                let buffer = self.fetch(url, &resource[..]).await?;
+                loader.write_all(&buffer[..]).ok()?;
             }
         };
 
@@ -26,6 +26,11 @@ pub use state::{AppAction, AppEvent, AppModel, AppState, BrowserAction, BrowserE
 
 pub mod credentials;
 pub mod loader;
+pub mod playlist_files;
+pub mod show_notes;
+
+pub mod settings;
+use settings::SpotSettings;
 
 pub struct App {
     builder: gtk::Builder,
@@ -37,13 +42,13 @@ pub struct App {
 
 impl App {
     pub fn new(builder: gtk::Builder, sender: UnboundedSender<AppAction>, worker: Worker) -> Self {
-        let state = AppState::new();
+        let state = AppState::new(SpotSettings::new_from_gsettings());
         let spotify_client = Arc::new(CachedSpotifyClient::new());
         let model = Rc::new(AppModel::new(state, spotify_client));
 
         let components: Vec<Box<dyn EventListener>> = vec![
-            App::make_player_notifier(sender.clone()),
-            App::make_dbus(Rc::clone(&model), sender.clone()),
+            App::make_player_notifier(Rc::clone(&model), sender.clone()),
+            App::make_dbus(Rc::clone(&model), sender.clone(), worker.clone()),
         ];
 
         Self {
@@ -66,6 +71,8 @@ impl App {
             App::make_window(builder, worker.clone()),
             App::make_selection_editor(builder, Rc::clone(model), dispatcher.box_clone()),
             App::make_playback_control(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_sleep_timer(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_repeat(builder, Rc::clone(model), dispatcher.box_clone()),
             App::make_playback_info(
                 builder,
                 Rc::clone(model),
@@ -79,23 +86,39 @@ impl App {
                 dispatcher.box_clone(),
                 worker.clone(),
             ),
-            App::make_search_bar(builder, dispatcher.box_clone()),
+            App::make_search_bar(builder, Rc::clone(model), dispatcher.box_clone()),
             App::make_user_menu(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_settings(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_up_next_notifier(builder, Rc::clone(model), dispatcher.box_clone()),
+            App::make_session_keeper(Rc::clone(model), dispatcher.box_clone()),
+            App::make_shuffle_keeper(Rc::clone(model)),
+            App::make_autoplay(Rc::clone(model), dispatcher.box_clone()),
+            App::make_radio(Rc::clone(model), dispatcher.box_clone()),
+            App::make_song_credits_loader(Rc::clone(model), dispatcher.box_clone()),
             App::make_notification(builder, dispatcher),
         ];
 
         self.components.append(&mut components);
     }
 
-    fn make_player_notifier(sender: UnboundedSender<AppAction>) -> Box<impl EventListener> {
-        Box::new(PlayerNotifier::new(backend::start_player_service(sender)))
+    fn make_player_notifier(
+        app_model: Rc<AppModel>,
+        sender: UnboundedSender<AppAction>,
+    ) -> Box<impl EventListener> {
+        Box::new(PlayerNotifier::new(
+            app_model,
+            backend::start_player_service(sender),
+        ))
     }
 
     fn make_dbus(
         app_model: Rc<AppModel>,
         sender: UnboundedSender<AppAction>,
+        worker: Worker,
     ) -> Box<impl EventListener> {
-        Box::new(dbus::start_dbus_server(app_model, sender).expect("could not start server"))
+        Box::new(
+            dbus::start_dbus_server(app_model, sender, worker).expect("could not start server"),
+        )
     }
 
     fn make_window(builder: &gtk::Builder, worker: Worker) -> Box<impl EventListener> {
@@ -210,19 +233,47 @@ impl App {
         Box::new(PlaybackControl::new(model, widget))
     }
 
+    fn make_sleep_timer(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<SleepTimerControl> {
+        let button: gtk::MenuButton = builder.get_object("sleep_timer").unwrap();
+        let remaining: gtk::Label = builder.get_object("sleep_timer_remaining").unwrap();
+        let model = SleepTimerControlModel::new(app_model, dispatcher);
+        Box::new(SleepTimerControl::new(model, button, remaining))
+    }
+
+    fn make_repeat(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<RepeatControl> {
+        let button: gtk::Button = builder.get_object("repeat").unwrap();
+        let model = RepeatControlModel::new(app_model, dispatcher);
+        Box::new(RepeatControl::new(model, button))
+    }
+
     fn make_search_bar(
         builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
         dispatcher: Box<dyn ActionDispatcher>,
     ) -> Box<SearchBar> {
         let search_button: gtk::ToggleButton = builder.get_object("search_button").unwrap();
         let search_entry: gtk::SearchEntry = builder.get_object("search_entry").unwrap();
         let search_bar: libhandy::SearchBar = builder.get_object("search_bar").unwrap();
-        let model = SearchBarModel(dispatcher);
+        let suggestions = SearchSuggestions {
+            popover: builder.get_object("search_suggestions").unwrap(),
+            list: builder.get_object("search_suggestion_list").unwrap(),
+            clear_history: builder.get_object("clear_search_history").unwrap(),
+        };
+        let model = SearchBarModel::new(app_model, dispatcher);
         Box::new(SearchBar::new(
             model,
             search_button,
             search_bar,
             search_entry,
+            suggestions,
         ))
     }
 
@@ -238,6 +289,54 @@ impl App {
         Box::new(user_menu)
     }
 
+    fn make_settings(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<Settings> {
+        let parent: gtk::Window = builder.get_object("window").unwrap();
+        let model = SettingsModel::new(app_model, dispatcher);
+        Box::new(Settings::new(parent, model))
+    }
+
+    fn make_up_next_notifier(
+        builder: &gtk::Builder,
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<UpNextNotifier> {
+        let window: gtk::Window = builder.get_object("window").unwrap();
+        Box::new(UpNextNotifier::new(app_model, dispatcher, window))
+    }
+
+    fn make_session_keeper(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<SessionKeeper> {
+        Box::new(SessionKeeper::new(app_model, dispatcher))
+    }
+
+    fn make_autoplay(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<Autoplay> {
+        Box::new(Autoplay::new(app_model, dispatcher))
+    }
+
+    fn make_radio(app_model: Rc<AppModel>, dispatcher: Box<dyn ActionDispatcher>) -> Box<Radio> {
+        Box::new(Radio::new(app_model, dispatcher))
+    }
+
+    fn make_shuffle_keeper(app_model: Rc<AppModel>) -> Box<ShuffleKeeper> {
+        Box::new(ShuffleKeeper::new(app_model))
+    }
+
+    fn make_song_credits_loader(
+        app_model: Rc<AppModel>,
+        dispatcher: Box<dyn ActionDispatcher>,
+    ) -> Box<SongCreditsLoader> {
+        Box::new(SongCreditsLoader::new(app_model, dispatcher))
+    }
+
     fn make_notification(
         builder: &gtk::Builder,
         dispatcher: Box<dyn ActionDispatcher>,
@@ -245,8 +344,9 @@ impl App {
         let root: gtk::Box = builder.get_object("notification").unwrap();
         let content: gtk::Label = builder.get_object("notification_content").unwrap();
         let close: gtk::Button = builder.get_object("close_notification").unwrap();
+        let undo: gtk::Button = builder.get_object("notification_undo").unwrap();
         let model = NotificationModel::new(dispatcher);
-        Box::new(Notification::new(model, root, content, close))
+        Box::new(Notification::new(model, root, content, close, undo))
     }
 
     fn handle(&mut self, message: AppAction) {
@@ -1,5 +1,7 @@
 pub use super::gtypes::*;
-use crate::app::components::utils::format_duration;
+use super::show_notes;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet};
 use std::convert::From;
 
 impl From<&AlbumDescription> for AlbumModel {
@@ -31,6 +33,48 @@ impl From<PlaylistDescription> for AlbumModel {
     }
 }
 
+impl From<&CategoryDescription> for AlbumModel {
+    fn from(category: &CategoryDescription) -> Self {
+        AlbumModel::new("", &category.name, &category.icon, &category.id)
+    }
+}
+
+impl From<&PodcastDescription> for AlbumModel {
+    fn from(podcast: &PodcastDescription) -> Self {
+        AlbumModel::new(&podcast.publisher, &podcast.name, &podcast.art, &podcast.id)
+    }
+}
+
+impl From<PodcastDescription> for AlbumModel {
+    fn from(podcast: PodcastDescription) -> Self {
+        Self::from(&podcast)
+    }
+}
+
+impl From<&EpisodeDescription> for EpisodeModel {
+    fn from(episode: &EpisodeDescription) -> Self {
+        let show_notes = match episode.html_description.as_ref() {
+            Some(html) => show_notes::html_to_markup(html),
+            None => show_notes::text_to_markup(&episode.description),
+        };
+        EpisodeModel::new(
+            &episode.id,
+            &episode.title,
+            &episode.description,
+            &show_notes,
+            &episode.release_date,
+            episode.duration,
+            episode.fully_played,
+        )
+    }
+}
+
+impl From<EpisodeDescription> for EpisodeModel {
+    fn from(episode: EpisodeDescription) -> Self {
+        Self::from(&episode)
+    }
+}
+
 impl SongDescription {
     pub fn to_song_model(&self, position: usize) -> SongModel {
         SongModel::new(
@@ -38,7 +82,9 @@ impl SongDescription {
             (position + 1) as u32,
             &self.title,
             &self.artists_name(),
-            &format_duration(self.duration.into()),
+            &self.album.name,
+            self.duration,
+            self.popularity.unwrap_or(0),
         )
     }
 }
@@ -65,6 +111,46 @@ pub struct AlbumRef {
 pub struct SearchResults {
     pub albums: Vec<AlbumDescription>,
     pub artists: Vec<ArtistSummary>,
+    pub songs: Vec<SongDescription>,
+    pub playlists: Vec<PlaylistDescription>,
+    pub podcasts: Vec<PodcastDescription>,
+    pub episodes: Vec<EpisodeDescription>,
+}
+
+// Which type of search results is shown, or all of them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchFilter {
+    All,
+    Songs,
+    Albums,
+    Artists,
+    Playlists,
+    Podcasts,
+    Episodes,
+}
+
+impl SearchFilter {
+    // Every filter but All, each type of results being paged on its own
+    pub const TYPES: [SearchFilter; 6] = [
+        Self::Songs,
+        Self::Albums,
+        Self::Artists,
+        Self::Playlists,
+        Self::Podcasts,
+        Self::Episodes,
+    ];
+
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "songs" => Self::Songs,
+            "albums" => Self::Albums,
+            "artists" => Self::Artists,
+            "playlists" => Self::Playlists,
+            "podcasts" => Self::Podcasts,
+            "episodes" => Self::Episodes,
+            _ => Self::All,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -75,6 +161,10 @@ pub struct AlbumDescription {
     pub art: Option<String>,
     pub songs: Vec<SongDescription>,
     pub is_liked: bool,
+    // Only known once the full album was fetched, the date as precise as Spotify has it
+    pub release_date: Option<String>,
+    pub label: Option<String>,
+    pub copyright: Option<String>,
 }
 
 impl AlbumDescription {
@@ -85,6 +175,11 @@ impl AlbumDescription {
             .collect::<Vec<String>>()
             .join(", ")
     }
+
+    // In milliseconds
+    pub fn duration(&self) -> u32 {
+        self.songs.iter().map(|song| song.duration).sum()
+    }
 }
 
 impl PartialEq for AlbumDescription {
@@ -99,9 +194,23 @@ impl Eq for AlbumDescription {}
 pub struct PlaylistDescription {
     pub id: String,
     pub title: String,
+    pub description: String,
     pub art: Option<String>,
     pub songs: Vec<SongDescription>,
     pub owner: UserRef,
+    pub collaborative: bool,
+    // Song ids to the name of whoever added them, only kept for collaborative playlists
+    pub added_by: HashMap<String, String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PlaylistFolder {
+    pub id: String,
+    pub name: String,
+    // 0 for folders that aren't inside another one
+    pub depth: usize,
+    // Including the playlists of the folders inside this one
+    pub playlist_ids: Vec<String>,
 }
 
 #[derive(Clone, Debug)]
@@ -112,6 +221,40 @@ pub struct PodcastDescription {
     pub publisher: String,
 }
 
+// A podcast with its first episodes, as shown on its page
+#[derive(Clone, Debug)]
+pub struct ShowDescription {
+    pub id: String,
+    pub name: String,
+    pub publisher: String,
+    pub description: String,
+    pub art: Option<String>,
+    pub episodes: Vec<EpisodeDescription>,
+    pub is_followed: bool,
+}
+
+// A group of playlists picked by Spotify, e.g. Chill or Rock
+#[derive(Clone, Debug)]
+pub struct CategoryDescription {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EpisodeDescription {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    // The same with links and formatting, missing from older cached responses
+    pub html_description: Option<String>,
+    // As given by Spotify, e.g. 2021-03-14, possibly less precise
+    pub release_date: String,
+    pub duration: u32,
+    // As reported by Spotify from the listening progress
+    pub fully_played: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct SongDescription {
     pub id: String,
@@ -120,6 +263,11 @@ pub struct SongDescription {
     pub album: AlbumRef,
     pub duration: u32,
     pub art: Option<String>,
+    pub explicit: bool,
+    // From 0 to 100, based on how much a song was played recently
+    pub popularity: Option<u32>,
+    // Seconds since the epoch, only known for liked songs
+    pub added_at: Option<i64>,
 }
 
 impl SongDescription {
@@ -132,12 +280,303 @@ impl SongDescription {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct PlayedSong {
+    pub song: SongDescription,
+    // Seconds since the epoch
+    pub played_at: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecentKind {
+    Album,
+    Playlist,
+    Show,
+}
+
+// An album, playlist or show songs were recently played from
+#[derive(Clone, Debug)]
+pub struct RecentContext {
+    pub id: String,
+    pub kind: RecentKind,
+    pub title: String,
+    // The artists of an album, the owner of a playlist or the publisher of a show
+    pub subtitle: String,
+    pub art: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mood {
+    Energetic,
+    Chill,
+    Happy,
+    Melancholic,
+    Danceable,
+}
+
+impl Mood {
+    pub const ALL: [Mood; 5] = [
+        Mood::Energetic,
+        Mood::Chill,
+        Mood::Happy,
+        Mood::Melancholic,
+        Mood::Danceable,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Energetic => "energetic",
+            Self::Chill => "chill",
+            Self::Happy => "happy",
+            Self::Melancholic => "melancholic",
+            Self::Danceable => "danceable",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|mood| mood.id() == id)
+    }
+
+    fn matches(self, features: &SongFeatures) -> bool {
+        match self {
+            Self::Energetic => features.energy > 0.7,
+            Self::Chill => features.energy < 0.4,
+            Self::Happy => features.valence > 0.7,
+            Self::Melancholic => features.valence < 0.3,
+            Self::Danceable => features.danceability > 0.7,
+        }
+    }
+}
+
+// A condition the songs of a smart playlist have to meet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SmartRule {
+    LikedWithinDays(u32),
+    ByFollowedArtist,
+    // In seconds
+    ShorterThan(u32),
+    LongerThan(u32),
+}
+
+impl SmartRule {
+    // Such as "liked-within:30" or "followed-artists"
+    pub fn id(self) -> String {
+        match self {
+            Self::LikedWithinDays(days) => format!("liked-within:{}", days),
+            Self::ByFollowedArtist => "followed-artists".to_string(),
+            Self::ShorterThan(seconds) => format!("shorter-than:{}", seconds),
+            Self::LongerThan(seconds) => format!("longer-than:{}", seconds),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        let mut parts = id.splitn(2, ':');
+        let name = parts.next()?;
+        let value = parts.next().map(|value| value.parse::<u32>().ok());
+        match (name, value) {
+            ("liked-within", Some(Some(days))) => Some(Self::LikedWithinDays(days)),
+            ("followed-artists", None) => Some(Self::ByFollowedArtist),
+            ("shorter-than", Some(Some(seconds))) => Some(Self::ShorterThan(seconds)),
+            ("longer-than", Some(Some(seconds))) => Some(Self::LongerThan(seconds)),
+            _ => None,
+        }
+    }
+
+    fn matches(self, song: &SongDescription, followed: &HashSet<&str>, now: i64) -> bool {
+        match self {
+            Self::LikedWithinDays(days) => song
+                .added_at
+                .map(|added_at| now - added_at <= days as i64 * 86400)
+                .unwrap_or(false),
+            Self::ByFollowedArtist => song
+                .artists
+                .iter()
+                .any(|artist| followed.contains(&artist.id[..])),
+            Self::ShorterThan(seconds) => song.duration < seconds * 1000,
+            Self::LongerThan(seconds) => song.duration > seconds * 1000,
+        }
+    }
+}
+
+// A playlist of the liked songs meeting all of its rules, kept in the settings
+#[derive(Clone, Debug, PartialEq)]
+pub struct SmartPlaylist {
+    pub name: String,
+    pub rules: Vec<SmartRule>,
+}
+
+impl SmartPlaylist {
+    // followed holds the ids of followed artists, now is in seconds since the epoch
+    pub fn matches(&self, song: &SongDescription, followed: &HashSet<&str>, now: i64) -> bool {
+        self.rules
+            .iter()
+            .all(|rule| rule.matches(song, followed, now))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SongSort {
+    // The order in which songs were received, e.g. most recently liked first
+    Default,
+    Title,
+    Artist,
+    Album,
+    Duration,
+    // Most popular first
+    Popularity,
+}
+
+impl SongSort {
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "title" => Self::Title,
+            "artist" => Self::Artist,
+            "album" => Self::Album,
+            "duration" => Self::Duration,
+            "popularity" => Self::Popularity,
+            _ => Self::Default,
+        }
+    }
+
+    pub fn sort(self, songs: &mut Vec<&SongDescription>) {
+        match self {
+            Self::Default => {}
+            Self::Title => songs.sort_by_cached_key(|s| s.title.to_lowercase()),
+            Self::Artist => songs.sort_by_cached_key(|s| s.artists_name().to_lowercase()),
+            Self::Album => songs.sort_by_cached_key(|s| s.album.name.to_lowercase()),
+            Self::Duration => songs.sort_by_key(|s| s.duration),
+            Self::Popularity => songs.sort_by_key(|s| Reverse(s.popularity.unwrap_or(0))),
+        }
+    }
+
+    // Same order as sort, for songs already in a list
+    pub fn compare(self, a: &SongModel, b: &SongModel) -> Ordering {
+        match self {
+            Self::Default => a.get_index().cmp(&b.get_index()),
+            Self::Title => compare_lowercase(&a.get_title(), &b.get_title()),
+            Self::Artist => compare_lowercase(&a.get_artist(), &b.get_artist()),
+            Self::Album => compare_lowercase(&a.get_album(), &b.get_album()),
+            Self::Duration => a.get_length().cmp(&b.get_length()),
+            Self::Popularity => b.get_popularity().cmp(&a.get_popularity()),
+        }
+    }
+}
+
+fn compare_lowercase(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+// Names of who took part in a song, when Spotify knows
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SongCredits {
+    pub performers: Vec<String>,
+    pub writers: Vec<String>,
+    pub producers: Vec<String>,
+}
+
+impl SongCredits {
+    pub fn is_empty(&self) -> bool {
+        self.performers.is_empty() && self.writers.is_empty() && self.producers.is_empty()
+    }
+}
+
+// A subset of the audio features Spotify computes for each track, all between 0 and 1
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SongFeatures {
+    pub energy: f32,
+    pub valence: f32,
+    pub danceability: f32,
+}
+
+impl SongFeatures {
+    pub fn moods(&self) -> Vec<Mood> {
+        Mood::ALL
+            .iter()
+            .copied()
+            .filter(|mood| mood.matches(self))
+            .collect()
+    }
+
+    pub fn has_mood(&self, mood: Mood) -> bool {
+        mood.matches(self)
+    }
+
+    pub fn describe(&self) -> Option<String> {
+        let moods = self.moods();
+        if moods.is_empty() {
+            None
+        } else {
+            Some(
+                moods
+                    .iter()
+                    .map(|mood| mood.id())
+                    .collect::<Vec<&str>>()
+                    .join(" · "),
+            )
+        }
+    }
+}
+
+// Bounds in milliseconds, both inclusive
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DurationRange {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+}
+
+impl DurationRange {
+    pub fn is_active(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    pub fn contains(&self, duration: u32) -> bool {
+        self.min.map(|min| duration >= min).unwrap_or(true)
+            && self.max.map(|max| duration <= max).unwrap_or(true)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ArtistDescription {
     pub id: String,
     pub name: String,
+    // The first page of each group of releases
     pub albums: Vec<AlbumDescription>,
+    pub singles: Vec<AlbumDescription>,
+    pub compilations: Vec<AlbumDescription>,
+    pub appears_on: Vec<AlbumDescription>,
     pub top_tracks: Vec<SongDescription>,
+    pub related_artists: Vec<ArtistSummary>,
+    pub is_followed: bool,
+}
+
+// Which releases of an artist are shown, each group being paged on its own
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReleaseGroup {
+    Albums,
+    // Including EPs
+    Singles,
+    Compilations,
+    // Other artists' releases featuring the artist, shown apart from the discography
+    AppearsOn,
+}
+
+impl ReleaseGroup {
+    pub const GROUPS: [ReleaseGroup; 4] = [
+        Self::Albums,
+        Self::Singles,
+        Self::Compilations,
+        Self::AppearsOn,
+    ];
+
+    // As named by the Spotify API
+    pub fn api_name(self) -> &'static str {
+        match self {
+            Self::Albums => "album",
+            Self::Singles => "single",
+            Self::Compilations => "compilation",
+            Self::AppearsOn => "appears_on",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,448 @@
+use serde_json::json;
+use std::path::Path;
+
+use crate::app::models::{PlaylistDescription, SongDescription};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaylistFileFormat {
+    M3u,
+    Csv,
+    Json,
+}
+
+impl PlaylistFileFormat {
+    pub const ALL: [PlaylistFileFormat; 3] = [Self::M3u, Self::Csv, Self::Json];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::M3u => "m3u",
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::M3u => "M3U playlist",
+            Self::Csv => "CSV spreadsheet",
+            Self::Json => "JSON document",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match &extension[..] {
+            "m3u" | "m3u8" => Some(Self::M3u),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+fn track_uri(song: &SongDescription) -> String {
+    format!("spotify:track:{}", song.id)
+}
+
+// Fields holding a separator, a quote or a line break are quoted, quotes are doubled
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Extended M3U, with players that know of Spotify URIs able to resolve the entries
+fn to_m3u(playlist: &PlaylistDescription) -> String {
+    let mut lines = vec![
+        "#EXTM3U".to_string(),
+        format!("#PLAYLIST:{}", playlist.title),
+    ];
+    for song in playlist.songs.iter() {
+        lines.push(format!(
+            "#EXTINF:{},{} - {}",
+            song.duration / 1000,
+            song.artists_name(),
+            song.title
+        ));
+        lines.push(track_uri(song));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn to_csv(playlist: &PlaylistDescription) -> String {
+    let mut lines = vec!["title,artists,album,duration_ms,uri".to_string()];
+    for song in playlist.songs.iter() {
+        let fields = [
+            csv_field(&song.title),
+            csv_field(&song.artists_name()),
+            csv_field(&song.album.name),
+            song.duration.to_string(),
+            track_uri(song),
+        ];
+        lines.push(fields.join(","));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn to_json(playlist: &PlaylistDescription) -> String {
+    let tracks = playlist
+        .songs
+        .iter()
+        .map(|song| {
+            json!({
+                "title": song.title,
+                "artists": song.artists.iter().map(|a| &a.name).collect::<Vec<&String>>(),
+                "album": song.album.name,
+                "duration_ms": song.duration,
+                "uri": track_uri(song),
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+    let document = json!({
+        "title": playlist.title,
+        "description": playlist.description,
+        "owner": playlist.owner.display_name,
+        "uri": format!("spotify:playlist:{}", playlist.id),
+        "tracks": tracks,
+    });
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+pub fn export_playlist(playlist: &PlaylistDescription, format: PlaylistFileFormat) -> String {
+    match format {
+        PlaylistFileFormat::M3u => to_m3u(playlist),
+        PlaylistFileFormat::Csv => to_csv(playlist),
+        PlaylistFileFormat::Json => to_json(playlist),
+    }
+}
+
+// A song listed in an imported file, only known by its id if it comes from Spotify
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportEntry {
+    pub id: Option<String>,
+    pub title: String,
+    pub artists: String,
+}
+
+impl ImportEntry {
+    pub fn describe(&self) -> String {
+        if self.artists.is_empty() {
+            self.title.clone()
+        } else {
+            format!("{} - {}", self.artists, self.title)
+        }
+    }
+
+    pub fn query(&self) -> String {
+        format!("{} {}", self.title, self.artists)
+    }
+
+    // Same title, by one of the artists listed, ignoring case
+    pub fn is_exact_match(&self, song: &SongDescription) -> bool {
+        let title = self.title.trim().to_lowercase();
+        let artists = self.artists.to_lowercase();
+        song.title.trim().to_lowercase() == title
+            && (artists.is_empty()
+                || song
+                    .artists
+                    .iter()
+                    .any(|a| artists.contains(&a.name.to_lowercase())))
+    }
+}
+
+// Either a Spotify URI or a link to the Spotify web player
+fn track_id(value: &str) -> Option<String> {
+    let value = value.trim();
+    let id = if value.starts_with("spotify:track:") {
+        &value["spotify:track:".len()..]
+    } else {
+        let index = value.find("open.spotify.com/track/")?;
+        let id = &value[index + "open.spotify.com/track/".len()..];
+        id.split('?').next()?
+    };
+    Some(id.to_string()).filter(|id| !id.is_empty() && id.chars().all(char::is_alphanumeric))
+}
+
+// Players usually describe entries as "Artist - Title"
+fn entry_from_description(description: &str, id: Option<String>) -> ImportEntry {
+    let mut parts = description.splitn(2, " - ");
+    let first = parts.next().unwrap_or_default().trim().to_string();
+    match parts.next() {
+        Some(title) => ImportEntry {
+            id,
+            title: title.trim().to_string(),
+            artists: first,
+        },
+        None => ImportEntry {
+            id,
+            title: first,
+            artists: String::new(),
+        },
+    }
+}
+
+// Entries are file paths or URIs, described by the #EXTINF line preceding them if any
+fn from_m3u(contents: &str) -> Vec<ImportEntry> {
+    let mut entries = vec![];
+    let mut description: Option<&str> = None;
+    for line in contents.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if line.starts_with("#EXTINF:") {
+            description = line.splitn(2, ',').nth(1);
+        } else if !line.starts_with('#') {
+            let id = track_id(line);
+            let description = description.take().map(str::to_string).unwrap_or_else(|| {
+                let path = Path::new(line);
+                let stem = path.file_stem().and_then(|s| s.to_str());
+                stem.unwrap_or(line).to_string()
+            });
+            entries.push(entry_from_description(&description, id));
+        }
+    }
+    entries
+}
+
+fn csv_records(contents: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = contents.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+        .into_iter()
+        .filter(|r| r.iter().any(|f| !f.trim().is_empty()))
+        .collect()
+}
+
+// Columns are found from the header, files without one being read as title then artists
+fn from_csv(contents: &str) -> Vec<ImportEntry> {
+    let mut records = csv_records(contents).into_iter().peekable();
+    let header = records
+        .peek()
+        .map(|r| {
+            r.iter()
+                .map(|f| f.trim().to_lowercase())
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    let column = |names: &[&str]| header.iter().position(|f| names.contains(&&f[..]));
+    let title = column(&["title", "name", "track", "track name"]);
+    let artists = column(&["artists", "artist", "artist name", "artist name(s)"]);
+    let uri = column(&["uri", "spotify uri", "track uri", "url"]);
+
+    let (title, artists) = match (title, artists) {
+        (Some(title), artists) => {
+            records.next();
+            (title, artists)
+        }
+        (None, _) => (0, Some(1)),
+    };
+    records
+        .map(|record| {
+            let field = |i: Option<usize>| i.and_then(|i| record.get(i)).map(|f| f.trim());
+            ImportEntry {
+                id: field(uri).and_then(track_id),
+                title: field(Some(title)).unwrap_or_default().to_string(),
+                artists: field(artists).unwrap_or_default().to_string(),
+            }
+        })
+        .filter(|entry| entry.id.is_some() || !entry.title.is_empty())
+        .collect()
+}
+
+// The format written by the export
+fn from_json(contents: &str) -> Vec<ImportEntry> {
+    let document: serde_json::Value = match serde_json::from_str(contents) {
+        Ok(document) => document,
+        Err(_) => return vec![],
+    };
+    let tracks = match document["tracks"].as_array() {
+        Some(tracks) => tracks,
+        None => return vec![],
+    };
+    tracks
+        .iter()
+        .map(|track| ImportEntry {
+            id: track["uri"].as_str().and_then(track_id),
+            title: track["title"].as_str().unwrap_or_default().to_string(),
+            artists: track["artists"]
+                .as_array()
+                .map(|artists| {
+                    artists
+                        .iter()
+                        .filter_map(|a| a.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                })
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+pub fn import_playlist(contents: &str, format: PlaylistFileFormat) -> Vec<ImportEntry> {
+    match format {
+        PlaylistFileFormat::M3u => from_m3u(contents),
+        PlaylistFileFormat::Csv => from_csv(contents),
+        PlaylistFileFormat::Json => from_json(contents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::{AlbumRef, ArtistRef, UserRef};
+
+    fn playlist() -> PlaylistDescription {
+        PlaylistDescription {
+            id: "p1".to_string(),
+            title: "Road trip".to_string(),
+            description: "".to_string(),
+            art: None,
+            songs: vec![SongDescription {
+                id: "t1".to_string(),
+                title: "Hello, \"World\"".to_string(),
+                artists: vec![
+                    ArtistRef {
+                        id: "a1".to_string(),
+                        name: "Foo".to_string(),
+                    },
+                    ArtistRef {
+                        id: "a2".to_string(),
+                        name: "Bar".to_string(),
+                    },
+                ],
+                album: AlbumRef {
+                    id: "b1".to_string(),
+                    name: "Album".to_string(),
+                },
+                duration: 185_000,
+                art: None,
+                explicit: false,
+                popularity: None,
+                added_at: None,
+            }],
+            owner: UserRef {
+                id: "me".to_string(),
+                display_name: "Me".to_string(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_export_m3u() {
+        let m3u = export_playlist(&playlist(), PlaylistFileFormat::M3u);
+        assert_eq!(
+            m3u,
+            "#EXTM3U\n#PLAYLIST:Road trip\n#EXTINF:185,Foo, Bar - Hello, \"World\"\nspotify:track:t1\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv_escapes_fields() {
+        let csv = export_playlist(&playlist(), PlaylistFileFormat::Csv);
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "title,artists,album,duration_ms,uri");
+        assert_eq!(
+            lines[1],
+            "\"Hello, \"\"World\"\"\",\"Foo, Bar\",Album,185000,spotify:track:t1"
+        );
+    }
+
+    #[test]
+    fn test_export_json() {
+        let json = export_playlist(&playlist(), PlaylistFileFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["title"], "Road trip");
+        assert_eq!(value["tracks"][0]["artists"][1], "Bar");
+        assert_eq!(value["tracks"][0]["uri"], "spotify:track:t1");
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        for &format in PlaylistFileFormat::ALL.iter() {
+            let exported = export_playlist(&playlist(), format);
+            let entries = import_playlist(&exported, format);
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].id, Some("t1".to_string()));
+            assert_eq!(entries[0].title, "Hello, \"World\"");
+            assert_eq!(entries[0].artists, "Foo, Bar");
+        }
+    }
+
+    #[test]
+    fn test_import_m3u_from_files() {
+        let m3u = "#EXTM3U\n#EXTINF:200,Daft Punk - One More Time\n/music/01.mp3\n\n/music/Air - Sexy Boy.flac\nhttps://open.spotify.com/track/abc123?si=x\n";
+        let entries = import_playlist(m3u, PlaylistFileFormat::M3u);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].describe(), "Daft Punk - One More Time");
+        assert_eq!(entries[0].id, None);
+        assert_eq!(entries[1].artists, "Air");
+        assert_eq!(entries[1].title, "Sexy Boy");
+        assert_eq!(entries[2].id, Some("abc123".to_string()));
+    }
+
+    #[test]
+    fn test_import_csv_columns() {
+        let csv = "Artist Name,Track Name\r\n\"Simon & Garfunkel\",\"Cecilia\"\r\n\r\n";
+        let entries = import_playlist(csv, PlaylistFileFormat::Csv);
+        assert_eq!(
+            entries,
+            vec![ImportEntry {
+                id: None,
+                title: "Cecilia".to_string(),
+                artists: "Simon & Garfunkel".to_string(),
+            }]
+        );
+
+        let entries = import_playlist("Song,Someone\n", PlaylistFileFormat::Csv);
+        assert_eq!(entries[0].title, "Song");
+        assert_eq!(entries[0].artists, "Someone");
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let song = &playlist().songs[0];
+        let entry = ImportEntry {
+            id: None,
+            title: "hello, \"world\"".to_string(),
+            artists: "Bar feat. Baz".to_string(),
+        };
+        assert!(entry.is_exact_match(song));
+        let entry = ImportEntry {
+            artists: "Someone else".to_string(),
+            ..entry
+        };
+        assert!(!entry.is_exact_match(song));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        let format = PlaylistFileFormat::from_path(Path::new("/tmp/mix.M3U8"));
+        assert_eq!(format, Some(PlaylistFileFormat::M3u));
+        assert_eq!(PlaylistFileFormat::from_path(Path::new("mix.txt")), None);
+    }
+}
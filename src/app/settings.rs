@@ -0,0 +1,628 @@
+use gio::SettingsExt;
+use glib::ToVariant;
+use std::collections::HashMap;
+
+use crate::app::models::{SmartPlaylist, SmartRule};
+use crate::app::state::ShuffleMode;
+
+const SETTINGS: &str = "dev.alextren.Spot";
+// Searches remembered, older ones are forgotten
+const SEARCH_HISTORY_LIMIT: usize = 20;
+// Episodes marked as played or unplayed remembered, older marks are forgotten
+const PLAYED_EPISODES_LIMIT: usize = 1000;
+
+// Albums and playlists whose shuffle is remembered, the least recently toggled are forgotten
+const SHUFFLE_CONTEXTS_LIMIT: usize = 500;
+// Albums and playlists with their own explicit filter, the least recently set are forgotten
+const EXPLICIT_OVERRIDES_LIMIT: usize = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StartupView {
+    Library,
+    SavedPlaylists,
+    Podcasts,
+    NowPlaying,
+    Search,
+    // An album, playlist or artist URI
+    Context(String),
+}
+
+impl StartupView {
+    fn from_settings(view: &str, context: String) -> Self {
+        match view {
+            "saved_playlists" => Self::SavedPlaylists,
+            "podcasts" => Self::Podcasts,
+            "now_playing" => Self::NowPlaying,
+            "search" => Self::Search,
+            "context" => Self::Context(context),
+            _ => Self::Library,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaylistSort {
+    // As returned by Spotify, most recently added first
+    Default,
+    Name,
+}
+
+impl PlaylistSort {
+    fn from_settings(value: &str) -> Self {
+        match value {
+            "name" => Self::Name,
+            _ => Self::Default,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NormalizationLevel {
+    Quiet,
+    Normal,
+    Loud,
+}
+
+impl NormalizationLevel {
+    fn from_settings(value: &str) -> Self {
+        match value {
+            "quiet" => Self::Quiet,
+            "loud" => Self::Loud,
+            _ => Self::Normal,
+        }
+    }
+
+    // Added to each track's own gain; the player still avoids clipping
+    pub fn pregain(self) -> f32 {
+        match self {
+            Self::Quiet => -5.0,
+            Self::Normal => 0.0,
+            Self::Loud => 3.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EqualizerPreset {
+    Flat,
+    Rock,
+    Pop,
+    Custom,
+}
+
+impl EqualizerPreset {
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "flat" => Self::Flat,
+            "rock" => Self::Rock,
+            "pop" => Self::Pop,
+            _ => Self::Custom,
+        }
+    }
+
+    // Gains in dB for each band, from 31 Hz to 16 kHz
+    pub fn gains(self) -> Option<[f64; 10]> {
+        match self {
+            Self::Flat => Some([0.0; 10]),
+            Self::Rock => Some([5.0, 4.0, 3.0, 1.0, -1.0, -1.0, 1.0, 3.0, 4.0, 5.0]),
+            Self::Pop => Some([-1.0, 0.0, 2.0, 4.0, 5.0, 4.0, 2.0, 0.0, -1.0, -1.0]),
+            Self::Custom => None,
+        }
+    }
+}
+
+// Per context (album, playlist) override of the explicit content setting
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExplicitFilter {
+    Inherit,
+    Hide,
+    Show,
+}
+
+impl ExplicitFilter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Inherit => "inherit",
+            Self::Hide => "hide",
+            Self::Show => "show",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "hide" => Self::Hide,
+            "show" => Self::Show,
+            _ => Self::Inherit,
+        }
+    }
+}
+
+// How the episodes of a show are listed, remembered per show
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EpisodeView {
+    pub oldest_first: bool,
+    pub hide_played: bool,
+}
+
+impl EpisodeView {
+    // e.g. "oldest,unplayed", empty for the default view
+    fn as_entry(self) -> String {
+        let mut parts = vec![];
+        if self.oldest_first {
+            parts.push("oldest");
+        }
+        if self.hide_played {
+            parts.push("unplayed");
+        }
+        parts.join(",")
+    }
+
+    fn from_entry(value: &str) -> Self {
+        let parts: Vec<&str> = value.split(',').collect();
+        Self {
+            oldest_first: parts.contains(&"oldest"),
+            hide_played: parts.contains(&"unplayed"),
+        }
+    }
+}
+
+// Overrides are stored as a list of "<context id>=<filter>" strings
+fn parse_explicit_overrides(entries: &[String]) -> HashMap<String, ExplicitFilter> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let id = parts.next()?.to_string();
+            let filter = ExplicitFilter::from_str(parts.next()?);
+            Some((id, filter))
+        })
+        .collect()
+}
+
+// Shuffle preferences and played episodes are stored as a list of "<id or uri>=on|off" strings
+fn parse_toggles(entries: &[String]) -> HashMap<String, bool> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let id = parts.next()?.to_string();
+            let on = parts.next()? == "on";
+            Some((id, on))
+        })
+        .collect()
+}
+
+// Episode views are stored as a list of "<show id>=<view>" strings
+fn parse_episode_views(entries: &[String]) -> HashMap<String, EpisodeView> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let mut parts = entry.splitn(2, '=');
+            let id = parts.next()?.to_string();
+            let view = EpisodeView::from_entry(parts.next()?);
+            Some((id, view))
+        })
+        .collect()
+}
+
+// Smart playlists are stored as a list of "<name>=<rule>;<rule>" strings,
+// rules that can't be parsed are left out
+fn parse_smart_playlist(entry: &str) -> Option<SmartPlaylist> {
+    let mut parts = entry.rsplitn(2, '=');
+    let rules = parts
+        .next()?
+        .split(';')
+        .filter_map(SmartRule::from_id)
+        .collect();
+    let name = parts.next()?.to_string();
+    Some(SmartPlaylist { name, rules })
+}
+
+fn smart_playlist_entry(playlist: &SmartPlaylist) -> String {
+    let rules = playlist
+        .rules
+        .iter()
+        .map(|rule| rule.id())
+        .collect::<Vec<String>>();
+    format!("{}={}", playlist.name, rules.join(";"))
+}
+
+// What was playing when Spot was closed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SavedSession {
+    // Empty when the queue wasn't loaded from an album or playlist
+    pub context_uri: String,
+    pub track_ids: Vec<String>,
+    pub track_id: String,
+    // In milliseconds
+    pub position: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpotSettings {
+    pub no_images: bool,
+    pub mpris_combined_title: bool,
+    pub startup_view: StartupView,
+    pub hide_explicit: bool,
+    pub explicit_overrides: HashMap<String, ExplicitFilter>,
+    pub swap_resumes_position: bool,
+    pub up_next_toast: bool,
+    pub up_next_toast_fullscreen: bool,
+    pub auto_show_now_playing: bool,
+    pub auto_hide_now_playing: bool,
+    pub auto_hide_now_playing_delay: u32,
+    pub mood_tags: bool,
+    pub resume_card: bool,
+    pub restore_session: bool,
+    pub previous_restarts: bool,
+    pub shuffle_per_context: bool,
+    pub shuffle_default: bool,
+    pub shuffle_contexts: HashMap<String, bool>,
+    pub shuffle_mode: ShuffleMode,
+    pub clear_queue_on_new_context: bool,
+    pub autoplay: bool,
+    pub sleep_timer_quits: bool,
+    pub playlists_sort: PlaylistSort,
+    pub smart_playlists: Vec<SmartPlaylist>,
+    pub force_mono: bool,
+    pub audio_balance: f64,
+    pub volume_normalization: bool,
+    pub normalization_level: NormalizationLevel,
+    pub equalizer_enabled: bool,
+    pub equalizer_bands: Vec<f64>,
+    pub visualizer: bool,
+    pub fade_duration: u32,
+    // None for the worldwide charts
+    pub charts_country: Option<String>,
+    pub played_episodes: HashMap<String, bool>,
+    pub episode_views: HashMap<String, EpisodeView>,
+}
+
+impl SpotSettings {
+    pub fn gsettings() -> gio::Settings {
+        gio::Settings::new(SETTINGS)
+    }
+
+    pub fn new_from_gsettings() -> Self {
+        let settings = Self::gsettings();
+        Self {
+            no_images: settings.get_boolean("no-images"),
+            mpris_combined_title: settings.get_boolean("mpris-combined-title"),
+            startup_view: StartupView::from_settings(
+                settings.get_string("startup-view").as_deref().unwrap_or(""),
+                settings
+                    .get_string("startup-context")
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+            ),
+            hide_explicit: settings.get_boolean("hide-explicit"),
+            explicit_overrides: parse_explicit_overrides(&Self::strv(
+                &settings,
+                "explicit-overrides",
+            )),
+            swap_resumes_position: settings.get_boolean("swap-resumes-position"),
+            up_next_toast: settings.get_boolean("up-next-toast"),
+            up_next_toast_fullscreen: settings.get_boolean("up-next-toast-fullscreen"),
+            auto_show_now_playing: settings.get_boolean("auto-show-now-playing"),
+            auto_hide_now_playing: settings.get_boolean("auto-hide-now-playing"),
+            auto_hide_now_playing_delay: settings.get_uint("auto-hide-now-playing-delay"),
+            mood_tags: settings.get_boolean("mood-tags"),
+            resume_card: settings.get_boolean("resume-card"),
+            restore_session: settings.get_boolean("restore-session"),
+            previous_restarts: settings.get_boolean("previous-restarts"),
+            shuffle_per_context: settings.get_boolean("shuffle-per-context"),
+            shuffle_default: settings.get_boolean("shuffle-default"),
+            shuffle_contexts: parse_toggles(&Self::strv(&settings, "shuffle-contexts")),
+            shuffle_mode: ShuffleMode::from_id(
+                settings.get_string("shuffle-mode").as_deref().unwrap_or(""),
+            ),
+            clear_queue_on_new_context: settings.get_boolean("clear-queue-on-new-context"),
+            autoplay: settings.get_boolean("autoplay"),
+            sleep_timer_quits: settings.get_boolean("sleep-timer-quit"),
+            playlists_sort: PlaylistSort::from_settings(
+                settings
+                    .get_string("playlists-sort")
+                    .as_deref()
+                    .unwrap_or(""),
+            ),
+            smart_playlists: Self::strv(&settings, "smart-playlists")
+                .iter()
+                .filter_map(|entry| parse_smart_playlist(entry))
+                .collect(),
+            force_mono: settings.get_boolean("force-mono"),
+            audio_balance: settings.get_double("audio-balance"),
+            volume_normalization: settings.get_boolean("volume-normalization"),
+            normalization_level: NormalizationLevel::from_settings(
+                settings
+                    .get_string("normalization-level")
+                    .as_deref()
+                    .unwrap_or(""),
+            ),
+            equalizer_enabled: settings.get_boolean("equalizer-enabled"),
+            equalizer_bands: Self::equalizer_bands(&settings),
+            visualizer: settings.get_boolean("visualizer"),
+            fade_duration: settings.get_uint("fade-duration"),
+            charts_country: settings
+                .get_string("charts-country")
+                .map(|s| s.to_string())
+                .filter(|country| !country.is_empty() && country != "global"),
+            played_episodes: parse_toggles(&Self::strv(&settings, "played-episodes")),
+            episode_views: parse_episode_views(&Self::strv(&settings, "episode-views")),
+        }
+    }
+
+    fn strv(settings: &gio::Settings, key: &str) -> Vec<String> {
+        settings
+            .get_strv(key)
+            .into_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    pub fn equalizer_bands(settings: &gio::Settings) -> Vec<f64> {
+        settings
+            .get_value("equalizer-bands")
+            .and_then(|value| value.get::<Vec<f64>>())
+            .unwrap_or_default()
+    }
+
+    pub fn save_equalizer_bands(bands: &[f64]) {
+        let settings = Self::gsettings();
+        let _ = settings.set_value("equalizer-bands", &bands.to_variant());
+    }
+
+    pub fn save_explicit_filter(context_id: &str, filter: ExplicitFilter) {
+        let settings = Self::gsettings();
+        let prefix = format!("{}=", context_id);
+        let mut entries: Vec<String> = Self::strv(&settings, "explicit-overrides")
+            .into_iter()
+            .filter(|entry| !entry.starts_with(&prefix))
+            .collect();
+        if filter != ExplicitFilter::Inherit {
+            entries.push(format!("{}{}", prefix, filter.as_str()));
+        }
+        let overflow = entries.len().saturating_sub(EXPLICIT_OVERRIDES_LIMIT);
+        entries.drain(..overflow);
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_strv("explicit-overrides", &entries[..]);
+    }
+
+    pub fn save_shuffle(context_uri: &str, shuffled: bool) {
+        let settings = Self::gsettings();
+        let prefix = format!("{}=", context_uri);
+        let mut entries: Vec<String> = Self::strv(&settings, "shuffle-contexts")
+            .into_iter()
+            .filter(|entry| !entry.starts_with(&prefix))
+            .collect();
+        entries.push(format!("{}{}", prefix, if shuffled { "on" } else { "off" }));
+        let overflow = entries.len().saturating_sub(SHUFFLE_CONTEXTS_LIMIT);
+        entries.drain(..overflow);
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_strv("shuffle-contexts", &entries[..]);
+    }
+
+    // Spotify doesn't let apps mark episodes, so marks are only kept here
+    pub fn save_episode_played(episode_id: &str, played: bool) {
+        let settings = Self::gsettings();
+        let prefix = format!("{}=", episode_id);
+        let mut entries: Vec<String> = Self::strv(&settings, "played-episodes")
+            .into_iter()
+            .filter(|entry| !entry.starts_with(&prefix))
+            .collect();
+        entries.push(format!("{}{}", prefix, if played { "on" } else { "off" }));
+        let overflow = entries.len().saturating_sub(PLAYED_EPISODES_LIMIT);
+        entries.drain(..overflow);
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_strv("played-episodes", &entries[..]);
+    }
+
+    pub fn save_episode_view(show_id: &str, view: EpisodeView) {
+        let settings = Self::gsettings();
+        let prefix = format!("{}=", show_id);
+        let mut entries: Vec<String> = Self::strv(&settings, "episode-views")
+            .into_iter()
+            .filter(|entry| !entry.starts_with(&prefix))
+            .collect();
+        if view != EpisodeView::default() {
+            entries.push(format!("{}{}", prefix, view.as_entry()));
+        }
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_strv("episode-views", &entries[..]);
+    }
+
+    // Replaces the smart playlist of the same name, if any
+    pub fn save_smart_playlist(playlist: &SmartPlaylist) {
+        Self::update_smart_playlists(&playlist.name, Some(playlist));
+    }
+
+    pub fn remove_smart_playlist(name: &str) {
+        Self::update_smart_playlists(name, None);
+    }
+
+    fn update_smart_playlists(name: &str, playlist: Option<&SmartPlaylist>) {
+        let settings = Self::gsettings();
+        let mut entries: Vec<String> = Self::strv(&settings, "smart-playlists")
+            .into_iter()
+            .filter(|entry| {
+                parse_smart_playlist(entry)
+                    .map(|p| p.name != name)
+                    .unwrap_or(true)
+            })
+            .collect();
+        if let Some(playlist) = playlist {
+            entries.push(smart_playlist_entry(playlist));
+        }
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_strv("smart-playlists", &entries[..]);
+    }
+
+    // Most recent first
+    pub fn search_history() -> Vec<String> {
+        Self::strv(&Self::gsettings(), "search-history")
+    }
+
+    // Searches the query continues are replaced by it, e.g. those made while still typing
+    pub fn save_search(query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        let lowercase = query.to_lowercase();
+        let mut entries: Vec<String> = Self::search_history()
+            .into_iter()
+            .filter(|entry| !lowercase.starts_with(&entry.to_lowercase()))
+            .collect();
+        entries.insert(0, query.to_string());
+        entries.truncate(SEARCH_HISTORY_LIMIT);
+        let entries: Vec<&str> = entries.iter().map(|s| &s[..]).collect();
+        let _ = Self::gsettings().set_strv("search-history", &entries[..]);
+    }
+
+    pub fn clear_search_history() {
+        let _ = Self::gsettings().set_strv("search-history", &[]);
+    }
+
+    // The track id and context URI saved by save_last_played
+    pub fn last_played() -> Option<(String, String)> {
+        let settings = Self::gsettings();
+        let track = settings.get_string("last-played-track")?.to_string();
+        let context = settings.get_string("last-played-context")?.to_string();
+        if track.is_empty() || context.is_empty() {
+            None
+        } else {
+            Some((track, context))
+        }
+    }
+
+    pub fn save_last_played(track_id: &str, context_uri: &str) {
+        let settings = Self::gsettings();
+        let _ = settings.set_string("last-played-track", track_id);
+        let _ = settings.set_string("last-played-context", context_uri);
+    }
+
+    // Either "off", "all" or "one"
+    pub fn repeat_mode() -> String {
+        Self::gsettings()
+            .get_string("repeat-mode")
+            .map(|s| s.to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn save_repeat_mode(mode: &str) {
+        let _ = Self::gsettings().set_string("repeat-mode", mode);
+    }
+
+    pub fn saved_session() -> Option<SavedSession> {
+        let settings = Self::gsettings();
+        let track_id = settings.get_string("session-track")?.to_string();
+        let track_ids = Self::strv(&settings, "session-tracks");
+        if track_id.is_empty() || track_ids.is_empty() {
+            return None;
+        }
+        Some(SavedSession {
+            context_uri: settings
+                .get_string("session-context")
+                .map(|s| s.to_string())
+                .unwrap_or_default(),
+            track_ids,
+            track_id,
+            position: settings.get_uint("session-position"),
+        })
+    }
+
+    pub fn save_session(session: &SavedSession) {
+        let settings = Self::gsettings();
+        let track_ids: Vec<&str> = session.track_ids.iter().map(|s| &s[..]).collect();
+        let _ = settings.set_string("session-context", &session.context_uri);
+        let _ = settings.set_strv("session-tracks", &track_ids[..]);
+        let _ = settings.set_string("session-track", &session.track_id);
+        let _ = settings.set_uint("session-position", session.position);
+    }
+
+    pub fn explicit_filter(&self, context_id: &str) -> ExplicitFilter {
+        self.explicit_overrides
+            .get(context_id)
+            .copied()
+            .unwrap_or(ExplicitFilter::Inherit)
+    }
+
+    // Apart from what's remembered along the way, per album, playlist or episode
+    pub fn same_preferences(&self, other: &Self) -> bool {
+        self.without_remembered() == other.without_remembered()
+    }
+
+    fn without_remembered(&self) -> Self {
+        Self {
+            shuffle_contexts: HashMap::new(),
+            played_episodes: HashMap::new(),
+            episode_views: HashMap::new(),
+            ..self.clone()
+        }
+    }
+
+    pub fn shuffles(&self, context_uri: &str) -> bool {
+        self.shuffle_contexts
+            .get(context_uri)
+            .copied()
+            .unwrap_or(self.shuffle_default)
+    }
+
+    // Whether the episode was marked as played, if it was marked at all
+    pub fn played_mark(&self, episode_id: &str) -> Option<bool> {
+        self.played_episodes.get(episode_id).copied()
+    }
+
+    pub fn episode_view(&self, show_id: &str) -> EpisodeView {
+        self.episode_views.get(show_id).copied().unwrap_or_default()
+    }
+
+    pub fn hides_explicit(&self, context_id: &str) -> bool {
+        match self.explicit_filter(context_id) {
+            ExplicitFilter::Hide => true,
+            ExplicitFilter::Show => false,
+            ExplicitFilter::Inherit => self.hide_explicit,
+        }
+    }
+}
+
+impl Default for SpotSettings {
+    fn default() -> Self {
+        Self {
+            no_images: false,
+            mpris_combined_title: false,
+            startup_view: StartupView::Library,
+            hide_explicit: false,
+            explicit_overrides: HashMap::new(),
+            swap_resumes_position: false,
+            up_next_toast: false,
+            up_next_toast_fullscreen: false,
+            auto_show_now_playing: false,
+            auto_hide_now_playing: true,
+            auto_hide_now_playing_delay: 5,
+            mood_tags: false,
+            resume_card: true,
+            restore_session: true,
+            previous_restarts: false,
+            shuffle_per_context: false,
+            shuffle_default: false,
+            shuffle_contexts: HashMap::new(),
+            shuffle_mode: ShuffleMode::Random,
+            clear_queue_on_new_context: false,
+            autoplay: false,
+            sleep_timer_quits: false,
+            playlists_sort: PlaylistSort::Default,
+            smart_playlists: vec![],
+            force_mono: false,
+            audio_balance: 0.0,
+            volume_normalization: false,
+            normalization_level: NormalizationLevel::Normal,
+            equalizer_enabled: false,
+            equalizer_bands: vec![0.0; 10],
+            visualizer: false,
+            fade_duration: 200,
+            charts_country: None,
+            played_episodes: HashMap::new(),
+            episode_views: HashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+// Spotify gives episode descriptions as HTML, which labels can't display as is:
+// only links and basic formatting are kept, as Pango markup, everything else is dropped.
+
+fn markup_tag(tag: &str) -> Option<&'static str> {
+    match tag {
+        "a" => Some("a"),
+        "b" | "strong" => Some("b"),
+        "i" | "em" => Some("i"),
+        "u" => Some("u"),
+        _ => None,
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(|c| c == 'x' || c == 'X') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse::<u32>().ok()?,
+            };
+            std::char::from_u32(code)
+        }
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 8)
+            .and_then(|end| Some((end, decode_entity(&rest[1..end + 1])?)));
+        match entity {
+            Some((end, c)) => {
+                decoded.push(c);
+                rest = &rest[end + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// Only web links can be opened, anything else (e.g. javascript:) is left as text
+fn link_target(attributes: &str) -> Option<String> {
+    let lowercase = attributes.to_ascii_lowercase();
+    let start = lowercase.find("href=")? + "href=".len();
+    let value = &attributes[start..];
+    let href = match value.chars().next()? {
+        quote @ '"' | quote @ '\'' => value[1..].split(quote).next()?,
+        _ => value.split(char::is_whitespace).next()?,
+    };
+    let href = decode_entities(href.trim());
+    let lowercase = href.to_lowercase();
+    if lowercase.starts_with("https://") || lowercase.starts_with("http://") {
+        Some(href)
+    } else {
+        None
+    }
+}
+
+struct MarkupWriter {
+    markup: String,
+    // Pango tags currently open, as they have to be closed in order
+    open_tags: Vec<&'static str>,
+    // Whitespace is collapsed like a browser would, and written before the next word
+    pending_space: bool,
+    after_space: bool,
+}
+
+impl MarkupWriter {
+    fn new() -> Self {
+        Self {
+            markup: String::new(),
+            open_tags: vec![],
+            pending_space: false,
+            after_space: true,
+        }
+    }
+
+    fn space(&mut self) {
+        if self.pending_space && !self.after_space {
+            self.markup.push(' ');
+            self.after_space = true;
+        }
+        self.pending_space = false;
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = decode_entities(text);
+        for (i, word) in text.split(char::is_whitespace).enumerate() {
+            if i > 0 {
+                self.pending_space = true;
+            }
+            if !word.is_empty() {
+                self.space();
+                self.markup.push_str(&escape(word));
+                self.after_space = false;
+            }
+        }
+    }
+
+    fn line_break(&mut self, count: usize) {
+        let current = self.markup.len() - self.markup.trim_end_matches('\n').len();
+        if !self.markup.is_empty() && current < count {
+            self.markup.push_str(&"\n".repeat(count - current));
+        }
+        self.pending_space = false;
+        self.after_space = true;
+    }
+
+    fn open(&mut self, tag: &'static str, attributes: &str) {
+        self.space();
+        if tag == "a" {
+            // Nested links aren't valid markup
+            if self.open_tags.contains(&"a") {
+                return;
+            }
+            match link_target(attributes) {
+                Some(href) => self
+                    .markup
+                    .push_str(&format!("<a href=\"{}\">", escape(&href))),
+                None => return,
+            }
+        } else {
+            self.markup.push_str(&format!("<{}>", tag));
+        }
+        self.open_tags.push(tag);
+    }
+
+    fn close(&mut self, tag: &'static str) {
+        if let Some(position) = self.open_tags.iter().rposition(|open| *open == tag) {
+            for open in self.open_tags.split_off(position).iter().rev() {
+                self.markup.push_str(&format!("</{}>", open));
+            }
+        }
+    }
+
+    fn tag(&mut self, tag: &str) {
+        let is_closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let (name, attributes) = match tag.find(char::is_whitespace) {
+            Some(i) => (&tag[..i], &tag[i..]),
+            None => (tag, ""),
+        };
+        let name = name.to_lowercase();
+        match (&name[..], is_closing) {
+            ("br", _) => self.line_break(1),
+            ("p", _) | ("div", _) | ("ul", _) | ("ol", _) => self.line_break(2),
+            ("li", false) => {
+                self.line_break(1);
+                self.markup.push_str("• ");
+                self.after_space = true;
+            }
+            ("li", true) => self.line_break(1),
+            (name, false) => {
+                if let Some(tag) = markup_tag(name) {
+                    self.open(tag, attributes);
+                }
+            }
+            (name, true) => {
+                if let Some(tag) = markup_tag(name) {
+                    self.close(tag);
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        while let Some(open) = self.open_tags.pop() {
+            self.markup.push_str(&format!("</{}>", open));
+        }
+        self.markup.trim().to_string()
+    }
+}
+
+pub fn html_to_markup(html: &str) -> String {
+    let mut writer = MarkupWriter::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        writer.text(&rest[..start]);
+        match rest[start..].find('>') {
+            Some(end) => {
+                writer.tag(rest[start + 1..start + end].trim());
+                rest = &rest[start + end + 1..];
+            }
+            // Not a tag after all
+            None => {
+                writer.text(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    writer.text(rest);
+    writer.finish()
+}
+
+// When there's no HTML version, the plain description is shown as is
+pub fn text_to_markup(text: &str) -> String {
+    escape(text.trim())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_links_kept() {
+        let markup = html_to_markup(
+            r#"<p>Sponsored by <a href="https://example.com/?a=1&amp;b=2" target="_blank">Example</a></p>"#,
+        );
+        assert_eq!(
+            markup,
+            r#"Sponsored by <a href="https://example.com/?a=1&amp;b=2">Example</a>"#
+        );
+    }
+
+    #[test]
+    fn test_unsafe_links_dropped() {
+        let markup = html_to_markup(r#"<a href="javascript:alert(1)">click</a> <a>here</a>"#);
+        assert_eq!(markup, "click here");
+    }
+
+    #[test]
+    fn test_formatting_and_paragraphs() {
+        let markup = html_to_markup(
+            "<p>First  <strong>part</strong></p>\n<p>Then <em>more</em><br/>and 1 &lt; 2</p>",
+        );
+        assert_eq!(
+            markup,
+            "First <b>part</b>\n\nThen <i>more</i>\nand 1 &lt; 2"
+        );
+    }
+
+    #[test]
+    fn test_lists_and_unknown_tags() {
+        let markup = html_to_markup(
+            "<ul><li>One</li><li><span class=\"x\">Two</span></li></ul><img src=\"a.png\">",
+        );
+        assert_eq!(markup, "• One\n• Two");
+    }
+
+    #[test]
+    fn test_unbalanced_tags_closed() {
+        assert_eq!(
+            html_to_markup("<b>bold <i>both</b> plain</i>"),
+            "<b>bold <i>both</i></b> plain"
+        );
+        assert_eq!(html_to_markup("<i>open"), "<i>open</i>");
+        assert_eq!(html_to_markup("a < b & c"), "a &lt; b &amp; c");
+    }
+}
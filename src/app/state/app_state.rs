@@ -1,7 +1,9 @@
 use crate::app::credentials;
+use crate::app::models::SongDescription;
+use crate::app::settings::SpotSettings;
 use crate::app::state::{
     browser_state::{BrowserAction, BrowserEvent, BrowserState},
-    playback_state::{PlaybackAction, PlaybackEvent, PlaybackState},
+    playback_state::{PlaybackAction, PlaybackEvent, PlaybackState, PlaylistSource},
     selection_state::{SelectionAction, SelectionEvent, SelectionState},
     ScreenName, UpdatableState,
 };
@@ -19,10 +21,14 @@ pub enum AppAction {
     SetLoginSuccess(credentials::Credentials),
     Logout,
     ShowNotification(String),
+    ShowUndoableNotification(String, Box<AppAction>),
     HideNotification,
     ViewNowPlaying,
+    ViewHomePage(String),
     QueueSelection,
     DequeueSelection,
+    ShowSettings,
+    UpdateSettings(SpotSettings),
 }
 
 impl AppAction {
@@ -40,6 +46,21 @@ impl AppAction {
     pub fn ViewPlaylist(id: String) -> Self {
         BrowserAction::NavigationPush(ScreenName::PlaylistDetails(id)).into()
     }
+
+    #[allow(non_snake_case)]
+    pub fn ViewCategory(id: String) -> Self {
+        BrowserAction::NavigationPush(ScreenName::Category(id)).into()
+    }
+
+    #[allow(non_snake_case)]
+    pub fn ViewRecommendations(id: String) -> Self {
+        BrowserAction::NavigationPush(ScreenName::Recommendations(id)).into()
+    }
+
+    #[allow(non_snake_case)]
+    pub fn ViewShow(id: String) -> Self {
+        BrowserAction::NavigationPush(ScreenName::Show(id)).into()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -54,8 +75,14 @@ pub enum AppEvent {
     LoginCompleted(credentials::Credentials),
     LogoutCompleted,
     NotificationShown(String),
+    UndoableNotificationShown(String, Box<AppAction>),
     NotificationHidden,
     NowPlayingShown,
+    HomePageShown(String),
+    SettingsShown,
+    SettingsChanged,
+    // Only what's remembered per album, playlist or episode changed, not the preferences
+    RememberedSettingsChanged,
 }
 
 pub struct AppState {
@@ -63,15 +90,19 @@ pub struct AppState {
     pub browser: BrowserState,
     pub user: Option<String>,
     pub selection: SelectionState,
+    pub settings: SpotSettings,
 }
 
 impl AppState {
-    pub fn new() -> Self {
+    pub fn new(settings: SpotSettings) -> Self {
+        let mut playback = PlaybackState::default();
+        playback.set_shuffle_mode(settings.shuffle_mode);
         Self {
-            playback: Default::default(),
+            playback,
             browser: BrowserState::new(),
             user: None,
             selection: Default::default(),
+            settings,
         }
     }
 
@@ -82,6 +113,99 @@ impl AppState {
             .all(|s| self.playback.song(&s.id).is_some())
     }
 
+    pub fn owns_playlist(&self, id: &str) -> bool {
+        let owner = self
+            .browser
+            .playlist_details_state(id)
+            .and_then(|state| state.content.as_ref())
+            .map(|playlist| &playlist.owner.id);
+        owner.is_some() && owner == self.user.as_ref()
+    }
+
+    // The playlist on screen, if it's owned by the user and all selected songs are from it
+    pub fn selection_owned_playlist(&self) -> Option<String> {
+        let id = match self.browser.current_screen() {
+            ScreenName::PlaylistDetails(id) if self.owns_playlist(id) => id,
+            _ => return None,
+        };
+        let songs = &self
+            .browser
+            .playlist_details_state(id)?
+            .content
+            .as_ref()?
+            .songs;
+        let all_from_playlist = self
+            .selection
+            .peek_selection()
+            .iter()
+            .all(|s| songs.iter().any(|song| song.id == s.id));
+        Some(id.clone()).filter(|_| all_from_playlist)
+    }
+
+    // Whether the album or playlist being played has its explicit songs hidden
+    fn hides_explicit_playing(&self) -> bool {
+        match &self.playback.source {
+            PlaylistSource::Album(id) | PlaylistSource::Playlist(id) => {
+                self.settings.hides_explicit(id)
+            }
+            _ => false,
+        }
+    }
+
+    // Playing a song from the context that is already playing loads it again,
+    // which must not count as switching to a new context
+    fn load_playlist(
+        &mut self,
+        source: PlaylistSource,
+        tracks: Vec<SongDescription>,
+    ) -> Vec<AppEvent> {
+        let is_new_context = source != self.playback.source;
+        let shuffled = source
+            .uri()
+            .filter(|_| self.settings.shuffle_per_context)
+            .map(|uri| self.settings.shuffles(&uri));
+
+        let mut events = vec![];
+        // The song to play next and the songs queued by hand survive a context switch
+        // unless told otherwise, even if they were picked right before switching
+        let clears_queue = is_new_context && self.settings.clear_queue_on_new_context;
+        let (queued, dropped) = if clears_queue {
+            (vec![], self.playback.upcoming_queued())
+        } else {
+            (self.playback.upcoming_queued(), vec![])
+        };
+        let dropped_pin = if clears_queue {
+            self.playback.pinned_song().cloned()
+        } else {
+            None
+        };
+        let removed: Vec<&str> = dropped_pin
+            .iter()
+            .chain(dropped.iter())
+            .map(|song| &song.title[..])
+            .collect();
+        let message = match removed[..] {
+            [] => None,
+            [title] => Some(format!("Removed {} from the queue", title)),
+            _ => Some(format!("Removed {} songs from the queue", removed.len())),
+        };
+        if let Some(message) = message {
+            self.playback.unpin();
+            let undo: AppAction = PlaybackAction::Requeue(dropped_pin, dropped).into();
+            events.push(AppEvent::UndoableNotificationShown(message, Box::new(undo)));
+        }
+
+        let loaded = self
+            .playback
+            .update_with(PlaybackAction::LoadPlaylist(source, tracks));
+        if let Some(shuffled) = shuffled {
+            self.playback.set_shuffled(shuffled);
+        }
+        self.playback.requeue(queued);
+        events.extend(loaded.into_iter().map(AppEvent::PlaybackEvent));
+        events
+    }
+
     pub fn update_state(&mut self, message: AppAction) -> Vec<AppEvent> {
         match message {
             AppAction::SetLoginSuccess(credentials) => {
@@ -99,16 +223,47 @@ impl AppState {
             AppAction::Start => vec![AppEvent::Started],
             AppAction::TryLogin(u, p) => vec![AppEvent::LoginStarted(u, p)],
             AppAction::ShowNotification(c) => vec![AppEvent::NotificationShown(c)],
+            AppAction::ShowUndoableNotification(c, undo) => {
+                vec![AppEvent::UndoableNotificationShown(c, undo)]
+            }
             AppAction::HideNotification => vec![AppEvent::NotificationHidden],
             AppAction::ViewNowPlaying => vec![AppEvent::NowPlayingShown],
+            AppAction::ViewHomePage(page) => vec![AppEvent::HomePageShown(page)],
             AppAction::Raise => vec![AppEvent::Raised],
+            AppAction::ShowSettings => vec![AppEvent::SettingsShown],
+            // Pages only refresh when preferences changed, e.g. not for every toggled shuffle
+            AppAction::UpdateSettings(settings) if settings != self.settings => {
+                let preferences_changed = !settings.same_preferences(&self.settings);
+                let reshuffled = self.playback.set_shuffle_mode(settings.shuffle_mode);
+                self.settings = settings;
+                let filtered = self.hides_explicit_playing() && self.playback.remove_explicit();
+                let changed = if preferences_changed {
+                    AppEvent::SettingsChanged
+                } else {
+                    AppEvent::RememberedSettingsChanged
+                };
+                let mut events = vec![changed];
+                if reshuffled || filtered {
+                    events.push(PlaybackEvent::PlaylistChanged.into());
+                }
+                events
+            }
+            AppAction::UpdateSettings(_) => vec![],
             AppAction::QueueSelection => {
+                let mut added = 0;
                 for track in self.selection.take_selection() {
-                    self.playback.queue(track);
+                    if self.playback.queue(track) {
+                        added += 1;
+                    }
                 }
+                let message = match added {
+                    1 => "Added 1 song to the queue".to_string(),
+                    n => format!("Added {} songs to the queue", n),
+                };
                 vec![
                     SelectionEvent::SelectionModeChanged(false).into(),
                     PlaybackEvent::PlaylistChanged.into(),
+                    AppEvent::NotificationShown(message),
                 ]
             }
             AppAction::DequeueSelection => {
@@ -120,6 +275,19 @@ impl AppState {
                     PlaybackEvent::PlaylistChanged.into(),
                 ]
             }
+            // Applies to both the UI and MPRIS, which dispatch the same action
+            AppAction::PlaybackAction(PlaybackAction::Previous)
+                if self.settings.previous_restarts =>
+            {
+                if self.playback.current_song().is_some() {
+                    vec![PlaybackEvent::TrackSeeked(0).into()]
+                } else {
+                    vec![]
+                }
+            }
+            AppAction::PlaybackAction(PlaybackAction::LoadPlaylist(source, tracks)) => {
+                self.load_playlist(source, tracks)
+            }
             AppAction::PlaybackAction(a) => self
                 .playback
                 .update_with(a)
@@ -141,3 +309,143 @@ impl AppState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::AlbumRef;
+
+    fn song(id: &str) -> SongDescription {
+        SongDescription {
+            id: id.to_string(),
+            title: "Title".to_string(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "".to_string(),
+            },
+            duration: 1000,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        }
+    }
+
+    fn state_with_queued_song(clear_queue_on_new_context: bool) -> AppState {
+        let mut state = AppState::new(SpotSettings {
+            clear_queue_on_new_context,
+            ..Default::default()
+        });
+        state.update_state(
+            PlaybackAction::LoadPlaylist(
+                PlaylistSource::Album("a".to_string()),
+                vec![song("1"), song("2")],
+            )
+            .into(),
+        );
+        state.update_state(PlaybackAction::Load("1".to_string()).into());
+        state.update_state(PlaybackAction::Queue(song("q")).into());
+        state
+    }
+
+    fn ids(state: &AppState) -> Vec<&str> {
+        state.playback.songs().map(|s| &s.id[..]).collect()
+    }
+
+    #[test]
+    fn test_remembered_settings_dont_change_preferences() {
+        let mut state = AppState::new(Default::default());
+        let mut settings = SpotSettings::default();
+        settings
+            .shuffle_contexts
+            .insert("spotify:album:a".to_string(), true);
+
+        let events = state.update_state(AppAction::UpdateSettings(settings.clone()));
+        assert!(matches!(events[..], [AppEvent::RememberedSettingsChanged]));
+
+        settings.autoplay = !settings.autoplay;
+        let events = state.update_state(AppAction::UpdateSettings(settings));
+        assert!(matches!(events[..], [AppEvent::SettingsChanged]));
+    }
+
+    #[test]
+    fn test_queue_kept_on_new_context() {
+        let mut state = state_with_queued_song(false);
+
+        state.update_state(
+            PlaybackAction::LoadPlaylist(
+                PlaylistSource::Album("b".to_string()),
+                vec![song("3"), song("4")],
+            )
+            .into(),
+        );
+
+        assert_eq!(ids(&state), vec!["3", "4", "q"]);
+        assert!(state.playback.source == PlaylistSource::Album("b".to_string()));
+    }
+
+    #[test]
+    fn test_queue_cleared_on_new_context() {
+        let mut state = state_with_queued_song(true);
+        state.update_state(PlaybackAction::Pin(song("p")).into());
+
+        let events = state.update_state(
+            PlaybackAction::LoadPlaylist(
+                PlaylistSource::Album("b".to_string()),
+                vec![song("3"), song("4")],
+            )
+            .into(),
+        );
+
+        assert_eq!(ids(&state), vec!["3", "4"]);
+        assert!(state.playback.upcoming_queued().is_empty());
+        assert!(state.playback.pinned_song().is_none());
+
+        let undo = events.into_iter().find_map(|event| match event {
+            AppEvent::UndoableNotificationShown(message, undo) => {
+                assert_eq!(message, "Removed 2 songs from the queue");
+                Some(*undo)
+            }
+            _ => None,
+        });
+        state.update_state(undo.expect("an undoable notification"));
+        assert_eq!(ids(&state), vec!["3", "4", "q"]);
+        assert_eq!(state.playback.pinned_song().map(|s| &s.id[..]), Some("p"));
+    }
+
+    #[test]
+    fn test_queue_kept_in_same_context() {
+        let mut state = state_with_queued_song(true);
+
+        state.update_state(
+            PlaybackAction::LoadPlaylist(
+                PlaylistSource::Album("a".to_string()),
+                vec![song("1"), song("2")],
+            )
+            .into(),
+        );
+        state.update_state(PlaybackAction::Load("2".to_string()).into());
+
+        assert_eq!(ids(&state), vec!["1", "2", "q"]);
+        assert!(state.playback.source == PlaylistSource::Album("a".to_string()));
+    }
+
+    #[test]
+    fn test_queue_selection_counts_added_songs() {
+        let mut state = state_with_queued_song(false);
+        state.update_state(SelectionAction::ChangeSelectionMode(true).into());
+        for id in &["2", "q", "n"] {
+            state.update_state(SelectionAction::Select(song(id)).into());
+        }
+
+        let events = state.update_state(AppAction::QueueSelection);
+
+        assert_eq!(ids(&state), vec!["1", "2", "q", "n"]);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            AppEvent::NotificationShown(message) if message == "Added 1 song to the queue"
+        )));
+    }
+}
@@ -1,9 +1,11 @@
 use super::{
-    ArtistState, DetailsState, HomeState, PlaylistDetailsState, ScreenName, SearchState,
-    UpdatableState,
+    ArtistState, CategoryState, DetailsState, HomeState, PlaylistDetailsState,
+    RecommendationsState, ScreenName, SearchState, ShowState, UpdatableState,
 };
 use crate::app::models::*;
+use crate::app::settings::PlaylistSort;
 use crate::app::state::AppAction;
+use std::collections::HashMap;
 use std::convert::Into;
 use std::iter::Iterator;
 
@@ -13,17 +15,74 @@ pub enum BrowserAction {
     AppendLibraryContent(Vec<AlbumDescription>),
     SetPlaylistsContent(Vec<PlaylistDescription>),
     AppendPlaylistsContent(Vec<PlaylistDescription>),
+    SetPodcastsContent(Vec<PodcastDescription>),
+    // The offset the page was requested at
+    AppendPodcastsContent(u32, Vec<PodcastDescription>),
+    SetPlaylistsSort(PlaylistSort),
+    SetPlaylistFolders(Vec<PlaylistFolder>),
+    // A playlist just created, added without fetching the whole list again
+    AddPlaylist(PlaylistDescription),
+    SetSavedTracks(Vec<SongDescription>),
+    // The offset the page was requested at
+    AppendSavedTracks(u32, Vec<SongDescription>),
+    SetSavedTracksSort(SongSort),
+    // Applied right away, before the API call completes
+    RemoveSavedTrack(String),
     SetAlbumDetails(AlbumDescription),
     SetPlaylistDetails(PlaylistDescription),
+    // Applied right away, before the API call completes: id and positions of the songs
+    RemoveFromPlaylist(String, Vec<usize>),
+    // Applied right away, before the API call completes: id, old and new position
+    MovePlaylistTrack(String, usize, usize),
+    // Applied right away, before the API call completes: id, name and description
+    UpdatePlaylistDetails(String, String, String),
+    // Applied right away, before the API call completes: id and url of the new cover
+    SetPlaylistArt(String, String),
+    SetNewReleases(Vec<AlbumDescription>),
+    AppendNewReleases(Vec<AlbumDescription>),
+    SetCategories(Vec<CategoryDescription>),
+    AppendCategories(Vec<CategoryDescription>),
+    // The id of the category, and its playlists
+    SetCategoryPlaylists(String, Vec<PlaylistDescription>),
+    AppendCategoryPlaylists(String, Vec<PlaylistDescription>),
+    // The song recommendations are based on, and the recommended songs
+    SetRecommendations(SongDescription, Vec<SongDescription>),
     Search(String),
     SetSearchResults(SearchResults),
+    // More results of the filtered type, following those already there
+    AppendSearchResults(SearchFilter, SearchResults),
+    SetSearchFilter(SearchFilter),
     SetArtistDetails(ArtistDescription),
-    AppendArtistReleases(Vec<AlbumDescription>),
+    AppendArtistReleases(String, ReleaseGroup, Vec<AlbumDescription>),
+    SetArtistReleaseGroup(String, ReleaseGroup),
+    SetShowDetails(ShowDescription),
+    AppendShowEpisodes(String, Vec<EpisodeDescription>),
+    FollowShow(PodcastDescription),
+    UnfollowShow(String),
     NavigationPush(ScreenName),
     NavigationPop,
     NavigationPopTo(ScreenName),
     SaveAlbum(AlbumDescription),
     UnsaveAlbum(String),
+    SetFollowedArtists(Vec<ArtistSummary>),
+    FollowArtist(ArtistSummary),
+    UnfollowArtist(String),
+    SetAudioFeatures(Vec<(String, Option<SongFeatures>)>),
+    // By track id, fetched unless already known
+    ShowSongCredits(String),
+    SetSongCredits(String, SongCredits),
+    // Once credits couldn't be fetched, they aren't offered anymore
+    DisableSongCredits,
+    // Recently played songs, as reported by Spotify
+    SetHistory(Vec<PlayedSong>),
+    // A song that just started playing here
+    AddToHistory(PlayedSong),
+    SetRecentlyPlayed(Vec<RecentContext>),
+    SetMadeForYou(Vec<PlaylistDescription>),
+    // The country of the charts, None for the worldwide ones
+    SetCharts(Option<String>, Vec<PlaylistDescription>),
+    // By name
+    SelectSmartPlaylist(String),
 }
 
 impl Into<AppAction> for BrowserAction {
@@ -36,16 +95,45 @@ impl Into<AppAction> for BrowserAction {
 pub enum BrowserEvent {
     LibraryUpdated,
     SavedPlaylistsUpdated,
+    SavedPodcastsUpdated,
+    PlaylistFoldersUpdated,
+    SavedTracksUpdated,
+    // The position of the first appended track
+    SavedTracksAppended(usize),
     AlbumDetailsLoaded(String),
     PlaylistDetailsLoaded(String),
+    PlaylistTracksRemoved(String),
+    PlaylistTrackMoved(String, usize, usize),
+    PlaylistDetailsUpdated(String),
     SearchUpdated,
     SearchResultsUpdated,
+    SearchFilterChanged,
     ArtistDetailsUpdated(String),
+    ArtistReleaseGroupChanged(String),
+    ShowDetailsUpdated(String),
+    ShowEpisodesAppended(String),
+    ShowFollowed(String),
+    ShowUnfollowed(String),
     NavigationPushed(ScreenName),
     NavigationPopped,
     NavigationPoppedTo(ScreenName),
     AlbumSaved(String),
     AlbumUnsaved(String),
+    FollowedArtistsUpdated,
+    ArtistFollowed(String),
+    ArtistUnfollowed(String),
+    AudioFeaturesLoaded,
+    SongCreditsRequested(String),
+    SongCreditsShown(String, SongCredits),
+    HistoryUpdated,
+    RecentlyPlayedUpdated,
+    MadeForYouUpdated,
+    ChartsUpdated,
+    SmartPlaylistSelected,
+    NewReleasesUpdated,
+    CategoriesUpdated,
+    CategoryPlaylistsUpdated(String),
+    RecommendationsUpdated(String),
 }
 
 pub enum BrowserScreen {
@@ -54,6 +142,9 @@ pub enum BrowserScreen {
     Search(SearchState),
     Artist(ArtistState),
     PlaylistDetails(PlaylistDetailsState),
+    Category(CategoryState),
+    Recommendations(RecommendationsState),
+    Show(ShowState),
 }
 
 impl BrowserScreen {
@@ -68,6 +159,11 @@ impl BrowserScreen {
             ScreenName::PlaylistDetails(id) => {
                 BrowserScreen::PlaylistDetails(PlaylistDetailsState::new(id.to_string()))
             }
+            ScreenName::Category(id) => BrowserScreen::Category(CategoryState::new(id.to_string())),
+            ScreenName::Recommendations(id) => {
+                BrowserScreen::Recommendations(RecommendationsState::new(id.to_string()))
+            }
+            ScreenName::Show(id) => BrowserScreen::Show(ShowState::new(id.to_string())),
         }
     }
 
@@ -78,6 +174,9 @@ impl BrowserScreen {
             Self::Search(state) => state,
             Self::Artist(state) => state,
             Self::PlaylistDetails(state) => state,
+            Self::Category(state) => state,
+            Self::Recommendations(state) => state,
+            Self::Show(state) => state,
         }
     }
 }
@@ -92,6 +191,9 @@ impl NamedScreen for BrowserScreen {
             Self::Search(state) => &state.name,
             Self::Artist(state) => &state.name,
             Self::PlaylistDetails(state) => &state.name,
+            Self::Category(state) => &state.name,
+            Self::Recommendations(state) => &state.name,
+            Self::Show(state) => &state.name,
         }
     }
 }
@@ -179,15 +281,35 @@ where
 
 pub struct BrowserState {
     navigation: NavStack<BrowserScreen>,
+    // Kept for the whole session, by track id, None for tracks without features
+    audio_features: HashMap<String, Option<SongFeatures>>,
+    // Kept for the whole session, by track id
+    song_credits: HashMap<String, SongCredits>,
+    song_credits_disabled: bool,
 }
 
 impl BrowserState {
     pub fn new() -> Self {
         Self {
             navigation: NavStack::new(BrowserScreen::Home(Default::default())),
+            audio_features: HashMap::new(),
+            song_credits: HashMap::new(),
+            song_credits_disabled: false,
         }
     }
 
+    pub fn has_song_credits(&self) -> bool {
+        !self.song_credits_disabled
+    }
+
+    pub fn audio_features(&self, id: &str) -> Option<&SongFeatures> {
+        self.audio_features.get(id)?.as_ref()
+    }
+
+    pub fn has_fetched_audio_features(&self, id: &str) -> bool {
+        self.audio_features.contains_key(id)
+    }
+
     pub fn current_screen(&self) -> &ScreenName {
         self.navigation.current().name()
     }
@@ -235,6 +357,27 @@ impl BrowserState {
         })
     }
 
+    pub fn category_state(&self, id: &str) -> Option<&CategoryState> {
+        self.navigation.iter_rev().find_map(|screen| match screen {
+            BrowserScreen::Category(state) if state.id == id => Some(state),
+            _ => None,
+        })
+    }
+
+    pub fn recommendations_state(&self, id: &str) -> Option<&RecommendationsState> {
+        self.navigation.iter_rev().find_map(|screen| match screen {
+            BrowserScreen::Recommendations(state) if state.id == id => Some(state),
+            _ => None,
+        })
+    }
+
+    pub fn show_state(&self, id: &str) -> Option<&ShowState> {
+        self.navigation.iter_rev().find_map(|screen| match screen {
+            BrowserScreen::Show(state) if state.id == id => Some(state),
+            _ => None,
+        })
+    }
+
     fn push_if_needed(&mut self, name: ScreenName) -> Vec<BrowserEvent> {
         let navigation = &mut self.navigation;
         let screen_state = navigation.screen_state(&name);
@@ -269,6 +412,23 @@ impl UpdatableState for BrowserState {
                 events
             }
             BrowserAction::NavigationPush(name) => self.push_if_needed(name),
+            BrowserAction::SetAudioFeatures(features) => {
+                self.audio_features.extend(features);
+                vec![BrowserEvent::AudioFeaturesLoaded]
+            }
+            BrowserAction::ShowSongCredits(id) => match self.song_credits.get(&id) {
+                Some(credits) => vec![BrowserEvent::SongCreditsShown(id, credits.clone())],
+                None if self.song_credits_disabled => vec![],
+                None => vec![BrowserEvent::SongCreditsRequested(id)],
+            },
+            BrowserAction::SetSongCredits(id, credits) => {
+                self.song_credits.insert(id.clone(), credits.clone());
+                vec![BrowserEvent::SongCreditsShown(id, credits)]
+            }
+            BrowserAction::DisableSongCredits => {
+                self.song_credits_disabled = true;
+                vec![]
+            }
             BrowserAction::NavigationPopTo(name) => {
                 self.navigation.pop_to(&name);
                 vec![BrowserEvent::NavigationPoppedTo(name)]
@@ -356,4 +516,63 @@ pub mod tests {
         assert_eq!(state.count(), 2);
         assert_eq!(events, vec![BrowserEvent::NavigationPoppedTo(new_screen)]);
     }
+
+    #[test]
+    fn test_audio_features_cache() {
+        let mut state = BrowserState::new();
+        let features = SongFeatures {
+            energy: 0.9,
+            valence: 0.5,
+            danceability: 0.2,
+        };
+
+        let events = state.update_with(BrowserAction::SetAudioFeatures(vec![
+            ("1".to_string(), Some(features)),
+            ("2".to_string(), None),
+        ]));
+
+        assert_eq!(events, vec![BrowserEvent::AudioFeaturesLoaded]);
+        assert_eq!(state.audio_features("1"), Some(&features));
+        assert_eq!(state.audio_features("2"), None);
+        assert!(state.has_fetched_audio_features("2"));
+        assert!(!state.has_fetched_audio_features("3"));
+        assert_eq!(features.moods(), vec![Mood::Energetic]);
+    }
+
+    #[test]
+    fn test_song_credits_fetched_once() {
+        let mut state = BrowserState::new();
+        let credits = SongCredits {
+            performers: vec!["Foo".to_string()],
+            writers: vec![],
+            producers: vec!["Bar".to_string()],
+        };
+
+        let events = state.update_with(BrowserAction::ShowSongCredits("1".to_string()));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::SongCreditsRequested("1".to_string())]
+        );
+
+        state.update_with(BrowserAction::SetSongCredits(
+            "1".to_string(),
+            credits.clone(),
+        ));
+        let events = state.update_with(BrowserAction::ShowSongCredits("1".to_string()));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::SongCreditsShown("1".to_string(), credits)]
+        );
+    }
+
+    #[test]
+    fn test_song_credits_disabled() {
+        let mut state = BrowserState::new();
+        assert!(state.has_song_credits());
+
+        state.update_with(BrowserAction::DisableSongCredits);
+        assert!(!state.has_song_credits());
+        let events = state.update_with(BrowserAction::ShowSongCredits("1".to_string()));
+        assert!(events.is_empty());
+    }
 }
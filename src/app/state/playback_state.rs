@@ -1,5 +1,6 @@
 use rand::{rngs::SmallRng, seq::SliceRandom, RngCore, SeedableRng};
 use std::collections::HashMap;
+use std::time::Instant;
 
 use crate::app::models::SongDescription;
 use crate::app::state::{AppAction, AppEvent, UpdatableState};
@@ -8,14 +9,37 @@ use crate::app::state::{AppAction, AppEvent, UpdatableState};
 pub enum PlaylistSource {
     Playlist(String),
     Album(String),
+    SavedTracks,
     None,
 }
 
+impl PlaylistSource {
+    pub fn uri(&self) -> Option<String> {
+        match self {
+            Self::Playlist(id) => Some(format!("spotify:playlist:{}", id)),
+            Self::Album(id) => Some(format!("spotify:album:{}", id)),
+            Self::SavedTracks => Some("spotify:collection:tracks".to_string()),
+            Self::None => None,
+        }
+    }
+
+    pub fn from_uri(uri: &str) -> Self {
+        let mut parts = uri.splitn(3, ':').skip(1);
+        match (parts.next(), parts.next()) {
+            (Some("playlist"), Some(id)) => Self::Playlist(id.to_string()),
+            (Some("album"), Some(id)) => Self::Album(id.to_string()),
+            (Some("collection"), Some("tracks")) => Self::SavedTracks,
+            _ => Self::None,
+        }
+    }
+}
+
 impl PartialEq for PlaylistSource {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (&Self::Playlist(ref a), &Self::Playlist(ref b)) => a == b,
             (&Self::Album(ref a), &Self::Album(ref b)) => a == b,
+            (&Self::SavedTracks, &Self::SavedTracks) => true,
             _ => false,
         }
     }
@@ -23,14 +47,204 @@ impl PartialEq for PlaylistSource {
 
 impl Eq for PlaylistSource {}
 
+// What the songs of a radio are recommended after
+#[derive(Clone, Debug)]
+pub enum RadioSeed {
+    // The song itself plays first
+    Song(SongDescription),
+    // The id and name of the artist
+    Artist(String, String),
+}
+
+impl RadioSeed {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Song(song) => &song.title,
+            Self::Artist(_, name) => name,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SleepTimer {
+    // Playback pauses once this is reached, see PlaybackAction::SleepTimerExpired
+    At(Instant),
+    // Playback pauses when the current track ends, on the next one
+    EndOfTrack,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepeatMode {
+    Off,
+    // The whole queue starts over once it ends
+    All,
+    // The current song plays again when it ends, skipping still moves on
+    One,
+}
+
+impl RepeatMode {
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "all" => Self::All,
+            "one" => Self::One,
+            _ => Self::Off,
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::All => "all",
+            Self::One => "one",
+        }
+    }
+
+    // In the order a repeat button goes through
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::All,
+            Self::All => Self::One,
+            Self::One => Self::Off,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShuffleMode {
+    Random,
+    // Songs by the same artist are kept apart whenever possible
+    ArtistSpread,
+    // Albums play in a random order, each one from start to end
+    Album,
+}
+
+impl ShuffleMode {
+    pub fn from_id(id: &str) -> Self {
+        match id {
+            "artist-spread" => Self::ArtistSpread,
+            "album" => Self::Album,
+            _ => Self::Random,
+        }
+    }
+}
+
+// Groups ids by key, in the order each key first appears
+fn group_by_key(songs: Vec<(String, String)>) -> Vec<(String, Vec<String>)> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<(String, Vec<String>)> = vec![];
+    for (id, key) in songs {
+        match index.get(&key) {
+            Some(&i) => groups[i].1.push(id),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key, vec![id]));
+            }
+        }
+    }
+    groups
+}
+
+// Picks songs one group at a time, weighted by how many songs each group has left.
+// The group picked last is skipped unless nothing else is left, and a group holding
+// more than half of the remaining songs is picked right away so it can still be spread out.
+fn spread_groups(
+    songs: Vec<(String, String)>,
+    previous: Option<String>,
+    rng: &mut SmallRng,
+) -> Vec<String> {
+    let mut groups = group_by_key(songs);
+    for (_, group) in groups.iter_mut() {
+        group.shuffle(rng);
+    }
+
+    let mut previous = previous;
+    let mut spread = vec![];
+    loop {
+        let remaining: usize = groups.iter().map(|(_, g)| g.len()).sum();
+        if remaining == 0 {
+            break;
+        }
+
+        let eligible: Vec<usize> = (0..groups.len())
+            .filter(|&i| !groups[i].1.is_empty() && Some(&groups[i].0) != previous.as_ref())
+            .collect();
+        let urgent = eligible
+            .iter()
+            .find(|&&i| groups[i].1.len() > remaining / 2)
+            .cloned();
+        let candidates: Vec<usize> = match urgent {
+            Some(i) => vec![i],
+            None if !eligible.is_empty() => eligible,
+            None => (0..groups.len())
+                .filter(|&i| !groups[i].1.is_empty())
+                .collect(),
+        };
+
+        let total: usize = candidates.iter().map(|&i| groups[i].1.len()).sum();
+        let mut pick = (rng.next_u32() as usize) % total;
+        let chosen = candidates
+            .into_iter()
+            .find(|&i| {
+                let len = groups[i].1.len();
+                if pick < len {
+                    true
+                } else {
+                    pick -= len;
+                    false
+                }
+            })
+            .unwrap();
+
+        let (key, group) = &mut groups[chosen];
+        spread.push(group.pop().unwrap());
+        previous = Some(key.clone());
+    }
+    spread
+}
+
+// Keeps the songs of each group together and in order, shuffling the groups around
+fn shuffle_groups(
+    songs: Vec<(String, String)>,
+    first: Option<String>,
+    rng: &mut SmallRng,
+) -> Vec<String> {
+    let mut groups = group_by_key(songs);
+    groups.shuffle(rng);
+    if let Some(i) = groups
+        .iter()
+        .position(|(key, _)| Some(key) == first.as_ref())
+    {
+        let group = groups.remove(i);
+        groups.insert(0, group);
+    }
+    groups.into_iter().flat_map(|(_, group)| group).collect()
+}
+
 pub struct PlaybackState {
     rng: SmallRng,
     indexed_songs: HashMap<String, SongDescription>,
     running_order: Vec<String>,
     running_order_shuffled: Option<Vec<String>>,
     pub source: PlaylistSource,
+    // Songs were queued, moved or removed by hand since the source was loaded
+    modified: bool,
+    // Set while the queue is made of recommendations, until another playlist is loaded
+    pub radio: Option<RadioSeed>,
     is_playing: bool,
     pub current_song_id: Option<String>,
+    // A song that plays right after the current one, whatever happens to the running order
+    pinned_song_id: Option<String>,
+    // Where to resume in the running order after a pinned song was played
+    resume_from_id: Option<String>,
+    // A song interrupted by a swap, and the position it should resume from
+    swapped_out: Option<(String, u32)>,
+    // Songs queued by hand, which can outlive the context they were queued in
+    queued_ids: Vec<String>,
+    pub sleep_timer: Option<SleepTimer>,
+    pub repeat_mode: RepeatMode,
+    shuffle_mode: ShuffleMode,
+    // From 0 to 1, applied on top of the system volume
+    pub volume: f64,
 }
 
 impl PlaybackState {
@@ -42,6 +256,17 @@ impl PlaybackState {
         self.running_order_shuffled.is_some()
     }
 
+    // Whether the songs of the source play as they were loaded, so that playing
+    // one of them doesn't require loading it again
+    pub fn plays_as_loaded(&self, source: &PlaylistSource) -> bool {
+        !self.modified && &self.source == source
+    }
+
+    // Same as plays_as_loaded, for a source that was loaded with these very songs
+    pub fn plays_as_loaded_with(&self, source: &PlaylistSource, songs: &[SongDescription]) -> bool {
+        self.plays_as_loaded(source) && self.running_order.iter().eq(songs.iter().map(|s| &s.id))
+    }
+
     pub fn song(&self, id: &str) -> Option<&SongDescription> {
         self.indexed_songs.get(id)
     }
@@ -61,16 +286,40 @@ impl PlaybackState {
             .and_then(|current_song_id| self.song(current_song_id))
     }
 
+    pub fn pinned_song(&self) -> Option<&SongDescription> {
+        self.pinned_song_id
+            .as_ref()
+            .filter(|&id| Some(id) != self.current_song_id.as_ref())
+            .and_then(|id| self.song(id))
+    }
+
+    fn position_id(&self) -> Option<&String> {
+        self.resume_from_id
+            .as_ref()
+            .or_else(|| self.current_song_id.as_ref())
+    }
+
+    fn repeats_all(&self) -> bool {
+        self.repeat_mode == RepeatMode::All && self.current_song_id.is_some()
+    }
+
     pub fn prev_song(&self) -> Option<&SongDescription> {
+        if let Some(id) = self.resume_from_id.as_ref() {
+            return self.song(id);
+        }
         self.current_song_id
             .as_ref()
             .and_then(|id| self.songs().take_while(|&song| song.id != *id).last())
+            .or_else(|| self.songs().last().filter(|_| self.repeats_all()))
     }
 
     pub fn next_song(&self) -> Option<&SongDescription> {
-        self.current_song_id
-            .as_ref()
-            .and_then(|id| self.songs().skip_while(|&song| song.id != *id).nth(1))
+        self.pinned_song()
+            .or_else(|| {
+                self.position_id()
+                    .and_then(|id| self.songs().skip_while(|&song| song.id != *id).nth(1))
+            })
+            .or_else(|| self.songs().next().filter(|_| self.repeats_all()))
     }
 
     fn index_tracks(tracks: Vec<SongDescription>) -> HashMap<String, SongDescription> {
@@ -82,78 +331,486 @@ impl PlaybackState {
     }
 
     fn shuffle(&mut self) {
-        let mut to_shuffle: Vec<String> = self
+        let position_id = self
+            .position_id()
+            .filter(|&id| self.running_order.contains(id))
+            .cloned();
+        let to_shuffle: Vec<String> = self
             .running_order
             .iter()
-            .filter(|&id| Some(id) != self.current_song_id.as_ref())
+            .filter(|&id| Some(id) != position_id.as_ref())
             .cloned()
             .collect();
-        let mut final_list: Vec<String> = self.current_song_id.iter().cloned().collect();
-        to_shuffle.shuffle(&mut self.rng);
-        final_list.append(&mut to_shuffle);
+        let mut shuffled = match self.shuffle_mode {
+            ShuffleMode::Random => {
+                let mut to_shuffle = to_shuffle;
+                to_shuffle.shuffle(&mut self.rng);
+                to_shuffle
+            }
+            ShuffleMode::ArtistSpread => {
+                let previous = position_id.as_ref().map(|id| self.artist_key(id));
+                let songs = self.keyed(to_shuffle, Self::artist_key);
+                spread_groups(songs, previous, &mut self.rng)
+            }
+            ShuffleMode::Album => {
+                let first = position_id.as_ref().map(|id| self.album_key(id));
+                let songs = self.keyed(to_shuffle, Self::album_key);
+                shuffle_groups(songs, first, &mut self.rng)
+            }
+        };
+        let mut final_list: Vec<String> = position_id.into_iter().collect();
+        final_list.append(&mut shuffled);
         self.running_order_shuffled = Some(final_list);
     }
 
+    fn keyed(&self, ids: Vec<String>, key: fn(&Self, &str) -> String) -> Vec<(String, String)> {
+        ids.into_iter()
+            .map(|id| {
+                let key = key(self, &id);
+                (id, key)
+            })
+            .collect()
+    }
+
+    // Songs with no known artist or album are only grouped with themselves
+    fn artist_key(&self, id: &str) -> String {
+        self.song(id)
+            .and_then(|song| song.artists.first())
+            .map(|artist| artist.id.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    fn album_key(&self, id: &str) -> String {
+        self.song(id)
+            .map(|song| song.album.id.clone())
+            .filter(|album_id| !album_id.is_empty())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    // Returns whether the running order was shuffled again
+    pub fn set_shuffle_mode(&mut self, mode: ShuffleMode) -> bool {
+        if mode == self.shuffle_mode {
+            return false;
+        }
+        self.shuffle_mode = mode;
+        if self.is_shuffled() {
+            self.shuffle();
+        }
+        self.is_shuffled()
+    }
+
     fn set_playlist(&mut self, source: PlaylistSource, tracks: Vec<SongDescription>) {
         self.source = source;
+        self.modified = false;
         self.running_order = tracks.iter().map(|t| t.id.clone()).collect();
-        self.indexed_songs = Self::index_tracks(tracks);
+        let mut indexed_songs = Self::index_tracks(tracks);
+        // Songs that are not part of the new playlist but still need to be known
+        let kept_ids: Vec<String> = self
+            .pinned_song_id
+            .iter()
+            .chain(self.current_song_id.iter())
+            .cloned()
+            .collect();
+        for id in kept_ids {
+            if let Some(song) = self.indexed_songs.remove(&id) {
+                indexed_songs.entry(id).or_insert(song);
+            }
+        }
+        self.indexed_songs = indexed_songs;
+        self.queued_ids.clear();
         if self.is_shuffled() {
             self.shuffle();
         }
     }
 
-    pub fn queue(&mut self, track: SongDescription) {
-        if !self.indexed_songs.contains_key(&track.id) {
-            self.source = PlaylistSource::None;
+    // Returns whether the song was added, songs already in the queue aren't
+    pub fn queue(&mut self, track: SongDescription) -> bool {
+        if self.running_order.contains(&track.id) {
+            return false;
+        }
+        self.modified = true;
+        self.running_order.push(track.id.clone());
+        if let Some(shuffled) = self.running_order_shuffled.as_mut() {
+            // Anywhere after the first song, which may be playing
+            let next = (self.rng.next_u32() as usize) % shuffled.len().max(1);
+            shuffled.insert((next + 1).min(shuffled.len()), track.id.clone());
+        }
+        self.queued_ids.push(track.id.clone());
+        self.indexed_songs.insert(track.id.clone(), track);
+        true
+    }
+
+    pub fn queue_at(&mut self, track: SongDescription, position: usize) {
+        if !self.running_order.contains(&track.id) {
+            self.modified = true;
+            if let Some(shuffled) = self.running_order_shuffled.as_mut() {
+                shuffled.insert(position.min(shuffled.len()), track.id.clone());
+                self.running_order.push(track.id.clone());
+            } else {
+                let position = position.min(self.running_order.len());
+                self.running_order.insert(position, track.id.clone());
+            }
+            self.queued_ids.push(track.id.clone());
+            self.indexed_songs.insert(track.id.clone(), track);
+        }
+    }
+
+    // What's still to play after the current position, in the order it would play
+    fn upcoming_ids(&self) -> &[String] {
+        let order = self
+            .running_order_shuffled
+            .as_ref()
+            .unwrap_or(&self.running_order);
+        let start = self
+            .position_id()
+            .and_then(|position_id| order.iter().position(|id| id == position_id))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        &order[start..]
+    }
+
+    // The songs queued by hand that are still to play, in the order they would play
+    pub fn upcoming_queued(&self) -> Vec<SongDescription> {
+        self.upcoming_ids()
+            .iter()
+            .filter(|&id| self.queued_ids.contains(id))
+            .filter_map(|id| self.song(id))
+            .cloned()
+            .collect()
+    }
+
+    // Queued songs carried over from a previous context go after the new one
+    pub fn requeue(&mut self, tracks: Vec<SongDescription>) {
+        let ids: Vec<String> = tracks
+            .iter()
+            .filter(|t| !self.indexed_songs.contains_key(&t.id))
+            .map(|t| t.id.clone())
+            .collect();
+        self.append(tracks);
+        self.queued_ids.extend(ids);
+    }
+
+    // Unlike queue, tracks go after everything else, in order
+    fn append(&mut self, tracks: Vec<SongDescription>) -> Option<String> {
+        let mut first = None;
+        for track in tracks {
+            if self.indexed_songs.contains_key(&track.id) {
+                continue;
+            }
             self.running_order.push(track.id.clone());
             if let Some(shuffled) = self.running_order_shuffled.as_mut() {
-                let next = (self.rng.next_u32() as usize) % (shuffled.len() - 1);
-                shuffled.insert(next + 1, track.id.clone());
+                shuffled.push(track.id.clone());
             }
+            first.get_or_insert_with(|| track.id.clone());
             self.indexed_songs.insert(track.id.clone(), track);
         }
+        if first.is_some() {
+            self.modified = true;
+        }
+        first
+    }
+
+    // Moves a song within the order songs play in, returning where it was and where it went
+    fn move_song(&mut self, id: &str, position: usize) -> Option<(usize, usize)> {
+        let order = match self.running_order_shuffled.as_mut() {
+            Some(shuffled) => shuffled,
+            None => &mut self.running_order,
+        };
+        let from = order.iter().position(|t| t == id)?;
+        let to = position.min(order.len() - 1);
+        if from == to {
+            return None;
+        }
+        let id = order.remove(from);
+        order.insert(to, id);
+        self.modified = true;
+        Some((from, to))
     }
 
     pub fn dequeue(&mut self, id: &str) {
         if self.indexed_songs.contains_key(id) {
-            self.source = PlaylistSource::None;
+            self.modified = true;
             self.running_order.retain(|t| t != id);
             if let Some(shuffled) = self.running_order_shuffled.as_mut() {
                 shuffled.retain(|t| t != id);
             }
+            self.queued_ids.retain(|t| t != id);
+            if self.pinned_song_id.as_deref() == Some(id) {
+                self.pinned_song_id = None;
+            }
+            self.forget_if_unused(id);
+        }
+    }
+
+    pub fn pin(&mut self, track: SongDescription) -> bool {
+        if self.current_song_id.as_ref() == Some(&track.id) {
+            return false;
+        }
+        if let Some(previous) = self.pinned_song_id.replace(track.id.clone()) {
+            self.forget_if_unused(&previous);
+        }
+        self.indexed_songs.entry(track.id.clone()).or_insert(track);
+        true
+    }
+
+    pub fn unpin(&mut self) -> bool {
+        if let Some(id) = self.pinned_song_id.take() {
+            self.forget_if_unused(&id);
+            true
+        } else {
+            false
+        }
+    }
+
+    // Drop a song from the index unless something still refers to it
+    fn forget_if_unused(&mut self, id: &str) {
+        let is_used = self.running_order.iter().any(|t| t == id)
+            || self.current_song_id.as_deref() == Some(id)
+            || self.pinned_song_id.as_deref() == Some(id);
+        if !is_used {
             self.indexed_songs.remove(id);
         }
     }
 
+    fn set_current_song(&mut self, id: String) {
+        let previous = self.current_song_id.replace(id);
+        if let Some(previous) = previous {
+            self.forget_if_unused(&previous);
+        }
+        self.is_playing = true;
+    }
+
+    // Drops everything that would play after the current song, what was already played is kept
+    fn clear_upcoming(&mut self) -> bool {
+        let position_id = match self.position_id() {
+            Some(id) => id.clone(),
+            None => return false,
+        };
+        let order = self
+            .running_order_shuffled
+            .as_ref()
+            .unwrap_or(&self.running_order);
+        let upcoming: Vec<String> = order
+            .iter()
+            .skip_while(|&id| *id != position_id)
+            .skip(1)
+            .cloned()
+            .collect();
+        let unpinned = self.pinned_song().is_some();
+        if upcoming.is_empty() && !unpinned {
+            return false;
+        }
+
+        self.running_order.retain(|id| !upcoming.contains(id));
+        if let Some(shuffled) = self.running_order_shuffled.as_mut() {
+            shuffled.retain(|id| !upcoming.contains(id));
+        }
+        self.queued_ids.retain(|id| !upcoming.contains(id));
+        self.unpin();
+        for id in upcoming.iter() {
+            self.forget_if_unused(id);
+        }
+        self.modified = true;
+        true
+    }
+
+    // Once explicit songs are hidden from the context, those of its songs still to play are
+    // dropped. Songs already played, the current one and those queued or pinned by hand stay.
+    pub fn remove_explicit(&mut self) -> bool {
+        let removed: Vec<String> = self
+            .upcoming_ids()
+            .iter()
+            .filter(|&id| !self.queued_ids.contains(id))
+            .filter(|&id| Some(id) != self.pinned_song_id.as_ref())
+            .filter(|&id| Some(id) != self.current_song_id.as_ref())
+            .filter(|&id| self.song(id).map(|s| s.explicit).unwrap_or(false))
+            .cloned()
+            .collect();
+        if removed.is_empty() {
+            return false;
+        }
+
+        self.running_order.retain(|id| !removed.contains(id));
+        if let Some(shuffled) = self.running_order_shuffled.as_mut() {
+            shuffled.retain(|id| !removed.contains(id));
+        }
+        for id in removed.iter() {
+            self.forget_if_unused(id);
+        }
+        true
+    }
+
+    // The sleep timer, repeat and shuffle modes and volume are kept,
+    // they aren't part of what's queued
     fn clear(&mut self) {
+        let sleep_timer = self.sleep_timer.take();
+        let repeat_mode = self.repeat_mode;
+        let shuffle_mode = self.shuffle_mode;
+        let volume = self.volume;
         *self = Default::default();
+        self.sleep_timer = sleep_timer;
+        self.repeat_mode = repeat_mode;
+        self.shuffle_mode = shuffle_mode;
+        self.volume = volume;
+    }
+
+    // The next song takes over, and the interrupted one is pinned right after it.
+    // Whether the next song comes from the context or was pinned, we then carry on
+    // from the same place in the running order.
+    fn swap_with_next(&mut self, position: Option<u32>) -> Option<String> {
+        let current_id = self.current_song_id.clone()?;
+        let next_id = self.next_song()?.id.clone();
+        self.resume_from_id = if self.pinned_song().is_some() {
+            self.position_id().cloned()
+        } else {
+            None
+        };
+        self.pinned_song_id = Some(current_id.clone());
+        self.set_current_song(next_id.clone());
+        self.swapped_out = position.filter(|&p| p > 0).map(|p| (current_id, p));
+        Some(next_id)
+    }
+
+    fn take_start_position(&mut self, id: &str) -> Option<u32> {
+        match self.swapped_out.take() {
+            Some((swapped_id, position)) if swapped_id == id => Some(position),
+            _ => None,
+        }
+    }
+
+    fn track_changed(&mut self, id: String) -> Vec<PlaybackEvent> {
+        let mut events = vec![];
+        if let Some(position) = self.take_start_position(&id) {
+            events.push(PlaybackEvent::StartPositionSet(position));
+        }
+        events.push(PlaybackEvent::TrackChanged(id));
+        if self.is_playing {
+            events.push(PlaybackEvent::PlaybackResumed);
+        } else {
+            events.push(PlaybackEvent::PlaybackPaused);
+        }
+        events
     }
 
     fn play(&mut self, id: &str) {
-        self.current_song_id = Some(id.to_string());
-        self.is_playing = true;
+        self.resume_from_id = None;
+        if self.pinned_song_id.as_deref() == Some(id) {
+            self.pinned_song_id = None;
+        }
+        self.set_current_song(id.to_string());
     }
 
     fn stop(&mut self) {
-        self.current_song_id = None;
+        if let Some(id) = self.current_song_id.take() {
+            self.forget_if_unused(&id);
+        }
+        self.resume_from_id = None;
         self.is_playing = false;
     }
 
     fn play_next(&mut self) -> Option<String> {
+        if let Some(id) = self.pinned_song().map(|pinned| pinned.id.clone()) {
+            self.pinned_song_id = None;
+            self.resume_from_id = self.position_id().cloned();
+            self.set_current_song(id.clone());
+            return Some(id);
+        }
         let id = self.next_song().map(|next| next.id.clone());
         if let Some(id) = id.clone() {
-            self.current_song_id = Some(id);
-            self.is_playing = true;
+            self.resume_from_id = None;
+            self.set_current_song(id);
         }
         id
     }
 
+    // Moves on to the next song as usual, but without playing it
+    fn sleep_at_end_of_track(&mut self) -> Vec<PlaybackEvent> {
+        self.sleep_timer = None;
+        let mut events = vec![PlaybackEvent::SleepTimerChanged];
+        if let Some(id) = self.play_next() {
+            self.is_playing = false;
+            events.append(&mut self.track_changed(id));
+        } else {
+            self.stop();
+            events.push(PlaybackEvent::PlaybackStopped);
+        }
+        events.push(PlaybackEvent::SleepTimerEnded);
+        events
+    }
+
+    fn sleep(&mut self) -> Vec<PlaybackEvent> {
+        self.sleep_timer = None;
+        let mut events = vec![PlaybackEvent::SleepTimerChanged];
+        if self.is_playing() && self.toggle_play() == Some(false) {
+            events.push(PlaybackEvent::PlaybackPaused);
+        }
+        events.push(PlaybackEvent::SleepTimerEnded);
+        events
+    }
+
+    // Picks up where a previous session left, paused, unless something already plays
+    fn restore(
+        &mut self,
+        source: PlaylistSource,
+        tracks: Vec<SongDescription>,
+        id: String,
+        position: u32,
+    ) -> Vec<PlaybackEvent> {
+        if self.current_song_id.is_some() || !tracks.iter().any(|t| t.id == id) {
+            return vec![];
+        }
+        self.set_playlist(source, tracks);
+        // Only part of the context was saved
+        self.modified = true;
+        self.play(&id);
+        self.is_playing = false;
+        let mut events = vec![PlaybackEvent::PlaylistChanged];
+        if position > 0 {
+            events.push(PlaybackEvent::StartPositionSet(position));
+        }
+        events.push(PlaybackEvent::TrackChanged(id));
+        events.push(PlaybackEvent::PlaybackPaused);
+        events.push(PlaybackEvent::SeekSynced(position));
+        events
+    }
+
+    // The queue is replaced by the recommendations, which start playing
+    fn load_radio(&mut self, seed: RadioSeed, tracks: Vec<SongDescription>) -> Vec<PlaybackEvent> {
+        let first = match tracks.first() {
+            Some(track) => track.id.clone(),
+            None => return vec![],
+        };
+        self.set_playlist(PlaylistSource::None, tracks);
+        self.radio = Some(seed);
+        let mut events = vec![PlaybackEvent::PlaylistChanged];
+        if self.current_song_id.as_ref() != Some(&first) {
+            self.play(&first);
+            events.append(&mut self.track_changed(first));
+        }
+        events
+    }
+
+    // Playback carries on with the recommendations if the queue ran out in the meantime,
+    // but not if it was cleared
+    fn autoplay(&mut self, tracks: Vec<SongDescription>) -> Vec<PlaybackEvent> {
+        let ended = self.current_song_id.is_none() && !self.running_order.is_empty();
+        let first = match self.append(tracks) {
+            Some(first) => first,
+            None => return vec![],
+        };
+        let mut events = vec![PlaybackEvent::PlaylistChanged];
+        if ended {
+            self.play(&first);
+            events.append(&mut self.track_changed(first));
+        }
+        events
+    }
+
     fn play_prev(&mut self) -> Option<String> {
         let id = self.prev_song().map(|prev| prev.id.clone());
         if let Some(id) = id.clone() {
-            self.current_song_id = Some(id);
-            self.is_playing = true;
+            self.resume_from_id = None;
+            self.set_current_song(id);
         }
         id
     }
@@ -167,6 +824,12 @@ impl PlaybackState {
         }
     }
 
+    pub fn set_shuffled(&mut self, shuffled: bool) {
+        if shuffled != self.is_shuffled() {
+            self.toggle_shuffle();
+        }
+    }
+
     fn toggle_shuffle(&mut self) {
         if !self.is_shuffled() {
             self.shuffle();
@@ -184,8 +847,18 @@ impl Default for PlaybackState {
             running_order: vec![],
             running_order_shuffled: None,
             source: PlaylistSource::None,
+            modified: false,
+            radio: None,
             is_playing: false,
             current_song_id: None,
+            pinned_song_id: None,
+            resume_from_id: None,
+            swapped_out: None,
+            queued_ids: vec![],
+            sleep_timer: None,
+            repeat_mode: RepeatMode::Off,
+            shuffle_mode: ShuffleMode::Random,
+            volume: 1.0,
         }
     }
 }
@@ -198,13 +871,43 @@ pub enum PlaybackAction {
     ToggleShuffle,
     Seek(u32),
     SyncSeek(u32),
+    // Sent by the player while the visualizer is on, see PlaybackEvent::SpectrumSynced
+    SyncSpectrum(Vec<f32>),
     Load(String),
     LoadPlaylist(PlaylistSource, Vec<SongDescription>),
+    // Recommendations are fetched first, see PlaybackEvent::RadioRequested
+    StartRadio(RadioSeed),
+    LoadRadio(RadioSeed, Vec<SongDescription>),
+    // The queue, the song that was playing and its position, as saved on exit
+    RestoreSession(PlaylistSource, Vec<SongDescription>, String, u32),
     Next,
     Previous,
     Queue(SongDescription),
+    QueueAt(SongDescription, usize),
+    // Moves a song to another position in the queue, as it is shown
+    Move(String, usize),
     Dequeue(String),
+    Pin(SongDescription),
+    Unpin,
+    // Puts back what was dropped from the queue: the song to play next, and songs queued by hand
+    Requeue(Option<SongDescription>, Vec<SongDescription>),
+    // Carries the position to later resume the interrupted song from, if any
+    SwapWithNext(Option<u32>),
     ClearQueue,
+    // Unlike ClearQueue, the current song keeps playing
+    ClearUpcoming,
+    // Sent by the player shortly before the current track ends
+    PreloadNext,
+    // Sent by the player when the current track is over, unlike Next which is the user skipping
+    TrackEnded,
+    // Recommended tracks to carry on with once the queue ends
+    Autoplay(Vec<SongDescription>),
+    SetSleepTimer(Option<SleepTimer>),
+    SetRepeatMode(RepeatMode),
+    // From 0 to 1, out of range values are clamped
+    SetVolume(f64),
+    // Sent once a SleepTimer::At is reached
+    SleepTimerExpired,
 }
 
 impl Into<AppAction> for PlaybackAction {
@@ -219,9 +922,28 @@ pub enum PlaybackEvent {
     PlaybackResumed,
     TrackSeeked(u32),
     SeekSynced(u32),
+    // Levels between 0 and 1 for each frequency band, from the lowest
+    SpectrumSynced(Vec<f32>),
+    // Sent right before TrackChanged when the new track shouldn't start from the beginning
+    StartPositionSet(u32),
     TrackChanged(String),
+    // The track expected to play next, so that the player can buffer it ahead of time
+    PreloadRequested(String),
     PlaylistChanged,
+    // Only when asked to, unlike the shuffle restored along with a context
+    ShuffleToggled,
+    // A song moved from one position in the queue to another, nothing else changed
+    SongMoved(usize, usize),
     PlaybackStopped,
+    // Nothing is left to play after the current track, or at all
+    QueueEnding,
+    // Recommendations for the seed are to be fetched, see PlaybackAction::LoadRadio
+    RadioRequested(RadioSeed),
+    SleepTimerChanged,
+    RepeatModeChanged,
+    VolumeChanged,
+    // Sent after the sleep timer paused playback
+    SleepTimerEnded,
 }
 
 impl Into<AppEvent> for PlaybackEvent {
@@ -263,25 +985,31 @@ impl UpdatableState for PlaybackState {
             }
             PlaybackAction::ToggleShuffle => {
                 self.toggle_shuffle();
-                vec![PlaybackEvent::PlaylistChanged]
+                vec![
+                    PlaybackEvent::PlaylistChanged,
+                    PlaybackEvent::ShuffleToggled,
+                ]
+            }
+            PlaybackAction::TrackEnded if self.sleep_timer == Some(SleepTimer::EndOfTrack) => {
+                self.sleep_at_end_of_track()
             }
-            PlaybackAction::Next => {
+            PlaybackAction::TrackEnded if self.repeat_mode == RepeatMode::One => {
+                match self.current_song_id.clone() {
+                    Some(id) => self.track_changed(id),
+                    None => vec![],
+                }
+            }
+            PlaybackAction::Next | PlaybackAction::TrackEnded => {
                 if let Some(id) = self.play_next() {
-                    vec![
-                        PlaybackEvent::TrackChanged(id),
-                        PlaybackEvent::PlaybackResumed,
-                    ]
+                    self.track_changed(id)
                 } else {
                     self.stop();
-                    vec![PlaybackEvent::PlaybackStopped]
+                    vec![PlaybackEvent::PlaybackStopped, PlaybackEvent::QueueEnding]
                 }
             }
             PlaybackAction::Previous => {
                 if let Some(id) = self.play_prev() {
-                    vec![
-                        PlaybackEvent::TrackChanged(id),
-                        PlaybackEvent::PlaybackResumed,
-                    ]
+                    self.track_changed(id)
                 } else {
                     vec![]
                 }
@@ -289,26 +1017,67 @@ impl UpdatableState for PlaybackState {
             PlaybackAction::Load(id) => {
                 if self.current_song_id.as_ref() != Some(&id) {
                     self.play(&id);
-                    vec![
-                        PlaybackEvent::TrackChanged(id),
-                        PlaybackEvent::PlaybackResumed,
-                    ]
+                    self.track_changed(id)
                 } else {
                     vec![]
                 }
             }
             PlaybackAction::LoadPlaylist(source, tracks) => {
+                self.radio = None;
                 self.set_playlist(source, tracks);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::StartRadio(seed) => vec![PlaybackEvent::RadioRequested(seed)],
+            PlaybackAction::LoadRadio(seed, tracks) => self.load_radio(seed, tracks),
+            PlaybackAction::RestoreSession(source, tracks, id, position) => {
+                self.restore(source, tracks, id, position)
+            }
             PlaybackAction::Queue(track) => {
                 self.queue(track);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::QueueAt(track, position) => {
+                self.queue_at(track, position);
+                vec![PlaybackEvent::PlaylistChanged]
+            }
+            PlaybackAction::Move(id, position) => self
+                .move_song(&id, position)
+                .map(|(from, to)| vec![PlaybackEvent::SongMoved(from, to)])
+                .unwrap_or_default(),
             PlaybackAction::Dequeue(id) => {
                 self.dequeue(&id);
                 vec![PlaybackEvent::PlaylistChanged]
             }
+            PlaybackAction::Pin(track) => {
+                if self.pin(track) {
+                    vec![PlaybackEvent::PlaylistChanged]
+                } else {
+                    vec![]
+                }
+            }
+            PlaybackAction::Unpin => {
+                if self.unpin() {
+                    vec![PlaybackEvent::PlaylistChanged]
+                } else {
+                    vec![]
+                }
+            }
+            PlaybackAction::Requeue(pinned, tracks) => {
+                if let Some(track) = pinned {
+                    self.pin(track);
+                }
+                self.requeue(tracks);
+                vec![PlaybackEvent::PlaylistChanged]
+            }
+            PlaybackAction::SwapWithNext(position) => {
+                if let Some(id) = self.swap_with_next(position) {
+                    let mut events = vec![PlaybackEvent::PlaylistChanged];
+                    events.append(&mut self.track_changed(id));
+                    events
+                } else {
+                    vec![]
+                }
+            }
             PlaybackAction::ClearQueue => {
                 self.clear();
                 vec![
@@ -316,8 +1085,721 @@ impl UpdatableState for PlaybackState {
                     PlaybackEvent::PlaybackStopped,
                 ]
             }
+            PlaybackAction::ClearUpcoming => {
+                if self.clear_upcoming() {
+                    vec![PlaybackEvent::PlaylistChanged]
+                } else {
+                    vec![]
+                }
+            }
+            PlaybackAction::PreloadNext if self.repeat_mode == RepeatMode::One => self
+                .current_song_id
+                .iter()
+                .map(|id| PlaybackEvent::PreloadRequested(id.clone()))
+                .collect(),
+            PlaybackAction::PreloadNext => match self.next_song() {
+                Some(song) => vec![PlaybackEvent::PreloadRequested(song.id.clone())],
+                None if self.current_song_id.is_some() => vec![PlaybackEvent::QueueEnding],
+                None => vec![],
+            },
+            PlaybackAction::Autoplay(tracks) => self.autoplay(tracks),
+            PlaybackAction::SetSleepTimer(timer) => {
+                self.sleep_timer = timer;
+                vec![PlaybackEvent::SleepTimerChanged]
+            }
+            PlaybackAction::SetRepeatMode(mode) => {
+                self.repeat_mode = mode;
+                vec![PlaybackEvent::RepeatModeChanged]
+            }
+            PlaybackAction::SetVolume(volume) => {
+                let volume = volume.max(0.0).min(1.0);
+                if (volume - self.volume).abs() > f64::EPSILON {
+                    self.volume = volume;
+                    vec![PlaybackEvent::VolumeChanged]
+                } else {
+                    vec![]
+                }
+            }
+            PlaybackAction::SleepTimerExpired => match self.sleep_timer {
+                Some(SleepTimer::At(_)) => self.sleep(),
+                _ => vec![],
+            },
             PlaybackAction::Seek(pos) => vec![PlaybackEvent::TrackSeeked(pos)],
             PlaybackAction::SyncSeek(pos) => vec![PlaybackEvent::SeekSynced(pos)],
+            PlaybackAction::SyncSpectrum(levels) => vec![PlaybackEvent::SpectrumSynced(levels)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::models::{AlbumRef, ArtistRef};
+
+    fn song(id: &str) -> SongDescription {
+        SongDescription {
+            id: id.to_string(),
+            title: "Title".to_string(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "".to_string(),
+            },
+            duration: 1000,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        }
+    }
+
+    fn song_by(id: &str, artist: &str, album: &str) -> SongDescription {
+        SongDescription {
+            artists: vec![ArtistRef {
+                id: artist.to_string(),
+                name: artist.to_string(),
+            }],
+            album: AlbumRef {
+                id: album.to_string(),
+                name: album.to_string(),
+            },
+            ..song(id)
+        }
+    }
+
+    // Songs named after their artist, e.g. "a1" and "a2" are both by "a"
+    fn by_artists(ids: &[&str]) -> Vec<SongDescription> {
+        ids.iter().map(|&id| song_by(id, &id[..1], "")).collect()
+    }
+
+    fn shuffled(songs: Vec<SongDescription>, mode: ShuffleMode) -> Vec<String> {
+        let mut state = PlaybackState::default();
+        state.set_shuffle_mode(mode);
+        state.update_with(PlaybackAction::LoadPlaylist(PlaylistSource::None, songs));
+        state.update_with(PlaybackAction::ToggleShuffle);
+        state.songs().map(|s| s.id.clone()).collect()
+    }
+
+    fn back_to_back(ids: &[String]) -> usize {
+        ids.windows(2).filter(|w| w[0][..1] == w[1][..1]).count()
+    }
+
+    fn playlist(ids: &[&str]) -> Vec<SongDescription> {
+        ids.iter().map(|&id| song(id)).collect()
+    }
+
+    fn current(state: &PlaybackState) -> Option<&str> {
+        state.current_song_id.as_deref()
+    }
+
+    #[test]
+    fn test_pinned_song_plays_next() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(song("4")));
+
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("4"));
+
+        state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("4"));
+        assert!(state.pinned_song().is_none());
+
+        state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("2"));
+    }
+
+    #[test]
+    fn test_preload_next_song() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(song("4")));
+
+        let events = state.update_with(PlaybackAction::PreloadNext);
+        assert!(matches!(&events[..], [PlaybackEvent::PreloadRequested(id)] if id == "4"));
+
+        state.update_with(PlaybackAction::Next);
+        state.update_with(PlaybackAction::Next);
+        let events = state.update_with(PlaybackAction::PreloadNext);
+        assert!(matches!(&events[..], [PlaybackEvent::QueueEnding]));
+    }
+
+    #[test]
+    fn test_clear_upcoming() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            playlist(&["1", "2", "3", "4"]),
+        ));
+        state.update_with(PlaybackAction::Load("2".to_string()));
+        state.update_with(PlaybackAction::Pin(song("5")));
+
+        let events = state.update_with(PlaybackAction::ClearUpcoming);
+        assert!(matches!(&events[..], [PlaybackEvent::PlaylistChanged]));
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids, vec!["1", "2"]);
+        assert_eq!(current(&state), Some("2"));
+        assert!(state.is_playing());
+        assert!(state.next_song().is_none());
+        assert!(state.song("4").is_none());
+
+        assert!(state.update_with(PlaybackAction::ClearUpcoming).is_empty());
+    }
+
+    #[test]
+    fn test_move_song() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3", "4"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+
+        let events = state.update_with(PlaybackAction::Move("4".to_string(), 1));
+        assert!(matches!(&events[..], [PlaybackEvent::SongMoved(3, 1)]));
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids, vec!["1", "4", "2", "3"]);
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("4"));
+
+        let events = state.update_with(PlaybackAction::Move("2".to_string(), 10));
+        assert!(matches!(&events[..], [PlaybackEvent::SongMoved(2, 3)]));
+        assert!(state
+            .update_with(PlaybackAction::Move("2".to_string(), 3))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_repeat_modes() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("2".to_string()));
+        state.update_with(PlaybackAction::SetRepeatMode(RepeatMode::One));
+
+        let events = state.update_with(PlaybackAction::PreloadNext);
+        assert!(matches!(&events[..], [PlaybackEvent::PreloadRequested(id)] if id == "2"));
+        state.update_with(PlaybackAction::TrackEnded);
+        assert_eq!(current(&state), Some("2"));
+        assert!(state.is_playing());
+
+        state.update_with(PlaybackAction::SetRepeatMode(RepeatMode::All));
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("1"));
+        state.update_with(PlaybackAction::TrackEnded);
+        assert_eq!(current(&state), Some("1"));
+        assert_eq!(state.prev_song().map(|s| &s.id[..]), Some("2"));
+
+        state.update_with(PlaybackAction::ClearQueue);
+        assert_eq!(state.repeat_mode, RepeatMode::All);
+        assert!(state.next_song().is_none());
+    }
+
+    #[test]
+    fn test_remove_explicit() {
+        let mut state = PlaybackState::default();
+        let explicit = |id: &str| SongDescription {
+            explicit: true,
+            ..song(id)
+        };
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            vec![explicit("1"), song("2"), explicit("3"), explicit("4")],
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(explicit("5")));
+
+        assert!(state.remove_explicit());
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids, vec!["1", "2"]);
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("5"));
+        assert_eq!(state.source, PlaylistSource::Album("a".to_string()));
+        assert!(!state.remove_explicit());
+    }
+
+    #[test]
+    fn test_remove_explicit_keeps_played_current_and_queued() {
+        let mut state = PlaybackState::default();
+        let explicit = |id: &str| SongDescription {
+            explicit: true,
+            ..song(id)
+        };
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            vec![explicit("1"), explicit("2"), song("3"), explicit("4")],
+        ));
+        state.update_with(PlaybackAction::Load("2".to_string()));
+        state.update_with(PlaybackAction::Queue(explicit("5")));
+
+        assert!(state.remove_explicit());
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "5"]);
+        assert_eq!(current(&state), Some("2"));
+    }
+
+    #[test]
+    fn test_volume_clamped() {
+        let mut state = PlaybackState::default();
+        assert!(state.update_with(PlaybackAction::SetVolume(1.0)).is_empty());
+
+        let events = state.update_with(PlaybackAction::SetVolume(-0.5));
+        assert!(matches!(&events[..], [PlaybackEvent::VolumeChanged]));
+        assert_eq!(state.volume, 0.0);
+
+        state.update_with(PlaybackAction::SetVolume(2.0));
+        assert_eq!(state.volume, 1.0);
+    }
+
+    #[test]
+    fn test_clear_queue_keeps_volume() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::SetVolume(0.5));
+
+        state.update_with(PlaybackAction::ClearQueue);
+        assert_eq!(state.volume, 0.5);
+    }
+
+    #[test]
+    fn test_plays_as_loaded_with() {
+        let mut state = PlaybackState::default();
+        let source = PlaylistSource::Playlist("p".to_string());
+        state.update_with(PlaybackAction::LoadPlaylist(
+            source.clone(),
+            playlist(&["1", "2"]),
+        ));
+        assert!(state.plays_as_loaded_with(&source, &playlist(&["1", "2"])));
+        // e.g. a filter hid the third song when the playlist was loaded
+        assert!(!state.plays_as_loaded_with(&source, &playlist(&["1", "2", "3"])));
+
+        state.update_with(PlaybackAction::Queue(song("3")));
+        assert!(!state.plays_as_loaded_with(&source, &playlist(&["1", "2"])));
+    }
+
+    #[test]
+    fn test_autoplay() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("2".to_string()));
+
+        state.update_with(PlaybackAction::Autoplay(playlist(&["1", "3", "4"])));
+        assert_eq!(current(&state), Some("2"));
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("3"));
+        assert!(!state.plays_as_loaded(&PlaylistSource::Album("a".to_string())));
+
+        state.update_with(PlaybackAction::Next);
+        state.update_with(PlaybackAction::Next);
+        let events = state.update_with(PlaybackAction::TrackEnded);
+        assert_eq!(current(&state), None);
+        assert!(matches!(events.last(), Some(PlaybackEvent::QueueEnding)));
+
+        // The queue ran out before the recommendations came
+        state.update_with(PlaybackAction::Autoplay(playlist(&["5", "6"])));
+        assert_eq!(current(&state), Some("5"));
+        assert!(state.is_playing());
+
+        state.update_with(PlaybackAction::ClearQueue);
+        state.update_with(PlaybackAction::Autoplay(playlist(&["7"])));
+        assert_eq!(current(&state), None);
+    }
+
+    #[test]
+    fn test_radio() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+
+        let seed = RadioSeed::Artist("artist".to_string(), "Artist".to_string());
+        let events = state.update_with(PlaybackAction::StartRadio(seed.clone()));
+        assert!(matches!(events[..], [PlaybackEvent::RadioRequested(_)]));
+        assert!(state.radio.is_none());
+
+        state.update_with(PlaybackAction::LoadRadio(seed, playlist(&["3", "4"])));
+        assert_eq!(current(&state), Some("3"));
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("4"));
+        assert!(matches!(state.source, PlaylistSource::None));
+        assert_eq!(state.radio.as_ref().map(|seed| seed.name()), Some("Artist"));
+
+        // More recommendations keep the radio going
+        state.update_with(PlaybackAction::Autoplay(playlist(&["5"])));
+        assert!(state.radio.is_some());
+
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::Album("a".to_string()),
+            playlist(&["1", "2"]),
+        ));
+        assert!(state.radio.is_none());
+    }
+
+    #[test]
+    fn test_sleep_timer_at_end_of_track() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::SetSleepTimer(Some(SleepTimer::EndOfTrack)));
+
+        state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("2"));
+        assert!(state.is_playing());
+
+        let events = state.update_with(PlaybackAction::TrackEnded);
+        assert_eq!(current(&state), Some("3"));
+        assert!(!state.is_playing());
+        assert!(state.sleep_timer.is_none());
+        assert!(matches!(
+            events.last(),
+            Some(PlaybackEvent::SleepTimerEnded)
+        ));
+
+        state.update_with(PlaybackAction::Play);
+        state.update_with(PlaybackAction::TrackEnded);
+        assert!(!state.is_playing());
+        assert_eq!(current(&state), None);
+    }
+
+    #[test]
+    fn test_sleep_timer_expired() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::SetSleepTimer(Some(SleepTimer::At(
+            Instant::now(),
+        ))));
+        state.update_with(PlaybackAction::ClearQueue);
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+
+        let events = state.update_with(PlaybackAction::SleepTimerExpired);
+        assert!(matches!(
+            &events[..],
+            [
+                PlaybackEvent::SleepTimerChanged,
+                PlaybackEvent::PlaybackPaused,
+                PlaybackEvent::SleepTimerEnded
+            ]
+        ));
+        assert_eq!(current(&state), Some("1"));
+        assert!(state.sleep_timer.is_none());
+
+        assert!(state
+            .update_with(PlaybackAction::SleepTimerExpired)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_restore_session() {
+        let mut state = PlaybackState::default();
+        let events = state.update_with(PlaybackAction::RestoreSession(
+            PlaylistSource::from_uri("spotify:album:a"),
+            playlist(&["1", "2", "3"]),
+            "2".to_string(),
+            5000,
+        ));
+        assert_eq!(current(&state), Some("2"));
+        assert_eq!(state.source, PlaylistSource::Album("a".to_string()));
+        assert!(!state.is_playing());
+        assert!(matches!(
+            events.get(1),
+            Some(PlaybackEvent::StartPositionSet(5000))
+        ));
+
+        let events = state.update_with(PlaybackAction::RestoreSession(
+            PlaylistSource::None,
+            playlist(&["4"]),
+            "4".to_string(),
+            0,
+        ));
+        assert!(events.is_empty());
+        assert_eq!(current(&state), Some("2"));
+    }
+
+    #[test]
+    fn test_pinned_song_survives_shuffle_and_playlist_change() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(song("3")));
+
+        state.update_with(PlaybackAction::ToggleShuffle);
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("3"));
+
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["5", "6"]),
+        ));
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("3"));
+    }
+
+    #[test]
+    fn test_previous_after_pinned_song() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Load("2".to_string()));
+        state.update_with(PlaybackAction::Pin(song("4")));
+        state.update_with(PlaybackAction::Next);
+
+        assert_eq!(current(&state), Some("4"));
+        state.update_with(PlaybackAction::Previous);
+        assert_eq!(current(&state), Some("2"));
+        assert!(state.song("4").is_none());
+    }
+
+    #[test]
+    fn test_queue_at_restores_position() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Dequeue("2".to_string()));
+        state.update_with(PlaybackAction::QueueAt(song("2"), 1));
+
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_unpin() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(song("4")));
+        state.update_with(PlaybackAction::Unpin);
+
+        assert_eq!(state.next_song().map(|s| &s.id[..]), Some("2"));
+        assert!(state.song("4").is_none());
+    }
+
+    #[test]
+    fn test_swap_with_next() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2", "3"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::SwapWithNext(Some(5000)));
+        assert_eq!(current(&state), Some("2"));
+
+        let events = state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("1"));
+        assert!(matches!(
+            events.first(),
+            Some(PlaybackEvent::StartPositionSet(5000))
+        ));
+
+        state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("3"));
+    }
+
+    #[test]
+    fn test_swap_with_pinned_song() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1", "2"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::Pin(song("4")));
+        state.update_with(PlaybackAction::SwapWithNext(None));
+        assert_eq!(current(&state), Some("4"));
+
+        let events = state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("1"));
+        assert!(matches!(
+            events.first(),
+            Some(PlaybackEvent::TrackChanged(_))
+        ));
+
+        state.update_with(PlaybackAction::Next);
+        assert_eq!(current(&state), Some("2"));
+    }
+
+    #[test]
+    fn test_swap_without_next_song() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+
+        let events = state.update_with(PlaybackAction::SwapWithNext(None));
+        assert!(events.is_empty());
+        assert_eq!(current(&state), Some("1"));
+    }
+
+    #[test]
+    fn test_shuffle_modes_keep_every_song() {
+        let ids = ["a1", "a2", "b1", "b2", "b3", "c1"];
+        for &mode in &[
+            ShuffleMode::Random,
+            ShuffleMode::ArtistSpread,
+            ShuffleMode::Album,
+        ] {
+            let mut order = shuffled(by_artists(&ids), mode);
+            order.sort();
+            assert_eq!(order, ids);
         }
     }
+
+    #[test]
+    fn test_artist_spread_keeps_artists_apart() {
+        let ids = [
+            "a1", "a2", "a3", "a4", "b1", "b2", "b3", "b4", "c1", "c2", "c3", "c4",
+        ];
+        for _ in 0..100 {
+            let order = shuffled(by_artists(&ids), ShuffleMode::ArtistSpread);
+            assert_eq!(back_to_back(&order), 0, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_artist_spread_with_a_dominant_artist() {
+        // Two songs by "b" can split the six by "a" into three runs at best
+        let ids = ["a1", "a2", "a3", "a4", "a5", "a6", "b1", "b2"];
+        for _ in 0..100 {
+            let order = shuffled(by_artists(&ids), ShuffleMode::ArtistSpread);
+            assert_eq!(back_to_back(&order), 3, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_artist_spread_starts_apart_from_current_song() {
+        for _ in 0..100 {
+            let mut state = PlaybackState::default();
+            state.set_shuffle_mode(ShuffleMode::ArtistSpread);
+            state.update_with(PlaybackAction::LoadPlaylist(
+                PlaylistSource::None,
+                by_artists(&["a1", "a2", "b1", "b2"]),
+            ));
+            state.update_with(PlaybackAction::Load("a1".to_string()));
+            state.update_with(PlaybackAction::ToggleShuffle);
+
+            let order: Vec<String> = state.songs().map(|s| s.id.clone()).collect();
+            assert_eq!(order[0], "a1");
+            assert_eq!(back_to_back(&order), 0, "{:?}", order);
+        }
+    }
+
+    #[test]
+    fn test_artist_spread_is_still_random() {
+        let ids = ["a1", "a2", "b1", "b2", "c1", "c2"];
+        let mut firsts = std::collections::HashSet::new();
+        for _ in 0..200 {
+            let order = shuffled(by_artists(&ids), ShuffleMode::ArtistSpread);
+            firsts.insert(order[0].clone());
+        }
+        assert_eq!(firsts.len(), ids.len());
+    }
+
+    #[test]
+    fn test_album_shuffle_keeps_albums_in_order() {
+        let songs = vec![
+            song_by("x1", "a", "x"),
+            song_by("x2", "a", "x"),
+            song_by("x3", "a", "x"),
+            song_by("y1", "a", "y"),
+            song_by("y2", "a", "y"),
+            song_by("z1", "a", "z"),
+            song_by("z2", "a", "z"),
+        ];
+        let mut album_orders = std::collections::HashSet::new();
+        for _ in 0..100 {
+            let mut state = PlaybackState::default();
+            state.set_shuffle_mode(ShuffleMode::Album);
+            state.update_with(PlaybackAction::LoadPlaylist(
+                PlaylistSource::None,
+                songs.clone(),
+            ));
+            state.update_with(PlaybackAction::Load("y2".to_string()));
+            state.update_with(PlaybackAction::ToggleShuffle);
+
+            let order: Vec<String> = state.songs().map(|s| s.id.clone()).collect();
+            // The album playing goes on first
+            assert_eq!(order[..2], ["y2", "y1"]);
+            let rest = order[2..].join(" ");
+            assert!(
+                rest == "x1 x2 x3 z1 z2" || rest == "z1 z2 x1 x2 x3",
+                "{:?}",
+                order
+            );
+            album_orders.insert(rest);
+        }
+        assert_eq!(album_orders.len(), 2);
+    }
+
+    #[test]
+    fn test_clear_queue_keeps_shuffle_mode() {
+        let mut state = PlaybackState::default();
+        state.set_shuffle_mode(ShuffleMode::Album);
+
+        state.update_with(PlaybackAction::ClearQueue);
+        assert_eq!(state.shuffle_mode, ShuffleMode::Album);
+    }
+
+    #[test]
+    fn test_changing_shuffle_mode_reshuffles() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            by_artists(&["a1", "a2", "b1"]),
+        ));
+        assert!(!state.set_shuffle_mode(ShuffleMode::Album));
+
+        state.update_with(PlaybackAction::ToggleShuffle);
+        assert!(state.set_shuffle_mode(ShuffleMode::ArtistSpread));
+        assert!(!state.set_shuffle_mode(ShuffleMode::ArtistSpread));
+        let order: Vec<String> = state.songs().map(|s| s.id.clone()).collect();
+        assert_eq!(back_to_back(&order), 0);
+    }
+
+    #[test]
+    fn test_queue_while_shuffled() {
+        let mut state = PlaybackState::default();
+        state.update_with(PlaybackAction::LoadPlaylist(
+            PlaylistSource::None,
+            playlist(&["1"]),
+        ));
+        state.update_with(PlaybackAction::Load("1".to_string()));
+        state.update_with(PlaybackAction::ToggleShuffle);
+
+        state.update_with(PlaybackAction::Queue(song("2")));
+        state.update_with(PlaybackAction::Queue(song("3")));
+
+        let ids: Vec<&str> = state.songs().map(|s| &s.id[..]).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(ids[0], "1");
+    }
 }
@@ -1,10 +1,17 @@
 use std::borrow::Cow;
 use std::cmp::PartialEq;
+use std::collections::HashSet;
 
 use super::{BrowserAction, BrowserEvent, UpdatableState};
 use crate::app::models::*;
+use crate::app::settings::PlaylistSort;
 use crate::app::ListStore;
 
+// Songs kept in the listening history, most recent first
+const HISTORY_LIMIT: usize = 200;
+// Spotify logs a play a bit after the song started, which is when plays are observed locally
+const HISTORY_SLACK_SECS: i64 = 60;
+
 #[derive(Clone, Debug)]
 pub enum ScreenName {
     Home,
@@ -12,6 +19,11 @@ pub enum ScreenName {
     Search,
     Artist(String),
     PlaylistDetails(String),
+    Category(String),
+    // Songs like the one with that id
+    Recommendations(String),
+    // A podcast
+    Show(String),
 }
 
 impl ScreenName {
@@ -22,6 +34,34 @@ impl ScreenName {
             Self::Search => Cow::Borrowed("search"),
             Self::Artist(s) => Cow::Owned(format!("artist_{}", s)),
             Self::PlaylistDetails(s) => Cow::Owned(format!("playlist_{}", s)),
+            Self::Category(s) => Cow::Owned(format!("category_{}", s)),
+            Self::Recommendations(s) => Cow::Owned(format!("recommendations_{}", s)),
+            Self::Show(s) => Cow::Owned(format!("show_{}", s)),
+        }
+    }
+
+    // Accepts both spotify:<kind>:<id> URIs and open.spotify.com links
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let path = uri
+            .strip_prefix("spotify:")
+            .map(|rest| rest.replace(':', "/"))
+            .or_else(|| {
+                let rest = uri
+                    .strip_prefix("https://")
+                    .or_else(|| uri.strip_prefix("http://"))?
+                    .strip_prefix("open.spotify.com/")?;
+                Some(rest.split('?').next()?.to_string())
+            })?;
+        let mut parts = path.split('/');
+        let kind = parts.next()?;
+        let id = parts.next().filter(|id| !id.is_empty())?.to_string();
+        match kind {
+            "album" => Some(Self::AlbumDetails(id)),
+            "artist" => Some(Self::Artist(id)),
+            "playlist" => Some(Self::PlaylistDetails(id)),
+            "show" => Some(Self::Show(id)),
+            _ => None,
         }
     }
 }
@@ -97,6 +137,35 @@ impl PlaylistDetailsState {
             content: None,
         }
     }
+
+    fn same_content(a: &PlaylistDescription, b: &PlaylistDescription) -> bool {
+        a.title == b.title
+            && a.description == b.description
+            && a.added_by == b.added_by
+            && a.songs.len() == b.songs.len()
+            && a.songs
+                .iter()
+                .zip(b.songs.iter())
+                .all(|(a, b)| a.id == b.id)
+    }
+
+    // The positions of the given songs, rows of the same song can't be told apart
+    // so copies are taken in order
+    pub fn positions_of(&self, ids: &[String]) -> Vec<(String, usize)> {
+        let songs = match self.content.as_ref() {
+            Some(content) => &content.songs[..],
+            None => return vec![],
+        };
+        let mut positions: Vec<(String, usize)> = vec![];
+        for id in ids {
+            let position = (0..songs.len())
+                .find(|&i| &songs[i].id == id && !positions.iter().any(|(_, p)| *p == i));
+            if let Some(position) = position {
+                positions.push((id.clone(), position));
+            }
+        }
+        positions
+    }
 }
 
 impl UpdatableState for PlaylistDetailsState {
@@ -105,11 +174,135 @@ impl UpdatableState for PlaylistDetailsState {
 
     fn update_with(&mut self, action: Self::Action) -> Vec<Self::Event> {
         match action {
-            BrowserAction::SetPlaylistDetails(playlist) => {
+            BrowserAction::SetPlaylistDetails(playlist) if playlist.id == self.id => {
+                // Refreshing a collaborative playlist mostly brings back the same content
+                let unchanged = self
+                    .content
+                    .as_ref()
+                    .map(|content| Self::same_content(content, &playlist))
+                    .unwrap_or(false);
+                if unchanged {
+                    return vec![];
+                }
                 let id = playlist.id.clone();
                 self.content = Some(playlist);
                 vec![BrowserEvent::PlaylistDetailsLoaded(id)]
             }
+            BrowserAction::RemoveFromPlaylist(id, positions) if id == self.id => {
+                if let Some(content) = self.content.as_mut() {
+                    let mut position = 0;
+                    content.songs.retain(|_| {
+                        let keep = !positions.contains(&position);
+                        position += 1;
+                        keep
+                    });
+                }
+                vec![BrowserEvent::PlaylistTracksRemoved(id)]
+            }
+            BrowserAction::MovePlaylistTrack(id, from, to) if id == self.id => {
+                let songs = match self.content.as_mut() {
+                    Some(content) => &mut content.songs,
+                    None => return vec![],
+                };
+                if from == to || from >= songs.len() || to >= songs.len() {
+                    return vec![];
+                }
+                let song = songs.remove(from);
+                songs.insert(to, song);
+                vec![BrowserEvent::PlaylistTrackMoved(id, from, to)]
+            }
+            BrowserAction::UpdatePlaylistDetails(id, title, description) if id == self.id => {
+                if let Some(content) = self.content.as_mut() {
+                    content.title = title;
+                    content.description = description;
+                }
+                vec![BrowserEvent::PlaylistDetailsUpdated(id)]
+            }
+            BrowserAction::SetPlaylistArt(id, art) if id == self.id => {
+                if let Some(content) = self.content.as_mut() {
+                    content.art = Some(art);
+                }
+                vec![BrowserEvent::PlaylistDetailsUpdated(id)]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+pub struct CategoryState {
+    pub id: String,
+    pub name: ScreenName,
+    pub next_page: Pagination<String>,
+    pub playlists: ListStore<AlbumModel>,
+}
+
+impl CategoryState {
+    pub fn new(id: String) -> Self {
+        Self {
+            id: id.clone(),
+            name: ScreenName::Category(id.clone()),
+            next_page: Pagination::new(id, 20),
+            playlists: ListStore::new(),
+        }
+    }
+}
+
+pub struct RecommendationsState {
+    pub id: String,
+    pub name: ScreenName,
+    pub seed: Option<SongDescription>,
+    pub songs: Vec<SongDescription>,
+}
+
+impl RecommendationsState {
+    pub fn new(id: String) -> Self {
+        Self {
+            id: id.clone(),
+            name: ScreenName::Recommendations(id),
+            seed: None,
+            songs: vec![],
+        }
+    }
+}
+
+impl UpdatableState for RecommendationsState {
+    type Action = BrowserAction;
+    type Event = BrowserEvent;
+
+    fn update_with(&mut self, action: Self::Action) -> Vec<Self::Event> {
+        match action {
+            BrowserAction::SetRecommendations(seed, songs) if seed.id == self.id => {
+                let id = seed.id.clone();
+                self.seed = Some(seed);
+                self.songs = songs;
+                vec![BrowserEvent::RecommendationsUpdated(id)]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+impl UpdatableState for CategoryState {
+    type Action = BrowserAction;
+    type Event = BrowserEvent;
+
+    fn update_with(&mut self, action: Self::Action) -> Vec<Self::Event> {
+        match action {
+            BrowserAction::SetCategoryPlaylists(id, playlists) if id == self.id => {
+                self.playlists.remove_all();
+                for playlist in playlists {
+                    self.playlists.append(playlist.into());
+                }
+                self.next_page.reset(self.playlists.len() as u32);
+                vec![BrowserEvent::CategoryPlaylistsUpdated(id)]
+            }
+            BrowserAction::AppendCategoryPlaylists(id, playlists) if id == self.id => {
+                for playlist in playlists {
+                    self.playlists.append(playlist.into());
+                }
+                self.next_page.update(self.playlists.len() as u32);
+                vec![BrowserEvent::CategoryPlaylistsUpdated(id)]
+            }
             _ => vec![],
         }
     }
@@ -165,20 +358,73 @@ pub struct ArtistState {
     pub id: String,
     pub name: ScreenName,
     pub artist: Option<String>,
-    pub next_page: Pagination<String>,
+    pub release_group: ReleaseGroup,
+    pub next_pages: Vec<Pagination<ReleaseGroup>>,
     pub albums: ListStore<AlbumModel>,
+    pub singles: ListStore<AlbumModel>,
+    pub compilations: ListStore<AlbumModel>,
+    pub appears_on: ListStore<AlbumModel>,
     pub top_tracks: Vec<SongDescription>,
+    pub related_artists: Vec<ArtistSummary>,
+    pub is_followed: bool,
 }
 
 impl ArtistState {
     pub fn new(id: String) -> Self {
         Self {
             id: id.clone(),
-            name: ScreenName::Artist(id.clone()),
+            name: ScreenName::Artist(id),
             artist: None,
-            next_page: Pagination::new(id, 20),
+            release_group: ReleaseGroup::Albums,
+            next_pages: ReleaseGroup::GROUPS
+                .iter()
+                .map(|group| Pagination::new(*group, 20))
+                .collect(),
             albums: ListStore::new(),
+            singles: ListStore::new(),
+            compilations: ListStore::new(),
+            appears_on: ListStore::new(),
             top_tracks: vec![],
+            related_artists: vec![],
+            is_followed: false,
+        }
+    }
+
+    pub fn releases(&self, group: ReleaseGroup) -> &ListStore<AlbumModel> {
+        match group {
+            ReleaseGroup::Albums => &self.albums,
+            ReleaseGroup::Singles => &self.singles,
+            ReleaseGroup::Compilations => &self.compilations,
+            ReleaseGroup::AppearsOn => &self.appears_on,
+        }
+    }
+
+    fn releases_mut(&mut self, group: ReleaseGroup) -> &mut ListStore<AlbumModel> {
+        match group {
+            ReleaseGroup::Albums => &mut self.albums,
+            ReleaseGroup::Singles => &mut self.singles,
+            ReleaseGroup::Compilations => &mut self.compilations,
+            ReleaseGroup::AppearsOn => &mut self.appears_on,
+        }
+    }
+
+    pub fn next_page(&self, group: ReleaseGroup) -> Option<&Pagination<ReleaseGroup>> {
+        self.next_pages.iter().find(|page| page.data == group)
+    }
+
+    fn next_page_mut(&mut self, group: ReleaseGroup) -> Option<&mut Pagination<ReleaseGroup>> {
+        self.next_pages.iter_mut().find(|page| page.data == group)
+    }
+
+    fn set_releases(&mut self, group: ReleaseGroup, albums: Vec<AlbumDescription>) {
+        let releases = self.releases_mut(group);
+        releases.remove_all();
+        for album in albums {
+            releases.append(album.into());
+        }
+        let len = releases.len() as u32;
+        if let Some(page) = self.next_page_mut(group) {
+            page.reset(len);
         }
     }
 }
@@ -193,27 +439,112 @@ impl UpdatableState for ArtistState {
                 id,
                 name,
                 albums,
+                singles,
+                compilations,
+                appears_on,
                 mut top_tracks,
+                related_artists,
+                is_followed,
             }) => {
                 self.artist = Some(name);
+                self.is_followed = is_followed;
 
-                self.albums.remove_all();
-                for album in albums {
-                    self.albums.append(album.into());
-                }
-                self.next_page.reset(self.albums.len() as u32);
+                self.set_releases(ReleaseGroup::Albums, albums);
+                self.set_releases(ReleaseGroup::Singles, singles);
+                self.set_releases(ReleaseGroup::Compilations, compilations);
+                self.set_releases(ReleaseGroup::AppearsOn, appears_on);
 
                 top_tracks.truncate(5);
                 self.top_tracks = top_tracks;
+                self.related_artists = related_artists;
 
                 vec![BrowserEvent::ArtistDetailsUpdated(id)]
             }
-            BrowserAction::AppendArtistReleases(albums) => {
+            BrowserAction::AppendArtistReleases(id, group, albums) if id == self.id => {
+                let releases = self.releases_mut(group);
                 for album in albums {
-                    self.albums.append(album.into());
+                    releases.append(album.into());
+                }
+                let len = releases.len() as u32;
+                if let Some(page) = self.next_page_mut(group) {
+                    page.update(len);
                 }
-                self.next_page.update(self.albums.len() as u32);
-                vec![BrowserEvent::ArtistDetailsUpdated(self.id.clone())]
+                vec![BrowserEvent::ArtistDetailsUpdated(id)]
+            }
+            BrowserAction::SetArtistReleaseGroup(id, group)
+                if id == self.id && group != self.release_group =>
+            {
+                self.release_group = group;
+                vec![BrowserEvent::ArtistReleaseGroupChanged(id)]
+            }
+            BrowserAction::FollowArtist(artist) if artist.id == self.id => {
+                self.is_followed = true;
+                vec![BrowserEvent::ArtistFollowed(artist.id)]
+            }
+            BrowserAction::UnfollowArtist(id) if id == self.id => {
+                self.is_followed = false;
+                vec![BrowserEvent::ArtistUnfollowed(id)]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+pub struct ShowState {
+    pub id: String,
+    pub name: ScreenName,
+    // Without its episodes, which are paged into the store below
+    pub show: Option<ShowDescription>,
+    pub next_page: Pagination<String>,
+    pub episodes: ListStore<EpisodeModel>,
+}
+
+impl ShowState {
+    pub fn new(id: String) -> Self {
+        Self {
+            id: id.clone(),
+            name: ScreenName::Show(id.clone()),
+            show: None,
+            next_page: Pagination::new(id, 20),
+            episodes: ListStore::new(),
+        }
+    }
+}
+
+impl UpdatableState for ShowState {
+    type Action = BrowserAction;
+    type Event = BrowserEvent;
+
+    fn update_with(&mut self, action: Self::Action) -> Vec<Self::Event> {
+        match action {
+            BrowserAction::SetShowDetails(mut show) if show.id == self.id => {
+                let id = show.id.clone();
+                self.episodes.remove_all();
+                for episode in show.episodes.drain(..) {
+                    self.episodes.append(episode.into());
+                }
+                self.next_page.reset(self.episodes.len() as u32);
+                self.show = Some(show);
+                vec![BrowserEvent::ShowDetailsUpdated(id)]
+            }
+            BrowserAction::AppendShowEpisodes(id, episodes) if id == self.id => {
+                for episode in episodes {
+                    self.episodes.append(episode.into());
+                }
+                self.next_page.update(self.episodes.len() as u32);
+                vec![BrowserEvent::ShowEpisodesAppended(id)]
+            }
+            BrowserAction::FollowShow(podcast) if podcast.id == self.id => {
+                if let Some(show) = self.show.as_mut() {
+                    show.is_followed = true;
+                }
+                vec![BrowserEvent::ShowFollowed(podcast.id)]
+            }
+            BrowserAction::UnfollowShow(id) if id == self.id => {
+                if let Some(show) = self.show.as_mut() {
+                    show.is_followed = false;
+                }
+                vec![BrowserEvent::ShowUnfollowed(id)]
             }
             _ => vec![],
         }
@@ -226,6 +557,68 @@ pub struct HomeState {
     pub albums: ListStore<AlbumModel>,
     pub next_playlists_page: Pagination<()>,
     pub playlists: ListStore<AlbumModel>,
+    pub next_podcasts_page: Pagination<()>,
+    pub podcasts: ListStore<AlbumModel>,
+    pub next_categories_page: Pagination<()>,
+    pub categories: ListStore<AlbumModel>,
+    pub next_new_releases_page: Pagination<()>,
+    pub new_releases: ListStore<AlbumModel>,
+    // None for the worldwide charts
+    pub charts_country: Option<String>,
+    pub charts: ListStore<AlbumModel>,
+    pub playlists_sort: PlaylistSort,
+    pub playlist_folders: Vec<PlaylistFolder>,
+    pub next_saved_tracks_page: Pagination<()>,
+    pub saved_tracks: Vec<SongDescription>,
+    pub saved_tracks_sort: SongSort,
+    pub followed_artists: Vec<ArtistSummary>,
+    pub history: Vec<PlayedSong>,
+    pub recently_played: Vec<RecentContext>,
+    // Mixes put together for the user, like Discover Weekly
+    pub made_for_you: Vec<PlaylistDescription>,
+    // The name of the smart playlist shown, the first one if none was picked
+    pub smart_playlist: Option<String>,
+}
+
+impl HomeState {
+    // Only what was loaded so far is sorted, further pages get mixed in as they come
+    fn sort_playlists(&mut self) {
+        if self.playlists_sort == PlaylistSort::Name {
+            self.playlists.sort_by(|a, b| {
+                let a = a.title().unwrap_or_default().to_lowercase();
+                let b = b.title().unwrap_or_default().to_lowercase();
+                a.cmp(&b)
+            });
+        }
+    }
+
+    // Plays reported by Spotify replace the same plays observed locally
+    fn merge_history(&mut self, reported: Vec<PlayedSong>) {
+        self.history.retain(|local| {
+            !reported.iter().any(|played| {
+                let window = (local.song.duration / 1000) as i64 + HISTORY_SLACK_SECS;
+                played.song.id == local.song.id
+                    && (played.played_at - local.played_at).abs() <= window
+            })
+        });
+        self.history.extend(reported);
+        self.history.sort_by(|a, b| b.played_at.cmp(&a.played_at));
+        self.history.truncate(HISTORY_LIMIT);
+    }
+
+    // Only the liked songs loaded so far are considered, now is in seconds since the epoch
+    pub fn smart_playlist_songs(&self, playlist: &SmartPlaylist, now: i64) -> Vec<SongDescription> {
+        let followed = self
+            .followed_artists
+            .iter()
+            .map(|artist| &artist.id[..])
+            .collect::<HashSet<&str>>();
+        self.saved_tracks
+            .iter()
+            .filter(|song| playlist.matches(song, &followed, now))
+            .cloned()
+            .collect()
+    }
 }
 
 impl Default for HomeState {
@@ -236,6 +629,25 @@ impl Default for HomeState {
             albums: ListStore::new(),
             next_playlists_page: Pagination::new((), 30),
             playlists: ListStore::new(),
+            next_podcasts_page: Pagination::new((), 30),
+            podcasts: ListStore::new(),
+            next_categories_page: Pagination::new((), 30),
+            categories: ListStore::new(),
+            next_new_releases_page: Pagination::new((), 30),
+            new_releases: ListStore::new(),
+            charts_country: None,
+            charts: ListStore::new(),
+            playlists_sort: PlaylistSort::Default,
+            playlist_folders: vec![],
+            // The saved tracks endpoint returns at most 50 tracks at once
+            next_saved_tracks_page: Pagination::new((), 50),
+            saved_tracks: vec![],
+            saved_tracks_sort: SongSort::Default,
+            followed_artists: vec![],
+            history: vec![],
+            recently_played: vec![],
+            made_for_you: vec![],
+            smart_playlist: None,
         }
     }
 }
@@ -269,6 +681,70 @@ impl UpdatableState for HomeState {
                 self.next_albums_page.update(self.albums.len() as u32);
                 vec![BrowserEvent::LibraryUpdated]
             }
+            BrowserAction::SetNewReleases(albums) => {
+                let converted = albums.iter().map(|a| a.into()).collect::<Vec<AlbumModel>>();
+                if !self.new_releases.eq(&converted, |a, b| a.uri() == b.uri()) {
+                    self.new_releases.remove_all();
+                    for album in converted {
+                        self.new_releases.append(album);
+                    }
+                    self.next_new_releases_page
+                        .reset(self.new_releases.len() as u32);
+                    vec![BrowserEvent::NewReleasesUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::AppendNewReleases(albums) => {
+                for album in albums {
+                    self.new_releases.append(album.into());
+                }
+                self.next_new_releases_page
+                    .update(self.new_releases.len() as u32);
+                vec![BrowserEvent::NewReleasesUpdated]
+            }
+            BrowserAction::SetCharts(country, playlists) => {
+                let converted = playlists
+                    .iter()
+                    .map(|p| p.into())
+                    .collect::<Vec<AlbumModel>>();
+                let same_country = self.charts_country == country;
+                if !same_country || !self.charts.eq(&converted, |a, b| a.uri() == b.uri()) {
+                    self.charts_country = country;
+                    self.charts.remove_all();
+                    for playlist in converted {
+                        self.charts.append(playlist);
+                    }
+                    vec![BrowserEvent::ChartsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetCategories(categories) => {
+                let converted = categories
+                    .iter()
+                    .map(|c| c.into())
+                    .collect::<Vec<AlbumModel>>();
+                if !self.categories.eq(&converted, |a, b| a.uri() == b.uri()) {
+                    self.categories.remove_all();
+                    for category in converted {
+                        self.categories.append(category);
+                    }
+                    self.next_categories_page
+                        .reset(self.categories.len() as u32);
+                    vec![BrowserEvent::CategoriesUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::AppendCategories(categories) => {
+                for category in categories.iter() {
+                    self.categories.append(category.into());
+                }
+                self.next_categories_page
+                    .update(self.categories.len() as u32);
+                vec![BrowserEvent::CategoriesUpdated]
+            }
             BrowserAction::SaveAlbum(album) => {
                 let album_id = album.id.clone();
                 let already_present = self
@@ -296,7 +772,43 @@ impl UpdatableState for HomeState {
                     vec![]
                 }
             }
+            BrowserAction::SetFollowedArtists(artists) => {
+                let unchanged = self.followed_artists.len() == artists.len()
+                    && self
+                        .followed_artists
+                        .iter()
+                        .zip(artists.iter())
+                        .all(|(a, b)| a.id == b.id);
+                if !unchanged {
+                    self.followed_artists = artists;
+                    vec![BrowserEvent::FollowedArtistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::FollowArtist(artist) => {
+                let already_present = self.followed_artists.iter().any(|a| a.id == artist.id);
+                if already_present {
+                    vec![]
+                } else {
+                    self.followed_artists.insert(0, artist);
+                    vec![BrowserEvent::FollowedArtistsUpdated]
+                }
+            }
+            BrowserAction::UnfollowArtist(id) => {
+                let position = self.followed_artists.iter().position(|a| a.id == id);
+                if let Some(position) = position {
+                    self.followed_artists.remove(position);
+                    vec![BrowserEvent::FollowedArtistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
             BrowserAction::SetPlaylistsContent(content) => {
+                let mut content = content;
+                if self.playlists_sort == PlaylistSort::Name {
+                    content.sort_by_key(|p| p.title.to_lowercase());
+                }
                 let converted = content
                     .iter()
                     .map(|a| a.into())
@@ -316,9 +828,178 @@ impl UpdatableState for HomeState {
                 for playlist in content {
                     self.playlists.append(playlist.into());
                 }
+                self.sort_playlists();
                 self.next_playlists_page.update(self.playlists.len() as u32);
                 vec![BrowserEvent::SavedPlaylistsUpdated]
             }
+            BrowserAction::SetPodcastsContent(content) => {
+                let converted = content
+                    .iter()
+                    .map(|p| p.into())
+                    .collect::<Vec<AlbumModel>>();
+                if !self.podcasts.eq(&converted, |a, b| a.uri() == b.uri()) {
+                    self.podcasts.replace_all(converted);
+                    self.next_podcasts_page.reset(self.podcasts.len() as u32);
+                    vec![BrowserEvent::SavedPodcastsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            // A page arriving twice, e.g. when scrolling to the bottom again before it
+            // was loaded, is only appended once
+            BrowserAction::AppendPodcastsContent(offset, content)
+                if Some(offset) == self.next_podcasts_page.next_offset =>
+            {
+                self.podcasts
+                    .extend(content.into_iter().map(|p| p.into()).collect());
+                self.next_podcasts_page.update(self.podcasts.len() as u32);
+                vec![BrowserEvent::SavedPodcastsUpdated]
+            }
+            BrowserAction::FollowShow(podcast) => {
+                let id = Some(podcast.id.clone());
+                if self.podcasts.iter().any(|p| p.uri() == id) {
+                    vec![]
+                } else {
+                    self.podcasts.insert(0, podcast.into());
+                    self.next_podcasts_page.increment();
+                    vec![BrowserEvent::SavedPodcastsUpdated]
+                }
+            }
+            BrowserAction::UnfollowShow(id) => {
+                let position = self
+                    .podcasts
+                    .iter()
+                    .position(|p| p.uri().as_ref() == Some(&id));
+                if let Some(position) = position {
+                    self.podcasts.remove(position as u32);
+                    vec![BrowserEvent::SavedPodcastsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetPlaylistFolders(folders) => {
+                self.playlist_folders = folders;
+                vec![BrowserEvent::PlaylistFoldersUpdated]
+            }
+            BrowserAction::AddPlaylist(playlist) => {
+                self.playlists.insert(0, playlist.into());
+                self.sort_playlists();
+                self.next_playlists_page.increment();
+                vec![BrowserEvent::SavedPlaylistsUpdated]
+            }
+            BrowserAction::UpdatePlaylistDetails(id, title, _) => {
+                let playlist = self
+                    .playlists
+                    .iter()
+                    .find(|p| p.uri().as_ref() == Some(&id));
+                if let Some(playlist) = playlist {
+                    playlist.set_title(&title);
+                    self.sort_playlists();
+                    vec![BrowserEvent::SavedPlaylistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            // Album widgets load their cover once, so the playlist is replaced with an updated copy
+            BrowserAction::SetPlaylistArt(id, art) => {
+                let position = self
+                    .playlists
+                    .iter()
+                    .position(|p| p.uri().as_ref() == Some(&id));
+                if let Some(position) = position {
+                    let playlist = self.playlists.get(position as u32);
+                    let updated = AlbumModel::new(
+                        &playlist.artist().unwrap_or_default(),
+                        &playlist.title().unwrap_or_default(),
+                        &Some(art),
+                        &id,
+                    );
+                    self.playlists.remove(position as u32);
+                    self.playlists.insert(position as u32, updated);
+                    vec![BrowserEvent::SavedPlaylistsUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetSavedTracks(tracks) => {
+                let unchanged = self.saved_tracks.len() == tracks.len()
+                    && self
+                        .saved_tracks
+                        .iter()
+                        .zip(tracks.iter())
+                        .all(|(a, b)| a.id == b.id);
+                if !unchanged {
+                    self.saved_tracks = tracks;
+                    self.next_saved_tracks_page
+                        .reset(self.saved_tracks.len() as u32);
+                    vec![BrowserEvent::SavedTracksUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            // A page that isn't the one expected next (e.g. requested twice) is dropped
+            BrowserAction::AppendSavedTracks(offset, tracks)
+                if Some(offset) == self.next_saved_tracks_page.next_offset =>
+            {
+                let position = self.saved_tracks.len();
+                self.saved_tracks.extend(tracks);
+                self.next_saved_tracks_page
+                    .update(self.saved_tracks.len() as u32);
+                vec![BrowserEvent::SavedTracksAppended(position)]
+            }
+            // Only the displayed order changes, pages are still fetched in the default order
+            BrowserAction::SetSavedTracksSort(sort) if sort != self.saved_tracks_sort => {
+                self.saved_tracks_sort = sort;
+                vec![BrowserEvent::SavedTracksUpdated]
+            }
+            BrowserAction::RemoveSavedTrack(id) => {
+                let position = self.saved_tracks.iter().position(|t| t.id == id);
+                if let Some(position) = position {
+                    self.saved_tracks.remove(position);
+                    self.next_saved_tracks_page.decrement();
+                    vec![BrowserEvent::SavedTracksUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetHistory(reported) => {
+                self.merge_history(reported);
+                vec![BrowserEvent::HistoryUpdated]
+            }
+            BrowserAction::AddToHistory(played) => {
+                self.history.insert(0, played);
+                self.history.truncate(HISTORY_LIMIT);
+                vec![BrowserEvent::HistoryUpdated]
+            }
+            // Refreshed with every song played, which rarely changes where songs are played from
+            BrowserAction::SetRecentlyPlayed(recent) => {
+                let unchanged = self.recently_played.len() == recent.len()
+                    && self
+                        .recently_played
+                        .iter()
+                        .zip(recent.iter())
+                        .all(|(a, b)| a.id == b.id);
+                if !unchanged {
+                    self.recently_played = recent;
+                    vec![BrowserEvent::RecentlyPlayedUpdated]
+                } else {
+                    vec![]
+                }
+            }
+            BrowserAction::SetMadeForYou(playlists) => {
+                self.made_for_you = playlists;
+                vec![BrowserEvent::MadeForYouUpdated]
+            }
+            BrowserAction::SelectSmartPlaylist(name) => {
+                self.smart_playlist = Some(name);
+                vec![BrowserEvent::SmartPlaylistSelected]
+            }
+            // Going back to the default order requires fetching playlists again
+            BrowserAction::SetPlaylistsSort(sort) if sort != self.playlists_sort => {
+                self.playlists_sort = sort;
+                self.sort_playlists();
+                vec![BrowserEvent::SavedPlaylistsUpdated]
+            }
             _ => vec![],
         }
     }
@@ -329,6 +1010,13 @@ pub struct SearchState {
     pub query: String,
     pub album_results: Vec<AlbumDescription>,
     pub artist_results: Vec<ArtistSummary>,
+    pub song_results: Vec<SongDescription>,
+    pub playlist_results: Vec<PlaylistDescription>,
+    pub podcast_results: Vec<PodcastDescription>,
+    pub episode_results: Vec<EpisodeDescription>,
+    pub filter: SearchFilter,
+    // One page per type of results, the data being the type
+    pub next_pages: Vec<Pagination<SearchFilter>>,
 }
 
 impl Default for SearchState {
@@ -338,6 +1026,49 @@ impl Default for SearchState {
             query: "".to_owned(),
             album_results: vec![],
             artist_results: vec![],
+            song_results: vec![],
+            playlist_results: vec![],
+            podcast_results: vec![],
+            episode_results: vec![],
+            filter: SearchFilter::All,
+            next_pages: SearchFilter::TYPES
+                .iter()
+                .map(|filter| Pagination::new(*filter, 20))
+                .collect(),
+        }
+    }
+}
+
+impl SearchState {
+    pub fn next_page(&self, filter: SearchFilter) -> Option<&Pagination<SearchFilter>> {
+        self.next_pages.iter().find(|page| page.data == filter)
+    }
+
+    fn next_page_mut(&mut self, filter: SearchFilter) -> Option<&mut Pagination<SearchFilter>> {
+        self.next_pages.iter_mut().find(|page| page.data == filter)
+    }
+
+    fn results_len(&self, filter: SearchFilter) -> usize {
+        match filter {
+            SearchFilter::All => 0,
+            SearchFilter::Songs => self.song_results.len(),
+            SearchFilter::Albums => self.album_results.len(),
+            SearchFilter::Artists => self.artist_results.len(),
+            SearchFilter::Playlists => self.playlist_results.len(),
+            SearchFilter::Podcasts => self.podcast_results.len(),
+            SearchFilter::Episodes => self.episode_results.len(),
+        }
+    }
+
+    fn append_results(&mut self, filter: SearchFilter, results: SearchResults) {
+        match filter {
+            SearchFilter::All => {}
+            SearchFilter::Songs => self.song_results.extend(results.songs),
+            SearchFilter::Albums => self.album_results.extend(results.albums),
+            SearchFilter::Artists => self.artist_results.extend(results.artists),
+            SearchFilter::Playlists => self.playlist_results.extend(results.playlists),
+            SearchFilter::Podcasts => self.podcast_results.extend(results.podcasts),
+            SearchFilter::Episodes => self.episode_results.extend(results.episodes),
         }
     }
 }
@@ -355,8 +1086,30 @@ impl UpdatableState for SearchState {
             BrowserAction::SetSearchResults(results) => {
                 self.album_results = results.albums;
                 self.artist_results = results.artists;
+                self.song_results = results.songs;
+                self.playlist_results = results.playlists;
+                self.podcast_results = results.podcasts;
+                self.episode_results = results.episodes;
+                for filter in SearchFilter::TYPES.iter() {
+                    let len = self.results_len(*filter) as u32;
+                    if let Some(page) = self.next_page_mut(*filter) {
+                        page.reset(len);
+                    }
+                }
+                vec![BrowserEvent::SearchResultsUpdated]
+            }
+            BrowserAction::AppendSearchResults(filter, results) => {
+                self.append_results(filter, results);
+                let len = self.results_len(filter) as u32;
+                if let Some(page) = self.next_page_mut(filter) {
+                    page.update(len);
+                }
                 vec![BrowserEvent::SearchResultsUpdated]
             }
+            BrowserAction::SetSearchFilter(filter) if filter != self.filter => {
+                self.filter = filter;
+                vec![BrowserEvent::SearchFilterChanged]
+            }
             _ => vec![],
         }
     }
@@ -367,6 +1120,21 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_screen_from_uri() {
+        assert_eq!(
+            ScreenName::from_uri("spotify:album:abc"),
+            Some(ScreenName::AlbumDetails("abc".to_string()))
+        );
+        assert_eq!(
+            ScreenName::from_uri("https://open.spotify.com/playlist/abc?si=123"),
+            Some(ScreenName::PlaylistDetails("abc".to_string()))
+        );
+        assert_eq!(ScreenName::from_uri("spotify:track:abc"), None);
+        assert_eq!(ScreenName::from_uri("spotify:artist:"), None);
+        assert_eq!(ScreenName::from_uri(""), None);
+    }
+
     #[test]
     fn test_next_page_no_next() {
         let mut artist_state = ArtistState::new("id".to_owned());
@@ -374,10 +1142,15 @@ mod tests {
             id: "".to_owned(),
             name: "Foo".to_owned(),
             albums: vec![],
+            singles: vec![],
+            compilations: vec![],
+            appears_on: vec![],
             top_tracks: vec![],
+            related_artists: vec![],
+            is_followed: false,
         }));
 
-        let next = artist_state.next_page;
+        let next = artist_state.next_page(ReleaseGroup::Albums).unwrap();
         assert_eq!(None, next.next_offset);
     }
 
@@ -390,21 +1163,872 @@ mod tests {
             art: Some("".to_owned()),
             songs: vec![],
             is_liked: false,
+            release_date: None,
+            label: None,
+            copyright: None,
         };
         let mut artist_state = ArtistState::new("id".to_owned());
         artist_state.update_with(BrowserAction::SetArtistDetails(ArtistDescription {
             id: "".to_owned(),
             name: "Foo".to_owned(),
             albums: (0..20).map(|_| fake_album.clone()).collect(),
+            singles: vec![],
+            compilations: vec![],
+            appears_on: vec![],
             top_tracks: vec![],
+            related_artists: vec![],
+            is_followed: false,
         }));
 
-        let next = &artist_state.next_page;
+        let next = artist_state.next_page(ReleaseGroup::Albums).unwrap();
         assert_eq!(Some(20), next.next_offset);
 
-        artist_state.update_with(BrowserAction::AppendArtistReleases(vec![]));
+        artist_state.update_with(BrowserAction::AppendArtistReleases(
+            "id".to_owned(),
+            ReleaseGroup::Albums,
+            vec![],
+        ));
 
-        let next = &artist_state.next_page;
+        let next = artist_state.next_page(ReleaseGroup::Albums).unwrap();
         assert_eq!(None, next.next_offset);
     }
+
+    #[test]
+    fn test_release_groups_paged_separately() {
+        let fake_album = AlbumDescription {
+            id: "".to_owned(),
+            title: "".to_owned(),
+            artists: vec![],
+            art: Some("".to_owned()),
+            songs: vec![],
+            is_liked: false,
+            release_date: None,
+            label: None,
+            copyright: None,
+        };
+        let albums = |count: usize| {
+            (0..count)
+                .map(|_| fake_album.clone())
+                .collect::<Vec<AlbumDescription>>()
+        };
+        let mut artist_state = ArtistState::new("id".to_owned());
+        artist_state.update_with(BrowserAction::SetArtistDetails(ArtistDescription {
+            id: "id".to_owned(),
+            name: "Foo".to_owned(),
+            albums: albums(3),
+            singles: albums(20),
+            compilations: vec![],
+            appears_on: albums(20),
+            top_tracks: vec![],
+            related_artists: vec![],
+            is_followed: false,
+        }));
+
+        let next_offset = |state: &ArtistState, group: ReleaseGroup| {
+            state.next_page(group).and_then(|page| page.next_offset)
+        };
+        assert_eq!(next_offset(&artist_state, ReleaseGroup::Albums), None);
+        assert_eq!(next_offset(&artist_state, ReleaseGroup::Singles), Some(20));
+
+        // Releases of another artist are left out
+        artist_state.update_with(BrowserAction::AppendArtistReleases(
+            "other".to_owned(),
+            ReleaseGroup::Singles,
+            albums(20),
+        ));
+        assert_eq!(artist_state.singles.len(), 20);
+
+        artist_state.update_with(BrowserAction::AppendArtistReleases(
+            "id".to_owned(),
+            ReleaseGroup::Singles,
+            albums(20),
+        ));
+        assert_eq!(artist_state.singles.len(), 40);
+        assert_eq!(artist_state.albums.len(), 3);
+        assert_eq!(artist_state.appears_on.len(), 20);
+        assert_eq!(next_offset(&artist_state, ReleaseGroup::Singles), Some(40));
+        assert_eq!(
+            next_offset(&artist_state, ReleaseGroup::AppearsOn),
+            Some(20)
+        );
+
+        let events = artist_state.update_with(BrowserAction::SetArtistReleaseGroup(
+            "id".to_owned(),
+            ReleaseGroup::Singles,
+        ));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::ArtistReleaseGroupChanged("id".to_owned())]
+        );
+        assert_eq!(artist_state.releases(ReleaseGroup::Singles).len(), 40);
+    }
+
+    #[test]
+    fn test_playlists_sorted_by_name() {
+        let playlist = |title: &str| PlaylistDescription {
+            id: title.to_owned(),
+            title: title.to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![],
+            owner: UserRef {
+                id: "".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        };
+        let titles = |state: &HomeState| {
+            state
+                .playlists
+                .iter()
+                .filter_map(|p| p.title())
+                .collect::<Vec<String>>()
+        };
+
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::SetPlaylistsContent(vec![
+            playlist("b"),
+            playlist("C"),
+        ]));
+        home_state.update_with(BrowserAction::SetPlaylistsSort(PlaylistSort::Name));
+        home_state.update_with(BrowserAction::AppendPlaylistsContent(vec![playlist("a")]));
+
+        assert_eq!(titles(&home_state), vec!["a", "b", "C"]);
+
+        home_state.update_with(BrowserAction::AddPlaylist(playlist("B2")));
+        assert_eq!(titles(&home_state), vec!["a", "b", "B2", "C"]);
+
+        home_state.update_with(BrowserAction::SetPlaylistsSort(PlaylistSort::Default));
+        let events = home_state.update_with(BrowserAction::AddPlaylist(playlist("z")));
+        assert_eq!(events, vec![BrowserEvent::SavedPlaylistsUpdated]);
+        assert_eq!(titles(&home_state)[0], "z");
+    }
+
+    #[test]
+    fn test_saved_tracks_refresh_and_remove() {
+        let song = |id: &str| SongDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_owned(),
+                name: "".to_owned(),
+            },
+            duration: 0,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        };
+        let page = (0..50).map(|i| song(&i.to_string())).collect::<Vec<_>>();
+
+        let mut home_state = HomeState::default();
+        let events = home_state.update_with(BrowserAction::SetSavedTracks(page.clone()));
+        assert_eq!(events, vec![BrowserEvent::SavedTracksUpdated]);
+        assert_eq!(home_state.next_saved_tracks_page.next_offset, Some(50));
+
+        // Refreshing with the same tracks leaves the view alone
+        let events = home_state.update_with(BrowserAction::SetSavedTracks(page));
+        assert!(events.is_empty());
+
+        home_state.update_with(BrowserAction::RemoveSavedTrack("0".to_owned()));
+        assert_eq!(home_state.saved_tracks.len(), 49);
+        assert_eq!(home_state.next_saved_tracks_page.next_offset, Some(49));
+    }
+
+    #[test]
+    fn test_saved_tracks_pages_appended_once() {
+        let song = |id: &str| SongDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_owned(),
+                name: "".to_owned(),
+            },
+            duration: 0,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        };
+        let page = |from: u32, to: u32| (from..to).map(|i| song(&i.to_string())).collect();
+
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::SetSavedTracks(page(0, 50)));
+
+        let events = home_state.update_with(BrowserAction::AppendSavedTracks(50, page(50, 100)));
+        assert_eq!(events, vec![BrowserEvent::SavedTracksAppended(50)]);
+        assert_eq!(home_state.next_saved_tracks_page.next_offset, Some(100));
+
+        let events = home_state.update_with(BrowserAction::AppendSavedTracks(50, page(50, 100)));
+        assert!(events.is_empty());
+        assert_eq!(home_state.saved_tracks.len(), 100);
+
+        let events = home_state.update_with(BrowserAction::AppendSavedTracks(100, page(100, 120)));
+        assert_eq!(events, vec![BrowserEvent::SavedTracksAppended(100)]);
+        assert_eq!(home_state.next_saved_tracks_page.next_offset, None);
+    }
+
+    #[test]
+    fn test_podcasts_pages_appended_once() {
+        let podcast = |id: u32| PodcastDescription {
+            id: id.to_string(),
+            name: id.to_string(),
+            art: None,
+            publisher: String::new(),
+        };
+        let page = |from: u32, to: u32| (from..to).map(podcast).collect::<Vec<_>>();
+
+        let mut home_state = HomeState::default();
+        let events = home_state.update_with(BrowserAction::SetPodcastsContent(page(0, 30)));
+        assert_eq!(events, vec![BrowserEvent::SavedPodcastsUpdated]);
+        assert_eq!(home_state.next_podcasts_page.next_offset, Some(30));
+
+        let append =
+            |from: u32, to: u32| BrowserAction::AppendPodcastsContent(from, page(from, to));
+        let events = home_state.update_with(append(30, 60));
+        assert_eq!(events, vec![BrowserEvent::SavedPodcastsUpdated]);
+        assert_eq!(home_state.next_podcasts_page.next_offset, Some(60));
+
+        assert!(home_state.update_with(append(30, 60)).is_empty());
+        assert_eq!(home_state.podcasts.len(), 60);
+
+        home_state.update_with(append(60, 70));
+        assert_eq!(home_state.podcasts.len(), 70);
+        assert_eq!(home_state.next_podcasts_page.next_offset, None);
+    }
+
+    #[test]
+    fn test_history_merges_reported_plays() {
+        let played = |id: &str, played_at: i64| PlayedSong {
+            song: SongDescription {
+                id: id.to_owned(),
+                title: id.to_owned(),
+                artists: vec![],
+                album: AlbumRef {
+                    id: "".to_owned(),
+                    name: "".to_owned(),
+                },
+                duration: 180_000,
+                art: None,
+                explicit: false,
+                popularity: None,
+                added_at: None,
+            },
+            played_at,
+        };
+        let history = |state: &HomeState| {
+            state
+                .history
+                .iter()
+                .map(|p| (p.song.id.clone(), p.played_at))
+                .collect::<Vec<_>>()
+        };
+
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::AddToHistory(played("a", 1000)));
+        home_state.update_with(BrowserAction::AddToHistory(played("b", 1200)));
+        assert_eq!(
+            history(&home_state),
+            vec![("b".to_owned(), 1200), ("a".to_owned(), 1000)]
+        );
+
+        // Spotify logged "a" once it ended, and an older play of "c"
+        let events = home_state.update_with(BrowserAction::SetHistory(vec![
+            played("a", 1180),
+            played("c", 500),
+        ]));
+        assert_eq!(events, vec![BrowserEvent::HistoryUpdated]);
+        assert_eq!(
+            history(&home_state),
+            vec![
+                ("b".to_owned(), 1200),
+                ("a".to_owned(), 1180),
+                ("c".to_owned(), 500)
+            ]
+        );
+
+        // Reported again, nothing gets duplicated
+        home_state.update_with(BrowserAction::SetHistory(vec![played("a", 1180)]));
+        assert_eq!(home_state.history.len(), 3);
+    }
+
+    #[test]
+    fn test_remove_from_playlist() {
+        let song = |id: &str| SongDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_owned(),
+                name: "".to_owned(),
+            },
+            duration: 0,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        };
+        let mut state = PlaylistDetailsState::new("p".to_owned());
+        state.update_with(BrowserAction::SetPlaylistDetails(PlaylistDescription {
+            id: "p".to_owned(),
+            title: "Playlist".to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![song("1"), song("2"), song("3"), song("1")],
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        }));
+
+        // Copies of the same song are taken in order
+        assert_eq!(
+            state.positions_of(&["1".to_owned(), "3".to_owned()]),
+            vec![("1".to_owned(), 0), ("3".to_owned(), 2)]
+        );
+        assert_eq!(
+            state.positions_of(&["1".to_owned(), "1".to_owned(), "1".to_owned()]),
+            vec![("1".to_owned(), 0), ("1".to_owned(), 3)]
+        );
+
+        let events = state.update_with(BrowserAction::RemoveFromPlaylist(
+            "other".to_owned(),
+            vec![0],
+        ));
+        assert!(events.is_empty());
+
+        let events = state.update_with(BrowserAction::RemoveFromPlaylist(
+            "p".to_owned(),
+            vec![0, 2],
+        ));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistTracksRemoved("p".to_owned())]
+        );
+        let ids = state
+            .content
+            .unwrap()
+            .songs
+            .into_iter()
+            .map(|s| s.id)
+            .collect::<Vec<String>>();
+        assert_eq!(ids, vec!["2", "1"]);
+    }
+
+    #[test]
+    fn test_move_playlist_track() {
+        let song = |id: &str| SongDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_owned(),
+                name: "".to_owned(),
+            },
+            duration: 0,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        };
+        let mut state = PlaylistDetailsState::new("p".to_owned());
+        state.update_with(BrowserAction::SetPlaylistDetails(PlaylistDescription {
+            id: "p".to_owned(),
+            title: "Playlist".to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![song("1"), song("2"), song("3")],
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        }));
+        let ids = |state: &PlaylistDetailsState| {
+            state
+                .content
+                .as_ref()
+                .unwrap()
+                .songs
+                .iter()
+                .map(|s| s.id.clone())
+                .collect::<Vec<String>>()
+        };
+
+        let events = state.update_with(BrowserAction::MovePlaylistTrack("p".to_owned(), 0, 2));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistTrackMoved("p".to_owned(), 0, 2)]
+        );
+        assert_eq!(ids(&state), vec!["2", "3", "1"]);
+
+        let events = state.update_with(BrowserAction::MovePlaylistTrack("p".to_owned(), 1, 3));
+        assert!(events.is_empty());
+        assert_eq!(ids(&state), vec!["2", "3", "1"]);
+    }
+
+    #[test]
+    fn test_refresh_playlist_details() {
+        let playlist = |id: &str, title: &str| PlaylistDescription {
+            id: id.to_owned(),
+            title: title.to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![],
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: true,
+            added_by: Default::default(),
+        };
+        let mut state = PlaylistDetailsState::new("p".to_owned());
+
+        let events = state.update_with(BrowserAction::SetPlaylistDetails(playlist("p", "A")));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistDetailsLoaded("p".to_owned())]
+        );
+
+        let events = state.update_with(BrowserAction::SetPlaylistDetails(playlist("p", "A")));
+        assert!(events.is_empty());
+
+        let events = state.update_with(BrowserAction::SetPlaylistDetails(playlist("q", "B")));
+        assert!(events.is_empty());
+        assert_eq!(state.content.unwrap().title, "A");
+    }
+
+    #[test]
+    fn test_update_playlist_details() {
+        let playlist = |id: &str, title: &str| PlaylistDescription {
+            id: id.to_owned(),
+            title: title.to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![],
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        };
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::SetPlaylistsContent(vec![
+            playlist("1", "One"),
+            playlist("2", "Two"),
+        ]));
+        let mut details_state = PlaylistDetailsState::new("2".to_owned());
+        details_state.update_with(BrowserAction::SetPlaylistDetails(playlist("2", "Two")));
+
+        let update = BrowserAction::UpdatePlaylistDetails(
+            "2".to_owned(),
+            "Deux".to_owned(),
+            "Second".to_owned(),
+        );
+        let events = home_state.update_with(update.clone());
+        assert_eq!(events, vec![BrowserEvent::SavedPlaylistsUpdated]);
+        let events = details_state.update_with(update);
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistDetailsUpdated("2".to_owned())]
+        );
+
+        let titles = home_state
+            .playlists
+            .iter()
+            .filter_map(|p| p.title())
+            .collect::<Vec<String>>();
+        assert_eq!(titles, vec!["One", "Deux"]);
+        let content = details_state.content.unwrap();
+        assert_eq!(content.title, "Deux");
+        assert_eq!(content.description, "Second");
+    }
+
+    #[test]
+    fn test_set_playlist_art() {
+        let playlist = |id: &str| PlaylistDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            description: "".to_owned(),
+            art: None,
+            songs: vec![],
+            owner: UserRef {
+                id: "me".to_owned(),
+                display_name: "".to_owned(),
+            },
+            collaborative: false,
+            added_by: Default::default(),
+        };
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::SetPlaylistsContent(vec![
+            playlist("1"),
+            playlist("2"),
+            playlist("3"),
+        ]));
+        let mut details_state = PlaylistDetailsState::new("2".to_owned());
+        details_state.update_with(BrowserAction::SetPlaylistDetails(playlist("2")));
+
+        let update = BrowserAction::SetPlaylistArt("2".to_owned(), "file:///cover.png".to_owned());
+        let events = home_state.update_with(update.clone());
+        assert_eq!(events, vec![BrowserEvent::SavedPlaylistsUpdated]);
+        let events = details_state.update_with(update);
+        assert_eq!(
+            events,
+            vec![BrowserEvent::PlaylistDetailsUpdated("2".to_owned())]
+        );
+
+        let covers = home_state
+            .playlists
+            .iter()
+            .map(|p| (p.uri().unwrap(), p.cover_url()))
+            .collect::<Vec<(String, Option<String>)>>();
+        assert_eq!(
+            covers,
+            vec![
+                ("1".to_owned(), None),
+                ("2".to_owned(), Some("file:///cover.png".to_owned())),
+                ("3".to_owned(), None),
+            ]
+        );
+        assert_eq!(
+            details_state.content.unwrap().art,
+            Some("file:///cover.png".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_category_playlists_only_for_their_category() {
+        let playlists = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| PlaylistDescription {
+                    id: id.to_string(),
+                    title: id.to_string(),
+                    description: "".to_owned(),
+                    art: None,
+                    songs: vec![],
+                    owner: UserRef {
+                        id: "spotify".to_owned(),
+                        display_name: "".to_owned(),
+                    },
+                    collaborative: false,
+                    added_by: Default::default(),
+                })
+                .collect::<Vec<PlaylistDescription>>()
+        };
+        let mut state = CategoryState::new("chill".to_owned());
+
+        let events = state.update_with(BrowserAction::SetCategoryPlaylists(
+            "rock".to_owned(),
+            playlists(&["a"]),
+        ));
+        assert!(events.is_empty());
+
+        let events = state.update_with(BrowserAction::SetCategoryPlaylists(
+            "chill".to_owned(),
+            playlists(&["b", "c"]),
+        ));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::CategoryPlaylistsUpdated("chill".to_owned())]
+        );
+        state.update_with(BrowserAction::AppendCategoryPlaylists(
+            "chill".to_owned(),
+            playlists(&["d"]),
+        ));
+        let ids = state
+            .playlists
+            .iter()
+            .filter_map(|p| p.uri())
+            .collect::<Vec<String>>();
+        assert_eq!(ids, vec!["b", "c", "d"]);
+        // Fewer playlists than asked for, so there are no more
+        assert_eq!(state.next_page.next_offset, None);
+    }
+
+    #[test]
+    fn test_recommendations_only_for_their_seed() {
+        let song = |id: &str| SongDescription {
+            id: id.to_owned(),
+            title: id.to_owned(),
+            artists: vec![],
+            album: AlbumRef {
+                id: "".to_owned(),
+                name: "".to_owned(),
+            },
+            duration: 0,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: None,
+        };
+        let mut state = RecommendationsState::new("a".to_owned());
+
+        let events = state.update_with(BrowserAction::SetRecommendations(
+            song("b"),
+            vec![song("c")],
+        ));
+        assert!(events.is_empty());
+        assert!(state.seed.is_none());
+
+        let events = state.update_with(BrowserAction::SetRecommendations(
+            song("a"),
+            vec![song("c"), song("d")],
+        ));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::RecommendationsUpdated("a".to_owned())]
+        );
+        assert_eq!(state.seed.map(|seed| seed.id), Some("a".to_owned()));
+        assert_eq!(state.songs.len(), 2);
+    }
+
+    #[test]
+    fn test_follow_artist() {
+        let artist = |id: &str| ArtistSummary {
+            id: id.to_owned(),
+            name: id.to_owned(),
+            photo: None,
+        };
+        let mut home_state = HomeState::default();
+        let mut artist_state = ArtistState::new("b".to_owned());
+        home_state.update_with(BrowserAction::SetFollowedArtists(vec![artist("a")]));
+
+        let follow = BrowserAction::FollowArtist(artist("b"));
+        let events = home_state.update_with(follow.clone());
+        assert_eq!(events, vec![BrowserEvent::FollowedArtistsUpdated]);
+        let events = artist_state.update_with(follow.clone());
+        assert_eq!(events, vec![BrowserEvent::ArtistFollowed("b".to_owned())]);
+        assert!(artist_state.is_followed);
+
+        // Following twice doesn't list the artist twice
+        assert!(home_state.update_with(follow).is_empty());
+        let ids = |state: &HomeState| {
+            state
+                .followed_artists
+                .iter()
+                .map(|a| a.id.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(ids(&home_state), vec!["b", "a"]);
+
+        let unfollow = BrowserAction::UnfollowArtist("b".to_owned());
+        home_state.update_with(unfollow.clone());
+        let events = artist_state.update_with(unfollow);
+        assert_eq!(events, vec![BrowserEvent::ArtistUnfollowed("b".to_owned())]);
+        assert!(!artist_state.is_followed);
+        assert_eq!(ids(&home_state), vec!["a"]);
+    }
+
+    #[test]
+    fn test_show_episodes_paged() {
+        let episodes = |count: usize| {
+            (0..count)
+                .map(|i| EpisodeDescription {
+                    id: i.to_string(),
+                    title: i.to_string(),
+                    description: String::new(),
+                    html_description: None,
+                    release_date: "2021-03-14".to_owned(),
+                    duration: 1000,
+                    fully_played: false,
+                })
+                .collect::<Vec<EpisodeDescription>>()
+        };
+        let mut state = ShowState::new("a".to_owned());
+
+        let events = state.update_with(BrowserAction::SetShowDetails(ShowDescription {
+            id: "a".to_owned(),
+            name: "A".to_owned(),
+            publisher: "Someone".to_owned(),
+            description: String::new(),
+            art: None,
+            episodes: episodes(20),
+            is_followed: false,
+        }));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::ShowDetailsUpdated("a".to_owned())]
+        );
+        assert_eq!(state.episodes.len(), 20);
+        assert_eq!(state.next_page.next_offset, Some(20));
+
+        // Another show's episodes are ignored
+        let events = state.update_with(BrowserAction::AppendShowEpisodes(
+            "b".to_owned(),
+            episodes(20),
+        ));
+        assert!(events.is_empty());
+
+        let events = state.update_with(BrowserAction::AppendShowEpisodes(
+            "a".to_owned(),
+            episodes(5),
+        ));
+        assert_eq!(
+            events,
+            vec![BrowserEvent::ShowEpisodesAppended("a".to_owned())]
+        );
+        assert_eq!(state.episodes.len(), 25);
+        assert_eq!(state.next_page.next_offset, None);
+    }
+
+    #[test]
+    fn test_recently_played_updated_when_changed() {
+        let recent = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| RecentContext {
+                    id: id.to_string(),
+                    kind: RecentKind::Album,
+                    title: id.to_string(),
+                    subtitle: String::new(),
+                    art: None,
+                })
+                .collect::<Vec<RecentContext>>()
+        };
+        let mut home_state = HomeState::default();
+
+        let events = home_state.update_with(BrowserAction::SetRecentlyPlayed(recent(&["a", "b"])));
+        assert_eq!(events, vec![BrowserEvent::RecentlyPlayedUpdated]);
+
+        let events = home_state.update_with(BrowserAction::SetRecentlyPlayed(recent(&["a", "b"])));
+        assert!(events.is_empty());
+
+        let events = home_state.update_with(BrowserAction::SetRecentlyPlayed(recent(&["b", "a"])));
+        assert_eq!(events, vec![BrowserEvent::RecentlyPlayedUpdated]);
+        assert_eq!(home_state.recently_played[0].id, "b");
+    }
+
+    #[test]
+    fn test_charts_replaced_for_another_country() {
+        let playlists = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| PlaylistDescription {
+                    id: id.to_string(),
+                    title: id.to_string(),
+                    description: "".to_owned(),
+                    art: None,
+                    songs: vec![],
+                    owner: UserRef {
+                        id: "spotify".to_owned(),
+                        display_name: "".to_owned(),
+                    },
+                    collaborative: false,
+                    added_by: Default::default(),
+                })
+                .collect::<Vec<PlaylistDescription>>()
+        };
+        let mut home_state = HomeState::default();
+
+        let events = home_state.update_with(BrowserAction::SetCharts(None, playlists(&["a", "b"])));
+        assert_eq!(events, vec![BrowserEvent::ChartsUpdated]);
+
+        let events = home_state.update_with(BrowserAction::SetCharts(None, playlists(&["a", "b"])));
+        assert!(events.is_empty());
+
+        // Some charts are the same everywhere, like the global one
+        let events = home_state.update_with(BrowserAction::SetCharts(
+            Some("FR".to_owned()),
+            playlists(&["a", "b"]),
+        ));
+        assert_eq!(events, vec![BrowserEvent::ChartsUpdated]);
+        assert_eq!(home_state.charts_country, Some("FR".to_owned()));
+        assert_eq!(home_state.charts.len(), 2);
+    }
+
+    #[test]
+    fn test_smart_playlist_songs() {
+        let day = 86400;
+        let now = 100 * day;
+        let song = |id: &str, artist: &str, seconds: u32, liked_days_ago: i64| SongDescription {
+            id: id.to_string(),
+            title: id.to_string(),
+            artists: vec![ArtistRef {
+                id: artist.to_string(),
+                name: artist.to_string(),
+            }],
+            album: AlbumRef {
+                id: "".to_string(),
+                name: "".to_string(),
+            },
+            duration: seconds * 1000,
+            art: None,
+            explicit: false,
+            popularity: None,
+            added_at: Some(now - liked_days_ago * day),
+        };
+        let mut home_state = HomeState::default();
+        home_state.update_with(BrowserAction::SetSavedTracks(vec![
+            song("recent", "a", 240, 2),
+            song("short", "b", 150, 40),
+            song("old", "a", 150, 60),
+        ]));
+        home_state.update_with(BrowserAction::SetFollowedArtists(vec![ArtistSummary {
+            id: "a".to_string(),
+            name: "a".to_string(),
+            photo: None,
+        }]));
+
+        let ids = |rules: Vec<SmartRule>| {
+            let playlist = SmartPlaylist {
+                name: "smart".to_string(),
+                rules,
+            };
+            home_state
+                .smart_playlist_songs(&playlist, now)
+                .iter()
+                .map(|s| s.id.clone())
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(ids(vec![SmartRule::LikedWithinDays(30)]), vec!["recent"]);
+        assert_eq!(
+            ids(vec![
+                SmartRule::ByFollowedArtist,
+                SmartRule::ShorterThan(180)
+            ]),
+            vec!["old"]
+        );
+        assert_eq!(ids(vec![]), vec!["recent", "short", "old"]);
+    }
+
+    #[test]
+    fn test_search_pages_per_type() {
+        let artists = |from: usize, to: usize| SearchResults {
+            albums: vec![],
+            artists: (from..to)
+                .map(|i| ArtistSummary {
+                    id: i.to_string(),
+                    name: i.to_string(),
+                    photo: None,
+                })
+                .collect(),
+            songs: vec![],
+            playlists: vec![],
+            podcasts: vec![],
+            episodes: vec![],
+        };
+        let next_offset = |state: &SearchState, filter: SearchFilter| {
+            state.next_page(filter).and_then(|page| page.next_offset)
+        };
+        let mut state = SearchState::default();
+
+        state.update_with(BrowserAction::SetSearchResults(artists(0, 20)));
+        assert_eq!(next_offset(&state, SearchFilter::Artists), Some(20));
+        assert_eq!(next_offset(&state, SearchFilter::Albums), None);
+
+        let events = state.update_with(BrowserAction::SetSearchFilter(SearchFilter::Artists));
+        assert_eq!(events, vec![BrowserEvent::SearchFilterChanged]);
+
+        let append = BrowserAction::AppendSearchResults(SearchFilter::Artists, artists(20, 30));
+        let events = state.update_with(append);
+        assert_eq!(events, vec![BrowserEvent::SearchResultsUpdated]);
+        assert_eq!(state.artist_results.len(), 30);
+        assert_eq!(next_offset(&state, SearchFilter::Artists), None);
+    }
 }